@@ -0,0 +1,226 @@
+use crate::get_runtime;
+use crate::resources::ResourceTable;
+use crate::tasks::spawn_task;
+use std::net::SocketAddr;
+use std::os::raw::c_int;
+use std::slice;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+
+// Sockets are registered with the tokio reactor, so accepts, reads and
+// writes wait on readiness instead of blocking a runtime thread. Each call
+// returns a task handle; `blaze_task_join` yields its result.
+
+enum AsyncSocket {
+    Listener(Arc<TcpListener>),
+    Stream(Arc<AsyncStream>),
+}
+
+/// The halves are locked separately so a pending read does not hold up a
+/// write on the same connection.
+struct AsyncStream {
+    reader: Mutex<OwnedReadHalf>,
+    writer: Mutex<OwnedWriteHalf>,
+}
+
+static SOCKETS: ResourceTable<AsyncSocket> = ResourceTable::new();
+
+fn listener(handle: i64) -> Option<Arc<TcpListener>> {
+    SOCKETS.with(handle, |socket| match socket {
+        AsyncSocket::Listener(listener) => Some(Arc::clone(listener)),
+        AsyncSocket::Stream(_) => None,
+    })?
+}
+
+fn stream(handle: i64) -> Option<Arc<AsyncStream>> {
+    SOCKETS.with(handle, |socket| match socket {
+        AsyncSocket::Stream(stream) => Some(Arc::clone(stream)),
+        AsyncSocket::Listener(_) => None,
+    })?
+}
+
+/// Binds a listener for the async calls and returns its handle, or -1 if the
+/// address is invalid or cannot be bound.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_tcp_bind_async(ptr: *const u8, len: usize) -> i64 {
+    if ptr.is_null() {
+        return -1;
+    }
+    let addr = match std::str::from_utf8(unsafe { slice::from_raw_parts(ptr, len) }).map(str::parse::<SocketAddr>) {
+        Ok(Ok(addr)) => addr,
+        _ => return -1,
+    };
+
+    let listener = match std::net::TcpListener::bind(addr) {
+        Ok(listener) => listener,
+        Err(_) => return -1,
+    };
+    if listener.set_nonblocking(true).is_err() {
+        return -1;
+    }
+
+    let _runtime = get_runtime().enter();
+    match TcpListener::from_std(listener) {
+        Ok(listener) => SOCKETS.insert(AsyncSocket::Listener(Arc::new(listener))),
+        Err(_) => -1,
+    }
+}
+
+/// The local port of a listener, useful after binding to port 0. Returns -1
+/// for an invalid handle.
+#[no_mangle]
+pub extern "C" fn blaze_tcp_listener_port(handle: i64) -> c_int {
+    listener(handle)
+        .and_then(|listener| listener.local_addr().ok())
+        .map_or(-1, |addr| addr.port() as c_int)
+}
+
+/// Starts accepting one connection. The task resolves to the new stream's
+/// handle, or -1. Returns the task handle, or -1 for an invalid listener.
+#[no_mangle]
+pub extern "C" fn blaze_tcp_accept_async(handle: i64) -> i64 {
+    let listener = match listener(handle) {
+        Some(listener) => listener,
+        None => return -1,
+    };
+
+    spawn_task(async move {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let (reader, writer) = stream.into_split();
+                let stream = AsyncStream { reader: Mutex::new(reader), writer: Mutex::new(writer) };
+                SOCKETS.insert(AsyncSocket::Stream(Arc::new(stream)))
+            }
+            Err(_) => -1,
+        }
+    })
+}
+
+/// Starts reading up to `len` bytes into `ptr`. The task resolves to the
+/// number of bytes read (0 at end of stream), or -1. The buffer must stay
+/// valid until the task has been joined.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` writable bytes that stay valid, and
+/// are not otherwise accessed, until the task has been joined.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_tcp_read_async(handle: i64, ptr: *mut u8, len: usize) -> i64 {
+    if ptr.is_null() {
+        return -1;
+    }
+    let stream = match stream(handle) {
+        Some(stream) => stream,
+        None => return -1,
+    };
+    let buffer = ptr as usize;
+
+    spawn_task(async move {
+        let buffer = unsafe { slice::from_raw_parts_mut(buffer as *mut u8, len) };
+        match stream.reader.lock().await.read(buffer).await {
+            Ok(n) => n as i64,
+            Err(_) => -1,
+        }
+    })
+}
+
+/// Starts writing all `len` bytes from `ptr`. The task resolves to the
+/// number of bytes written, or -1. The buffer must stay valid until the task
+/// has been joined.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes that stay valid until
+/// the task has been joined.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_tcp_write_async(handle: i64, ptr: *const u8, len: usize) -> i64 {
+    if ptr.is_null() {
+        return -1;
+    }
+    let stream = match stream(handle) {
+        Some(stream) => stream,
+        None => return -1,
+    };
+    let buffer = ptr as usize;
+
+    spawn_task(async move {
+        let buffer = unsafe { slice::from_raw_parts(buffer as *const u8, len) };
+        match stream.writer.lock().await.write_all(buffer).await {
+            Ok(()) => len as i64,
+            Err(_) => -1,
+        }
+    })
+}
+
+/// Closes an async listener or stream. Tasks already started on it keep
+/// their own reference and finish first. Returns 0, or -1 if the handle is
+/// not open.
+#[no_mangle]
+pub extern "C" fn blaze_tcp_close_async(handle: i64) -> c_int {
+    match SOCKETS.remove(handle) {
+        Some(_) => 0,
+        None => -1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::blaze_task_join;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    #[test]
+    fn test_two_concurrent_connections_on_one_listener() {
+        let addr = "127.0.0.1:0";
+        let listener = unsafe { blaze_tcp_bind_async(addr.as_ptr(), addr.len()) };
+        assert!(listener > 0);
+        let port = blaze_tcp_listener_port(listener) as u16;
+
+        // Both accepts are pending at the same time.
+        let accepts = [blaze_tcp_accept_async(listener), blaze_tcp_accept_async(listener)];
+        let connect = || TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut clients = [connect(), connect()];
+        let servers = accepts.map(|task| blaze_task_join(task));
+        assert!(servers.iter().all(|&server| server > 0));
+
+        let mut buffers = [[0u8; 16]; 2];
+        let reads: Vec<i64> = servers
+            .iter()
+            .zip(buffers.iter_mut())
+            .map(|(&server, buffer)| unsafe { blaze_tcp_read_async(server, buffer.as_mut_ptr(), buffer.len()) })
+            .collect();
+
+        // Both reads were started before either client wrote anything.
+        clients[1].write_all(b"second").unwrap();
+        clients[0].write_all(b"first").unwrap();
+        let lengths: Vec<i64> = reads.into_iter().map(|task| blaze_task_join(task)).collect();
+
+        // Echo each message back on the connection it arrived on.
+        let writes: Vec<i64> = servers
+            .iter()
+            .zip(&buffers)
+            .zip(&lengths)
+            .map(|((&server, buffer), &len)| unsafe { blaze_tcp_write_async(server, buffer.as_ptr(), len as usize) })
+            .collect();
+        assert_eq!(writes.into_iter().map(|task| blaze_task_join(task)).collect::<Vec<_>>(), lengths);
+
+        for (client, expected) in clients.iter_mut().zip(["first", "second"]) {
+            let mut echoed = vec![0u8; expected.len()];
+            client.read_exact(&mut echoed).unwrap();
+            assert_eq!(echoed, expected.as_bytes());
+        }
+
+        for handle in servers.into_iter().chain([listener]) {
+            assert_eq!(blaze_tcp_close_async(handle), 0);
+        }
+        assert_eq!(blaze_tcp_close_async(listener), -1);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::get_runtime;
+use crate::resources::ResourceTable;
+use parking_lot::Mutex;
+use std::future::Future;
+use std::os::raw::c_int;
+use std::slice;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+/// Returned by `blaze_channel_send` and `blaze_channel_recv` once the channel
+/// is closed (and, for receives, drained).
+pub const BLAZE_CHANNEL_CLOSED: isize = -2;
+
+/// Returned by `blaze_channel_recv` when the next message does not fit in
+/// the output buffer. The message stays queued for the next receive.
+pub const BLAZE_CHANNEL_BUFFER_TOO_SMALL: isize = -3;
+
+struct Channel {
+    /// `None` once the channel has been closed.
+    sender: Mutex<Option<Sender<Vec<u8>>>>,
+    receiver: tokio::sync::Mutex<ReceiverState>,
+}
+
+struct ReceiverState {
+    receiver: Receiver<Vec<u8>>,
+    /// A message taken off the channel that did not fit the caller's buffer.
+    pending: Option<Vec<u8>>,
+}
+
+static CHANNELS: ResourceTable<Arc<Channel>> = ResourceTable::new();
+
+fn channel(handle: i64) -> Option<Arc<Channel>> {
+    CHANNELS.with(handle, Arc::clone)
+}
+
+/// Runs `future` to completion from synchronous code. Inside a runtime
+/// worker (e.g. a task started with `blaze_spawn`) the worker is handed off
+/// with `block_in_place` so other tasks keep running while this one waits.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    match Handle::try_current() {
+        Ok(handle) => tokio::task::block_in_place(|| handle.block_on(future)),
+        Err(_) => get_runtime().block_on(future),
+    }
+}
+
+/// Creates a bounded multi-producer, single-consumer channel holding up to
+/// `capacity` messages and returns its handle, or -1 if `capacity` is 0.
+#[no_mangle]
+pub extern "C" fn blaze_channel_new(capacity: usize) -> i64 {
+    if capacity == 0 {
+        return -1;
+    }
+
+    let (sender, receiver) = mpsc::channel(capacity);
+    CHANNELS.insert(Arc::new(Channel {
+        sender: Mutex::new(Some(sender)),
+        receiver: tokio::sync::Mutex::new(ReceiverState { receiver, pending: None }),
+    }))
+}
+
+/// Sends a copy of the message, waiting while the channel is full.
+/// Returns 0 on success, `BLAZE_CHANNEL_CLOSED` if the channel was closed,
+/// or -1 for an invalid handle or null message.
+///
+/// # Safety
+///
+/// `msg_ptr` must be null or point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_channel_send(handle: i64, msg_ptr: *const u8, len: usize) -> c_int {
+    if msg_ptr.is_null() && len > 0 {
+        return -1;
+    }
+    let channel = match channel(handle) {
+        Some(channel) => channel,
+        None => return -1,
+    };
+    let sender = match channel.sender.lock().clone() {
+        Some(sender) => sender,
+        None => return BLAZE_CHANNEL_CLOSED as c_int,
+    };
+
+    let message = if len == 0 { Vec::new() } else { unsafe { slice::from_raw_parts(msg_ptr, len) }.to_vec() };
+    match block_on(sender.send(message)) {
+        Ok(()) => 0,
+        Err(_) => BLAZE_CHANNEL_CLOSED as c_int,
+    }
+}
+
+/// Receives the next message into `out_ptr`, waiting until one is available,
+/// and returns its length. Returns `BLAZE_CHANNEL_CLOSED` once the channel is
+/// closed and empty, `BLAZE_CHANNEL_BUFFER_TOO_SMALL` if the message is
+/// longer than `out_cap`, or -1 for an invalid handle or null buffer.
+///
+/// # Safety
+///
+/// `out_ptr` must be null or point to `out_cap` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_channel_recv(handle: i64, out_ptr: *mut u8, out_cap: usize) -> isize {
+    if out_ptr.is_null() && out_cap > 0 {
+        return -1;
+    }
+    let channel = match channel(handle) {
+        Some(channel) => channel,
+        None => return -1,
+    };
+
+    block_on(async {
+        let mut state = channel.receiver.lock().await;
+        let message = match state.pending.take() {
+            Some(message) => message,
+            None => match state.receiver.recv().await {
+                Some(message) => message,
+                None => return BLAZE_CHANNEL_CLOSED,
+            },
+        };
+
+        if message.len() > out_cap {
+            state.pending = Some(message);
+            return BLAZE_CHANNEL_BUFFER_TOO_SMALL;
+        }
+        if !message.is_empty() {
+            unsafe { std::ptr::copy_nonoverlapping(message.as_ptr(), out_ptr, message.len()) };
+        }
+        message.len() as isize
+    })
+}
+
+/// Closes the sending side. Messages already queued can still be received;
+/// further sends fail with `BLAZE_CHANNEL_CLOSED`. Returns 0, or -1 for an
+/// invalid handle.
+#[no_mangle]
+pub extern "C" fn blaze_channel_close(handle: i64) -> c_int {
+    match channel(handle) {
+        Some(channel) => {
+            channel.sender.lock().take();
+            0
+        }
+        None => -1,
+    }
+}
+
+/// Releases the handle. Blocked senders and receivers on other threads keep
+/// the channel alive until they return.
+#[no_mangle]
+pub extern "C" fn blaze_channel_free(handle: i64) {
+    CHANNELS.remove(handle);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(handle: i64, message: &str) -> c_int {
+        unsafe { blaze_channel_send(handle, message.as_ptr(), message.len()) }
+    }
+
+    fn recv(handle: i64, capacity: usize) -> Result<String, isize> {
+        let mut buffer = vec![0u8; capacity];
+        match unsafe { blaze_channel_recv(handle, buffer.as_mut_ptr(), buffer.len()) } {
+            len if len >= 0 => Ok(String::from_utf8(buffer[..len as usize].to_vec()).unwrap()),
+            code => Err(code),
+        }
+    }
+
+    #[test]
+    fn test_messages_from_spawned_tasks_arrive_in_order() {
+        const PRODUCERS: usize = 3;
+        const MESSAGES: usize = 5;
+
+        // A capacity below the message count makes the producers block.
+        let handle = blaze_channel_new(2);
+        assert!(handle > 0);
+
+        let tasks: Vec<_> = (0..PRODUCERS)
+            .map(|producer| {
+                get_runtime().spawn(async move {
+                    for n in 0..MESSAGES {
+                        assert_eq!(send(handle, &format!("{}:{}", producer, n)), 0);
+                    }
+                })
+            })
+            .collect();
+
+        let mut next = [0; PRODUCERS];
+        for _ in 0..PRODUCERS * MESSAGES {
+            let message = recv(handle, 16).unwrap();
+            let (producer, n) = message.split_once(':').unwrap();
+            let (producer, n): (usize, usize) = (producer.parse().unwrap(), n.parse().unwrap());
+            assert_eq!(n, next[producer], "messages from producer {} out of order", producer);
+            next[producer] += 1;
+        }
+        assert_eq!(next, [MESSAGES; PRODUCERS]);
+
+        for task in tasks {
+            get_runtime().block_on(task).unwrap();
+        }
+        blaze_channel_free(handle);
+    }
+
+    #[test]
+    fn test_close_drains_queued_messages() {
+        let handle = blaze_channel_new(4);
+        assert_eq!(send(handle, "last"), 0);
+        assert_eq!(blaze_channel_close(handle), 0);
+
+        assert_eq!(send(handle, "late"), BLAZE_CHANNEL_CLOSED as c_int);
+        assert_eq!(recv(handle, 16), Ok("last".to_string()));
+        assert_eq!(recv(handle, 16), Err(BLAZE_CHANNEL_CLOSED));
+        blaze_channel_free(handle);
+    }
+
+    #[test]
+    fn test_small_buffer_keeps_message_queued() {
+        let handle = blaze_channel_new(1);
+        assert_eq!(send(handle, "hello"), 0);
+
+        assert_eq!(recv(handle, 2), Err(BLAZE_CHANNEL_BUFFER_TOO_SMALL));
+        assert_eq!(recv(handle, 8), Ok("hello".to_string()));
+        assert_eq!(blaze_channel_new(0), -1);
+        assert_eq!(unsafe { blaze_channel_send(-1, "x".as_ptr(), 1) }, -1);
+        blaze_channel_free(handle);
+    }
+}
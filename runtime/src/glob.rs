@@ -0,0 +1,129 @@
+use crate::resources::{input_str, write_bytes};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Writes `paths` sorted and newline-separated into `out_ptr`. Returns the
+/// number of bytes written, or -1 if they do not all fit.
+unsafe fn write_paths(mut paths: Vec<PathBuf>, out_ptr: *mut u8, out_cap: usize) -> isize {
+    paths.sort();
+    let listing = paths
+        .iter()
+        .map(|path| path.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("\n");
+    write_bytes(listing.as_bytes(), out_ptr, out_cap)
+}
+
+/// Writes the paths matching a glob pattern, such as `src/**/*.blz`, into
+/// `out_ptr` in sorted order, one per line. `**` matches any number of
+/// directories. Returns the number of bytes written, or -1 for an invalid
+/// pattern or a buffer too small for every match.
+///
+/// # Safety
+///
+/// `pattern_ptr` must be null or point to `pattern_len` readable bytes.
+/// `out_ptr` must be null or point to `out_cap` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_glob(pattern_ptr: *const u8, pattern_len: usize, out_ptr: *mut u8, out_cap: usize) -> isize {
+    let pattern = match unsafe { input_str(pattern_ptr, pattern_len) } {
+        Some(pattern) => pattern,
+        None => return -1,
+    };
+
+    let paths = match ::glob::glob(pattern) {
+        // Entries that cannot be read, e.g. for lack of permission, are skipped.
+        Ok(entries) => entries.filter_map(Result::ok).collect(),
+        Err(_) => return -1,
+    };
+    unsafe { write_paths(paths, out_ptr, out_cap) }
+}
+
+/// Writes every file under `root`, at any depth, into `out_ptr` in sorted
+/// order, one per line. Returns the number of bytes written, or -1 if
+/// `root` is not a readable directory or the buffer is too small.
+///
+/// # Safety
+///
+/// `root_ptr` must be null or point to `root_len` readable bytes. `out_ptr`
+/// must be null or point to `out_cap` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_walk_dir(root_ptr: *const u8, root_len: usize, out_ptr: *mut u8, out_cap: usize) -> isize {
+    let root = match unsafe { input_str(root_ptr, root_len) } {
+        Some(root) => root,
+        None => return -1,
+    };
+
+    let mut files = Vec::new();
+    if walk(Path::new(root), &mut files).is_err() {
+        return -1;
+    }
+    unsafe { write_paths(files, out_ptr, out_cap) }
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // Symlinked directories are listed, not followed, so a link cycle
+        // cannot recurse forever.
+        if entry.file_type()?.is_dir() {
+            walk(&entry.path(), files)?;
+        } else {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_tree() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("blaze_glob_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in ["src/parser", "src/lexer", "docs"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["main.blz", "src/parser/expr.blz", "src/lexer/token.blz", "src/lexer/notes.txt", "docs/guide.md"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        root
+    }
+
+    fn lines(out: &[u8], written: isize) -> Vec<String> {
+        assert!(written >= 0);
+        std::str::from_utf8(&out[..written as usize])
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_recursive_glob_and_walk_list_sorted_paths() {
+        let root = temp_tree();
+        let prefix = root.to_string_lossy().into_owned();
+        let mut out = vec![0u8; 4096];
+
+        let pattern = format!("{}/**/*.blz", prefix);
+        let written = unsafe { blaze_glob(pattern.as_ptr(), pattern.len(), out.as_mut_ptr(), out.len()) };
+        assert_eq!(
+            lines(&out, written),
+            vec![
+                format!("{}/main.blz", prefix),
+                format!("{}/src/lexer/token.blz", prefix),
+                format!("{}/src/parser/expr.blz", prefix),
+            ]
+        );
+
+        let written = unsafe { blaze_walk_dir(prefix.as_ptr(), prefix.len(), out.as_mut_ptr(), out.len()) };
+        assert_eq!(lines(&out, written).len(), 5);
+        assert_eq!(lines(&out, written)[0], format!("{}/docs/guide.md", prefix));
+
+        assert_eq!(unsafe { blaze_glob(pattern.as_ptr(), pattern.len(), out.as_mut_ptr(), 8) }, -1);
+        let invalid = "***";
+        assert_eq!(unsafe { blaze_glob(invalid.as_ptr(), invalid.len(), out.as_mut_ptr(), out.len()) }, -1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
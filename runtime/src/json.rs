@@ -0,0 +1,180 @@
+use crate::resources::{input_str, write_bytes, ResourceTable};
+use serde_json::Value;
+use std::os::raw::c_int;
+
+static JSON_VALUES: ResourceTable<Value> = ResourceTable::new();
+
+/// Parses JSON text and returns a handle to the value, or -1 on invalid input.
+///
+/// # Safety
+///
+/// `in_ptr` must be null or point to `in_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_json_parse(in_ptr: *const u8, in_len: usize) -> i64 {
+    let text = match unsafe { input_str(in_ptr, in_len) } {
+        Some(text) => text,
+        None => return -1,
+    };
+
+    match serde_json::from_str::<Value>(text) {
+        Ok(value) => JSON_VALUES.insert(value),
+        Err(_) => -1,
+    }
+}
+
+/// Returns a new handle to `handle[key]`, or -1 if the value is not an
+/// object or has no such field. The new handle holds its own copy of the
+/// field and must be released with `blaze_json_free`, separately from
+/// `handle`.
+///
+/// # Safety
+///
+/// `key_ptr` must be null or point to `key_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_json_get_field(handle: i64, key_ptr: *const u8, key_len: usize) -> i64 {
+    let key = match unsafe { input_str(key_ptr, key_len) } {
+        Some(key) => key,
+        None => return -1,
+    };
+
+    match JSON_VALUES.with(handle, |value| value.as_object()?.get(key).cloned()).flatten() {
+        Some(field) => JSON_VALUES.insert(field),
+        None => -1,
+    }
+}
+
+/// Returns a new handle to `handle[index]`, or -1 if out of bounds. Like
+/// `blaze_json_get_field`, the new handle must be released with
+/// `blaze_json_free`.
+#[no_mangle]
+pub extern "C" fn blaze_json_get_index(handle: i64, index: usize) -> i64 {
+    match JSON_VALUES.with(handle, |value| value.as_array()?.get(index).cloned()).flatten() {
+        Some(element) => JSON_VALUES.insert(element),
+        None => -1,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn blaze_json_array_len(handle: i64) -> i64 {
+    JSON_VALUES
+        .with(handle, |value| value.as_array().map(|a| a.len() as i64))
+        .flatten()
+        .unwrap_or(-1)
+}
+
+/// Writes the integer value to `out`. Returns 0 on success or -1 if the
+/// value is not an integer.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_json_as_int(handle: i64, out: *mut i64) -> c_int {
+    if out.is_null() {
+        return -1;
+    }
+
+    match JSON_VALUES.with(handle, Value::as_i64).flatten() {
+        Some(n) => {
+            unsafe { *out = n };
+            0
+        }
+        None => -1,
+    }
+}
+
+/// Copies a string value (without quotes) into `out_ptr`.
+///
+/// # Safety
+///
+/// `out_ptr` must be null or point to `out_cap` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_json_as_str(handle: i64, out_ptr: *mut u8, out_cap: usize) -> isize {
+    match JSON_VALUES.with(handle, |value| value.as_str().map(str::to_owned)).flatten() {
+        Some(s) => unsafe { write_bytes(s.as_bytes(), out_ptr, out_cap) },
+        None => -1,
+    }
+}
+
+/// Serializes the value as compact JSON into `out_ptr`.
+///
+/// # Safety
+///
+/// `out_ptr` must be null or point to `out_cap` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_json_stringify(handle: i64, out_ptr: *mut u8, out_cap: usize) -> isize {
+    match JSON_VALUES.with(handle, Value::to_string) {
+        Some(text) => unsafe { write_bytes(text.as_bytes(), out_ptr, out_cap) },
+        None => -1,
+    }
+}
+
+/// Releases a handle from `blaze_json_parse`, `blaze_json_get_field` or
+/// `blaze_json_get_index`. Handles from the accessors stay valid after the
+/// value they came from is freed.
+#[no_mangle]
+pub extern "C" fn blaze_json_free(handle: i64) {
+    JSON_VALUES.remove(handle);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> i64 {
+        unsafe { blaze_json_parse(text.as_ptr(), text.len()) }
+    }
+
+    fn field(handle: i64, key: &str) -> i64 {
+        unsafe { blaze_json_get_field(handle, key.as_ptr(), key.len()) }
+    }
+
+    fn read(f: impl FnOnce(*mut u8, usize) -> isize) -> String {
+        let mut buf = [0u8; 256];
+        let n = f(buf.as_mut_ptr(), buf.len());
+        assert!(n >= 0);
+        String::from_utf8(buf[..n as usize].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_nested_object() {
+        let root = parse(r#"{"name": "blaze", "version": {"major": 2}, "tags": ["a", "b", "c"]}"#);
+        assert!(root > 0);
+
+        let name = field(root, "name");
+        assert_eq!(read(|p, c| unsafe { blaze_json_as_str(name, p, c) }), "blaze");
+
+        let version = field(root, "version");
+        let major = field(version, "major");
+        let mut value = 0;
+        assert_eq!(unsafe { blaze_json_as_int(major, &mut value) }, 0);
+        assert_eq!(value, 2);
+
+        let tags = field(root, "tags");
+        assert_eq!(blaze_json_array_len(tags), 3);
+        let second = blaze_json_get_index(tags, 1);
+        assert_eq!(read(|p, c| unsafe { blaze_json_as_str(second, p, c) }), "b");
+
+        assert_eq!(field(root, "missing"), -1);
+        assert_eq!(unsafe { blaze_json_as_int(name, &mut value) }, -1);
+        for handle in [second, tags, major, version, name, root] {
+            blaze_json_free(handle);
+        }
+    }
+
+    #[test]
+    fn test_stringify_round_trip() {
+        let text = r#"{"a":[1,2,{"b":null}],"c":"d"}"#;
+        let handle = parse(text);
+
+        let output = read(|p, c| unsafe { blaze_json_stringify(handle, p, c) });
+        assert_eq!(output, text);
+        assert!(parse(&output) > 0);
+    }
+
+    #[test]
+    fn test_parse_failure_returns_sentinel() {
+        assert_eq!(parse("{\"unterminated\": "), -1);
+        assert_eq!(blaze_json_array_len(-1), -1);
+    }
+}
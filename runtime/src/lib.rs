@@ -1,43 +1,95 @@
-use std::ffi::CStr;
-use std::io::{self, Write, Read};
+use std::io::{self, Write, Read, Seek, SeekFrom};
 use std::slice;
 use std::fs::File;
-use std::os::raw::{c_char, c_int};
-use std::net::{TcpListener, TcpStream, UdpSocket, SocketAddr};
-use tokio::runtime::{Runtime, Handle};
-use std::sync::Once;
-use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::os::raw::c_int;
+use std::net::{TcpListener, TcpStream, UdpSocket, SocketAddr, ToSocketAddrs};
+use tokio::runtime::{Builder, Runtime};
+use std::sync::{Arc, OnceLock};
+use std::future::Future;
+use std::pin::Pin;
 
-static INIT: Once = Once::new();
-static mut RUNTIME: Option<Runtime> = None;
+pub mod resources;
+pub mod json;
+pub mod regex;
+pub mod channel;
+pub mod rusage;
+pub mod utf8;
+pub mod tasks;
+pub mod async_net;
+pub mod oom;
+pub mod glob;
+pub mod output;
+pub mod timers;
+
+pub use json::*;
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// The worker count for a runtime started without `blaze_runtime_init`: a
+/// positive `BLAZE_WORKER_THREADS`, else one per CPU.
+fn worker_threads(env: Option<&str>) -> usize {
+    env.and_then(|value| value.trim().parse().ok())
+        .filter(|&threads: &usize| threads > 0)
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+}
+
+fn build_runtime(worker_threads: usize) -> io::Result<Runtime> {
+    Builder::new_multi_thread().worker_threads(worker_threads).enable_all().build()
+}
 
 fn get_runtime() -> &'static Runtime {
-    unsafe {
-        INIT.call_once(|| {
-            RUNTIME = Some(Runtime::new().expect("Failed to create Tokio runtime"));
-        });
-        RUNTIME.as_ref().unwrap()
+    RUNTIME.get_or_init(|| {
+        let threads = worker_threads(std::env::var("BLAZE_WORKER_THREADS").ok().as_deref());
+        build_runtime(threads).expect("Failed to create Tokio runtime")
+    })
+}
+
+/// Starts the runtime with `worker_threads` workers, overriding
+/// `BLAZE_WORKER_THREADS`. Must be called before anything else uses the
+/// runtime. Returns 0 if the runtime runs with that many workers, or -1 if
+/// the count is 0 or the runtime had already started with another count.
+#[no_mangle]
+pub extern "C" fn blaze_runtime_init(worker_threads: usize) -> c_int {
+    if worker_threads == 0 {
+        return -1;
+    }
+    // Only the call that builds the runtime picks its size; a later call
+    // just reports whether the running runtime matches.
+    let runtime = RUNTIME.get_or_init(|| build_runtime(worker_threads).expect("Failed to create Tokio runtime"));
+
+    if runtime.metrics().num_workers() == worker_threads {
+        0
+    } else {
+        -1
     }
 }
 
+/// Writes UTF-8 text to stdout; invalid UTF-8 is ignored.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_print(ptr: *const u8, len: usize) {
+pub unsafe extern "C" fn blaze_print(ptr: *const u8, len: usize) {
     if ptr.is_null() {
         return;
     }
     
     unsafe {
         let slice = slice::from_raw_parts(ptr, len);
-        if let Ok(s) = std::str::from_utf8(slice) {
-            print!("{}", s);
-            let _ = io::stdout().flush();
+        if std::str::from_utf8(slice).is_ok() {
+            output::write_stdout(slice);
         }
     }
 }
 
+/// Writes UTF-8 text to stderr; invalid UTF-8 is ignored.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_eprint(ptr: *const u8, len: usize) {
+pub unsafe extern "C" fn blaze_eprint(ptr: *const u8, len: usize) {
     if ptr.is_null() {
         return;
     }
@@ -45,6 +97,8 @@ pub extern "C" fn blaze_eprint(ptr: *const u8, len: usize) {
     unsafe {
         let slice = slice::from_raw_parts(ptr, len);
         if let Ok(s) = std::str::from_utf8(slice) {
+            // Keep stdout and stderr in the order they were written.
+            let _ = output::flush_stdout();
             eprint!("{}", s);
             let _ = io::stderr().flush();
         }
@@ -53,6 +107,8 @@ pub extern "C" fn blaze_eprint(ptr: *const u8, len: usize) {
 
 #[no_mangle]
 pub extern "C" fn blaze_read_char() -> u32 {
+    // Show any prompt before blocking on input.
+    let _ = output::flush_stdout();
     let mut buffer = [0u8; 1];
     match io::stdin().read_exact(&mut buffer) {
         Ok(_) => buffer[0] as u32,
@@ -60,8 +116,14 @@ pub extern "C" fn blaze_read_char() -> u32 {
     }
 }
 
+/// Opens `path` for reading. Returns a file handle, which is a pointer to
+/// the boxed `File` and so needs all 64 bits, or -1 on failure.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_file_open(ptr: *const u8, len: usize) -> c_int {
+pub unsafe extern "C" fn blaze_file_open(ptr: *const u8, len: usize) -> i64 {
     if ptr.is_null() {
         return -1;
     }
@@ -70,10 +132,7 @@ pub extern "C" fn blaze_file_open(ptr: *const u8, len: usize) -> c_int {
         let slice = slice::from_raw_parts(ptr, len);
         if let Ok(path) = std::str::from_utf8(slice) {
             match File::open(path) {
-                Ok(file) => {
-                    let fd = Box::into_raw(Box::new(file)) as c_int;
-                    fd
-                }
+                Ok(file) => Box::into_raw(Box::new(file)) as i64,
                 Err(_) => -1,
             }
         } else {
@@ -82,8 +141,14 @@ pub extern "C" fn blaze_file_open(ptr: *const u8, len: usize) -> c_int {
     }
 }
 
+/// Creates or truncates `path` for writing. Returns a file handle as for
+/// `blaze_file_open`, or -1 on failure.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_file_create(ptr: *const u8, len: usize) -> c_int {
+pub unsafe extern "C" fn blaze_file_create(ptr: *const u8, len: usize) -> i64 {
     if ptr.is_null() {
         return -1;
     }
@@ -92,10 +157,7 @@ pub extern "C" fn blaze_file_create(ptr: *const u8, len: usize) -> c_int {
         let slice = slice::from_raw_parts(ptr, len);
         if let Ok(path) = std::str::from_utf8(slice) {
             match File::create(path) {
-                Ok(file) => {
-                    let fd = Box::into_raw(Box::new(file)) as c_int;
-                    fd
-                }
+                Ok(file) => Box::into_raw(Box::new(file)) as i64,
                 Err(_) => -1,
             }
         } else {
@@ -104,8 +166,14 @@ pub extern "C" fn blaze_file_create(ptr: *const u8, len: usize) -> c_int {
     }
 }
 
+/// Reads up to `len` bytes into `ptr`. Returns the number read, or -1.
+///
+/// # Safety
+///
+/// `fd` must be a handle from `blaze_file_open` or `blaze_file_create` that
+/// has not been closed. `ptr` must be null or point to `len` writable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_file_read(fd: c_int, ptr: *mut u8, len: usize) -> isize {
+pub unsafe extern "C" fn blaze_file_read(fd: i64, ptr: *mut u8, len: usize) -> isize {
     if ptr.is_null() {
         return -1;
     }
@@ -121,8 +189,14 @@ pub extern "C" fn blaze_file_read(fd: c_int, ptr: *mut u8, len: usize) -> isize
     }
 }
 
+/// Writes up to `len` bytes from `ptr`. Returns the number written, or -1.
+///
+/// # Safety
+///
+/// `fd` must be a handle from `blaze_file_open` or `blaze_file_create` that
+/// has not been closed. `ptr` must be null or point to `len` readable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_file_write(fd: c_int, ptr: *const u8, len: usize) -> isize {
+pub unsafe extern "C" fn blaze_file_write(fd: i64, ptr: *const u8, len: usize) -> isize {
     if ptr.is_null() {
         return -1;
     }
@@ -138,16 +212,60 @@ pub extern "C" fn blaze_file_write(fd: c_int, ptr: *const u8, len: usize) -> isi
     }
 }
 
+/// Moves the file position to `offset` bytes from the start (`whence` 0),
+/// the current position (1) or the end (2). Returns the new position from
+/// the start, or -1 for an unknown `whence` or a position before the start.
 #[no_mangle]
-pub extern "C" fn blaze_file_close(fd: c_int) -> c_int {
+pub extern "C" fn blaze_file_seek(fd: i64, offset: i64, whence: c_int) -> i64 {
+    let target = match whence {
+        0 => match u64::try_from(offset) {
+            Ok(offset) => SeekFrom::Start(offset),
+            Err(_) => return -1,
+        },
+        1 => SeekFrom::Current(offset),
+        2 => SeekFrom::End(offset),
+        _ => return -1,
+    };
+    
+    unsafe {
+        let file = &mut *(fd as *mut File);
+        
+        match file.seek(target) {
+            Ok(position) => position as i64,
+            Err(_) => -1,
+        }
+    }
+}
+
+/// Flushes buffered writes to the file and its data to disk. Returns 0, or
+/// -1 on failure.
+#[no_mangle]
+pub extern "C" fn blaze_file_flush(fd: i64) -> c_int {
+    unsafe {
+        let file = &mut *(fd as *mut File);
+        
+        match file.flush().and_then(|_| file.sync_data()) {
+            Ok(_) => 0,
+            Err(_) => -1,
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn blaze_file_close(fd: i64) -> c_int {
     unsafe {
         let _ = Box::from_raw(fd as *mut File);
         0
     }
 }
 
+/// Binds a TCP listener to `ip:port`. Returns its handle, or -1.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_tcp_bind(ptr: *const u8, len: usize) -> c_int {
+pub unsafe extern "C" fn blaze_tcp_bind(ptr: *const u8, len: usize) -> i64 {
     if ptr.is_null() {
         return -1;
     }
@@ -158,7 +276,7 @@ pub extern "C" fn blaze_tcp_bind(ptr: *const u8, len: usize) -> c_int {
             if let Ok(addr) = addr_str.parse::<SocketAddr>() {
                 match TcpListener::bind(addr) {
                     Ok(listener) => {
-                        Box::into_raw(Box::new(listener)) as c_int
+                        Box::into_raw(Box::new(listener)) as i64
                     }
                     Err(_) => -1,
                 }
@@ -171,12 +289,22 @@ pub extern "C" fn blaze_tcp_bind(ptr: *const u8, len: usize) -> c_int {
     }
 }
 
+/// Waits for a connection on a listener from `blaze_tcp_bind`. The peer's
+/// `ip:port` is written to `addr_buf`, truncated to `*addr_len` bytes, and
+/// `*addr_len` is set to the length written. Returns the stream handle, or
+/// -1.
+///
+/// # Safety
+///
+/// `fd` must be a handle from `blaze_tcp_bind`. `addr_buf` must be null
+/// or point to `*addr_len` writable bytes, and `addr_len` must be null or
+/// valid for reads and writes.
 #[no_mangle]
-pub extern "C" fn blaze_tcp_accept(
-    fd: c_int,
+pub unsafe extern "C" fn blaze_tcp_accept(
+    fd: i64,
     addr_buf: *mut u8,
     addr_len: *mut usize,
-) -> c_int {
+) -> i64 {
     if addr_buf.is_null() || addr_len.is_null() {
         return -1;
     }
@@ -197,40 +325,125 @@ pub extern "C" fn blaze_tcp_accept(
                 );
                 *addr_len = copy_len;
                 
-                Box::into_raw(Box::new(stream)) as c_int
+                Box::into_raw(Box::new(stream)) as i64
             }
             Err(_) => -1,
         }
     }
 }
 
+/// Returned by `blaze_resolve_host` when the host resolved but not even its
+/// first address fits in the output buffer, as opposed to -1 when the lookup
+/// itself fails.
+pub const BLAZE_RESOLVE_BUFFER_TOO_SMALL: isize = -3;
+
+/// Resolves `host` and writes each address as a newline-separated `ip:port`
+/// string into `out_ptr`. Only whole entries are written. Returns the number
+/// of bytes written, -1 if the host cannot be resolved, or
+/// `BLAZE_RESOLVE_BUFFER_TOO_SMALL`.
+///
+/// # Safety
+///
+/// `host_ptr` must be null or point to `host_len` readable bytes. `out_ptr`
+/// must be null or point to `out_cap` writable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_tcp_connect(ptr: *const u8, len: usize) -> c_int {
-    if ptr.is_null() {
+pub unsafe extern "C" fn blaze_resolve_host(
+    host_ptr: *const u8,
+    host_len: usize,
+    port: u16,
+    out_ptr: *mut u8,
+    out_cap: usize,
+) -> isize {
+    if host_ptr.is_null() || out_ptr.is_null() {
         return -1;
     }
     
     unsafe {
-        let slice = slice::from_raw_parts(ptr, len);
-        if let Ok(addr_str) = std::str::from_utf8(slice) {
-            if let Ok(addr) = addr_str.parse::<SocketAddr>() {
-                match TcpStream::connect(addr) {
-                    Ok(stream) => {
-                        Box::into_raw(Box::new(stream)) as c_int
-                    }
-                    Err(_) => -1,
-                }
+        let slice = slice::from_raw_parts(host_ptr, host_len);
+        let host = match std::str::from_utf8(slice) {
+            Ok(host) => host,
+            Err(_) => return -1,
+        };
+        
+        let addrs = match (host, port).to_socket_addrs() {
+            Ok(addrs) => addrs,
+            Err(_) => return -1,
+        };
+        
+        let out = slice::from_raw_parts_mut(out_ptr, out_cap);
+        let mut written = 0;
+        let mut resolved = false;
+        
+        for addr in addrs {
+            resolved = true;
+            let entry = if written == 0 {
+                addr.to_string()
             } else {
-                -1
+                format!("\n{}", addr)
+            };
+            let bytes = entry.as_bytes();
+            if written + bytes.len() > out_cap {
+                break;
             }
+            out[written..written + bytes.len()].copy_from_slice(bytes);
+            written += bytes.len();
+        }
+        
+        if written > 0 {
+            written as isize
+        } else if resolved {
+            BLAZE_RESOLVE_BUFFER_TOO_SMALL
         } else {
             -1
         }
     }
 }
 
+/// Connects to `ip:port` or `host:port`, resolving the hostname first and
+/// trying each resolved address in turn.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_tcp_connect(ptr: *const u8, len: usize) -> i64 {
+    if ptr.is_null() {
+        return -1;
+    }
+    
+    unsafe {
+        let slice = slice::from_raw_parts(ptr, len);
+        let addr_str = match std::str::from_utf8(slice) {
+            Ok(addr_str) => addr_str,
+            Err(_) => return -1,
+        };
+        
+        let addrs: Vec<SocketAddr> = match addr_str.parse::<SocketAddr>() {
+            Ok(addr) => vec![addr],
+            Err(_) => match addr_str.to_socket_addrs() {
+                Ok(addrs) => addrs.collect(),
+                Err(_) => return -1,
+            },
+        };
+        
+        match TcpStream::connect(&addrs[..]) {
+            Ok(stream) => {
+                Box::into_raw(Box::new(stream)) as i64
+            }
+            Err(_) => -1,
+        }
+    }
+}
+
+/// Reads up to `len` bytes into `ptr`. Returns the number read, or -1.
+///
+/// # Safety
+///
+/// `fd` must be a stream handle from `blaze_tcp_connect` or
+/// `blaze_tcp_accept` that has not been closed. `ptr` must be null or point
+/// to `len` writable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_tcp_read(fd: c_int, ptr: *mut u8, len: usize) -> isize {
+pub unsafe extern "C" fn blaze_tcp_read(fd: i64, ptr: *mut u8, len: usize) -> isize {
     if ptr.is_null() {
         return -1;
     }
@@ -246,8 +459,15 @@ pub extern "C" fn blaze_tcp_read(fd: c_int, ptr: *mut u8, len: usize) -> isize {
     }
 }
 
+/// Writes up to `len` bytes from `ptr`. Returns the number written, or -1.
+///
+/// # Safety
+///
+/// `fd` must be a stream handle from `blaze_tcp_connect` or
+/// `blaze_tcp_accept` that has not been closed. `ptr` must be null or point
+/// to `len` readable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_tcp_write(fd: c_int, ptr: *const u8, len: usize) -> isize {
+pub unsafe extern "C" fn blaze_tcp_write(fd: i64, ptr: *const u8, len: usize) -> isize {
     if ptr.is_null() {
         return -1;
     }
@@ -264,7 +484,7 @@ pub extern "C" fn blaze_tcp_write(fd: c_int, ptr: *const u8, len: usize) -> isiz
 }
 
 #[no_mangle]
-pub extern "C" fn blaze_tcp_shutdown(fd: c_int) -> c_int {
+pub extern "C" fn blaze_tcp_shutdown(fd: i64) -> c_int {
     unsafe {
         let stream = &*(fd as *const TcpStream);
         match stream.shutdown(std::net::Shutdown::Both) {
@@ -275,14 +495,75 @@ pub extern "C" fn blaze_tcp_shutdown(fd: c_int) -> c_int {
 }
 
 #[no_mangle]
-pub extern "C" fn blaze_close(fd: c_int) {
+pub extern "C" fn blaze_close(fd: i64) {
     unsafe {
         let _ = Box::from_raw(fd as *mut TcpStream);
     }
 }
 
+/// Returned by the socket option setters for a null or negative handle, as
+/// opposed to -1 when the OS rejects the option.
+pub const BLAZE_SOCKET_INVALID: c_int = -2;
+
+unsafe fn tcp_stream<'a>(fd: i64) -> Option<&'a TcpStream> {
+    if fd <= 0 {
+        return None;
+    }
+    Some(&*(fd as *const TcpStream))
+}
+
+fn timeout(ms: u64) -> Option<std::time::Duration> {
+    if ms == 0 {
+        None
+    } else {
+        Some(std::time::Duration::from_millis(ms))
+    }
+}
+
+fn socket_option(fd: i64, set: impl FnOnce(&TcpStream) -> io::Result<()>) -> c_int {
+    match unsafe { tcp_stream(fd) } {
+        Some(stream) => match set(stream) {
+            Ok(()) => 0,
+            Err(_) => -1,
+        },
+        None => BLAZE_SOCKET_INVALID,
+    }
+}
+
+/// Makes reads on the stream fail with -1 after waiting `ms` milliseconds
+/// for data; 0 waits forever again. Returns 0, -1 if the OS rejects it, or
+/// `BLAZE_SOCKET_INVALID`.
 #[no_mangle]
-pub extern "C" fn blaze_udp_bind(ptr: *const u8, len: usize) -> c_int {
+pub extern "C" fn blaze_tcp_set_read_timeout(fd: i64, ms: u64) -> c_int {
+    socket_option(fd, |stream| stream.set_read_timeout(timeout(ms)))
+}
+
+/// As `blaze_tcp_set_read_timeout`, for writes.
+#[no_mangle]
+pub extern "C" fn blaze_tcp_set_write_timeout(fd: i64, ms: u64) -> c_int {
+    socket_option(fd, |stream| stream.set_write_timeout(timeout(ms)))
+}
+
+/// Turns `TCP_NODELAY` on (non-zero `on`) to send small writes at once
+/// rather than batching them, or off. Returns as for the timeout setters.
+#[no_mangle]
+pub extern "C" fn blaze_tcp_set_nodelay(fd: i64, on: c_int) -> c_int {
+    socket_option(fd, |stream| stream.set_nodelay(on != 0))
+}
+
+/// Bound UDP sockets, addressed by the handle `blaze_udp_bind` returns.
+/// Sends and receives clone the socket out of the table so a blocked
+/// receive doesn't hold the table lock.
+static UDP_SOCKETS: resources::ResourceTable<Arc<UdpSocket>> = resources::ResourceTable::new();
+
+/// Binds a UDP socket to `ip:port` and returns its handle, or -1 if the
+/// address is invalid or cannot be bound.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_udp_bind(ptr: *const u8, len: usize) -> i64 {
     if ptr.is_null() {
         return -1;
     }
@@ -292,9 +573,7 @@ pub extern "C" fn blaze_udp_bind(ptr: *const u8, len: usize) -> c_int {
         if let Ok(addr_str) = std::str::from_utf8(slice) {
             if let Ok(addr) = addr_str.parse::<SocketAddr>() {
                 match UdpSocket::bind(addr) {
-                    Ok(socket) => {
-                        Box::into_raw(Box::new(socket)) as c_int
-                    }
+                    Ok(socket) => UDP_SOCKETS.insert(Arc::new(socket)),
                     Err(_) => -1,
                 }
             } else {
@@ -306,38 +585,198 @@ pub extern "C" fn blaze_udp_bind(ptr: *const u8, len: usize) -> c_int {
     }
 }
 
+/// Sends `len` bytes from `ptr` as one datagram to the `ip:port` address at
+/// `addr_ptr`. Returns the number of bytes sent, or -1 for a null pointer,
+/// an invalid socket or address, or a failed send.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes. `addr_ptr` must be
+/// null or point to `addr_len` readable bytes.
 #[no_mangle]
-pub extern "C" fn blaze_spawn(task_ptr: *mut u8) {
-    let runtime = get_runtime();
+pub unsafe extern "C" fn blaze_udp_send_to(
+    fd: i64,
+    ptr: *const u8,
+    len: usize,
+    addr_ptr: *const u8,
+    addr_len: usize,
+) -> isize {
+    if ptr.is_null() || addr_ptr.is_null() {
+        return -1;
+    }
     
-    runtime.spawn(async move {
-        unsafe {
-            let _task = Box::from_raw(task_ptr);
+    unsafe {
+        let buffer = slice::from_raw_parts(ptr, len);
+        let addr = match std::str::from_utf8(slice::from_raw_parts(addr_ptr, addr_len)) {
+            Ok(addr_str) => match addr_str.parse::<SocketAddr>() {
+                Ok(addr) => addr,
+                Err(_) => return -1,
+            },
+            Err(_) => return -1,
+        };
+        
+        let socket = match UDP_SOCKETS.with(fd, Arc::clone) {
+            Some(socket) => socket,
+            None => return -1,
+        };
+        match socket.send_to(buffer, addr) {
+            Ok(n) => n as isize,
+            Err(_) => -1,
         }
-    });
+    }
 }
 
+/// Waits for a datagram and copies up to `len` bytes of it into `ptr`; the
+/// rest of a longer datagram is discarded. The sender's `ip:port` is written
+/// to `addr_buf`, truncated to `*addr_len` bytes, and `*addr_len` is set to
+/// the length written. Returns the number of bytes received, or -1.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` writable bytes. `addr_buf` must be
+/// null or point to `*addr_len` writable bytes, and `addr_len` must be null
+/// or valid for reads and writes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_udp_recv_from(
+    fd: i64,
+    ptr: *mut u8,
+    len: usize,
+    addr_buf: *mut u8,
+    addr_len: *mut usize,
+) -> isize {
+    if ptr.is_null() || addr_buf.is_null() || addr_len.is_null() {
+        return -1;
+    }
+    
+    unsafe {
+        let buffer = slice::from_raw_parts_mut(ptr, len);
+        let socket = match UDP_SOCKETS.with(fd, Arc::clone) {
+            Some(socket) => socket,
+            None => return -1,
+        };
+        
+        match socket.recv_from(buffer) {
+            Ok((n, addr)) => {
+                let addr_string = addr.to_string();
+                let bytes = addr_string.as_bytes();
+                let copy_len = bytes.len().min(*addr_len);
+                
+                std::ptr::copy_nonoverlapping(
+                    bytes.as_ptr(),
+                    addr_buf,
+                    copy_len,
+                );
+                *addr_len = copy_len;
+                
+                n as isize
+            }
+            Err(_) => -1,
+        }
+    }
+}
+
+/// Closes a socket from `blaze_udp_bind`. Returns 0, or -1 for an invalid
+/// or already closed handle.
+#[no_mangle]
+pub extern "C" fn blaze_udp_close(fd: i64) -> c_int {
+    match UDP_SOCKETS.remove(fd) {
+        Some(_) => 0,
+        None => -1,
+    }
+}
+
+/// A spawned BLAZE task: a C function called once with its environment.
+pub type TaskFn = extern "C" fn(env: *mut u8);
+
+/// Runs `task(env)` on the runtime and returns a handle for `blaze_join`,
+/// or 0 if `task` is null. The task owns `env` from here on and must free
+/// it itself.
+#[no_mangle]
+pub extern "C" fn blaze_spawn(task: Option<TaskFn>, env: *mut u8) -> u64 {
+    let task = match task {
+        Some(task) => task,
+        None => return 0,
+    };
+    // Raw pointers are not `Send`; the address is handed to the worker as is.
+    let env = env as usize;
+    tasks::spawn_task(async move {
+        task(env as *mut u8);
+        0
+    }) as u64
+}
+
+/// A future handed to BLAZE code as an opaque pointer. The pointer owns it:
+/// `blaze_await` runs and frees it, and `blaze_future_drop` frees one that
+/// will never be awaited. Using the pointer after either is undefined.
+type BlazeFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+fn into_future_ptr(future: impl Future<Output = ()> + Send + 'static) -> *mut u8 {
+    let future: BlazeFuture = Box::pin(future);
+    Box::into_raw(Box::new(future)) as *mut u8
+}
+
+/// Returns a future that completes `duration_ms` milliseconds after it is
+/// first awaited. See `BlazeFuture` for who frees it.
 #[no_mangle]
 pub extern "C" fn blaze_sleep(duration_ms: u64) -> *mut u8 {
-    std::ptr::null_mut()
+    // The timer is created on first poll, when the runtime is current.
+    into_future_ptr(async move { tokio::time::sleep(std::time::Duration::from_millis(duration_ms)).await })
 }
 
+/// Returns a future that gives other tasks one chance to run before it
+/// completes. See `BlazeFuture` for who frees it.
 #[no_mangle]
 pub extern "C" fn blaze_yield() -> *mut u8 {
-    std::ptr::null_mut()
+    into_future_ptr(tokio::task::yield_now())
 }
 
+/// Runs a future from `blaze_sleep` or `blaze_yield` to completion on the
+/// runtime, then frees it. Does nothing for null.
+///
+/// # Safety
+///
+/// `ptr` must be null or a future from `blaze_sleep` or `blaze_yield` that
+/// has not been awaited or dropped.
 #[no_mangle]
-pub extern "C" fn blaze_await(ptr: *mut u8) {
+pub unsafe extern "C" fn blaze_await(ptr: *mut u8) {
+    if ptr.is_null() {
+        return;
+    }
+    let future = unsafe { Box::from_raw(ptr as *mut BlazeFuture) };
+    channel::block_on(*future);
 }
 
+/// Frees a future without running it. Does nothing for null.
+///
+/// # Safety
+///
+/// `ptr` must be null or a future from `blaze_sleep` or `blaze_yield` that
+/// has not been awaited or dropped.
 #[no_mangle]
-pub extern "C" fn blaze_join(task_id: u64) -> *mut u8 {
-    std::ptr::null_mut()
+pub unsafe extern "C" fn blaze_future_drop(ptr: *mut u8) {
+    if !ptr.is_null() {
+        unsafe { drop(Box::from_raw(ptr as *mut BlazeFuture)) };
+    }
 }
 
+/// Waits for a task started with `blaze_spawn` to finish. Returns 0 once it
+/// has, or -1 for an invalid or already joined handle, or if it panicked.
 #[no_mangle]
-pub extern "C" fn blaze_panic(ptr: *const u8, len: usize) -> ! {
+pub extern "C" fn blaze_join(task_id: u64) -> c_int {
+    match i64::try_from(task_id) {
+        Ok(handle) => tasks::blaze_task_join(handle) as c_int,
+        Err(_) => -1,
+    }
+}
+
+/// Prints the message to stderr and aborts the process.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_panic(ptr: *const u8, len: usize) -> ! {
+    let _ = output::flush_stdout();
     unsafe {
         let slice = slice::from_raw_parts(ptr, len);
         if let Ok(s) = std::str::from_utf8(slice) {
@@ -348,27 +787,305 @@ pub extern "C" fn blaze_panic(ptr: *const u8, len: usize) -> ! {
     }
 }
 
+/// Allocates `size` bytes. On failure the OOM handler runs (see
+/// `blaze_set_oom_handler`), and null is returned only if it returns.
 #[no_mangle]
 pub extern "C" fn blaze_alloc(size: usize, align: usize) -> *mut u8 {
-    unsafe {
+    let ptr = unsafe {
         std::alloc::alloc(std::alloc::Layout::from_size_align_unchecked(size, align))
+    };
+    if ptr.is_null() {
+        oom::out_of_memory(size);
+    } else {
+        rusage::track_alloc(size);
+    }
+    ptr
+}
+
+/// Like `blaze_alloc`, with the memory zeroed.
+#[no_mangle]
+pub extern "C" fn blaze_alloc_zeroed(size: usize, align: usize) -> *mut u8 {
+    let ptr = unsafe {
+        std::alloc::alloc_zeroed(std::alloc::Layout::from_size_align_unchecked(size, align))
+    };
+    if ptr.is_null() {
+        oom::out_of_memory(size);
+    } else {
+        rusage::track_alloc(size);
     }
+    ptr
 }
 
+/// Frees memory from `blaze_alloc`, `blaze_alloc_zeroed` or `blaze_realloc`.
+///
+/// # Safety
+///
+/// `ptr` must be null or an allocation from this allocator with the given
+/// `size` and `align`, not yet freed.
 #[no_mangle]
-pub extern "C" fn blaze_dealloc(ptr: *mut u8, size: usize, align: usize) {
+pub unsafe extern "C" fn blaze_dealloc(ptr: *mut u8, size: usize, align: usize) {
     unsafe {
         std::alloc::dealloc(ptr, std::alloc::Layout::from_size_align_unchecked(size, align));
     }
+    rusage::track_dealloc(size);
 }
 
+/// Resizes an allocation. On failure the OOM handler runs and, if it
+/// returns, null is returned with the old allocation left intact.
+///
+/// # Safety
+///
+/// `ptr` must be null or an allocation from this allocator with the given
+/// `old_size` and `align`, not yet freed.
 #[no_mangle]
-pub extern "C" fn blaze_realloc(ptr: *mut u8, old_size: usize, align: usize, new_size: usize) -> *mut u8 {
-    unsafe {
+pub unsafe extern "C" fn blaze_realloc(ptr: *mut u8, old_size: usize, align: usize, new_size: usize) -> *mut u8 {
+    let new_ptr = unsafe {
         std::alloc::realloc(
             ptr,
             std::alloc::Layout::from_size_align_unchecked(old_size, align),
             new_size,
         )
+    };
+    if new_ptr.is_null() {
+        oom::out_of_memory(new_size);
+    } else {
+        rusage::track_dealloc(old_size);
+        rusage::track_alloc(new_size);
+    }
+    new_ptr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_resolve_localhost() {
+        let host = "localhost";
+        let mut out = [0u8; 256];
+
+        let written = unsafe { blaze_resolve_host(host.as_ptr(), host.len(), 8080, out.as_mut_ptr(), out.len()) };
+        assert!(written > 0);
+
+        let text = std::str::from_utf8(&out[..written as usize]).unwrap();
+        for entry in text.lines() {
+            let addr: SocketAddr = entry.parse().unwrap();
+            assert!(addr.ip().is_loopback());
+            assert_eq!(addr.port(), 8080);
+        }
+    }
+
+    #[test]
+    fn test_resolve_into_too_small_buffer() {
+        let host = "127.0.0.1";
+        let mut out = [0u8; 4];
+
+        assert_eq!(
+            unsafe { blaze_resolve_host(host.as_ptr(), host.len(), 8080, out.as_mut_ptr(), out.len()) },
+            BLAZE_RESOLVE_BUFFER_TOO_SMALL
+        );
+    }
+
+    #[test]
+    fn test_resolve_unknown_host_fails() {
+        let host = "no-such-host.invalid";
+        let mut out = [0u8; 64];
+
+        assert_eq!(unsafe { blaze_resolve_host(host.as_ptr(), host.len(), 80, out.as_mut_ptr(), out.len()) }, -1);
+    }
+
+    #[test]
+    fn test_tcp_connect_by_hostname() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+
+        let fd = unsafe { blaze_tcp_connect(addr.as_ptr(), addr.len()) };
+        assert_ne!(fd, -1);
+        assert!(listener.accept().is_ok());
+    }
+
+    #[test]
+    fn test_file_seek_rewrites_and_rereads_from_an_offset() {
+        let path = std::env::temp_dir().join(format!("blaze_seek_{}", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+
+        let fd = unsafe { blaze_file_create(path.as_ptr(), path.len()) };
+        assert_ne!(fd, -1);
+        let data = b"hello world";
+        assert_eq!(unsafe { blaze_file_write(fd, data.as_ptr(), data.len()) }, 11);
+        assert_eq!(blaze_file_seek(fd, 0, 0), 0);
+        assert_eq!(unsafe { blaze_file_write(fd, b"J".as_ptr(), 1) }, 1);
+        assert_eq!(blaze_file_seek(fd, 0, 2), 11);
+        assert_eq!(blaze_file_flush(fd), 0);
+        assert_eq!(blaze_file_close(fd), 0);
+
+        let fd = unsafe { blaze_file_open(path.as_ptr(), path.len()) };
+        let mut buffer = [0u8; 11];
+        assert_eq!(unsafe { blaze_file_read(fd, buffer.as_mut_ptr(), buffer.len()) }, 11);
+        assert_eq!(&buffer, b"Jello world");
+
+        assert_eq!(blaze_file_seek(fd, 0, 0), 0);
+        assert_eq!(unsafe { blaze_file_read(fd, buffer.as_mut_ptr(), 5) }, 5);
+        assert_eq!(&buffer[..5], b"Jello");
+        assert_eq!(blaze_file_seek(fd, 1, 1), 6);
+        assert_eq!(unsafe { blaze_file_read(fd, buffer.as_mut_ptr(), 5) }, 5);
+        assert_eq!(&buffer[..5], b"world");
+
+        assert_eq!(blaze_file_seek(fd, -1, 0), -1);
+        assert_eq!(blaze_file_seek(fd, 0, 3), -1);
+        blaze_file_close(fd);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn udp_port(fd: i64) -> String {
+        let port = UDP_SOCKETS.with(fd, |socket| socket.local_addr().unwrap().port()).unwrap();
+        format!("127.0.0.1:{}", port)
+    }
+
+    #[test]
+    fn test_udp_datagram_round_trip() {
+        let any = "127.0.0.1:0";
+        let (sender, receiver) = (unsafe { blaze_udp_bind(any.as_ptr(), any.len()) }, unsafe { blaze_udp_bind(any.as_ptr(), any.len()) });
+        assert!(sender > 0 && receiver > 0);
+        let (from, to) = (udp_port(sender), udp_port(receiver));
+
+        let message = b"ping";
+        assert_eq!(unsafe { blaze_udp_send_to(sender, message.as_ptr(), message.len(), to.as_ptr(), to.len()) }, 4);
+
+        let mut buffer = [0u8; 16];
+        let mut addr = [0u8; 64];
+        let mut addr_len = addr.len();
+        let received = unsafe { blaze_udp_recv_from(receiver, buffer.as_mut_ptr(), buffer.len(), addr.as_mut_ptr(), &mut addr_len) };
+        assert_eq!(&buffer[..received as usize], message);
+        assert_eq!(std::str::from_utf8(&addr[..addr_len]).unwrap(), from);
+
+        let invalid = "not an address";
+        assert_eq!(unsafe { blaze_udp_send_to(sender, message.as_ptr(), message.len(), invalid.as_ptr(), invalid.len()) }, -1);
+        assert_eq!(unsafe { blaze_udp_send_to(sender, std::ptr::null(), 0, to.as_ptr(), to.len()) }, -1);
+        assert_eq!(blaze_udp_close(sender), 0);
+        assert_eq!(unsafe { blaze_udp_send_to(sender, message.as_ptr(), message.len(), to.as_ptr(), to.len()) }, -1);
+        assert_eq!(blaze_udp_close(receiver), 0);
+    }
+
+    #[test]
+    fn test_pending_receive_does_not_block_other_sockets() {
+        let any = "127.0.0.1:0";
+        let receiver = unsafe { blaze_udp_bind(any.as_ptr(), any.len()) };
+        let to = udp_port(receiver);
+
+        let pending = std::thread::spawn(move || {
+            let mut buffer = [0u8; 16];
+            let mut addr = [0u8; 64];
+            let mut addr_len = addr.len();
+            let received =
+                unsafe { blaze_udp_recv_from(receiver, buffer.as_mut_ptr(), buffer.len(), addr.as_mut_ptr(), &mut addr_len) };
+            buffer[..received as usize].to_vec()
+        });
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        // Binding and sending go through the socket table while the receive waits.
+        let sender = unsafe { blaze_udp_bind(any.as_ptr(), any.len()) };
+        assert!(sender > 0);
+        let message = b"late";
+        assert_eq!(unsafe { blaze_udp_send_to(sender, message.as_ptr(), message.len(), to.as_ptr(), to.len()) }, 4);
+
+        assert_eq!(pending.join().unwrap(), message);
+        assert_eq!(blaze_udp_close(sender), 0);
+        assert_eq!(blaze_udp_close(receiver), 0);
+    }
+
+    #[test]
+    fn test_read_on_silent_socket_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let fd = unsafe { blaze_tcp_connect(addr.as_ptr(), addr.len()) };
+        assert!(fd > 0);
+        let _silent_peer = listener.accept().unwrap();
+
+        assert_eq!(blaze_tcp_set_nodelay(fd, 1), 0);
+        assert_eq!(blaze_tcp_set_write_timeout(fd, 0), 0);
+        assert_eq!(blaze_tcp_set_read_timeout(fd, 50), 0);
+
+        let mut buffer = [0u8; 8];
+        let start = std::time::Instant::now();
+        assert_eq!(unsafe { blaze_tcp_read(fd, buffer.as_mut_ptr(), buffer.len()) }, -1);
+        let elapsed = start.elapsed();
+        assert!(elapsed >= std::time::Duration::from_millis(40), "{:?}", elapsed);
+        assert!(elapsed < std::time::Duration::from_secs(5), "{:?}", elapsed);
+
+        assert_eq!(blaze_tcp_set_read_timeout(-1, 50), BLAZE_SOCKET_INVALID);
+        assert_eq!(blaze_tcp_set_nodelay(0, 1), BLAZE_SOCKET_INVALID);
+        blaze_close(fd);
+    }
+
+    #[test]
+    fn test_worker_threads_from_environment() {
+        assert_eq!(worker_threads(Some("3")), 3);
+
+        let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+        for invalid in [None, Some("0"), Some("many")] {
+            assert_eq!(worker_threads(invalid), cpus);
+        }
+    }
+
+    #[test]
+    fn test_late_runtime_init_does_not_change_worker_count() {
+        let workers = get_runtime().metrics().num_workers();
+        assert_eq!(blaze_runtime_init(workers + 1), -1);
+        assert_eq!(get_runtime().metrics().num_workers(), workers);
+        assert_eq!(blaze_runtime_init(workers), 0);
+    }
+
+    #[test]
+    fn test_runtime_uses_configured_worker_count() {
+        let runtime = build_runtime(3).unwrap();
+        assert_eq!(runtime.metrics().num_workers(), 3);
+
+        // All three tasks must be running at once to get past the barrier.
+        let barrier = std::sync::Arc::new(std::sync::Barrier::new(3));
+        let tasks: Vec<_> = (0..3)
+            .map(|_| {
+                let barrier = barrier.clone();
+                runtime.spawn(async move {
+                    barrier.wait();
+                })
+            })
+            .collect();
+        for task in tasks {
+            runtime.block_on(task).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_awaiting_sleep_waits_for_its_duration() {
+        let start = std::time::Instant::now();
+        unsafe { blaze_await(blaze_sleep(50)) };
+        let elapsed = start.elapsed();
+        assert!(elapsed >= std::time::Duration::from_millis(50), "{:?}", elapsed);
+
+        unsafe { blaze_await(blaze_yield()) };
+        unsafe { blaze_future_drop(blaze_sleep(10_000)) };
+        unsafe { blaze_await(std::ptr::null_mut()) };
+    }
+
+    extern "C" fn increment(env: *mut u8) {
+        let counter = unsafe { &*(env as *const AtomicUsize) };
+        counter.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_spawned_task_runs_before_join_returns() {
+        let counter = AtomicUsize::new(0);
+        let env = &counter as *const AtomicUsize as *mut u8;
+
+        let tasks: Vec<u64> = (0..4).map(|_| blaze_spawn(Some(increment), env)).collect();
+        assert!(tasks.iter().all(|&task| task > 0));
+        for &task in &tasks {
+            assert_eq!(blaze_join(task), 0);
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 4);
+
+        assert_eq!(blaze_spawn(None, env), 0);
     }
 }
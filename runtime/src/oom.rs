@@ -0,0 +1,72 @@
+use parking_lot::RwLock;
+
+/// Called with the requested size when an allocation fails. A handler that
+/// returns makes the failed allocation return null.
+pub type OomHandler = extern "C" fn(size: usize);
+
+static OOM_HANDLER: RwLock<Option<OomHandler>> = RwLock::new(None);
+
+/// Installs `handler` to be called when `blaze_alloc`, `blaze_alloc_zeroed`
+/// or `blaze_realloc` cannot get memory. Null restores the default, which
+/// reports the failure and aborts.
+#[no_mangle]
+pub extern "C" fn blaze_set_oom_handler(handler: Option<OomHandler>) {
+    *OOM_HANDLER.write() = handler;
+}
+
+/// Reports a failed allocation of `size` bytes to the installed handler.
+pub(crate) fn out_of_memory(size: usize) {
+    // Copy the handler out so it can install another without deadlocking.
+    let handler = *OOM_HANDLER.read();
+    match handler {
+        Some(handler) => handler(size),
+        None => default_oom_handler(size),
+    }
+}
+
+// stderr is unbuffered, so reporting the failure needs no further memory.
+fn default_oom_handler(size: usize) {
+    eprintln!("\n╔═══════════════════════════════════════════════════════════════╗");
+    eprintln!("║                    BLAZE OUT OF MEMORY                        ║");
+    eprintln!("╚═══════════════════════════════════════════════════════════════╝");
+    eprintln!();
+    eprintln!("Failed to allocate {} bytes", size);
+    eprintln!();
+    std::process::abort();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{blaze_alloc, blaze_alloc_zeroed, blaze_realloc};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static FAILED_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn record_failure(size: usize) {
+        FAILED_SIZE.store(size, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_oom_handler_runs_on_failed_allocation() {
+        let _guard = crate::rusage::ALLOCATOR_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        // No allocator can satisfy a request this close to `isize::MAX`.
+        let huge = isize::MAX as usize - 7;
+        blaze_set_oom_handler(Some(record_failure));
+
+        assert!(blaze_alloc(huge, 8).is_null());
+        assert_eq!(FAILED_SIZE.swap(0, Ordering::SeqCst), huge);
+
+        assert!(blaze_alloc_zeroed(huge, 8).is_null());
+        assert_eq!(FAILED_SIZE.swap(0, Ordering::SeqCst), huge);
+
+        let ptr = blaze_alloc(16, 8);
+        assert!(!ptr.is_null());
+        assert!(unsafe { blaze_realloc(ptr, 16, 8, huge) }.is_null());
+        assert_eq!(FAILED_SIZE.swap(0, Ordering::SeqCst), huge);
+        unsafe { crate::blaze_dealloc(ptr, 16, 8) };
+
+        blaze_set_oom_handler(None);
+        assert!(OOM_HANDLER.read().is_none());
+    }
+}
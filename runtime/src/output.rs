@@ -0,0 +1,139 @@
+use parking_lot::Mutex;
+use std::io::{self, BufWriter, Write};
+use std::os::raw::c_int;
+use std::sync::OnceLock;
+
+/// Rewrites `\n` as `\r\n` when enabled, remembering whether the last byte
+/// written was `\r` so a CRLF split across two writes is not doubled.
+pub struct LineEndings {
+    crlf: bool,
+    after_cr: bool,
+}
+
+impl LineEndings {
+    pub const fn new(crlf: bool) -> Self {
+        Self { crlf, after_cr: false }
+    }
+
+    pub fn set_crlf(&mut self, crlf: bool) {
+        self.crlf = crlf;
+    }
+
+    /// Writes `bytes` to `out`, translating line endings if enabled.
+    pub fn write_to<W: Write>(&mut self, out: &mut W, bytes: &[u8]) -> io::Result<()> {
+        if !self.crlf {
+            out.write_all(bytes)?;
+        } else {
+            let mut translated = Vec::with_capacity(bytes.len());
+            let mut after_cr = self.after_cr;
+            for &byte in bytes {
+                if byte == b'\n' && !after_cr {
+                    translated.push(b'\r');
+                }
+                translated.push(byte);
+                after_cr = byte == b'\r';
+            }
+            out.write_all(&translated)?;
+        }
+        if let Some(&last) = bytes.last() {
+            self.after_cr = last == b'\r';
+        }
+        Ok(())
+    }
+}
+
+/// Buffered stdout used by `blaze_print`, with its line-ending state.
+struct StdoutWriter {
+    endings: LineEndings,
+    out: BufWriter<io::Stdout>,
+}
+
+static STDOUT: OnceLock<Mutex<StdoutWriter>> = OnceLock::new();
+
+/// Returns the stdout writer, creating it on first use. Output is held in
+/// the buffer until it fills, something flushes it, or the process exits.
+fn stdout_writer() -> &'static Mutex<StdoutWriter> {
+    STDOUT.get_or_init(|| {
+        extern "C" fn flush_at_exit() {
+            let _ = flush_stdout();
+        }
+
+        unsafe {
+            libc::atexit(flush_at_exit);
+        }
+        Mutex::new(StdoutWriter {
+            // CRLF by default on Windows only.
+            endings: LineEndings::new(cfg!(windows)),
+            out: BufWriter::new(io::stdout()),
+        })
+    })
+}
+
+/// Sets whether `blaze_print` writes `\n` as `\r\n` (non-zero) or as is (0).
+/// Existing `\r\n` pairs are never doubled.
+#[no_mangle]
+pub extern "C" fn blaze_set_output_mode(crlf: c_int) {
+    stdout_writer().lock().endings.set_crlf(crlf != 0);
+}
+
+/// Writes any buffered `blaze_print` output to stdout. Returns 0, or -1 on
+/// failure.
+#[no_mangle]
+pub extern "C" fn blaze_flush_stdout() -> c_int {
+    match flush_stdout() {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Writes `bytes` to the stdout buffer in the current output mode.
+pub(crate) fn write_stdout(bytes: &[u8]) {
+    let writer = &mut *stdout_writer().lock();
+    let _ = writer.endings.write_to(&mut writer.out, bytes);
+}
+
+/// Flushes the stdout buffer if anything has been printed.
+pub(crate) fn flush_stdout() -> io::Result<()> {
+    match STDOUT.get() {
+        Some(writer) => writer.lock().out.flush(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_all(endings: &mut LineEndings, chunks: &[&str]) -> String {
+        let mut out = Vec::new();
+        for chunk in chunks {
+            endings.write_to(&mut out, chunk.as_bytes()).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_crlf_mode_translates_lone_line_feeds() {
+        let mut endings = LineEndings::new(true);
+
+        assert_eq!(write_all(&mut endings, &["one\ntwo\r\nthree\n"]), "one\r\ntwo\r\nthree\r\n");
+        // A CRLF split across two prints is left alone.
+        assert_eq!(write_all(&mut endings, &["four\r", "\nfive"]), "four\r\nfive");
+    }
+
+    #[test]
+    fn test_lf_mode_passes_output_through() {
+        let mut endings = LineEndings::new(false);
+
+        assert_eq!(write_all(&mut endings, &["one\ntwo\r\n"]), "one\ntwo\r\n");
+    }
+
+    #[test]
+    fn test_print_output_is_buffered_until_flushed() {
+        write_stdout(b"buffered");
+        assert!(stdout_writer().lock().out.buffer().ends_with(b"buffered"));
+
+        assert_eq!(blaze_flush_stdout(), 0);
+        assert!(stdout_writer().lock().out.buffer().is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::resources::{input_str, ResourceTable};
+use ::regex::Regex;
+use std::os::raw::c_int;
+
+static PATTERNS: ResourceTable<Regex> = ResourceTable::new();
+
+/// Compiles a pattern and returns a handle to it, or -1 if the pattern is
+/// invalid.
+///
+/// # Safety
+///
+/// `pattern_ptr` must be null or point to `pattern_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_regex_compile(pattern_ptr: *const u8, pattern_len: usize) -> i64 {
+    let pattern = match unsafe { input_str(pattern_ptr, pattern_len) } {
+        Some(pattern) => pattern,
+        None => return -1,
+    };
+
+    match Regex::new(pattern) {
+        Ok(regex) => PATTERNS.insert(regex),
+        Err(_) => -1,
+    }
+}
+
+/// Returns 1 if the pattern matches anywhere in the text, 0 if it does not,
+/// or -1 for an invalid handle or non-UTF-8 text.
+///
+/// # Safety
+///
+/// `text_ptr` must be null or point to `text_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_regex_is_match(handle: i64, text_ptr: *const u8, text_len: usize) -> c_int {
+    let text = match unsafe { input_str(text_ptr, text_len) } {
+        Some(text) => text,
+        None => return -1,
+    };
+
+    match PATTERNS.with(handle, |regex| regex.is_match(text)) {
+        Some(matched) => matched as c_int,
+        None => -1,
+    }
+}
+
+/// Writes the byte range of the first match to `out_start`/`out_end`.
+/// Returns 1 if a match was found, 0 if not, or -1 on invalid input.
+///
+/// # Safety
+///
+/// `text_ptr` must be null or point to `text_len` readable bytes. `out_start`
+/// and `out_end` must each be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_regex_find(
+    handle: i64,
+    text_ptr: *const u8,
+    text_len: usize,
+    out_start: *mut usize,
+    out_end: *mut usize,
+) -> c_int {
+    if out_start.is_null() || out_end.is_null() {
+        return -1;
+    }
+
+    let text = match unsafe { input_str(text_ptr, text_len) } {
+        Some(text) => text,
+        None => return -1,
+    };
+
+    match PATTERNS.with(handle, |regex| regex.find(text).map(|m| (m.start(), m.end()))) {
+        Some(Some((start, end))) => {
+            unsafe {
+                *out_start = start;
+                *out_end = end;
+            }
+            1
+        }
+        Some(None) => 0,
+        None => -1,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn blaze_regex_free(handle: i64) {
+    PATTERNS.remove(handle);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(pattern: &str) -> i64 {
+        unsafe { blaze_regex_compile(pattern.as_ptr(), pattern.len()) }
+    }
+
+    fn find(handle: i64, text: &str) -> Option<(usize, usize)> {
+        let (mut start, mut end) = (0, 0);
+        match unsafe { blaze_regex_find(handle, text.as_ptr(), text.len(), &mut start, &mut end) } {
+            1 => Some((start, end)),
+            0 => None,
+            code => panic!("blaze_regex_find failed with {}", code),
+        }
+    }
+
+    #[test]
+    fn test_compile_and_match() {
+        let handle = compile(r"^[a-z]+@[a-z]+\.com$");
+        assert!(handle > 0);
+
+        let good = "dev@blaze.com";
+        let bad = "not an email";
+        assert_eq!(unsafe { blaze_regex_is_match(handle, good.as_ptr(), good.len()) }, 1);
+        assert_eq!(unsafe { blaze_regex_is_match(handle, bad.as_ptr(), bad.len()) }, 0);
+        blaze_regex_free(handle);
+    }
+
+    #[test]
+    fn test_find_returns_match_range() {
+        let handle = compile(r"\d+");
+        let text = "order 4521 shipped";
+
+        let (start, end) = find(handle, text).unwrap();
+        assert_eq!((start, end), (6, 10));
+        assert_eq!(&text[start..end], "4521");
+        assert_eq!(find(handle, "no digits"), None);
+    }
+
+    #[test]
+    fn test_invalid_pattern_returns_sentinel() {
+        assert_eq!(compile("(unclosed"), -1);
+        assert_eq!(unsafe { blaze_regex_is_match(-1, "x".as_ptr(), 1) }, -1);
+    }
+}
@@ -0,0 +1,144 @@
+use parking_lot::Mutex;
+use std::slice;
+
+/// Table of runtime-owned values handed to BLAZE code as opaque `i64`
+/// handles. Handles start at 1 so that -1 stays free as the error sentinel.
+/// A removed handle's slot is reused by a later insert, so a table holding
+/// short-lived values doesn't grow without bound. The slot's generation sits
+/// in the high bits of the handle, so a stale handle never reaches the value
+/// that took its slot.
+pub struct ResourceTable<T> {
+    slots: Mutex<Slots<T>>,
+}
+
+struct Slots<T> {
+    entries: Vec<Slot<T>>,
+    /// Indices of `entries` emptied by `remove`.
+    free: Vec<usize>,
+}
+
+struct Slot<T> {
+    /// Bumped each time the slot is emptied; kept below 2^31 so handles stay
+    /// positive.
+    generation: u32,
+    value: Option<T>,
+}
+
+const GENERATION_SHIFT: u32 = 32;
+const GENERATION_MASK: u32 = 0x7FFF_FFFF;
+
+fn encode_handle(index: usize, generation: u32) -> i64 {
+    ((generation as i64) << GENERATION_SHIFT) | (index as i64 + 1)
+}
+
+fn decode_handle(handle: i64) -> Option<(usize, u32)> {
+    if handle <= 0 {
+        return None;
+    }
+    let index = usize::try_from(handle & 0xFFFF_FFFF).ok()?.checked_sub(1)?;
+    Some((index, (handle >> GENERATION_SHIFT) as u32))
+}
+
+impl<T> Default for ResourceTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ResourceTable<T> {
+    pub const fn new() -> Self {
+        Self {
+            slots: Mutex::new(Slots { entries: Vec::new(), free: Vec::new() }),
+        }
+    }
+
+    pub fn insert(&self, value: T) -> i64 {
+        let mut slots = self.slots.lock();
+        match slots.free.pop() {
+            Some(index) => {
+                let slot = &mut slots.entries[index];
+                slot.value = Some(value);
+                encode_handle(index, slot.generation)
+            }
+            None => {
+                slots.entries.push(Slot { generation: 0, value: Some(value) });
+                encode_handle(slots.entries.len() - 1, 0)
+            }
+        }
+    }
+
+    pub fn with<R>(&self, handle: i64, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let slots = self.slots.lock();
+        let (index, generation) = decode_handle(handle)?;
+        let slot = slots.entries.get(index).filter(|slot| slot.generation == generation)?;
+        slot.value.as_ref().map(f)
+    }
+
+    pub fn remove(&self, handle: i64) -> Option<T> {
+        let mut slots = self.slots.lock();
+        let (index, generation) = decode_handle(handle)?;
+        let slot = slots.entries.get_mut(index).filter(|slot| slot.generation == generation)?;
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1) & GENERATION_MASK;
+        slots.free.push(index);
+        Some(value)
+    }
+}
+
+/// Borrows a UTF-8 string passed from BLAZE code as a pointer and length.
+/// An empty string may come with a null pointer; otherwise null or invalid
+/// UTF-8 gives `None`.
+pub(crate) unsafe fn input_str<'a>(ptr: *const u8, len: usize) -> Option<&'a str> {
+    if len == 0 {
+        return Some("");
+    }
+    if ptr.is_null() {
+        return None;
+    }
+    std::str::from_utf8(slice::from_raw_parts(ptr, len)).ok()
+}
+
+/// Copies `bytes` into a caller-provided buffer. Returns the number of bytes
+/// written, or -1 for a null buffer or one smaller than `bytes`.
+pub(crate) unsafe fn write_bytes(bytes: &[u8], out_ptr: *mut u8, out_cap: usize) -> isize {
+    if out_ptr.is_null() || bytes.len() > out_cap {
+        return -1;
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), out_ptr, bytes.len());
+    bytes.len() as isize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removed_handles_are_reused() {
+        let table = ResourceTable::new();
+        let first = table.insert("a");
+        let second = table.insert("b");
+        assert_eq!((first, second), (1, 2));
+
+        assert_eq!(table.remove(first), Some("a"));
+        assert_eq!(table.remove(first), None);
+        assert_eq!(table.with(first, |value| *value), None);
+
+        let third = table.insert("c");
+        assert_ne!(third, first);
+        assert_eq!(third & 0xFFFF_FFFF, first);
+        assert_eq!(table.with(third, |value| *value), Some("c"));
+        assert_eq!(table.insert("d"), 3);
+    }
+
+    #[test]
+    fn test_stale_handle_does_not_reach_reused_slot() {
+        let table = ResourceTable::new();
+        let stale = table.insert("closed");
+        table.remove(stale);
+        let live = table.insert("open");
+
+        assert_eq!(table.with(stale, |value| *value), None);
+        assert_eq!(table.remove(stale), None);
+        assert_eq!(table.remove(live), Some("open"));
+    }
+}
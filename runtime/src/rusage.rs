@@ -0,0 +1,186 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Bytes currently held through `blaze_alloc`/`blaze_realloc` and not yet
+/// returned with `blaze_dealloc`.
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Held by tests that allocate through the runtime allocator, so one test's
+/// allocations don't show up in another's view of `ALLOCATED_BYTES`.
+#[cfg(test)]
+pub(crate) static ALLOCATOR_TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+pub(crate) fn track_alloc(size: usize) {
+    ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+pub(crate) fn track_dealloc(size: usize) {
+    // Saturate rather than wrap if a program frees with a larger size than
+    // it allocated.
+    let _ = ALLOCATED_BYTES.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+        Some(current.saturating_sub(size as u64))
+    });
+}
+
+/// User plus system CPU time consumed by the process, in nanoseconds.
+/// Returns 0 if the platform query fails.
+#[no_mangle]
+pub extern "C" fn blaze_rusage_cpu_nanos() -> u64 {
+    platform::cpu_nanos().unwrap_or(0)
+}
+
+/// Peak resident set size of the process in bytes, or 0 if unavailable.
+#[no_mangle]
+pub extern "C" fn blaze_rusage_max_rss_bytes() -> u64 {
+    platform::max_rss_bytes().unwrap_or(0)
+}
+
+/// Bytes currently allocated through the runtime allocator.
+#[no_mangle]
+pub extern "C" fn blaze_rusage_allocated_bytes() -> u64 {
+    ALLOCATED_BYTES.load(Ordering::Relaxed)
+}
+
+#[cfg(unix)]
+mod platform {
+    fn rusage() -> Option<libc::rusage> {
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+            return None;
+        }
+        Some(usage)
+    }
+
+    fn timeval_nanos(time: libc::timeval) -> u64 {
+        time.tv_sec as u64 * 1_000_000_000 + time.tv_usec as u64 * 1_000
+    }
+
+    pub fn cpu_nanos() -> Option<u64> {
+        let usage = rusage()?;
+        Some(timeval_nanos(usage.ru_utime) + timeval_nanos(usage.ru_stime))
+    }
+
+    pub fn max_rss_bytes() -> Option<u64> {
+        let max_rss = rusage()?.ru_maxrss as u64;
+        // macOS reports bytes, other Unixes kilobytes.
+        if cfg!(target_os = "macos") {
+            Some(max_rss)
+        } else {
+            Some(max_rss * 1024)
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct FileTime {
+        low: u32,
+        high: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ProcessMemoryCounters {
+        cb: u32,
+        page_fault_count: u32,
+        peak_working_set_size: usize,
+        working_set_size: usize,
+        quota_peak_paged_pool_usage: usize,
+        quota_paged_pool_usage: usize,
+        quota_peak_non_paged_pool_usage: usize,
+        quota_non_paged_pool_usage: usize,
+        pagefile_usage: usize,
+        peak_pagefile_usage: usize,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> *mut c_void;
+        fn GetProcessTimes(
+            process: *mut c_void,
+            creation: *mut FileTime,
+            exit: *mut FileTime,
+            kernel: *mut FileTime,
+            user: *mut FileTime,
+        ) -> i32;
+        fn K32GetProcessMemoryInfo(process: *mut c_void, counters: *mut ProcessMemoryCounters, cb: u32) -> i32;
+    }
+
+    /// FILETIME values count 100-nanosecond intervals.
+    fn filetime_nanos(time: &FileTime) -> u64 {
+        ((time.high as u64) << 32 | time.low as u64) * 100
+    }
+
+    pub fn cpu_nanos() -> Option<u64> {
+        let (mut creation, mut exit, mut kernel, mut user) =
+            (FileTime::default(), FileTime::default(), FileTime::default(), FileTime::default());
+        let ok = unsafe { GetProcessTimes(GetCurrentProcess(), &mut creation, &mut exit, &mut kernel, &mut user) };
+        if ok == 0 {
+            return None;
+        }
+        Some(filetime_nanos(&kernel) + filetime_nanos(&user))
+    }
+
+    pub fn max_rss_bytes() -> Option<u64> {
+        let mut counters = ProcessMemoryCounters {
+            cb: std::mem::size_of::<ProcessMemoryCounters>() as u32,
+            ..Default::default()
+        };
+        let ok = unsafe { K32GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, counters.cb) };
+        if ok == 0 {
+            return None;
+        }
+        Some(counters.peak_working_set_size as u64)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    pub fn cpu_nanos() -> Option<u64> {
+        None
+    }
+
+    pub fn max_rss_bytes() -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{blaze_alloc, blaze_dealloc, blaze_realloc};
+
+    #[test]
+    fn test_allocation_counter_returns_to_baseline() {
+        let _guard = ALLOCATOR_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let baseline = blaze_rusage_allocated_bytes();
+
+        let ptr = blaze_alloc(4096, 8);
+        assert!(!ptr.is_null());
+        assert_eq!(blaze_rusage_allocated_bytes(), baseline + 4096);
+
+        let ptr = unsafe { blaze_realloc(ptr, 4096, 8, 8192) };
+        assert!(!ptr.is_null());
+        assert_eq!(blaze_rusage_allocated_bytes(), baseline + 8192);
+
+        unsafe { blaze_dealloc(ptr, 8192, 8) };
+        assert_eq!(blaze_rusage_allocated_bytes(), baseline);
+    }
+
+    #[test]
+    fn test_process_usage_is_reported() {
+        let before = blaze_rusage_cpu_nanos();
+        let mut x = 0u64;
+        for i in 0..5_000_000u64 {
+            x = std::hint::black_box(x.wrapping_mul(31).wrapping_add(i));
+        }
+        std::hint::black_box(x);
+
+        assert!(blaze_rusage_cpu_nanos() >= before);
+        assert!(blaze_rusage_cpu_nanos() > 0);
+        assert!(blaze_rusage_max_rss_bytes() > 0);
+    }
+}
@@ -0,0 +1,40 @@
+use crate::channel::block_on;
+use crate::get_runtime;
+use crate::resources::ResourceTable;
+use std::future::Future;
+use tokio::task::JoinHandle;
+
+/// Tasks started by the runtime on behalf of BLAZE code, each resolving to
+/// an `i64` result (a handle, a byte count, or -1 on failure).
+static TASKS: ResourceTable<JoinHandle<i64>> = ResourceTable::new();
+
+/// Starts `future` on the runtime and returns the handle to pass to
+/// `blaze_task_join`.
+pub(crate) fn spawn_task<F>(future: F) -> i64
+where
+    F: Future<Output = i64> + Send + 'static,
+{
+    TASKS.insert(get_runtime().spawn(future))
+}
+
+/// Aborts the task if it has not finished. Returns whether it was still
+/// running; joining an aborted task yields -1.
+pub(crate) fn cancel_task(handle: i64) -> bool {
+    TASKS
+        .with(handle, |task| {
+            let running = !task.is_finished();
+            task.abort();
+            running
+        })
+        .unwrap_or(false)
+}
+
+/// Waits for the task to finish and returns its result. Returns -1 for an
+/// invalid or already joined handle, or if the task panicked.
+#[no_mangle]
+pub extern "C" fn blaze_task_join(handle: i64) -> i64 {
+    match TASKS.remove(handle) {
+        Some(task) => block_on(task).unwrap_or(-1),
+        None => -1,
+    }
+}
@@ -0,0 +1,129 @@
+use crate::channel::block_on;
+use crate::get_runtime;
+use crate::resources::ResourceTable;
+use crate::tasks::{cancel_task, spawn_task};
+use std::os::raw::c_int;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{interval_at, Instant, Interval, MissedTickBehavior};
+
+/// Called once by `blaze_timer_after` with the context it was given.
+pub type TimerCallback = extern "C" fn(ctx: *mut u8);
+
+static INTERVALS: ResourceTable<Arc<Mutex<Interval>>> = ResourceTable::new();
+
+/// Creates a timer that ticks every `period_ms` milliseconds, starting one
+/// period from now, and returns its handle, or -1 if `period_ms` is 0.
+/// Ticks missed while nobody waits are delayed, not fired in a burst.
+#[no_mangle]
+pub extern "C" fn blaze_interval_new(period_ms: u64) -> i64 {
+    if period_ms == 0 {
+        return -1;
+    }
+    let period = Duration::from_millis(period_ms);
+
+    // Tokio timers register with the runtime they are created in.
+    let _runtime = get_runtime().enter();
+    let mut interval = interval_at(Instant::now() + period, period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    INTERVALS.insert(Arc::new(Mutex::new(interval)))
+}
+
+/// Waits for the interval's next tick. Returns 0 on a tick, or -1 for an
+/// invalid or cancelled handle.
+#[no_mangle]
+pub extern "C" fn blaze_interval_tick(handle: i64) -> c_int {
+    let interval = match INTERVALS.with(handle, Arc::clone) {
+        Some(interval) => interval,
+        None => return -1,
+    };
+    block_on(async move {
+        interval.lock().await.tick().await;
+    });
+    0
+}
+
+/// Stops the interval; later ticks on its handle return -1. Returns 0, or
+/// -1 if the handle was invalid or already cancelled.
+#[no_mangle]
+pub extern "C" fn blaze_interval_cancel(handle: i64) -> c_int {
+    match INTERVALS.remove(handle) {
+        Some(_) => 0,
+        None => -1,
+    }
+}
+
+/// Calls `callback(ctx)` on the runtime after `ms` milliseconds. Returns a
+/// task handle that `blaze_task_join` waits on and `blaze_timer_cancel`
+/// cancels, or -1 if `callback` is null.
+#[no_mangle]
+pub extern "C" fn blaze_timer_after(ms: u64, callback: Option<TimerCallback>, ctx: *mut u8) -> i64 {
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return -1,
+    };
+    let ctx = ctx as usize;
+    spawn_task(async move {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        callback(ctx as *mut u8);
+        0
+    })
+}
+
+/// Cancels a timer from `blaze_timer_after`. Returns 0 if it was still
+/// pending or running, or -1 for an invalid, joined or finished handle.
+#[no_mangle]
+pub extern "C" fn blaze_timer_cancel(handle: i64) -> c_int {
+    if cancel_task(handle) {
+        0
+    } else {
+        -1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::blaze_task_join;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_interval_ticks_once_per_period_until_cancelled() {
+        let interval = blaze_interval_new(10);
+        assert!(interval > 0);
+
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            assert_eq!(blaze_interval_tick(interval), 0);
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(30), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(500), "{:?}", elapsed);
+
+        assert_eq!(blaze_interval_cancel(interval), 0);
+        assert_eq!(blaze_interval_tick(interval), -1);
+        assert_eq!(blaze_interval_cancel(interval), -1);
+        assert_eq!(blaze_interval_new(0), -1);
+    }
+
+    extern "C" fn count(ctx: *mut u8) {
+        let counter = unsafe { &*(ctx as *const AtomicUsize) };
+        counter.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_timer_fires_once_unless_cancelled() {
+        let counter: &'static AtomicUsize = Box::leak(Box::new(AtomicUsize::new(0)));
+        let ctx = counter as *const AtomicUsize as *mut u8;
+
+        let fired = blaze_timer_after(5, Some(count), ctx);
+        assert_eq!(blaze_task_join(fired), 0);
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+        let cancelled = blaze_timer_after(10_000, Some(count), ctx);
+        assert_eq!(blaze_timer_cancel(cancelled), 0);
+        assert_eq!(blaze_task_join(cancelled), -1);
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::resources::input_str;
+use std::os::raw::c_int;
+
+/// Returned for a null pointer or input that is not valid UTF-8.
+pub const BLAZE_UTF8_INVALID: c_int = -1;
+
+/// Returned by `blaze_utf8_char_at` when the index is past the last character.
+pub const BLAZE_UTF8_OUT_OF_RANGE: c_int = -2;
+
+/// Returns 1 if the bytes are valid UTF-8, 0 if they are not, or
+/// `BLAZE_UTF8_INVALID` for a null pointer.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_utf8_validate(ptr: *const u8, len: usize) -> c_int {
+    if ptr.is_null() && len > 0 {
+        return BLAZE_UTF8_INVALID;
+    }
+    unsafe { input_str(ptr, len) }.is_some() as c_int
+}
+
+/// Returns the number of Unicode scalar values in the string, or
+/// `BLAZE_UTF8_INVALID` if it is not valid UTF-8.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_utf8_char_count(ptr: *const u8, len: usize) -> isize {
+    match unsafe { input_str(ptr, len) } {
+        Some(text) => text.chars().count() as isize,
+        None => BLAZE_UTF8_INVALID as isize,
+    }
+}
+
+/// Writes the Unicode scalar value of the character at `char_index` (counted
+/// in characters, not bytes) to `out_cp` and returns 0. Returns
+/// `BLAZE_UTF8_OUT_OF_RANGE` if the string has no such character, or
+/// `BLAZE_UTF8_INVALID` for invalid UTF-8 or a null `out_cp`.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to `len` readable bytes. `out_cp` must be null
+/// or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_utf8_char_at(ptr: *const u8, len: usize, char_index: usize, out_cp: *mut u32) -> c_int {
+    if out_cp.is_null() {
+        return BLAZE_UTF8_INVALID;
+    }
+    let text = match unsafe { input_str(ptr, len) } {
+        Some(text) => text,
+        None => return BLAZE_UTF8_INVALID,
+    };
+
+    match text.chars().nth(char_index) {
+        Some(ch) => {
+            unsafe { *out_cp = ch as u32 };
+            0
+        }
+        None => BLAZE_UTF8_OUT_OF_RANGE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn char_at(text: &[u8], index: usize) -> Result<char, c_int> {
+        let mut cp = 0u32;
+        match unsafe { blaze_utf8_char_at(text.as_ptr(), text.len(), index, &mut cp) } {
+            0 => Ok(char::from_u32(cp).unwrap()),
+            code => Err(code),
+        }
+    }
+
+    #[test]
+    fn test_multibyte_strings_validate_and_count() {
+        // 1-, 2-, 3- and 4-byte encodings.
+        let text = "aé€😀";
+        assert_eq!(text.len(), 10);
+
+        assert_eq!(unsafe { blaze_utf8_validate(text.as_ptr(), text.len()) }, 1);
+        assert_eq!(unsafe { blaze_utf8_char_count(text.as_ptr(), text.len()) }, 4);
+        assert_eq!(unsafe { blaze_utf8_char_count(std::ptr::null(), 0) }, 0);
+    }
+
+    #[test]
+    fn test_char_at_indexes_by_character() {
+        let text = "aé€😀".as_bytes();
+
+        assert_eq!(char_at(text, 0), Ok('a'));
+        assert_eq!(char_at(text, 1), Ok('é'));
+        assert_eq!(char_at(text, 2), Ok('€'));
+        assert_eq!(char_at(text, 3), Ok('😀'));
+        assert_eq!(char_at(text, 4), Err(BLAZE_UTF8_OUT_OF_RANGE));
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_rejected() {
+        // A truncated three-byte sequence and a lone continuation byte.
+        for bytes in [&[0xe2, 0x82][..], &[b'a', 0x80, b'b'][..]] {
+            assert_eq!(unsafe { blaze_utf8_validate(bytes.as_ptr(), bytes.len()) }, 0);
+            assert_eq!(unsafe { blaze_utf8_char_count(bytes.as_ptr(), bytes.len()) }, BLAZE_UTF8_INVALID as isize);
+            assert_eq!(char_at(bytes, 0), Err(BLAZE_UTF8_INVALID));
+        }
+        assert_eq!(unsafe { blaze_utf8_validate(std::ptr::null(), 4) }, BLAZE_UTF8_INVALID);
+    }
+}
@@ -143,15 +143,17 @@ impl CFGBuilder {
                 self.add_edge(body_block, header_block);
             }
             
-            Statement::Break(_) => {
+            Statement::Break { .. } => {
                 let exit_block = self.create_block();
                 self.add_edge(self.current_block, exit_block);
                 self.current_block = exit_block;
             }
             
-            Statement::Continue => {
+            Statement::Continue { .. } => {
             }
             
+            Statement::Labeled { body, .. } => self.process_statement(body),
+            
             Statement::Return(_) => {
                 let return_block = self.create_block();
                 self.add_edge(self.current_block, return_block);
@@ -105,6 +105,14 @@ impl BorrowChecker {
                 self.exit_scope();
             }
             
+            Statement::Labeled { body, .. } => {
+                self.check_statement(body)?;
+            }
+            
+            Statement::Break { value: Some(expr), .. } => {
+                self.check_expression(expr)?;
+            }
+            
             _ => {}
         }
         
@@ -126,6 +134,11 @@ impl BorrowChecker {
                 self.check_expression(expr)?;
             }
             
+            Expression::Range { start, end, .. } => {
+                self.check_expression(start)?;
+                self.check_expression(end)?;
+            }
+            
             Expression::Call { func, args } | Expression::CallAlt { callee: func, args } => {
                 self.check_expression(func)?;
                 for arg in args {
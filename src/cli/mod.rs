@@ -1,3 +1,5 @@
+use crate::codegen::RelocModel;
+use crate::error::ColorChoice;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -7,6 +9,9 @@ use std::path::PathBuf;
 #[command(version = "0.1.0")]
 #[command(about = "🔥 BLAZE - Modern systems programming language compiler")]
 pub struct Cli {
+    #[arg(long, value_enum, global = true, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -42,6 +47,12 @@ pub enum Commands {
         
         #[arg(short, long)]
         release: bool,
+        
+        #[arg(long, value_name = "FILE")]
+        pgo_use: Option<PathBuf>,
+        
+        #[arg(long, value_enum, default_value_t = RelocModel::default())]
+        relocation_model: RelocModel,
     },
     
     Run {
@@ -1,3 +1,4 @@
+use super::RelocModel;
 use crate::ir::{Module, IRFunction, IRType, Instruction, Terminator, ICmpCondition, FCmpCondition};
 use anyhow::{Result, bail};
 use inkwell::context::Context;
@@ -17,6 +18,7 @@ pub struct LLVMCodegen<'ctx> {
     module: LLVMModule<'ctx>,
     builder: Builder<'ctx>,
     optimization_level: u8,
+    relocation_model: RelocModel,
     values: HashMap<String, BasicValueEnum<'ctx>>,
     blocks: HashMap<String, BasicBlock<'ctx>>,
 }
@@ -31,6 +33,7 @@ impl<'ctx> LLVMCodegen<'ctx> {
             module,
             builder,
             optimization_level: 2,
+            relocation_model: RelocModel::default(),
             values: HashMap::new(),
             blocks: HashMap::new(),
         }
@@ -40,6 +43,19 @@ impl<'ctx> LLVMCodegen<'ctx> {
         self.optimization_level = level.min(3);
     }
 
+    pub fn set_relocation_model(&mut self, model: RelocModel) {
+        self.relocation_model = model;
+    }
+
+    /// The LLVM relocation mode the target machine is created with.
+    pub fn reloc_mode(&self) -> RelocMode {
+        match self.relocation_model {
+            RelocModel::Static => RelocMode::Static,
+            RelocModel::PIC => RelocMode::PIC,
+            RelocModel::DynamicNoPic => RelocMode::DynamicNoPic,
+        }
+    }
+
     pub fn generate(
         &mut self,
         ir_module: &Module,
@@ -299,6 +315,27 @@ impl<'ctx> LLVMCodegen<'ctx> {
                     bail!("Condition must be an integer value");
                 }
             }
+            Terminator::Switch { value, cases, default } => {
+                let switch_val = match self.get_value(value) {
+                    Some(BasicValueEnum::IntValue(switch_int)) => switch_int,
+                    Some(_) => bail!("Switch value must be an integer value"),
+                    None => bail!("Switch value '{}' not found", value),
+                };
+                
+                let default_block = self.blocks.get(default)
+                    .ok_or_else(|| anyhow::anyhow!("Switch default target '{}' not found", default))?;
+                
+                let mut llvm_cases = Vec::new();
+                for (case, target) in cases {
+                    let target_block = self.blocks.get(target)
+                        .ok_or_else(|| anyhow::anyhow!("Switch case target '{}' not found", target))?;
+                    llvm_cases.push((switch_val.get_type().const_int(*case as u64, true), *target_block));
+                }
+                
+                // LLVM picks a jump table or a comparison tree depending on
+                // how dense the cases are.
+                self.builder.build_switch(switch_val, *default_block, &llvm_cases).into();
+            }
             Terminator::Unreachable => {
                 self.builder.build_unreachable().into();
             }
@@ -390,7 +427,7 @@ impl<'ctx> LLVMCodegen<'ctx> {
             cpu.to_str().unwrap_or("generic"),
             features.to_str().unwrap_or(""),
             opt_level,
-            RelocMode::PIC,  // Position Independent Code for better compatibility
+            self.reloc_mode(),
             CodeModel::Default,
         ).ok_or_else(|| anyhow::anyhow!("Failed to create target machine for triple '{}'", target_triple))?;
         
@@ -44,12 +44,34 @@ use anyhow::{Result, Context as AnyhowContext};
 #[cfg(feature = "llvm")]
 use inkwell::context::Context;
 
+/// Relocation model for generated code, as selected by `--relocation-model`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RelocModel {
+    /// Absolute addresses; only suitable for non-PIE executables.
+    Static,
+    /// Position-independent code, required for shared libraries and PIE.
+    PIC,
+    /// Dynamic symbol references without position-independent code.
+    DynamicNoPic,
+}
+
+impl Default for RelocModel {
+    /// PIC on Unix, where executables are PIE by default; static elsewhere.
+    fn default() -> Self {
+        if cfg!(unix) {
+            RelocModel::PIC
+        } else {
+            RelocModel::Static
+        }
+    }
+}
+
 /// Code generator that orchestrates the complete code generation pipeline.
 ///
 /// The `CodeGenerator` coordinates register allocation, LLVM code generation,
 /// and linking to produce executable binaries from IR modules.
 pub struct CodeGenerator {
-    
+    relocation_model: RelocModel,
 }
 
 impl CodeGenerator {
@@ -63,7 +85,18 @@ impl CodeGenerator {
     /// let codegen = CodeGenerator::new();
     /// ```
     pub fn new() -> Self {
-        Self {}
+        Self {
+            relocation_model: RelocModel::default(),
+        }
+    }
+
+    /// Sets the relocation model used when creating the target machine.
+    pub fn set_relocation_model(&mut self, model: RelocModel) {
+        self.relocation_model = model;
+    }
+
+    pub fn relocation_model(&self) -> RelocModel {
+        self.relocation_model
     }
 
     /// Generates an executable binary from an IR module.
@@ -158,6 +191,7 @@ impl CodeGenerator {
         // Create LLVM backend and pass optimization level
         let mut backend = LLVMCodegen::new(context, &optimized_module.name);
         backend.set_optimization_level(optimization_level);
+        backend.set_relocation_model(self.relocation_model);
         
         // Generate object file (LLVM optimization passes are configured inside)
         let object_file = output.with_extension("o");
@@ -1,10 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 pub struct ConcurrencyAnalyzer {
     thread_spawns: Vec<ThreadSpawn>,
     shared_data: HashMap<String, SharedResource>,
     locks: HashMap<String, LockInfo>,
+    held_locks: HashMap<usize, Vec<String>>,
+    lock_order: BTreeMap<String, BTreeSet<String>>,
 }
 
 #[derive(Debug, Clone)]
@@ -42,6 +44,8 @@ impl ConcurrencyAnalyzer {
             thread_spawns: Vec::new(),
             shared_data: HashMap::new(),
             locks: HashMap::new(),
+            held_locks: HashMap::new(),
+            lock_order: BTreeMap::new(),
         }
     }
     
@@ -53,6 +57,49 @@ impl ConcurrencyAnalyzer {
         self.shared_data.insert(resource.name.clone(), resource);
     }
     
+    /// Records `thread_id` requesting `lock`. Every lock the thread already
+    /// holds must now be released after `lock`, which adds a held -> requested
+    /// edge to the lock order; a cycle in that order can deadlock.
+    pub fn acquire_lock(&mut self, thread_id: usize, lock: &str) {
+        let held = self.held_locks.entry(thread_id).or_default();
+        for earlier in held.iter() {
+            self.lock_order.entry(earlier.clone()).or_default().insert(lock.to_string());
+        }
+        held.push(lock.to_string());
+        
+        let info = self.locks.entry(lock.to_string()).or_insert_with(|| LockInfo {
+            name: lock.to_string(),
+            held_by: None,
+            waiters: Vec::new(),
+        });
+        if info.held_by.is_none() {
+            info.held_by = Some(thread_id);
+        } else {
+            info.waiters.push(thread_id);
+        }
+    }
+    
+    /// Records `thread_id` releasing `lock`, handing it to the first waiter.
+    pub fn release_lock(&mut self, thread_id: usize, lock: &str) {
+        if let Some(held) = self.held_locks.get_mut(&thread_id) {
+            if let Some(position) = held.iter().rposition(|name| name == lock) {
+                held.remove(position);
+            }
+        }
+        
+        if let Some(info) = self.locks.get_mut(lock) {
+            if info.held_by == Some(thread_id) {
+                info.held_by = if info.waiters.is_empty() { None } else { Some(info.waiters.remove(0)) };
+            } else {
+                info.waiters.retain(|&waiter| waiter != thread_id);
+            }
+        }
+    }
+    
+    pub fn get_lock(&self, name: &str) -> Option<&LockInfo> {
+        self.locks.get(name)
+    }
+    
     pub fn check_data_races(&self) -> Vec<DataRace> {
         let mut races = Vec::new();
         
@@ -80,11 +127,57 @@ impl ConcurrencyAnalyzer {
         cycles
     }
     
+    /// Edges from each lock to the locks requested while it was held, in
+    /// name order so reported cycles are stable.
     fn build_lock_dependency_graph(&self) -> HashMap<String, Vec<String>> {
-        HashMap::new()
+        self.lock_order
+            .iter()
+            .map(|(lock, later)| (lock.clone(), later.iter().cloned().collect()))
+            .collect()
+    }
+    
+    /// Finds a cycle by depth-first search and returns its locks in edge
+    /// order, starting from the first lock reached on it.
+    fn detect_cycle(&self, graph: &HashMap<String, Vec<String>>) -> Option<Vec<String>> {
+        let mut visited = HashSet::new();
+        let mut path = Vec::new();
+        
+        let mut roots: Vec<&String> = graph.keys().collect();
+        roots.sort();
+        for root in roots {
+            if let Some(cycle) = self.detect_cycle_util(root, graph, &mut visited, &mut path) {
+                return Some(cycle);
+            }
+        }
+        
+        None
     }
     
-    fn detect_cycle(&self, _graph: &HashMap<String, Vec<String>>) -> Option<Vec<String>> {
+    fn detect_cycle_util<'a>(
+        &self,
+        node: &'a str,
+        graph: &'a HashMap<String, Vec<String>>,
+        visited: &mut HashSet<&'a str>,
+        path: &mut Vec<&'a str>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|&on_path| on_path == node) {
+            return Some(path[start..].iter().map(|lock| lock.to_string()).collect());
+        }
+        
+        if !visited.insert(node) {
+            return None;
+        }
+        
+        path.push(node);
+        if let Some(neighbors) = graph.get(node) {
+            for neighbor in neighbors {
+                if let Some(cycle) = self.detect_cycle_util(neighbor, graph, visited, path) {
+                    return Some(cycle);
+                }
+            }
+        }
+        path.pop();
+        
         None
     }
 }
@@ -241,3 +334,49 @@ impl Default for ThreadSanitizer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn(analyzer: &mut ConcurrencyAnalyzer, thread_id: usize) {
+        analyzer.register_thread_spawn(ThreadSpawn { thread_id, parent_id: Some(0), closure_vars: vec![] });
+    }
+
+    #[test]
+    fn test_inverse_lock_order_is_reported_as_deadlock() {
+        let mut analyzer = ConcurrencyAnalyzer::new();
+        spawn(&mut analyzer, 1);
+        spawn(&mut analyzer, 2);
+
+        // Thread 1 takes accounts then ledger; thread 2 takes them the other way round.
+        analyzer.acquire_lock(1, "accounts");
+        analyzer.acquire_lock(1, "ledger");
+        analyzer.release_lock(1, "ledger");
+        analyzer.release_lock(1, "accounts");
+        analyzer.acquire_lock(2, "ledger");
+        analyzer.acquire_lock(2, "accounts");
+
+        let deadlocks = analyzer.check_deadlocks();
+        assert_eq!(deadlocks.len(), 1);
+        assert_eq!(deadlocks[0].locks, vec!["accounts".to_string(), "ledger".to_string()]);
+    }
+
+    #[test]
+    fn test_consistent_lock_order_is_not_a_deadlock() {
+        let mut analyzer = ConcurrencyAnalyzer::new();
+        for thread in [1, 2] {
+            spawn(&mut analyzer, thread);
+            analyzer.acquire_lock(thread, "accounts");
+            analyzer.acquire_lock(thread, "ledger");
+        }
+
+        assert!(analyzer.check_deadlocks().is_empty());
+        // Thread 2 queued behind thread 1 on both locks.
+        assert_eq!(analyzer.get_lock("ledger").unwrap().held_by, Some(1));
+        assert_eq!(analyzer.get_lock("ledger").unwrap().waiters, vec![2]);
+
+        analyzer.release_lock(1, "ledger");
+        assert_eq!(analyzer.get_lock("ledger").unwrap().held_by, Some(2));
+    }
+}
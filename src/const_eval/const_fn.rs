@@ -0,0 +1,231 @@
+use crate::error::CompileError;
+use crate::parser::{Expression, Function, Item, Program, Statement};
+use std::collections::HashSet;
+
+/// Builtins that perform I/O and can never run at compile time.
+const IO_FUNCTIONS: &[&str] = &[
+    "print", "println", "eprint", "eprintln", "read_line", "read_file", "write_file", "open",
+    "blaze_print", "blaze_eprint", "blaze_println_str",
+];
+
+/// Builtins that allocate on the heap.
+const ALLOCATING_FUNCTIONS: &[&str] = &[
+    "alloc", "malloc", "blaze_alloc", "Box::new", "Vec::new", "Vec::with_capacity", "String::new", "String::from",
+];
+
+/// Verifies that `const fn` bodies only perform const-evaluable operations:
+/// no I/O, no heap allocation, and no calls to functions that are not
+/// themselves `const`.
+pub struct ConstFnChecker {
+    const_fns: HashSet<String>,
+}
+
+impl ConstFnChecker {
+    pub fn new(program: &Program) -> Self {
+        let const_fns = program
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Function(func) if func.is_const => Some(func.name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        Self { const_fns }
+    }
+
+    /// Checks every `const fn` in the program, collecting all violations.
+    pub fn check_program(&self, program: &Program) -> Result<(), Vec<CompileError>> {
+        let mut errors = Vec::new();
+        for item in &program.items {
+            if let Item::Function(func) = item {
+                if let Err(mut func_errors) = self.check_const_fn(func) {
+                    errors.append(&mut func_errors);
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns one error per non-const operation in `func`. Functions not
+    /// marked `const` are always accepted.
+    pub fn check_const_fn(&self, func: &Function) -> Result<(), Vec<CompileError>> {
+        if !func.is_const {
+            return Ok(());
+        }
+
+        let mut errors = Vec::new();
+        self.check_block(&func.name, &func.body, &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn check_block(&self, func: &str, body: &[Statement], errors: &mut Vec<CompileError>) {
+        for stmt in body {
+            self.check_statement(func, stmt, errors);
+        }
+    }
+
+    fn check_statement(&self, func: &str, stmt: &Statement, errors: &mut Vec<CompileError>) {
+        match stmt {
+            Statement::Let { value: Some(expr), .. }
+            | Statement::Return(Some(expr))
+            | Statement::Break { value: Some(expr), .. }
+            | Statement::Expression(expr) => self.check_expression(func, expr, errors),
+            Statement::While { condition, body } => {
+                self.check_expression(func, condition, errors);
+                self.check_block(func, body, errors);
+            }
+            Statement::For { iterable, body, .. } => {
+                errors.push(violation(
+                    func,
+                    "`for` loops are not allowed in a const fn",
+                    "iterate with a `while` loop and an index instead",
+                ));
+                self.check_expression(func, iterable, errors);
+                self.check_block(func, body, errors);
+            }
+            Statement::Loop { body } | Statement::Block(body) => self.check_block(func, body, errors),
+            Statement::Labeled { body, .. } => self.check_statement(func, body, errors),
+            Statement::If { condition, then_body, else_body } => {
+                self.check_expression(func, condition, errors);
+                self.check_block(func, then_body, errors);
+                if let Some(else_body) = else_body {
+                    self.check_block(func, else_body, errors);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_expression(&self, func: &str, expr: &Expression, errors: &mut Vec<CompileError>) {
+        match expr {
+            Expression::Call { func: callee, args } | Expression::CallAlt { callee, args } => {
+                self.check_call(func, callee, errors);
+                for arg in args {
+                    self.check_expression(func, arg, errors);
+                }
+            }
+            Expression::MethodCall { object, method, args } => {
+                errors.push(violation(
+                    func,
+                    &format!("method call `.{}()` is not allowed in a const fn", method),
+                    "call a `const fn` taking the receiver as an argument instead",
+                ));
+                self.check_expression(func, object, errors);
+                for arg in args {
+                    self.check_expression(func, arg, errors);
+                }
+            }
+            Expression::Closure { .. } => {
+                errors.push(violation(
+                    func,
+                    "closures are not allowed in a const fn",
+                    "move the closure body into a separate `const fn`",
+                ));
+            }
+            Expression::Binary { left, right, .. } | Expression::BinaryOp { left, right, .. } => {
+                self.check_expression(func, left, errors);
+                self.check_expression(func, right, errors);
+            }
+            Expression::Unary { expr, .. } | Expression::UnaryOp { operand: expr, .. } => {
+                self.check_expression(func, expr, errors)
+            }
+            Expression::Assignment { target, value } => {
+                self.check_expression(func, target, errors);
+                self.check_expression(func, value, errors);
+            }
+            Expression::FieldAccess { object, .. } => self.check_expression(func, object, errors),
+            Expression::Index { object, index } => {
+                self.check_expression(func, object, errors);
+                self.check_expression(func, index, errors);
+            }
+            Expression::Range { start, end, .. } => {
+                self.check_expression(func, start, errors);
+                self.check_expression(func, end, errors);
+            }
+            Expression::TupleLiteral(items) | Expression::ArrayLiteral(items) => {
+                for item in items {
+                    self.check_expression(func, item, errors);
+                }
+            }
+            Expression::StructLiteral { fields, .. } => {
+                for field in fields {
+                    self.check_expression(func, &field.value, errors);
+                }
+            }
+            Expression::Block(body) => self.check_block(func, body, errors),
+            Expression::If { condition, then_branch, else_branch } => {
+                self.check_expression(func, condition, errors);
+                self.check_expression(func, then_branch, errors);
+                if let Some(else_branch) = else_branch {
+                    self.check_expression(func, else_branch, errors);
+                }
+            }
+            Expression::Match { expression, arms } => {
+                self.check_expression(func, expression, errors);
+                for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        self.check_expression(func, guard, errors);
+                    }
+                    self.check_expression(func, &arm.body, errors);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_call(&self, func: &str, callee: &Expression, errors: &mut Vec<CompileError>) {
+        let name = match callee {
+            Expression::Ident(name) | Expression::Identifier(name) => name,
+            _ => {
+                errors.push(violation(
+                    func,
+                    "indirect calls are not allowed in a const fn",
+                    "call a `const fn` by name",
+                ));
+                return;
+            }
+        };
+
+        if IO_FUNCTIONS.contains(&name.as_str()) {
+            errors.push(violation(
+                func,
+                &format!("I/O call `{}()` is not allowed in a const fn", name),
+                &format!("perform the I/O in the caller, or remove `const` from `{}`", func),
+            ));
+        } else if ALLOCATING_FUNCTIONS.contains(&name.as_str()) {
+            errors.push(violation(
+                func,
+                &format!("heap allocation via `{}()` is not allowed in a const fn", name),
+                "use a fixed-size array or a value computed at compile time",
+            ));
+        } else if !self.const_fns.contains(name) {
+            errors.push(violation(
+                func,
+                &format!("call to non-const function `{}()` in a const fn", name),
+                &format!("mark `{}` as `const fn` if it is const-evaluable", name),
+            ));
+        }
+    }
+}
+
+fn violation(func: &str, message: &str, suggestion: &str) -> CompileError {
+    CompileError::SemanticError {
+        message: format!("in `const fn {}`: {}", func, message),
+        line: None,
+        column: None,
+        source_snippet: None,
+        suggestion: Some(suggestion.to_string()),
+        related_info: Vec::new(),
+    }
+}
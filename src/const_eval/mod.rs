@@ -1,3 +1,7 @@
+pub mod const_fn;
+
+pub use const_fn::ConstFnChecker;
+
 use std::collections::HashMap;
 use crate::parser::{Expression, Statement};
 use crate::analysis::constant_eval::{ConstantValue, ConstantEvaluator};
@@ -10,6 +10,33 @@ pub enum Severity {
     Help,
 }
 
+/// When to emit ANSI color codes, as selected by `--color=always|never|auto`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    Always,
+    Never,
+    #[default]
+    Auto,
+}
+
+impl ColorChoice {
+    /// Resolves the choice against the environment. `Auto` colors only when
+    /// stderr is a terminal and `NO_COLOR` is unset or empty.
+    pub fn should_color(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => DiagnosticEmitter::should_use_colors(),
+        }
+    }
+
+    /// Applies the choice process-wide, including output rendered through
+    /// the `colored` crate such as `BeautifulError`.
+    pub fn apply(self) {
+        colored::control::set_override(self.should_color());
+    }
+}
+
 impl fmt::Display for Severity {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -204,25 +231,37 @@ impl DiagnosticEmitter {
         self
     }
 
+    pub fn with_color_choice(mut self, choice: ColorChoice) -> Self {
+        self.use_colors = choice.should_color();
+        self
+    }
+
     pub fn with_max_errors(mut self, max_errors: usize) -> Self {
         self.max_errors = max_errors;
         self
     }
 
     fn should_use_colors() -> bool {
-        // Check if we're in a terminal that supports colors
+        use std::env;
+        use std::io::IsTerminal;
+
+        // https://no-color.org: any non-empty value disables colors
+        if env::var_os("NO_COLOR").map_or(false, |v| !v.is_empty()) {
+            return false;
+        }
+
+        if !std::io::stderr().is_terminal() {
+            return false;
+        }
+
         #[cfg(windows)]
         {
-            // On Windows, check if ANSI support is enabled
-            use std::env;
-            env::var("NO_COLOR").is_err() && 
-            (env::var("TERM").is_ok() || env::var("WT_SESSION").is_ok())
+            // On Windows, only assume ANSI support in terminals known to handle it
+            env::var("TERM").is_ok() || env::var("WT_SESSION").is_ok()
         }
         #[cfg(not(windows))]
         {
-            use std::env;
-            use std::io::IsTerminal;
-            env::var("NO_COLOR").is_err() && std::io::stderr().is_terminal()
+            true
         }
     }
 
@@ -706,6 +745,26 @@ mod tests {
         assert!(!output.is_empty());
     }
 
+    #[test]
+    fn test_color_choice_never_has_no_escape_codes() {
+        let mut emitter = DiagnosticEmitter::new().with_color_choice(ColorChoice::Never);
+
+        let diag = Diagnostic::error("test error").with_note("a note");
+        let output = emitter.emit(&diag);
+
+        assert!(output.contains("test error"));
+        assert!(!output.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_color_choice_always_has_escape_codes() {
+        let mut emitter = DiagnosticEmitter::new().with_color_choice(ColorChoice::Always);
+
+        let output = emitter.emit(&Diagnostic::error("test error"));
+
+        assert!(output.contains("\x1b[31m"));
+    }
+
     #[test]
     fn test_diagnostic_collector() {
         let mut collector = DiagnosticCollector::new();
@@ -31,7 +31,7 @@ pub mod suggestions;
 pub mod beautiful;
 
 use std::fmt;
-pub use diagnostics::{Diagnostic, DiagnosticBuilder, DiagnosticEmitter, DiagnosticCollector, Severity};
+pub use diagnostics::{ColorChoice, Diagnostic, DiagnosticBuilder, DiagnosticEmitter, DiagnosticCollector, Severity};
 pub use suggestions::{did_you_mean, suggest_type_conversion, suggest_borrow_fix, suggest_syntax_fix, example_for_pattern};
 
 /// Information about a borrow (immutable or mutable)
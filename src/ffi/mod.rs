@@ -1,10 +1,26 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_double, c_int, c_void};
 
 pub struct FFIRegistry {
     foreign_functions: HashMap<String, ForeignFunction>,
     type_mappings: HashMap<String, CType>,
+    struct_layouts: HashMap<String, StructLayout>,
+}
+
+/// The C layout of a registered struct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLayout {
+    pub fields: Vec<StructField>,
+    pub size: usize,
+    pub align: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructField {
+    pub name: String,
+    pub ty: CType,
+    pub offset: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +52,26 @@ pub enum CType {
     Function(Vec<CType>, Box<CType>),
 }
 
+/// An argument to or result of a foreign call. `Int` carries every integer
+/// type and `Float` both `float` and `double`; they are narrowed to the
+/// declared `CType` at the call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FFIValue {
+    Void,
+    Int(i64),
+    Float(f64),
+    Pointer(*mut c_void),
+}
+
+/// How a value travels through a call: in an integer register or stack word,
+/// or as a single- or double-precision float.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArgClass {
+    Word,
+    Single,
+    Double,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CallingConvention {
     C,
@@ -49,6 +85,7 @@ impl FFIRegistry {
         let mut registry = Self {
             foreign_functions: HashMap::new(),
             type_mappings: HashMap::new(),
+            struct_layouts: HashMap::new(),
         };
         
         registry.register_standard_types();
@@ -80,9 +117,87 @@ impl FFIRegistry {
         self.type_mappings.get(blaze_type)
     }
     
+    /// Lays out `fields` in order as a C compiler would: each field at the
+    /// next multiple of its alignment, and the size rounded up to the
+    /// largest alignment. Structs used as fields must be registered first.
+    pub fn register_struct_layout(&mut self, name: String, fields: Vec<(String, CType)>) -> Result<(), String> {
+        let mut laid_out = Vec::with_capacity(fields.len());
+        let mut offset: usize = 0;
+        let mut align = 1;
+        
+        for (field_name, ty) in fields {
+            let (field_size, field_align) = self.size_and_align(&ty)
+                .ok_or_else(|| format!("Field {}.{}: {}", name, field_name, self.unknown_layout_reason(&ty)))?;
+            offset = offset.next_multiple_of(field_align);
+            laid_out.push(StructField { name: field_name, ty, offset });
+            offset += field_size;
+            align = align.max(field_align);
+        }
+        
+        let layout = StructLayout { fields: laid_out, size: offset.next_multiple_of(align), align };
+        self.struct_layouts.insert(name, layout);
+        Ok(())
+    }
+    
+    pub fn get_struct_layout(&self, name: &str) -> Option<&StructLayout> {
+        self.struct_layouts.get(name)
+    }
+    
+    pub fn struct_size(&self, name: &str) -> Option<usize> {
+        self.struct_layouts.get(name).map(|layout| layout.size)
+    }
+    
+    pub fn struct_align(&self, name: &str) -> Option<usize> {
+        self.struct_layouts.get(name).map(|layout| layout.align)
+    }
+    
+    /// Why `size_and_align` has no answer for `ty`.
+    fn unknown_layout_reason(&self, ty: &CType) -> String {
+        match ty {
+            CType::Array(inner, _) => self.unknown_layout_reason(inner),
+            CType::Struct(name) => format!("struct {} has no registered layout", name),
+            other => format!("{} has no size", self.type_to_rust_str(other)),
+        }
+    }
+    
+    /// Size and alignment of `ty` on the target, or `None` for `void` and
+    /// unregistered structs.
+    fn size_and_align(&self, ty: &CType) -> Option<(usize, usize)> {
+        let scalar = |size: usize| Some((size, size));
+        match ty {
+            CType::Void => None,
+            CType::Int8 | CType::UInt8 => scalar(1),
+            CType::Int16 | CType::UInt16 => scalar(2),
+            CType::Int32 | CType::UInt32 | CType::Float => scalar(4),
+            CType::Int64 | CType::UInt64 | CType::Double => Some((8, std::mem::align_of::<u64>())),
+            CType::Pointer(_) | CType::Function(..) => scalar(std::mem::size_of::<*const c_void>()),
+            CType::Array(inner, len) => {
+                let (size, align) = self.size_and_align(inner)?;
+                Some((size * len, align))
+            }
+            CType::Struct(name) => self.struct_layouts.get(name).map(|layout| (layout.size, layout.align)),
+        }
+    }
+    
+    /// Renders `func` as a Rust `extern` declaration, preceded by a
+    /// `#[repr(C)]` definition of every registered struct it refers to.
     pub fn generate_wrapper(&self, func: &ForeignFunction) -> String {
         let mut wrapper = String::new();
         
+        let mut structs = Vec::new();
+        let mut seen = HashSet::new();
+        for ty in func.params.iter().chain([&func.return_type]) {
+            self.collect_structs(ty, &mut seen, &mut structs);
+        }
+        for name in structs {
+            let layout = &self.struct_layouts[name];
+            wrapper.push_str(&format!("#[repr(C)]\npub struct {} {{\n", name));
+            for field in &layout.fields {
+                wrapper.push_str(&format!("    pub {}: {},\n", field.name, self.type_to_rust_str(&field.ty)));
+            }
+            wrapper.push_str("}\n\n");
+        }
+        
         wrapper.push_str(&format!("extern \"{}\" fn {}(", 
             self.calling_convention_str(func.calling_convention),
             func.name
@@ -101,6 +216,133 @@ impl FFIRegistry {
         wrapper
     }
     
+    /// Calls `func` through the symbol `loader` resolved for it.
+    ///
+    /// Calls are made through a fixed set of function pointer types rather
+    /// than a general marshaller: up to six arguments, all integers and
+    /// pointers or all floats of one width, returning any scalar or void.
+    /// Structs can't be passed by value. Stdcall and fastcall only differ
+    /// from C on 32-bit x86, where they are rejected.
+    pub fn invoke(&self, loader: &LibraryLoader, func: &ForeignFunction, args: &[FFIValue]) -> Result<FFIValue, String> {
+        if args.len() != func.params.len() {
+            return Err(format!(
+                "{} expects {} argument(s), got {}",
+                func.name, func.params.len(), args.len()
+            ));
+        }
+        if cfg!(target_arch = "x86")
+            && !matches!(func.calling_convention, CallingConvention::C | CallingConvention::Cdecl)
+        {
+            return Err(format!(
+                "Calling convention {:?} is not supported for {}",
+                func.calling_convention, func.name
+            ));
+        }
+        
+        let mut classes = Vec::with_capacity(args.len());
+        let mut words = Vec::with_capacity(args.len());
+        let mut floats = Vec::with_capacity(args.len());
+        for (i, (param, arg)) in func.params.iter().zip(args).enumerate() {
+            let class = self.arg_class(param)
+                .ok_or_else(|| format!("Cannot pass {} by value to {}", self.type_to_rust_str(param), func.name))?;
+            match (class, arg) {
+                (ArgClass::Word, FFIValue::Int(value)) => words.push(self.narrow_int(param, *value)),
+                (ArgClass::Word, FFIValue::Pointer(ptr)) => words.push(*ptr as i64),
+                (ArgClass::Single | ArgClass::Double, FFIValue::Float(value)) => floats.push(*value),
+                _ => {
+                    return Err(format!(
+                        "Argument {} of {}: expected {}, got {:?}",
+                        i, func.name, self.type_to_rust_str(param), arg
+                    ))
+                }
+            }
+            classes.push(class);
+        }
+        
+        let ret = match func.return_type {
+            CType::Void => None,
+            ref ty => Some(self.arg_class(ty)
+                .ok_or_else(|| format!("Cannot return {} by value from {}", self.type_to_rust_str(ty), func.name))?),
+        };
+        
+        let ptr = loader.get_symbol(&func.library, &func.symbol)?;
+        let unsupported = || format!("Unsupported signature for {}: {}", func.name, self.generate_wrapper(func));
+        
+        // SAFETY: the registered signature is trusted to match the symbol, and
+        // every argument was converted to the type it declares.
+        let raw = unsafe {
+            if classes.iter().all(|class| *class == ArgClass::Word) {
+                call_with(ptr, &words, ret)
+            } else if classes.iter().all(|class| *class == ArgClass::Double) {
+                call_with(ptr, &floats, ret)
+            } else if classes.iter().all(|class| *class == ArgClass::Single) {
+                let singles: Vec<f32> = floats.iter().map(|value| *value as f32).collect();
+                call_with(ptr, &singles, ret)
+            } else {
+                None
+            }
+        }
+        .ok_or_else(unsupported)?;
+        
+        Ok(match (&func.return_type, raw) {
+            (CType::Pointer(_) | CType::Array(..) | CType::Function(..), FFIValue::Int(value)) => {
+                FFIValue::Pointer(value as *mut c_void)
+            }
+            (ty, FFIValue::Int(value)) => FFIValue::Int(self.narrow_int(ty, value)),
+            (_, other) => other,
+        })
+    }
+    
+    fn arg_class(&self, ty: &CType) -> Option<ArgClass> {
+        match ty {
+            CType::Float => Some(ArgClass::Single),
+            CType::Double => Some(ArgClass::Double),
+            CType::Void | CType::Struct(_) => None,
+            // Arrays decay to pointers, and function types are pointers too.
+            _ => Some(ArgClass::Word),
+        }
+    }
+    
+    /// Truncates and re-extends `value` as the integer type `ty`, so the full
+    /// register holds what C would have widened it to.
+    fn narrow_int(&self, ty: &CType, value: i64) -> i64 {
+        match ty {
+            CType::Int8 => value as i8 as i64,
+            CType::Int16 => value as i16 as i64,
+            CType::Int32 => value as i32 as i64,
+            CType::UInt8 => value as u8 as i64,
+            CType::UInt16 => value as u16 as i64,
+            CType::UInt32 => value as u32 as i64,
+            _ => value,
+        }
+    }
+    
+    /// Adds the registered structs `ty` refers to into `structs`, each after
+    /// the structs its own fields use. A struct is marked `seen` before its
+    /// fields are visited, so self-referential structs such as list nodes
+    /// are only added once.
+    fn collect_structs<'a>(&'a self, ty: &'a CType, seen: &mut HashSet<&'a str>, structs: &mut Vec<&'a str>) {
+        match ty {
+            CType::Pointer(inner) | CType::Array(inner, _) => self.collect_structs(inner, seen, structs),
+            CType::Function(params, ret) => {
+                for ty in params.iter().chain([ret.as_ref()]) {
+                    self.collect_structs(ty, seen, structs);
+                }
+            }
+            CType::Struct(name) => {
+                if let Some(layout) = self.struct_layouts.get(name) {
+                    if seen.insert(name) {
+                        for field in &layout.fields {
+                            self.collect_structs(&field.ty, seen, structs);
+                        }
+                        structs.push(name);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    
     fn calling_convention_str(&self, conv: CallingConvention) -> &str {
         match conv {
             CallingConvention::C | CallingConvention::Cdecl => "C",
@@ -142,6 +384,47 @@ impl Default for FFIRegistry {
     }
 }
 
+/// Calls `ptr` as `extern "C" fn(T, ...) -> R`, where `R` follows `ret`.
+/// Returns `None` for more arguments than there are arms.
+unsafe fn call_with<T: Copy>(ptr: *mut c_void, args: &[T], ret: Option<ArgClass>) -> Option<FFIValue> {
+    macro_rules! call {
+        ($($a:ident),*) => {{
+            let [$($a),*] = args else { unreachable!() };
+            match ret {
+                None => {
+                    let f: extern "C" fn($(call!(@arg $a)),*) = std::mem::transmute(ptr);
+                    f($(*$a),*);
+                    FFIValue::Void
+                }
+                Some(ArgClass::Word) => {
+                    let f: extern "C" fn($(call!(@arg $a)),*) -> i64 = std::mem::transmute(ptr);
+                    FFIValue::Int(f($(*$a),*))
+                }
+                Some(ArgClass::Single) => {
+                    let f: extern "C" fn($(call!(@arg $a)),*) -> f32 = std::mem::transmute(ptr);
+                    FFIValue::Float(f($(*$a),*) as f64)
+                }
+                Some(ArgClass::Double) => {
+                    let f: extern "C" fn($(call!(@arg $a)),*) -> c_double = std::mem::transmute(ptr);
+                    FFIValue::Float(f($(*$a),*))
+                }
+            }
+        }};
+        (@arg $a:ident) => { T };
+    }
+    
+    Some(match args.len() {
+        0 => call!(),
+        1 => call!(a),
+        2 => call!(a, b),
+        3 => call!(a, b, c),
+        4 => call!(a, b, c, d),
+        5 => call!(a, b, c, d, e),
+        6 => call!(a, b, c, d, e, g),
+        _ => return None,
+    })
+}
+
 pub struct CStringConverter;
 
 impl CStringConverter {
@@ -289,3 +572,170 @@ impl Drop for LibraryLoader {
         }
     }
 }
+
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    fn foreign(library: &str, name: &str, params: Vec<CType>, return_type: CType) -> ForeignFunction {
+        ForeignFunction {
+            name: name.to_string(),
+            library: library.to_string(),
+            symbol: name.to_string(),
+            params,
+            return_type,
+            calling_convention: CallingConvention::C,
+        }
+    }
+
+    fn libc_function(name: &str, params: Vec<CType>, return_type: CType) -> ForeignFunction {
+        foreign("libc.so.6", name, params, return_type)
+    }
+
+    fn loader() -> LibraryLoader {
+        let mut loader = LibraryLoader::new();
+        loader.load_library("libc.so.6").unwrap();
+        loader.load_library("libm.so.6").unwrap();
+        loader
+    }
+
+    #[test]
+    fn test_invoke_abs_from_libc() {
+        let registry = FFIRegistry::new();
+        let abs = libc_function("abs", vec![CType::Int32], CType::Int32);
+
+        assert_eq!(registry.invoke(&loader(), &abs, &[FFIValue::Int(-42)]), Ok(FFIValue::Int(42)));
+        // The argument is narrowed to the declared `int` first.
+        assert_eq!(registry.invoke(&loader(), &abs, &[FFIValue::Int(-(1 << 32) - 7)]), Ok(FFIValue::Int(7)));
+    }
+
+    #[test]
+    fn test_invoke_with_float_and_pointer_arguments() {
+        let registry = FFIRegistry::new();
+        let loader = loader();
+
+        let pow = foreign("libm.so.6", "pow", vec![CType::Double, CType::Double], CType::Double);
+        let args = [FFIValue::Float(2.0), FFIValue::Float(10.0)];
+        assert_eq!(registry.invoke(&loader, &pow, &args), Ok(FFIValue::Float(1024.0)));
+
+        let fabsf = foreign("libm.so.6", "fabsf", vec![CType::Float], CType::Float);
+        assert_eq!(registry.invoke(&loader, &fabsf, &[FFIValue::Float(-2.5)]), Ok(FFIValue::Float(2.5)));
+
+        let text = CString::new("blaze").unwrap();
+        let strlen = libc_function("strlen", vec![CType::Pointer(Box::new(CType::Int8))], CType::UInt64);
+        let arg = FFIValue::Pointer(text.as_ptr() as *mut c_void);
+        assert_eq!(registry.invoke(&loader, &strlen, &[arg]), Ok(FFIValue::Int(5)));
+    }
+
+    #[test]
+    fn test_invoke_rejects_mismatched_arguments() {
+        let registry = FFIRegistry::new();
+        let abs = libc_function("abs", vec![CType::Int32], CType::Int32);
+
+        assert!(registry.invoke(&loader(), &abs, &[]).is_err());
+        assert!(registry.invoke(&loader(), &abs, &[FFIValue::Float(1.0)]).is_err());
+
+        let by_value = libc_function("div", vec![CType::Int32, CType::Int32], CType::Struct("div_t".to_string()));
+        let err = registry.invoke(&loader(), &by_value, &[FFIValue::Int(7), FFIValue::Int(2)]).unwrap_err();
+        assert_eq!(err, "Cannot return div_t by value from div");
+    }
+}
+
+#[cfg(test)]
+mod layout_tests {
+    use super::*;
+
+    fn field(name: &str, ty: CType) -> (String, CType) {
+        (name.to_string(), ty)
+    }
+
+    fn offsets(registry: &FFIRegistry, name: &str) -> Vec<usize> {
+        registry.get_struct_layout(name).unwrap().fields.iter().map(|f| f.offset).collect()
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_struct_layout_pads_fields_to_their_alignment() {
+        let mut registry = FFIRegistry::new();
+        let fields = vec![field("id", CType::Int32), field("weight", CType::Double), field("flag", CType::UInt8)];
+        registry.register_struct_layout("Item".to_string(), fields).unwrap();
+
+        assert_eq!(offsets(&registry, "Item"), vec![0, 8, 16]);
+        assert_eq!(registry.struct_size("Item"), Some(24));
+        assert_eq!(registry.struct_align("Item"), Some(8));
+
+        // Nested structs and arrays align to their element.
+        let fields = vec![
+            field("tag", CType::UInt8),
+            field("item", CType::Struct("Item".to_string())),
+            field("code", CType::Array(Box::new(CType::UInt16), 3)),
+            field("next", CType::Pointer(Box::new(CType::Struct("Item".to_string())))),
+        ];
+        registry.register_struct_layout("Slot".to_string(), fields).unwrap();
+
+        assert_eq!(offsets(&registry, "Slot"), vec![0, 8, 32, 40]);
+        assert_eq!(registry.struct_size("Slot"), Some(48));
+    }
+
+    #[test]
+    fn test_struct_layout_rejects_unregistered_field_types() {
+        let mut registry = FFIRegistry::new();
+        let err = registry
+            .register_struct_layout("Outer".to_string(), vec![field("inner", CType::Struct("Inner".to_string()))])
+            .unwrap_err();
+
+        assert_eq!(err, "Field Outer.inner: struct Inner has no registered layout");
+        assert_eq!(registry.struct_size("Outer"), None);
+    }
+
+    #[test]
+    fn test_wrapper_defines_referenced_structs() {
+        let mut registry = FFIRegistry::new();
+        registry
+            .register_struct_layout("Point".to_string(), vec![field("x", CType::Int32), field("y", CType::Int32)])
+            .unwrap();
+        registry
+            .register_struct_layout(
+                "Rect".to_string(),
+                vec![field("min", CType::Struct("Point".to_string())), field("max", CType::Struct("Point".to_string()))],
+            )
+            .unwrap();
+
+        let area = ForeignFunction {
+            name: "area".to_string(),
+            library: "libgeom.so".to_string(),
+            symbol: "area".to_string(),
+            params: vec![CType::Struct("Rect".to_string()), CType::Pointer(Box::new(CType::Struct("Point".to_string())))],
+            return_type: CType::Int64,
+            calling_convention: CallingConvention::C,
+        };
+
+        let expected = "#[repr(C)]\npub struct Point {\n    pub x: i32,\n    pub y: i32,\n}\n\n\
+                        #[repr(C)]\npub struct Rect {\n    pub min: Point,\n    pub max: Point,\n}\n\n\
+                        extern \"C\" fn area(arg0: Rect, arg1: *mut Point) -> i64";
+        assert_eq!(registry.generate_wrapper(&area), expected);
+    }
+
+    #[test]
+    fn test_wrapper_defines_self_referential_struct_once() {
+        let mut registry = FFIRegistry::new();
+        let next = CType::Pointer(Box::new(CType::Struct("Node".to_string())));
+        registry
+            .register_struct_layout("Node".to_string(), vec![field("value", CType::Int32), field("next", next.clone())])
+            .unwrap();
+
+        let length = ForeignFunction {
+            name: "list_length".to_string(),
+            library: "liblist.so".to_string(),
+            symbol: "list_length".to_string(),
+            params: vec![next],
+            return_type: CType::UInt64,
+            calling_convention: CallingConvention::C,
+        };
+
+        let expected = "#[repr(C)]\npub struct Node {\n    pub value: i32,\n    pub next: *mut Node,\n}\n\n\
+                        extern \"C\" fn list_length(arg0: *mut Node) -> u64";
+        assert_eq!(registry.generate_wrapper(&length), expected);
+    }
+}
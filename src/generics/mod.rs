@@ -88,6 +88,11 @@ impl GenericResolver {
             Type::Char => "char".to_string(),
             Type::String => "String".to_string(),
             Type::Custom(name) => name.replace('<', "_").replace('>', "_").replace(',', "_"),
+            Type::ConstValue(value) => value.to_string(),
+            Type::Array { element, size: Some(size) } => format!("arr{}_{}", size, self.type_to_mangled_string(element)),
+            Type::Array { element, size: None } | Type::Slice(element) => {
+                format!("slice_{}", self.type_to_mangled_string(element))
+            }
             _ => "unknown".to_string(),
         }
     }
@@ -101,6 +106,22 @@ impl GenericResolver {
             Type::Custom(name) => {
                 substitutions.get(name).cloned().unwrap_or_else(|| ty.clone())
             }
+            Type::Generic(name, args) => Type::Generic(
+                name.clone(),
+                args.iter().map(|arg| self.substitute_type_params(arg, substitutions)).collect(),
+            ),
+            Type::ParamArray { element, size_param } => {
+                let element = Box::new(self.substitute_type_params(element, substitutions));
+                match substitutions.get(size_param) {
+                    Some(Type::ConstValue(size)) if *size >= 0 => Type::Array { element, size: Some(*size as usize) },
+                    _ => Type::ParamArray { element, size_param: size_param.clone() },
+                }
+            }
+            Type::Array { element, size } => Type::Array {
+                element: Box::new(self.substitute_type_params(element, substitutions)),
+                size: *size,
+            },
+            Type::Slice(element) => Type::Slice(Box::new(self.substitute_type_params(element, substitutions))),
             _ => ty.clone(),
         }
     }
@@ -196,3 +217,25 @@ impl Default for VarianceChecker {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn array(element: Type, size: usize) -> Type {
+        Type::Array { element: Box::new(element), size: Some(size) }
+    }
+
+    #[test]
+    fn test_array_and_slice_mangling_is_structural() {
+        let mut resolver = GenericResolver::new();
+        let matrix = array(array(Type::F32, 4), 4);
+
+        assert_eq!(resolver.monomorphize("transpose", vec![matrix.clone()]).unwrap(), "transpose_arr4_arr4_f32");
+        assert_eq!(resolver.monomorphize("transpose", vec![matrix]).unwrap(), "transpose_arr4_arr4_f32");
+        assert_eq!(
+            resolver.monomorphize("sum", vec![Type::Slice(Box::new(array(Type::I32, 2)))]).unwrap(),
+            "sum_slice_arr2_i32"
+        );
+    }
+}
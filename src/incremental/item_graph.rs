@@ -0,0 +1,320 @@
+use crate::parser::{Expression, Function, Item, Program, Statement, Type};
+use std::collections::{HashMap, HashSet};
+
+/// Item-level dependency graph: each function, struct, const or static maps
+/// to the top-level items it references.
+#[derive(Debug, Clone, Default)]
+pub struct ItemDependencyGraph {
+    dependencies: HashMap<String, HashSet<String>>,
+    hashes: HashMap<String, u64>,
+}
+
+impl ItemDependencyGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn build(program: &Program) -> Self {
+        let names: HashSet<String> = program.items.iter().filter_map(item_name).collect();
+        let mut graph = Self::new();
+
+        for item in &program.items {
+            let name = match item_name(item) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let mut referenced = HashSet::new();
+            match item {
+                Item::Function(func) => collect_function(func, &mut referenced),
+                Item::Struct(s) => {
+                    for field in &s.fields {
+                        collect_type(&field.ty, &mut referenced);
+                    }
+                }
+                Item::Const(c) => {
+                    collect_type(&c.ty, &mut referenced);
+                    collect_expression(&c.value, &mut referenced);
+                }
+                Item::Static(s) => {
+                    collect_type(&s.ty, &mut referenced);
+                    collect_expression(&s.value, &mut referenced);
+                }
+                _ => {}
+            }
+            referenced.retain(|r| names.contains(r) && r != &name);
+
+            graph.hashes.insert(name.clone(), hash_item(item));
+            graph.dependencies.insert(name, referenced);
+        }
+
+        graph
+    }
+
+    pub fn dependencies_of(&self, item: &str) -> Option<&HashSet<String>> {
+        self.dependencies.get(item)
+    }
+
+    /// Returns every item that transitively references `changed_item`.
+    pub fn get_affected_items(&self, changed_item: &str) -> HashSet<String> {
+        let mut affected = HashSet::new();
+        let mut to_check = vec![changed_item.to_string()];
+
+        while let Some(item) = to_check.pop() {
+            for (dependent, dependencies) in &self.dependencies {
+                if dependencies.contains(&item) && affected.insert(dependent.clone()) {
+                    to_check.push(dependent.clone());
+                }
+            }
+        }
+
+        affected
+    }
+
+    /// Groups of items that depend on each other, directly or through a
+    /// cycle of references.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<String>> {
+        strongly_connected_components(&self.dependencies)
+    }
+
+    /// Returns the items whose definition differs from `previous`, including
+    /// items that were added.
+    pub fn changed_items(&self, previous: &ItemDependencyGraph) -> HashSet<String> {
+        self.hashes
+            .iter()
+            .filter(|(name, hash)| previous.hashes.get(*name) != Some(hash))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+fn item_name(item: &Item) -> Option<String> {
+    match item {
+        Item::Function(func) => Some(func.name.clone()),
+        Item::Struct(s) => Some(s.name.clone()),
+        Item::Const(c) => Some(c.name.clone()),
+        Item::Static(s) => Some(s.name.clone()),
+        _ => None,
+    }
+}
+
+/// Tarjan's algorithm over `graph`, where each node maps to the nodes it
+/// points at. Every node ends up in exactly one component; a component with
+/// more than one member, or a node pointing at itself, is a cycle. Members
+/// are sorted and components come out dependencies first.
+pub fn strongly_connected_components(graph: &HashMap<String, HashSet<String>>) -> Vec<Vec<String>> {
+    struct Tarjan<'a> {
+        graph: &'a HashMap<String, HashSet<String>>,
+        index: HashMap<&'a str, usize>,
+        low_link: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: HashSet<&'a str>,
+        components: Vec<Vec<String>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn visit(&mut self, node: &'a str) {
+            let index = self.index.len();
+            self.index.insert(node, index);
+            self.low_link.insert(node, index);
+            self.stack.push(node);
+            self.on_stack.insert(node);
+
+            let mut successors: Vec<&'a str> = self.graph.get(node).into_iter().flatten().map(String::as_str).collect();
+            successors.sort_unstable();
+            for next in successors {
+                if !self.index.contains_key(next) {
+                    self.visit(next);
+                    let low = self.low_link[node].min(self.low_link[next]);
+                    self.low_link.insert(node, low);
+                } else if self.on_stack.contains(next) {
+                    let low = self.low_link[node].min(self.index[next]);
+                    self.low_link.insert(node, low);
+                }
+            }
+
+            if self.low_link[node] == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member.to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort();
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        graph,
+        index: HashMap::new(),
+        low_link: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+    let mut nodes: Vec<&str> = graph.keys().map(String::as_str).collect();
+    nodes.sort_unstable();
+    for node in nodes {
+        if !tarjan.index.contains_key(node) {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.components
+}
+
+fn hash_item(item: &Item) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", item).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn collect_function(func: &Function, out: &mut HashSet<String>) {
+    for param in &func.params {
+        collect_type(&param.ty, out);
+    }
+    if let Some(ty) = &func.return_type {
+        collect_type(ty, out);
+    }
+    collect_statements(&func.body, out);
+}
+
+fn collect_type(ty: &Type, out: &mut HashSet<String>) {
+    match ty {
+        Type::Custom(name) => {
+            out.insert(name.clone());
+        }
+        Type::Generic(name, args) => {
+            out.insert(name.clone());
+            for arg in args {
+                collect_type(arg, out);
+            }
+        }
+        Type::Reference { inner, .. } | Type::Pointer { inner, .. } => collect_type(inner, out),
+        Type::Array { element, .. } | Type::Slice(element) => collect_type(element, out),
+        Type::Tuple(types) => {
+            for ty in types {
+                collect_type(ty, out);
+            }
+        }
+        Type::Function { params, return_type } => {
+            for ty in params {
+                collect_type(ty, out);
+            }
+            collect_type(return_type, out);
+        }
+        _ => {}
+    }
+}
+
+fn collect_statements(statements: &[Statement], out: &mut HashSet<String>) {
+    for stmt in statements {
+        match stmt {
+            Statement::Let { ty, value, .. } => {
+                if let Some(ty) = ty {
+                    collect_type(ty, out);
+                }
+                if let Some(value) = value {
+                    collect_expression(value, out);
+                }
+            }
+            Statement::Return(Some(expr)) | Statement::Break { value: Some(expr), .. } | Statement::Expression(expr) => {
+                collect_expression(expr, out);
+            }
+            Statement::While { condition, body } => {
+                collect_expression(condition, out);
+                collect_statements(body, out);
+            }
+            Statement::For { iterable, body, .. } => {
+                collect_expression(iterable, out);
+                collect_statements(body, out);
+            }
+            Statement::Loop { body } | Statement::Block(body) => collect_statements(body, out),
+            Statement::Labeled { body, .. } => collect_statements(std::slice::from_ref(body), out),
+            Statement::If { condition, then_body, else_body } => {
+                collect_expression(condition, out);
+                collect_statements(then_body, out);
+                if let Some(else_body) = else_body {
+                    collect_statements(else_body, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn collect_expression(expr: &Expression, out: &mut HashSet<String>) {
+    match expr {
+        Expression::Ident(name) | Expression::Identifier(name) => {
+            out.insert(name.clone());
+        }
+        Expression::Binary { left, right, .. } | Expression::BinaryOp { left, right, .. } => {
+            collect_expression(left, out);
+            collect_expression(right, out);
+        }
+        Expression::Unary { expr, .. } | Expression::UnaryOp { operand: expr, .. } => {
+            collect_expression(expr, out)
+        }
+        Expression::Call { func, args } | Expression::CallAlt { callee: func, args } => {
+            collect_expression(func, out);
+            for arg in args {
+                collect_expression(arg, out);
+            }
+        }
+        Expression::Assignment { target, value } => {
+            collect_expression(target, out);
+            collect_expression(value, out);
+        }
+        Expression::FieldAccess { object, .. } => collect_expression(object, out),
+        Expression::MethodCall { object, args, .. } => {
+            collect_expression(object, out);
+            for arg in args {
+                collect_expression(arg, out);
+            }
+        }
+        Expression::Index { object, index } => {
+            collect_expression(object, out);
+            collect_expression(index, out);
+        }
+        Expression::Range { start, end, .. } => {
+            collect_expression(start, out);
+            collect_expression(end, out);
+        }
+        Expression::TupleLiteral(items) | Expression::ArrayLiteral(items) => {
+            for item in items {
+                collect_expression(item, out);
+            }
+        }
+        Expression::StructLiteral { name, fields } => {
+            out.insert(name.clone());
+            for field in fields {
+                collect_expression(&field.value, out);
+            }
+        }
+        Expression::Block(statements) => collect_statements(statements, out),
+        Expression::If { condition, then_branch, else_branch } => {
+            collect_expression(condition, out);
+            collect_expression(then_branch, out);
+            if let Some(else_branch) = else_branch {
+                collect_expression(else_branch, out);
+            }
+        }
+        Expression::Match { expression, arms } => {
+            collect_expression(expression, out);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    collect_expression(guard, out);
+                }
+                collect_expression(&arm.body, out);
+            }
+        }
+        Expression::Closure { body, .. } => collect_expression(body, out),
+        _ => {}
+    }
+}
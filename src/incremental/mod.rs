@@ -1,3 +1,7 @@
+pub mod item_graph;
+
+pub use item_graph::{strongly_connected_components, ItemDependencyGraph};
+
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::time::SystemTime;
@@ -5,6 +9,7 @@ use std::time::SystemTime;
 pub struct IncrementalCompiler {
     file_hashes: HashMap<PathBuf, FileHash>,
     dependency_graph: HashMap<PathBuf, HashSet<PathBuf>>,
+    item_graph: ItemDependencyGraph,
     cache: CompilationCache,
 }
 
@@ -32,6 +37,7 @@ impl IncrementalCompiler {
         Self {
             file_hashes: HashMap::new(),
             dependency_graph: HashMap::new(),
+            item_graph: ItemDependencyGraph::new(),
             cache: CompilationCache::new(),
         }
     }
@@ -94,6 +100,25 @@ impl IncrementalCompiler {
         affected
     }
     
+    pub fn get_affected_items(&self, changed_item: &str) -> HashSet<String> {
+        self.item_graph.get_affected_items(changed_item)
+    }
+    
+    /// Replaces the item graph with one from a fresh semantic analysis and
+    /// returns the items that must be recompiled: those whose definition
+    /// changed plus everything that depends on them.
+    pub fn dirty_items(&mut self, graph: ItemDependencyGraph) -> HashSet<String> {
+        let changed = graph.changed_items(&self.item_graph);
+        let mut dirty = changed.clone();
+        
+        for item in &changed {
+            dirty.extend(graph.get_affected_items(item));
+        }
+        
+        self.item_graph = graph;
+        dirty
+    }
+    
     pub fn cache_ast(&mut self, file: PathBuf, ast: Vec<u8>) {
         self.cache.ast_cache.insert(file, ast);
     }
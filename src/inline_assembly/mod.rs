@@ -120,10 +120,11 @@ impl InlineAssemblyParser {
             let constraint = parts[0].trim().trim_matches('"').to_string();
             let variable = parts[1].trim_end_matches(')').trim().to_string();
             
+            let register_class = self.register_class_for(&constraint);
             operands.push(AssemblyOperand {
                 constraint,
                 variable,
-                register_class: None,
+                register_class,
             });
         }
         
@@ -157,6 +158,9 @@ impl InlineAssemblyParser {
         Ok(options)
     }
     
+    /// Checks every constraint, and that a variable used as both an output
+    /// and an input is given compatible register classes, so an integer
+    /// value is never read back out of a vector register.
     pub fn validate_constraints(&self, asm: &InlineAssembly) -> Result<(), String> {
         for output in &asm.outputs {
             if !self.is_valid_constraint(&output.constraint) {
@@ -170,11 +174,52 @@ impl InlineAssemblyParser {
             }
         }
         
+        for output in &asm.outputs {
+            let output_class = output.register_class.or_else(|| self.register_class_for(&output.constraint));
+            for input in asm.inputs.iter().filter(|input| input.variable == output.variable) {
+                let input_class = input.register_class.or_else(|| self.register_class_for(&input.constraint));
+                if let (Some(out), Some(inp)) = (output_class, input_class) {
+                    if !self.classes_compatible(out, inp) {
+                        return Err(format!(
+                            "Operand {} is a {:?} output but a {:?} input",
+                            output.variable, out, inp
+                        ));
+                    }
+                }
+            }
+        }
+        
         Ok(())
     }
     
     fn is_valid_constraint(&self, constraint: &str) -> bool {
-        matches!(constraint, "r" | "m" | "i" | "a" | "b" | "c" | "d" | "x" | "y" | "z")
+        matches!(
+            self.constraint_letter(constraint),
+            "r" | "m" | "i" | "a" | "b" | "c" | "d" | "f" | "g" | "x" | "y" | "z"
+        )
+    }
+    
+    /// The constraint without its `=`, `+` and `&` modifiers.
+    fn constraint_letter<'a>(&self, constraint: &'a str) -> &'a str {
+        constraint.trim_start_matches(['=', '+', '&'])
+    }
+    
+    /// The register class a constraint selects, or `None` for memory and
+    /// immediate operands.
+    fn register_class_for(&self, constraint: &str) -> Option<RegisterClass> {
+        match self.constraint_letter(constraint) {
+            "r" => Some(RegisterClass::General),
+            "a" | "b" | "c" | "d" => Some(RegisterClass::Integer),
+            "f" => Some(RegisterClass::Float),
+            "x" | "y" | "z" => Some(RegisterClass::Vector),
+            "g" => Some(RegisterClass::Any),
+            _ => None,
+        }
+    }
+    
+    fn classes_compatible(&self, a: RegisterClass, b: RegisterClass) -> bool {
+        use RegisterClass::*;
+        matches!((a, b), (Any, _) | (_, Any) | (General | Integer, General | Integer)) || a == b
     }
 }
 
@@ -195,51 +240,28 @@ impl X86AssemblyEmitter {
         }
     }
     
+    /// Formats `asm` as a Rust `asm!` invocation. Rust reads templates as
+    /// Intel syntax unless told otherwise, so AT&T templates, whose operands
+    /// come in the opposite order, get `options(att_syntax)`.
     pub fn emit_asm(&mut self, asm: &InlineAssembly) -> String {
-        let mut output = String::new();
-        
-        output.push_str("asm!(");
-        output.push_str(&format!("\"{}\"", asm.template));
-        
-        if !asm.outputs.is_empty() {
-            output.push_str(", out(");
-            for (i, operand) in asm.outputs.iter().enumerate() {
-                if i > 0 {
-                    output.push_str(", ");
-                }
-                output.push_str(&operand.constraint);
-                output.push_str(") ");
-                output.push_str(&operand.variable);
-            }
-            output.push(')');
-        }
-        
-        if !asm.inputs.is_empty() {
-            output.push_str(", in(");
-            for (i, operand) in asm.inputs.iter().enumerate() {
-                if i > 0 {
-                    output.push_str(", ");
-                }
-                output.push_str(&operand.constraint);
-                output.push_str(") ");
-                output.push_str(&operand.variable);
-            }
-            output.push(')');
-        }
-        
-        if !asm.clobbers.is_empty() {
-            output.push_str(", clobber_abi(");
-            for (i, clobber) in asm.clobbers.iter().enumerate() {
-                if i > 0 {
-                    output.push_str(", ");
-                }
-                output.push_str(&format!("\"{}\"", clobber));
-            }
-            output.push(')');
+        let dialect_options: &[&str] = match asm.dialect {
+            AsmDialect::ATT => &["att_syntax"],
+            AsmDialect::Intel => &[],
+        };
+        format_asm_macro(asm, Self::register_spec, dialect_options)
+    }
+    
+    fn register_spec(constraint: &str) -> String {
+        match constraint {
+            "a" => "\"eax\"".to_string(),
+            "b" => "\"ebx\"".to_string(),
+            "c" => "\"ecx\"".to_string(),
+            "d" => "\"edx\"".to_string(),
+            "x" => "xmm_reg".to_string(),
+            "y" => "ymm_reg".to_string(),
+            "z" => "zmm_reg".to_string(),
+            _ => "reg".to_string(),
         }
-        
-        output.push_str(");");
-        output
     }
 }
 
@@ -248,3 +270,171 @@ impl Default for X86AssemblyEmitter {
         Self::new()
     }
 }
+
+/// Emits `asm!` for AArch64, whose assembly has a single syntax.
+pub struct ARM64AssemblyEmitter;
+
+impl ARM64AssemblyEmitter {
+    pub fn new() -> Self {
+        Self
+    }
+    
+    pub fn emit_asm(&mut self, asm: &InlineAssembly) -> String {
+        format_asm_macro(asm, Self::register_spec, &[])
+    }
+    
+    fn register_spec(constraint: &str) -> String {
+        match constraint {
+            "w" => "vreg".to_string(),
+            "x" => "vreg_low16".to_string(),
+            _ => "reg".to_string(),
+        }
+    }
+}
+
+impl Default for ARM64AssemblyEmitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Renders `asm!(template, operands..., clobbers..., options(...))`.
+/// `register_spec` turns a constraint letter into the target's register
+/// class or explicit register. Outputs with a `+` constraint are `inout`.
+/// Clobbered registers become discarded outputs; `memory` and `cc` are
+/// already assumed by `asm!` unless an option says otherwise.
+fn format_asm_macro(asm: &InlineAssembly, register_spec: fn(&str) -> String, extra_options: &[&str]) -> String {
+    let mut output = String::new();
+    
+    output.push_str("asm!(");
+    output.push_str(&format!("\"{}\"", asm.template));
+    
+    for operand in &asm.outputs {
+        let kind = if operand.constraint.starts_with('+') { "inout" } else { "out" };
+        let letter = operand.constraint.trim_start_matches(['=', '+', '&']);
+        output.push_str(&format!(", {}({}) {}", kind, register_spec(letter), operand.variable));
+    }
+    
+    for operand in &asm.inputs {
+        output.push_str(&format!(", in({}) {}", register_spec(&operand.constraint), operand.variable));
+    }
+    
+    for clobber in &asm.clobbers {
+        if clobber != "memory" && clobber != "cc" {
+            output.push_str(&format!(", out(\"{}\") _", clobber));
+        }
+    }
+    
+    let mut options: Vec<&str> = asm
+        .options
+        .iter()
+        .map(|option| match option {
+            AsmOption::Pure => "pure",
+            AsmOption::NoMem => "nomem",
+            AsmOption::ReadOnly => "readonly",
+            AsmOption::PreserveSignedFlags => "preserves_flags",
+            AsmOption::NoReturn => "noreturn",
+            AsmOption::NoStack => "nostack",
+        })
+        .collect();
+    options.extend_from_slice(extra_options);
+    if !options.is_empty() {
+        output.push_str(&format!(", options({})", options.join(", ")));
+    }
+    
+    output.push_str(");");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_class_follows_constraint_letter() {
+        let mut parser = InlineAssemblyParser::new();
+        let asm = parser.parse_inline_asm("addps %1, %0 : \"=x\"(sum) : \"x\"(v), \"r\"(n), \"a\"(k), \"m\"(buf)").unwrap();
+
+        let classes: Vec<Option<RegisterClass>> =
+            asm.outputs.iter().chain(&asm.inputs).map(|operand| operand.register_class).collect();
+        assert_eq!(
+            classes,
+            vec![
+                Some(RegisterClass::Vector),
+                Some(RegisterClass::Vector),
+                Some(RegisterClass::General),
+                Some(RegisterClass::Integer),
+                None,
+            ]
+        );
+        assert!(parser.validate_constraints(&asm).is_ok());
+    }
+
+    #[test]
+    fn test_vector_output_of_integer_operand_is_rejected() {
+        let mut parser = InlineAssemblyParser::new();
+        let asm = parser.parse_inline_asm("movd %1, %0 : \"=x\"(count) : \"r\"(count)").unwrap();
+
+        assert_eq!(
+            parser.validate_constraints(&asm),
+            Err("Operand count is a Vector output but a General input".to_string())
+        );
+
+        let asm = parser.parse_inline_asm("inc %0 : \"=r\"(count) : \"a\"(count)").unwrap();
+        assert!(parser.validate_constraints(&asm).is_ok());
+    }
+
+    fn operand(constraint: &str, variable: &str) -> AssemblyOperand {
+        AssemblyOperand { constraint: constraint.to_string(), variable: variable.to_string(), register_class: None }
+    }
+
+    fn add_asm(template: &str, dialect: AsmDialect, options: Vec<AsmOption>) -> InlineAssembly {
+        InlineAssembly {
+            template: template.to_string(),
+            inputs: vec![operand("r", "b")],
+            outputs: vec![operand("+r", "a")],
+            clobbers: vec![],
+            options,
+            dialect,
+        }
+    }
+
+    #[test]
+    fn test_x86_emitter_marks_only_att_templates() {
+        let mut emitter = X86AssemblyEmitter::new();
+
+        assert_eq!(
+            emitter.emit_asm(&add_asm("add {0}, {1}", AsmDialect::Intel, vec![])),
+            "asm!(\"add {0}, {1}\", inout(reg) a, in(reg) b);"
+        );
+        assert_eq!(
+            emitter.emit_asm(&add_asm("add {1}, {0}", AsmDialect::ATT, vec![])),
+            "asm!(\"add {1}, {0}\", inout(reg) a, in(reg) b, options(att_syntax));"
+        );
+    }
+
+    #[test]
+    fn test_options_and_clobbers_are_rendered() {
+        let mut asm = add_asm("add {0}, {1}", AsmDialect::Intel, vec![AsmOption::NoMem, AsmOption::NoStack]);
+        asm.outputs = vec![operand("=x", "v")];
+        asm.clobbers = vec!["ecx".to_string(), "memory".to_string()];
+
+        assert_eq!(
+            X86AssemblyEmitter::new().emit_asm(&asm),
+            "asm!(\"add {0}, {1}\", out(xmm_reg) v, in(reg) b, out(\"ecx\") _, options(nomem, nostack));"
+        );
+    }
+
+    #[test]
+    fn test_arm64_emitter_uses_aarch64_register_classes() {
+        let mut asm = add_asm("fadd {0:s}, {0:s}, {1:s}", AsmDialect::ATT, vec![AsmOption::Pure, AsmOption::NoMem]);
+        asm.outputs = vec![operand("+w", "acc")];
+        asm.inputs = vec![operand("w", "x")];
+        asm.clobbers = vec!["x9".to_string()];
+
+        assert_eq!(
+            ARM64AssemblyEmitter::new().emit_asm(&asm),
+            "asm!(\"fadd {0:s}, {0:s}, {1:s}\", inout(vreg) acc, in(vreg) x, out(\"x9\") _, options(pure, nomem));"
+        );
+    }
+}
@@ -60,6 +60,7 @@ impl IRBuilder {
             functions,
             globals,
             types,
+            constant_pool: Vec::new(),
         })
     }
 
@@ -401,6 +402,11 @@ impl IRBuilder {
         match ty {
             Type::I32 => IRType::I32,
             Type::I64 => IRType::I64,
+            // IR integers are signless; signedness lives in the operations.
+            Type::U8 => IRType::I8,
+            Type::U16 => IRType::I16,
+            Type::U32 => IRType::I32,
+            Type::U64 => IRType::I64,
             Type::F32 => IRType::F32,
             Type::F64 => IRType::F64,
             Type::Bool => IRType::I1,
@@ -11,11 +11,19 @@ pub enum Instruction {
     Mul { result: String, left: String, right: String, ty: IRType },
     Div { result: String, left: String, right: String, ty: IRType },
     Mod { result: String, left: String, right: String, ty: IRType },
+    /// Fused multiply-add `a * b + c` with a single rounding step.
+    FMA { result: String, a: String, b: String, c: String, ty: IRType },
     ICmp { result: String, condition: ICmpCondition, left: String, right: String },
     FCmp { result: String, condition: FCmpCondition, left: String, right: String },
     Call { result: Option<String>, func: String, args: Vec<String> },
     GetElementPtr { result: String, ptr: String, indices: Vec<String> },
     BitCast { result: String, value: String, ty: IRType },
+    /// Sign-extends an integer from `from_ty` to the wider `to_ty`.
+    SExt { result: String, value: String, from_ty: IRType, to_ty: IRType },
+    /// Zero-extends an integer from `from_ty` to the wider `to_ty`.
+    ZExt { result: String, value: String, from_ty: IRType, to_ty: IRType },
+    /// Truncates an integer from `from_ty` to the narrower `to_ty`.
+    Trunc { result: String, value: String, from_ty: IRType, to_ty: IRType },
     Phi { result: String, ty: IRType, incoming: Vec<(String, String)> },
 }
 
@@ -24,6 +32,9 @@ pub enum Terminator {
     Ret { value: Option<String> },
     Br { target: String },
     CondBr { condition: String, true_target: String, false_target: String },
+    /// Jumps to the target of the case equal to the integer `value`, or to
+    /// `default` if no case matches. Dense cases are lowered as a jump table.
+    Switch { value: String, cases: Vec<(i64, String)>, default: String },
     Unreachable,
 }
 
@@ -68,16 +79,45 @@ impl Instruction {
             Instruction::Mul { result, .. } => Some(result),
             Instruction::Div { result, .. } => Some(result),
             Instruction::Mod { result, .. } => Some(result),
+            Instruction::FMA { result, .. } => Some(result),
             Instruction::ICmp { result, .. } => Some(result),
             Instruction::FCmp { result, .. } => Some(result),
             Instruction::Call { result, .. } => result.as_deref(),
             Instruction::GetElementPtr { result, .. } => Some(result),
             Instruction::BitCast { result, .. } => Some(result),
+            Instruction::SExt { result, .. } => Some(result),
+            Instruction::ZExt { result, .. } => Some(result),
+            Instruction::Trunc { result, .. } => Some(result),
             Instruction::Phi { result, .. } => Some(result),
             _ => None,
         }
     }
 
+    /// Mutable access to the value defined by [`get_result`](Self::get_result).
+    pub fn result_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Instruction::Alloca { result, .. }
+            | Instruction::Load { result, .. }
+            | Instruction::Const { result, .. }
+            | Instruction::Add { result, .. }
+            | Instruction::Sub { result, .. }
+            | Instruction::Mul { result, .. }
+            | Instruction::Div { result, .. }
+            | Instruction::Mod { result, .. }
+            | Instruction::FMA { result, .. }
+            | Instruction::ICmp { result, .. }
+            | Instruction::FCmp { result, .. }
+            | Instruction::GetElementPtr { result, .. }
+            | Instruction::BitCast { result, .. }
+            | Instruction::SExt { result, .. }
+            | Instruction::ZExt { result, .. }
+            | Instruction::Trunc { result, .. }
+            | Instruction::Phi { result, .. } => Some(result),
+            Instruction::Call { result, .. } => result.as_mut(),
+            Instruction::Store { .. } => None,
+        }
+    }
+
     pub fn get_operands(&self) -> Vec<&str> {
         match self {
             Instruction::Store { value, ptr } => vec![value, ptr],
@@ -87,6 +127,7 @@ impl Instruction {
             Instruction::Mul { left, right, .. } => vec![left, right],
             Instruction::Div { left, right, .. } => vec![left, right],
             Instruction::Mod { left, right, .. } => vec![left, right],
+            Instruction::FMA { a, b, c, .. } => vec![a, b, c],
             Instruction::ICmp { left, right, .. } => vec![left, right],
             Instruction::FCmp { left, right, .. } => vec![left, right],
             Instruction::Call { func, args, .. } => {
@@ -100,10 +141,42 @@ impl Instruction {
                 ops
             }
             Instruction::BitCast { value, .. } => vec![value],
+            Instruction::SExt { value, .. } => vec![value],
+            Instruction::ZExt { value, .. } => vec![value],
+            Instruction::Trunc { value, .. } => vec![value],
             Instruction::Phi { incoming, .. } => {
                 incoming.iter().map(|(val, _)| val.as_str()).collect()
             }
             _ => vec![],
         }
     }
+
+    /// Mutable access to the same operands as [`get_operands`](Self::get_operands),
+    /// excluding call targets, for rewriting uses of a value.
+    pub fn operands_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Instruction::Store { value, ptr } => vec![value, ptr],
+            Instruction::Load { ptr, .. } => vec![ptr],
+            Instruction::Add { left, right, .. }
+            | Instruction::Sub { left, right, .. }
+            | Instruction::Mul { left, right, .. }
+            | Instruction::Div { left, right, .. }
+            | Instruction::Mod { left, right, .. }
+            | Instruction::ICmp { left, right, .. }
+            | Instruction::FCmp { left, right, .. } => vec![left, right],
+            Instruction::FMA { a, b, c, .. } => vec![a, b, c],
+            Instruction::Call { args, .. } => args.iter_mut().collect(),
+            Instruction::GetElementPtr { ptr, indices, .. } => {
+                let mut ops = vec![ptr];
+                ops.extend(indices.iter_mut());
+                ops
+            }
+            Instruction::BitCast { value, .. }
+            | Instruction::SExt { value, .. }
+            | Instruction::ZExt { value, .. }
+            | Instruction::Trunc { value, .. } => vec![value],
+            Instruction::Phi { incoming, .. } => incoming.iter_mut().map(|(val, _)| val).collect(),
+            _ => vec![],
+        }
+    }
 }
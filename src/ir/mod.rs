@@ -40,6 +40,9 @@ pub struct Module {
     pub functions: Vec<IRFunction>,
     pub globals: Vec<GlobalVariable>,
     pub types: Vec<TypeDefinition>,
+    /// Read-only constants shared by every function, referenced from
+    /// `Const` instructions as `@const.N`.
+    pub constant_pool: Vec<Constant>,
 }
 
 impl Module {
@@ -49,8 +52,27 @@ impl Module {
             functions: Vec::new(),
             globals: Vec::new(),
             types: Vec::new(),
+            constant_pool: Vec::new(),
         }
     }
+
+    /// Returns the pool index of `constant`, adding it if no identical
+    /// constant is pooled yet.
+    pub fn intern_constant(&mut self, constant: Constant) -> usize {
+        match self.constant_pool.iter().position(|pooled| *pooled == constant) {
+            Some(index) => index,
+            None => {
+                self.constant_pool.push(constant);
+                self.constant_pool.len() - 1
+            }
+        }
+    }
+
+    /// Looks up the pooled constant an `@const.N` operand refers to.
+    pub fn pooled_constant(&self, operand: &str) -> Option<&Constant> {
+        let index: usize = operand.strip_prefix("@const.")?.parse().ok()?;
+        self.constant_pool.get(index)
+    }
 }
 
 #[derive(Debug, Clone)]
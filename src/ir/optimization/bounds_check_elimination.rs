@@ -0,0 +1,343 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{ICmpCondition, IRFunction, Instruction, Terminator};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+/// Runtime function called before every checked array access, with the
+/// index and the length as its first two arguments.
+pub const BOUNDS_CHECK_FN: &str = "blaze_bounds_check";
+
+/// Removes bounds checks whose index is provably within `0..len`.
+///
+/// An index is in range when both it and the length are constants, or when
+/// the check sits on the true side of an `index < len` branch and the index
+/// cannot be negative (a non-negative constant, or a loop induction variable
+/// that starts non-negative and only counts up). Checks that cannot be
+/// proven safe are kept.
+pub struct BoundsCheckElimination;
+
+/// What is known about the values of one function.
+#[derive(Default)]
+struct ValueRanges {
+    constants: HashMap<String, i64>,
+    non_negative: HashSet<String>,
+    /// `(index, len)` pairs known to satisfy `index < len` on entry to a block.
+    less_than: HashMap<String, HashSet<(String, String)>>,
+}
+
+impl ValueRanges {
+    fn analyze(function: &IRFunction) -> Self {
+        let mut ranges = ValueRanges::default();
+
+        for block in &function.blocks {
+            for instruction in &block.instructions {
+                if let Instruction::Const { result, value, .. } = instruction {
+                    if let Ok(value) = value.parse::<i64>() {
+                        ranges.constants.insert(result.clone(), value);
+                        if value >= 0 {
+                            ranges.non_negative.insert(result.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        ranges.find_induction_variables(function);
+        ranges.propagate_branch_facts(function);
+        ranges
+    }
+
+    /// A phi is non-negative when every incoming value is either a
+    /// non-negative constant or the phi itself plus a non-negative constant.
+    fn find_induction_variables(&mut self, function: &IRFunction) {
+        let mut increments: HashMap<&str, (&str, &str)> = HashMap::new();
+        for block in &function.blocks {
+            for instruction in &block.instructions {
+                if let Instruction::Add { result, left, right, .. } = instruction {
+                    increments.insert(result, (left, right));
+                }
+            }
+        }
+
+        for block in &function.blocks {
+            for instruction in &block.instructions {
+                if let Instruction::Phi { result, incoming, .. } = instruction {
+                    let counts_up = incoming.iter().all(|(value, _)| {
+                        if self.non_negative.contains(value) {
+                            return true;
+                        }
+                        match increments.get(value.as_str()) {
+                            Some((left, right)) => {
+                                (*left == result && self.is_non_negative_constant(right))
+                                    || (*right == result && self.is_non_negative_constant(left))
+                            }
+                            None => false,
+                        }
+                    });
+
+                    if counts_up {
+                        self.non_negative.insert(result.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_non_negative_constant(&self, value: &str) -> bool {
+        self.constants.get(value).is_some_and(|c| *c >= 0)
+    }
+
+    /// Records `index < len` for the true target of a guarding branch and
+    /// carries it into blocks that can only be reached from there.
+    fn propagate_branch_facts(&mut self, function: &IRFunction) {
+        let mut predecessors: HashMap<&str, Vec<&str>> = HashMap::new();
+        for block in &function.blocks {
+            for target in successors(&block.terminator) {
+                predecessors.entry(target).or_default().push(&block.label);
+            }
+        }
+        let single_predecessor = |label: &str| -> Option<&str> {
+            match predecessors.get(label).map(Vec::as_slice) {
+                Some([only]) => Some(*only),
+                _ => None,
+            }
+        };
+
+        let comparisons: HashMap<&str, (&ICmpCondition, &str, &str)> = function
+            .blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .filter_map(|instruction| match instruction {
+                Instruction::ICmp { result, condition, left, right } => {
+                    Some((result.as_str(), (condition, left.as_str(), right.as_str())))
+                }
+                _ => None,
+            })
+            .collect();
+
+        for block in &function.blocks {
+            if let Terminator::CondBr { condition, true_target, .. } = &block.terminator {
+                if single_predecessor(true_target) != Some(block.label.as_str()) {
+                    continue;
+                }
+                let fact = match comparisons.get(condition.as_str()) {
+                    Some((ICmpCondition::Slt | ICmpCondition::Ult, index, len)) => (index, len),
+                    Some((ICmpCondition::Sgt | ICmpCondition::Ugt, len, index)) => (index, len),
+                    _ => continue,
+                };
+                self.less_than
+                    .entry(true_target.clone())
+                    .or_default()
+                    .insert((fact.0.to_string(), fact.1.to_string()));
+            }
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in &function.blocks {
+                let from = match single_predecessor(&block.label) {
+                    Some(from) => from,
+                    None => continue,
+                };
+                let inherited = match self.less_than.get(from) {
+                    Some(facts) => facts.clone(),
+                    None => continue,
+                };
+                let facts = self.less_than.entry(block.label.clone()).or_default();
+                for fact in inherited {
+                    changed |= facts.insert(fact);
+                }
+            }
+        }
+    }
+
+    fn in_bounds(&self, block: &str, index: &str, len: &str) -> bool {
+        if let (Some(index), Some(len)) = (self.constants.get(index), self.constants.get(len)) {
+            return *index >= 0 && index < len;
+        }
+
+        self.non_negative.contains(index)
+            && self
+                .less_than
+                .get(block)
+                .is_some_and(|facts| facts.contains(&(index.to_string(), len.to_string())))
+    }
+}
+
+fn successors(terminator: &Terminator) -> Vec<&str> {
+    match terminator {
+        Terminator::Br { target } => vec![target],
+        Terminator::CondBr { true_target, false_target, .. } => vec![true_target, false_target],
+        Terminator::Switch { cases, default, .. } => {
+            cases.iter().map(|(_, target)| target.as_str()).chain([default.as_str()]).collect()
+        }
+        _ => vec![],
+    }
+}
+
+impl BoundsCheckElimination {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl OptimizationPass for BoundsCheckElimination {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            let ranges = ValueRanges::analyze(function);
+
+            for block in &mut function.blocks {
+                let label = block.label.clone();
+                block.instructions.retain(|instruction| match instruction {
+                    Instruction::Call { result: None, func, args } if func == BOUNDS_CHECK_FN && args.len() >= 2 => {
+                        !ranges.in_bounds(&label, &args[0], &args[1])
+                    }
+                    _ => true,
+                });
+            }
+        }
+
+        Ok(optimized)
+    }
+}
+
+impl Default for BoundsCheckElimination {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{BasicBlock, IRType, Parameter};
+
+    fn constant(result: &str, value: i64) -> Instruction {
+        Instruction::Const { result: result.to_string(), ty: IRType::I64, value: value.to_string() }
+    }
+
+    fn bounds_check(index: &str, len: &str) -> Instruction {
+        Instruction::Call {
+            result: None,
+            func: BOUNDS_CHECK_FN.to_string(),
+            args: vec![index.to_string(), len.to_string()],
+        }
+    }
+
+    fn function(params: &[&str], blocks: Vec<BasicBlock>) -> Module {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "sum".to_string(),
+            params: params
+                .iter()
+                .map(|name| Parameter { name: name.to_string(), ty: IRType::I64 })
+                .collect(),
+            return_type: IRType::Void,
+            blocks,
+        });
+        module
+    }
+
+    fn checks_in(module: &Module, label: &str) -> Vec<String> {
+        module.functions[0]
+            .blocks
+            .iter()
+            .find(|block| block.label == label)
+            .unwrap()
+            .instructions
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Call { func, args, .. } if func == BOUNDS_CHECK_FN => Some(args[0].clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// `for i in 0..len { arr[i]; arr[j]; }`
+    #[test]
+    fn test_loop_induction_check_is_eliminated() {
+        let module = function(
+            &["%len", "%j"],
+            vec![
+                BasicBlock {
+                    label: "entry".to_string(),
+                    instructions: vec![constant("%zero", 0), constant("%one", 1)],
+                    terminator: Terminator::Br { target: "header".to_string() },
+                },
+                BasicBlock {
+                    label: "header".to_string(),
+                    instructions: vec![
+                        Instruction::Phi {
+                            result: "%i".to_string(),
+                            ty: IRType::I64,
+                            incoming: vec![
+                                ("%zero".to_string(), "entry".to_string()),
+                                ("%next".to_string(), "body".to_string()),
+                            ],
+                        },
+                        Instruction::ICmp {
+                            result: "%cond".to_string(),
+                            condition: ICmpCondition::Slt,
+                            left: "%i".to_string(),
+                            right: "%len".to_string(),
+                        },
+                    ],
+                    terminator: Terminator::CondBr {
+                        condition: "%cond".to_string(),
+                        true_target: "body".to_string(),
+                        false_target: "exit".to_string(),
+                    },
+                },
+                BasicBlock {
+                    label: "body".to_string(),
+                    instructions: vec![
+                        bounds_check("%i", "%len"),
+                        bounds_check("%j", "%len"),
+                        Instruction::Add {
+                            result: "%next".to_string(),
+                            left: "%i".to_string(),
+                            right: "%one".to_string(),
+                            ty: IRType::I64,
+                        },
+                    ],
+                    terminator: Terminator::Br { target: "header".to_string() },
+                },
+                BasicBlock {
+                    label: "exit".to_string(),
+                    instructions: vec![bounds_check("%i", "%len")],
+                    terminator: Terminator::Ret { value: None },
+                },
+            ],
+        );
+
+        let optimized = BoundsCheckElimination::new().optimize(&module).unwrap();
+
+        assert_eq!(checks_in(&optimized, "body"), vec!["%j".to_string()]);
+        assert_eq!(checks_in(&optimized, "exit"), vec!["%i".to_string()]);
+    }
+
+    #[test]
+    fn test_constant_index_on_fixed_size_array() {
+        let module = function(
+            &[],
+            vec![BasicBlock {
+                label: "entry".to_string(),
+                instructions: vec![
+                    constant("%len", 4),
+                    constant("%in", 3),
+                    constant("%out", 4),
+                    bounds_check("%in", "%len"),
+                    bounds_check("%out", "%len"),
+                ],
+                terminator: Terminator::Ret { value: None },
+            }],
+        );
+
+        let optimized = BoundsCheckElimination::new().optimize(&module).unwrap();
+
+        assert_eq!(checks_in(&optimized, "entry"), vec!["%out".to_string()]);
+    }
+}
@@ -0,0 +1,303 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{IRFunction, IRType, Instruction, Terminator};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+/// Folds chains of integer width casts.
+///
+/// - `trunc(ext(x))` back to the width of `x` is `x`; truncating to any other
+///   width becomes a single extension or truncation of `x`.
+/// - `sext(sext(x))` and `zext(zext(x))` become one extension to the widest
+///   type, and `sext(zext(x))` becomes `zext(x)` since the inner extension
+///   leaves the sign bit clear.
+/// - Casts whose source and target types are the same are removed.
+///
+/// `zext(sext(x))` is kept: the two extensions fill different bits. So is
+/// `ext(trunc(x))`, which clears or replicates bits the truncation dropped.
+pub struct CastCombiner;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CastKind {
+    SExt,
+    ZExt,
+    Trunc,
+}
+
+#[derive(Clone)]
+struct Cast {
+    kind: CastKind,
+    value: String,
+    from_ty: IRType,
+    to_ty: IRType,
+}
+
+impl Cast {
+    fn from_instruction(instruction: &Instruction) -> Option<(&str, Cast)> {
+        let (kind, result, value, from_ty, to_ty) = match instruction {
+            Instruction::SExt { result, value, from_ty, to_ty } => (CastKind::SExt, result, value, from_ty, to_ty),
+            Instruction::ZExt { result, value, from_ty, to_ty } => (CastKind::ZExt, result, value, from_ty, to_ty),
+            Instruction::Trunc { result, value, from_ty, to_ty } => (CastKind::Trunc, result, value, from_ty, to_ty),
+            _ => return None,
+        };
+        Some((result, Cast { kind, value: value.clone(), from_ty: from_ty.clone(), to_ty: to_ty.clone() }))
+    }
+
+    fn into_instruction(self, result: String) -> Instruction {
+        let Cast { kind, value, from_ty, to_ty } = self;
+        match kind {
+            CastKind::SExt => Instruction::SExt { result, value, from_ty, to_ty },
+            CastKind::ZExt => Instruction::ZExt { result, value, from_ty, to_ty },
+            CastKind::Trunc => Instruction::Trunc { result, value, from_ty, to_ty },
+        }
+    }
+}
+
+/// The outcome of combining a cast with the cast that produced its operand.
+enum Combined {
+    Identity(String),
+    Cast(Cast),
+}
+
+fn bit_width(ty: &IRType) -> Option<u32> {
+    match ty {
+        IRType::I1 => Some(1),
+        IRType::I8 => Some(8),
+        IRType::I16 => Some(16),
+        IRType::I32 => Some(32),
+        IRType::I64 => Some(64),
+        IRType::I128 => Some(128),
+        _ => None,
+    }
+}
+
+impl CastCombiner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn combine(outer: &Cast, inner: Option<&Cast>) -> Option<Combined> {
+        if outer.from_ty == outer.to_ty {
+            return Some(Combined::Identity(outer.value.clone()));
+        }
+
+        let inner = inner?;
+        let source = inner.from_ty.clone();
+        let (source_width, inner_width) = (bit_width(&source)?, bit_width(&inner.to_ty)?);
+        let target_width = bit_width(&outer.to_ty)?;
+        let cast = |kind| Some(Combined::Cast(Cast { kind, value: inner.value.clone(), from_ty: source.clone(), to_ty: outer.to_ty.clone() }));
+
+        match (inner.kind, outer.kind) {
+            (CastKind::SExt | CastKind::ZExt, CastKind::Trunc) if inner_width > source_width => {
+                if target_width == source_width {
+                    Some(Combined::Identity(inner.value.clone()))
+                } else if target_width > source_width {
+                    cast(inner.kind)
+                } else {
+                    cast(CastKind::Trunc)
+                }
+            }
+            (CastKind::SExt, CastKind::SExt) | (CastKind::ZExt, CastKind::ZExt) => cast(inner.kind),
+            (CastKind::ZExt, CastKind::SExt) if inner_width > source_width => cast(CastKind::ZExt),
+            (CastKind::Trunc, CastKind::Trunc) => cast(CastKind::Trunc),
+            _ => None,
+        }
+    }
+
+    fn combine_function(function: &mut IRFunction) {
+        let mut casts: HashMap<String, Cast> = HashMap::new();
+        let mut replacements: HashMap<String, String> = HashMap::new();
+        let mut rewritten = false;
+
+        for block in &mut function.blocks {
+            for instruction in &mut block.instructions {
+                for operand in instruction.operands_mut() {
+                    if let Some(replacement) = replacements.get(operand.as_str()) {
+                        *operand = replacement.clone();
+                    }
+                }
+
+                let (result, outer) = match Cast::from_instruction(instruction) {
+                    Some((result, cast)) => (result.to_string(), cast),
+                    None => continue,
+                };
+
+                match Self::combine(&outer, casts.get(&outer.value)) {
+                    Some(Combined::Identity(value)) => {
+                        replacements.insert(result, value);
+                        rewritten = true;
+                    }
+                    Some(Combined::Cast(cast)) => {
+                        *instruction = cast.clone().into_instruction(result.clone());
+                        casts.insert(result, cast);
+                        rewritten = true;
+                    }
+                    None => {
+                        casts.insert(result, outer);
+                    }
+                }
+            }
+
+            match &mut block.terminator {
+                Terminator::Ret { value: Some(value) }
+                | Terminator::CondBr { condition: value, .. }
+                | Terminator::Switch { value, .. } => {
+                    if let Some(replacement) = replacements.get(value.as_str()) {
+                        *value = replacement.clone();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if rewritten {
+            Self::remove_dead_casts(function, &replacements);
+        }
+    }
+
+    /// Drops casts that were replaced outright or lost their last use.
+    fn remove_dead_casts(function: &mut IRFunction, replaced: &HashMap<String, String>) {
+        loop {
+            let mut used: HashSet<String> = HashSet::new();
+            for block in &function.blocks {
+                for instruction in &block.instructions {
+                    used.extend(instruction.get_operands().into_iter().map(str::to_string));
+                }
+                match &block.terminator {
+                    Terminator::Ret { value: Some(value) }
+                    | Terminator::CondBr { condition: value, .. }
+                    | Terminator::Switch { value, .. } => {
+                        used.insert(value.clone());
+                    }
+                    _ => {}
+                }
+            }
+
+            let mut removed = false;
+            for block in &mut function.blocks {
+                block.instructions.retain(|instruction| match Cast::from_instruction(instruction) {
+                    Some((result, _)) if replaced.contains_key(result) || !used.contains(result) => {
+                        removed = true;
+                        false
+                    }
+                    _ => true,
+                });
+            }
+            if !removed {
+                break;
+            }
+        }
+    }
+}
+
+impl OptimizationPass for CastCombiner {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            Self::combine_function(function);
+        }
+
+        Ok(optimized)
+    }
+}
+
+impl Default for CastCombiner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{BasicBlock, Parameter};
+
+    fn sext(result: &str, value: &str, from_ty: IRType, to_ty: IRType) -> Instruction {
+        Instruction::SExt { result: result.to_string(), value: value.to_string(), from_ty, to_ty }
+    }
+
+    fn zext(result: &str, value: &str, from_ty: IRType, to_ty: IRType) -> Instruction {
+        Instruction::ZExt { result: result.to_string(), value: value.to_string(), from_ty, to_ty }
+    }
+
+    fn trunc(result: &str, value: &str, from_ty: IRType, to_ty: IRType) -> Instruction {
+        Instruction::Trunc { result: result.to_string(), value: value.to_string(), from_ty, to_ty }
+    }
+
+    /// Runs the pass on a block of casts returning `returned`, and gives back
+    /// the remaining instructions and the returned value.
+    fn combine(instructions: Vec<Instruction>, returned: &str) -> (Vec<Instruction>, String) {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "cast".to_string(),
+            params: vec![Parameter { name: "%x".to_string(), ty: IRType::I8 }],
+            return_type: IRType::I64,
+            blocks: vec![BasicBlock {
+                label: "entry".to_string(),
+                instructions,
+                terminator: Terminator::Ret { value: Some(returned.to_string()) },
+            }],
+        });
+
+        let mut optimized = CastCombiner::new().optimize(&module).unwrap();
+        let block = optimized.functions[0].blocks.remove(0);
+        match block.terminator {
+            Terminator::Ret { value: Some(value) } => (block.instructions, value),
+            other => panic!("expected return, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_trunc_of_ext_to_original_width_cancels() {
+        for ext in [sext, zext] {
+            let (instructions, returned) = combine(
+                vec![ext("%w", "%x", IRType::I8, IRType::I32), trunc("%n", "%w", IRType::I32, IRType::I8)],
+                "%n",
+            );
+
+            assert_eq!(instructions, vec![]);
+            assert_eq!(returned, "%x");
+        }
+    }
+
+    #[test]
+    fn test_double_extension_collapses() {
+        let (instructions, returned) = combine(
+            vec![sext("%a", "%x", IRType::I8, IRType::I16), sext("%b", "%a", IRType::I16, IRType::I64)],
+            "%b",
+        );
+        assert_eq!(instructions, vec![sext("%b", "%x", IRType::I8, IRType::I64)]);
+        assert_eq!(returned, "%b");
+
+        let (instructions, _) = combine(
+            vec![zext("%a", "%x", IRType::I8, IRType::I16), sext("%b", "%a", IRType::I16, IRType::I64)],
+            "%b",
+        );
+        assert_eq!(instructions, vec![zext("%b", "%x", IRType::I8, IRType::I64)]);
+    }
+
+    #[test]
+    fn test_same_width_cast_is_removed() {
+        let (instructions, returned) = combine(vec![zext("%same", "%x", IRType::I8, IRType::I8)], "%same");
+
+        assert_eq!(instructions, vec![]);
+        assert_eq!(returned, "%x");
+    }
+
+    #[test]
+    fn test_zext_of_sext_is_not_combined() {
+        let chain = vec![sext("%a", "%x", IRType::I8, IRType::I16), zext("%b", "%a", IRType::I16, IRType::I64)];
+        let (instructions, returned) = combine(chain.clone(), "%b");
+
+        assert_eq!(instructions, chain);
+        assert_eq!(returned, "%b");
+    }
+
+    #[test]
+    fn test_ext_of_trunc_is_not_combined() {
+        let chain = vec![trunc("%a", "%x", IRType::I8, IRType::I1), zext("%b", "%a", IRType::I1, IRType::I8)];
+        let (instructions, _) = combine(chain.clone(), "%b");
+
+        assert_eq!(instructions, chain);
+    }
+}
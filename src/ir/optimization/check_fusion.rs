@@ -0,0 +1,242 @@
+use super::{OptimizationPass, Module, BOUNDS_CHECK_FN};
+use crate::ir::{BasicBlock, Instruction};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+/// Runtime function called before dereferencing a pointer that may be null,
+/// with the pointer as its only argument.
+pub const NULL_CHECK_FN: &str = "blaze_null_check";
+
+/// Fuses repeated bounds and null checks within a basic block.
+///
+/// - A check with the same operands as an earlier check in the block is
+///   removed: values are in SSA form, so the earlier check already covered it.
+/// - Bounds checks against the same length with constant, non-negative
+///   indices are combined into the first of them, which is rewritten to check
+///   the largest index. Combining moves the failure point earlier, so it only
+///   happens when no store or call lies between the checks.
+///
+/// This runs after [`BoundsCheckElimination`](super::BoundsCheckElimination),
+/// which removes the checks it can prove safe before the survivors here are
+/// rewritten to larger indices.
+pub struct CheckFusion;
+
+/// Constant-index bounds checks on one length since the last side effect.
+struct FusedBoundsCheck {
+    /// Position of the first check, which is kept and rewritten.
+    position: usize,
+    max_index: i64,
+}
+
+fn is_check(instruction: &Instruction) -> bool {
+    matches!(instruction, Instruction::Call { result: None, func, .. } if func == BOUNDS_CHECK_FN || func == NULL_CHECK_FN)
+}
+
+impl CheckFusion {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// `dominating` holds constants defined in blocks that dominate this one.
+    fn fuse_block(block: &mut BasicBlock, dominating: &HashMap<String, i64>) {
+        // Constant values with the position they are defined at in this block.
+        let mut constants: HashMap<String, (i64, Option<usize>)> =
+            dominating.iter().map(|(name, value)| (name.clone(), (*value, None))).collect();
+        let mut seen: HashSet<(String, Vec<String>)> = HashSet::new();
+        let mut fused: HashMap<String, FusedBoundsCheck> = HashMap::new();
+        let mut rewrites: HashMap<usize, String> = HashMap::new();
+        let mut removed: HashSet<usize> = HashSet::new();
+
+        for (position, instruction) in block.instructions.iter().enumerate() {
+            match instruction {
+                Instruction::Const { result, value, .. } => {
+                    if let Ok(value) = value.parse::<i64>() {
+                        constants.insert(result.clone(), (value, Some(position)));
+                    }
+                }
+                Instruction::Call { result: None, func, args } if is_check(instruction) => {
+                    if !seen.insert((func.clone(), args.clone())) {
+                        removed.insert(position);
+                        continue;
+                    }
+                    if func != BOUNDS_CHECK_FN || args.len() < 2 {
+                        continue;
+                    }
+
+                    let (index, len) = (&args[0], &args[1]);
+                    let (value, defined_at) = match constants.get(index) {
+                        Some(&(value, defined_at)) if value >= 0 => (value, defined_at),
+                        _ => continue,
+                    };
+                    match fused.get_mut(len) {
+                        // The first check can only take over this index if
+                        // the index is already defined there.
+                        Some(group) if defined_at.is_none_or(|defined_at| defined_at < group.position) => {
+                            if value > group.max_index {
+                                group.max_index = value;
+                                rewrites.insert(group.position, index.clone());
+                            }
+                            removed.insert(position);
+                        }
+                        Some(_) => {}
+                        None => {
+                            fused.insert(len.clone(), FusedBoundsCheck { position, max_index: value });
+                        }
+                    }
+                }
+                Instruction::Store { .. } | Instruction::Call { .. } => fused.clear(),
+                _ => {}
+            }
+        }
+
+        for (position, index) in rewrites {
+            if let Instruction::Call { args, .. } = &mut block.instructions[position] {
+                args[0] = index;
+            }
+        }
+        let mut position = 0;
+        block.instructions.retain(|_| {
+            let keep = !removed.contains(&position);
+            position += 1;
+            keep
+        });
+    }
+}
+
+impl OptimizationPass for CheckFusion {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            // The entry block dominates every other block, so its constants
+            // can be used anywhere.
+            let mut entry_constants = HashMap::new();
+            if let Some(entry) = function.blocks.first() {
+                for instruction in &entry.instructions {
+                    if let Instruction::Const { result, value, .. } = instruction {
+                        if let Ok(value) = value.parse::<i64>() {
+                            entry_constants.insert(result.clone(), value);
+                        }
+                    }
+                }
+            }
+
+            for (index, block) in function.blocks.iter_mut().enumerate() {
+                let dominating = if index == 0 { HashMap::new() } else { entry_constants.clone() };
+                Self::fuse_block(block, &dominating);
+            }
+        }
+
+        Ok(optimized)
+    }
+}
+
+impl Default for CheckFusion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{IRFunction, IRType, Parameter, Terminator};
+
+    fn constant(result: &str, value: i64) -> Instruction {
+        Instruction::Const { result: result.to_string(), ty: IRType::I64, value: value.to_string() }
+    }
+
+    fn check(func: &str, args: &[&str]) -> Instruction {
+        Instruction::Call {
+            result: None,
+            func: func.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    fn load(result: &str, ptr: &str) -> Instruction {
+        Instruction::Load { result: result.to_string(), ptr: ptr.to_string() }
+    }
+
+    fn fuse(instructions: Vec<Instruction>) -> Vec<Instruction> {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "access".to_string(),
+            params: vec![
+                Parameter { name: "%a".to_string(), ty: IRType::Pointer(Box::new(IRType::I64)) },
+                Parameter { name: "%len".to_string(), ty: IRType::I64 },
+            ],
+            return_type: IRType::Void,
+            blocks: vec![BasicBlock {
+                label: "entry".to_string(),
+                instructions,
+                terminator: Terminator::Ret { value: None },
+            }],
+        });
+
+        let mut optimized = CheckFusion::new().optimize(&module).unwrap();
+        optimized.functions.remove(0).blocks.remove(0).instructions
+    }
+
+    /// `a[0]; a[1]; a[2];`
+    #[test]
+    fn test_consecutive_constant_accesses_share_one_check() {
+        let instructions = fuse(vec![
+            constant("%0", 0),
+            constant("%1", 1),
+            constant("%2", 2),
+            check(BOUNDS_CHECK_FN, &["%0", "%len"]),
+            load("%x", "%a"),
+            check(BOUNDS_CHECK_FN, &["%1", "%len"]),
+            load("%y", "%a"),
+            check(BOUNDS_CHECK_FN, &["%2", "%len"]),
+            load("%z", "%a"),
+        ]);
+
+        assert_eq!(
+            instructions,
+            vec![
+                constant("%0", 0),
+                constant("%1", 1),
+                constant("%2", 2),
+                check(BOUNDS_CHECK_FN, &["%2", "%len"]),
+                load("%x", "%a"),
+                load("%y", "%a"),
+                load("%z", "%a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_side_effect_between_checks_prevents_combining() {
+        let chain = vec![
+            constant("%0", 0),
+            constant("%2", 2),
+            check(BOUNDS_CHECK_FN, &["%0", "%len"]),
+            Instruction::Store { value: "%0".to_string(), ptr: "%a".to_string() },
+            check(BOUNDS_CHECK_FN, &["%2", "%len"]),
+        ];
+
+        assert_eq!(fuse(chain.clone()), chain);
+    }
+
+    #[test]
+    fn test_repeated_null_and_dynamic_checks_are_deduplicated() {
+        let instructions = fuse(vec![
+            check(NULL_CHECK_FN, &["%a"]),
+            check(BOUNDS_CHECK_FN, &["%i", "%len"]),
+            Instruction::Call { result: None, func: "blaze_print".to_string(), args: vec!["%a".to_string()] },
+            check(NULL_CHECK_FN, &["%a"]),
+            check(BOUNDS_CHECK_FN, &["%i", "%len"]),
+        ]);
+
+        assert_eq!(
+            instructions,
+            vec![
+                check(NULL_CHECK_FN, &["%a"]),
+                check(BOUNDS_CHECK_FN, &["%i", "%len"]),
+                Instruction::Call { result: None, func: "blaze_print".to_string(), args: vec!["%a".to_string()] },
+            ]
+        );
+    }
+}
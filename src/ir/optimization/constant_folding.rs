@@ -18,7 +18,7 @@ impl ConstantFolder {
                 right,
             } => {
                 if let (Ok(left_val), Ok(right_val)) = (left.parse::<i64>(), right.parse::<i64>()) {
-                    let computed = left_val + right_val;
+                    let computed = Self::wrap_to_width(left_val.wrapping_add(right_val), ty);
                     Some(Instruction::Const {
                         result: result.clone(),
                         ty: ty.clone(),
@@ -35,7 +35,7 @@ impl ConstantFolder {
                 right,
             } => {
                 if let (Ok(left_val), Ok(right_val)) = (left.parse::<i64>(), right.parse::<i64>()) {
-                    let computed = left_val - right_val;
+                    let computed = Self::wrap_to_width(left_val.wrapping_sub(right_val), ty);
                     Some(Instruction::Const {
                         result: result.clone(),
                         ty: ty.clone(),
@@ -52,7 +52,7 @@ impl ConstantFolder {
                 right,
             } => {
                 if let (Ok(left_val), Ok(right_val)) = (left.parse::<i64>(), right.parse::<i64>()) {
-                    let computed = left_val * right_val;
+                    let computed = Self::wrap_to_width(left_val.wrapping_mul(right_val), ty);
                     Some(Instruction::Const {
                         result: result.clone(),
                         ty: ty.clone(),
@@ -69,16 +69,13 @@ impl ConstantFolder {
                 right,
             } => {
                 if let (Ok(left_val), Ok(right_val)) = (left.parse::<i64>(), right.parse::<i64>()) {
-                    if right_val != 0 {
-                        let computed = left_val / right_val;
-                        Some(Instruction::Const {
-                            result: result.clone(),
-                            ty: ty.clone(),
-                            value: computed.to_string(),
-                        })
-                    } else {
-                        None  // Don't fold division by zero
-                    }
+                    // Division by zero and `MIN / -1` are left for run time.
+                    let computed = left_val.checked_div(right_val)?;
+                    Some(Instruction::Const {
+                        result: result.clone(),
+                        ty: ty.clone(),
+                        value: Self::wrap_to_width(computed, ty).to_string(),
+                    })
                 } else {
                     None
                 }
@@ -87,6 +84,20 @@ impl ConstantFolder {
         }
     }
 
+    /// Truncates `value` to the bit width of `ty` and sign-extends it back,
+    /// which is how a constant of that width is stored. IR integers are
+    /// signless, so this gives the wrapping result for signed and unsigned
+    /// source types alike (`255u8 + 1` folds to 0).
+    fn wrap_to_width(value: i64, ty: &IRType) -> i64 {
+        match ty {
+            IRType::I1 => value & 1,
+            IRType::I8 => value as i8 as i64,
+            IRType::I16 => value as i16 as i64,
+            IRType::I32 => value as i32 as i64,
+            _ => value,
+        }
+    }
+
     fn is_constant(&self, operand: &str) -> bool {
         operand.parse::<i64>().is_ok() || operand.parse::<f64>().is_ok()
     }
@@ -159,3 +170,44 @@ impl OptimizationPass for ConstantFolder {
         Ok(optimized_module)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fold(instruction: Instruction) -> Option<String> {
+        match ConstantFolder::new().fold_instruction(&instruction)? {
+            Instruction::Const { value, .. } => Some(value),
+            other => panic!("expected a constant, got {:?}", other),
+        }
+    }
+
+    fn add(ty: IRType, left: &str, right: &str) -> Instruction {
+        Instruction::Add { result: "%r".to_string(), left: left.to_string(), right: right.to_string(), ty }
+    }
+
+    #[test]
+    fn test_arithmetic_wraps_at_type_width() {
+        // A `u8` holding 255 plus one wraps to zero.
+        assert_eq!(fold(add(IRType::I8, "255", "1")), Some("0".to_string()));
+        // 200 + 100 as `u8` is 44; as `i32` it does not overflow.
+        assert_eq!(fold(add(IRType::I8, "200", "100")), Some("44".to_string()));
+        assert_eq!(fold(add(IRType::I32, "200", "100")), Some("300".to_string()));
+        assert_eq!(fold(add(IRType::I32, "2147483647", "1")), Some("-2147483648".to_string()));
+        assert_eq!(fold(add(IRType::I64, &i64::MAX.to_string(), "1")), Some(i64::MIN.to_string()));
+    }
+
+    #[test]
+    fn test_overflowing_division_is_not_folded() {
+        let div = |left: &str, right: &str| Instruction::Div {
+            result: "%r".to_string(),
+            left: left.to_string(),
+            right: right.to_string(),
+            ty: IRType::I64,
+        };
+
+        assert_eq!(fold(div("7", "0")), None);
+        assert_eq!(fold(div(&i64::MIN.to_string(), "-1")), None);
+        assert_eq!(fold(div("-7", "2")), Some("-3".to_string()));
+    }
+}
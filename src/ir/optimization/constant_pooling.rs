@@ -0,0 +1,145 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{Constant, Instruction, IRType};
+use anyhow::Result;
+
+/// Moves every `Const` value into the module's constant pool, so a literal
+/// used by several functions is stored once and its instructions refer to
+/// the same `@const.N` slot.
+///
+/// Other passes read literal values straight from `Const` instructions, so
+/// this runs after them.
+pub struct ConstantPooling;
+
+impl ConstantPooling {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn to_constant(ty: &IRType, value: &str) -> Constant {
+        match ty {
+            IRType::I1 | IRType::I8 | IRType::I16 | IRType::I32 | IRType::I64 | IRType::I128 => {
+                match value {
+                    "true" => Constant::Boolean(true),
+                    "false" => Constant::Boolean(false),
+                    _ => match value.parse() {
+                        Ok(value) => Constant::Integer { value, ty: ty.clone() },
+                        Err(_) => Constant::String(value.to_string()),
+                    },
+                }
+            }
+            IRType::F32 | IRType::F64 => match value.parse() {
+                Ok(value) => Constant::Float { value, ty: ty.clone() },
+                Err(_) => Constant::String(value.to_string()),
+            },
+            _ => Constant::String(value.to_string()),
+        }
+    }
+}
+
+impl Default for ConstantPooling {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OptimizationPass for ConstantPooling {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut pooled = module.clone();
+        let mut functions = std::mem::take(&mut pooled.functions);
+
+        for function in &mut functions {
+            for block in &mut function.blocks {
+                for instruction in &mut block.instructions {
+                    if let Instruction::Const { ty, value, .. } = instruction {
+                        if value.starts_with("@const.") {
+                            continue;
+                        }
+                        let index = pooled.intern_constant(Self::to_constant(ty, value));
+                        *value = format!("@const.{}", index);
+                    }
+                }
+            }
+        }
+
+        pooled.functions = functions;
+        Ok(pooled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{BasicBlock, IRFunction, Terminator};
+
+    fn returns_constant(name: &str, ty: IRType, value: &str) -> IRFunction {
+        IRFunction {
+            name: name.to_string(),
+            params: vec![],
+            return_type: ty.clone(),
+            blocks: vec![BasicBlock {
+                label: "entry".to_string(),
+                instructions: vec![Instruction::Const {
+                    result: "%t0".to_string(),
+                    ty,
+                    value: value.to_string(),
+                }],
+                terminator: Terminator::Ret { value: Some("%t0".to_string()) },
+            }],
+        }
+    }
+
+    fn const_operand(function: &IRFunction) -> &str {
+        match &function.blocks[0].instructions[0] {
+            Instruction::Const { value, .. } => value,
+            other => panic!("expected a Const, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_same_literal_in_two_functions_shares_a_slot() {
+        let greeting = "a long greeting shared by two functions";
+        let mut module = Module::new("test");
+        module.functions.push(returns_constant("first", IRType::Pointer(Box::new(IRType::I8)), greeting));
+        module.functions.push(returns_constant("second", IRType::Pointer(Box::new(IRType::I8)), greeting));
+
+        let pooled = ConstantPooling::new().optimize(&module).unwrap();
+
+        assert_eq!(pooled.constant_pool, vec![Constant::String(greeting.to_string())]);
+        assert_eq!(const_operand(&pooled.functions[0]), "@const.0");
+        assert_eq!(const_operand(&pooled.functions[1]), "@const.0");
+        assert_eq!(
+            pooled.pooled_constant("@const.0"),
+            Some(&Constant::String(greeting.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_same_value_with_different_types_gets_separate_slots() {
+        let mut module = Module::new("test");
+        module.functions.push(returns_constant("narrow", IRType::I32, "42"));
+        module.functions.push(returns_constant("wide", IRType::I64, "42"));
+
+        let pooled = ConstantPooling::new().optimize(&module).unwrap();
+
+        assert_eq!(
+            pooled.constant_pool,
+            vec![
+                Constant::Integer { value: 42, ty: IRType::I32 },
+                Constant::Integer { value: 42, ty: IRType::I64 },
+            ]
+        );
+        assert_eq!(const_operand(&pooled.functions[1]), "@const.1");
+    }
+
+    #[test]
+    fn test_pooling_twice_is_stable() {
+        let mut module = Module::new("test");
+        module.functions.push(returns_constant("answer", IRType::I32, "42"));
+
+        let once = ConstantPooling::new().optimize(&module).unwrap();
+        let twice = ConstantPooling::new().optimize(&once).unwrap();
+
+        assert_eq!(twice.constant_pool, once.constant_pool);
+        assert_eq!(const_operand(&twice.functions[0]), "@const.0");
+    }
+}
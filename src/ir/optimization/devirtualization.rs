@@ -0,0 +1,182 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{Instruction, IRFunction, IRType};
+use crate::parser::Type;
+use crate::trait_system::TraitRegistry;
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Rewrites trait method calls into direct calls when the receiver's
+/// concrete type is known.
+///
+/// A trait call is a `Call` whose callee is `Trait::method` and whose first
+/// argument is the receiver. The receiver type comes from the parameter,
+/// `Alloca` or `Load` that defines it; named struct types are matched
+/// against the module's type definitions.
+pub struct Devirtualizer {
+    registry: TraitRegistry,
+}
+
+impl Devirtualizer {
+    pub fn new(registry: TraitRegistry) -> Self {
+        Self { registry }
+    }
+
+    fn receiver_types(&self, function: &IRFunction, module: &Module) -> HashMap<String, Type> {
+        let mut allocas = HashMap::new();
+        let mut types = HashMap::new();
+
+        for param in &function.params {
+            if let Some(ty) = Self::source_type(&param.ty, module) {
+                types.insert(param.name.clone(), ty);
+            }
+        }
+
+        for block in &function.blocks {
+            for instruction in &block.instructions {
+                match instruction {
+                    Instruction::Alloca { result, ty } => {
+                        allocas.insert(result.clone(), ty.clone());
+                        if let Some(ty) = Self::source_type(ty, module) {
+                            types.insert(result.clone(), ty);
+                        }
+                    }
+                    Instruction::Load { result, ptr } => {
+                        if let Some(ty) = allocas.get(ptr).and_then(|ty| Self::source_type(ty, module)) {
+                            types.insert(result.clone(), ty);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        types
+    }
+
+    fn source_type(ty: &IRType, module: &Module) -> Option<Type> {
+        match ty {
+            IRType::I1 => Some(Type::Bool),
+            IRType::I8 => Some(Type::Char),
+            IRType::I32 => Some(Type::I32),
+            IRType::I64 => Some(Type::I64),
+            IRType::F32 => Some(Type::F32),
+            IRType::F64 => Some(Type::F64),
+            IRType::Pointer(inner) => Self::source_type(inner, module),
+            IRType::Struct { .. } => {
+                let mut matches = module.types.iter().filter(|def| &def.ty == ty);
+                match (matches.next(), matches.next()) {
+                    (Some(def), None) => Some(Type::Custom(def.name.clone())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn resolve_call(&self, func: &str, args: &[String], types: &HashMap<String, Type>) -> Option<String> {
+        let (trait_name, method) = func.split_once("::")?;
+        let receiver_type = types.get(args.first()?)?;
+
+        self.registry
+            .get_impl(trait_name, receiver_type)?
+            .methods
+            .get(method)
+            .cloned()
+    }
+}
+
+impl OptimizationPass for Devirtualizer {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            let types = self.receiver_types(function, module);
+
+            for block in &mut function.blocks {
+                for instruction in &mut block.instructions {
+                    if let Instruction::Call { func, args, .. } = instruction {
+                        if let Some(target) = self.resolve_call(func, args, &types) {
+                            *func = target;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(optimized)
+    }
+}
+
+impl Default for Devirtualizer {
+    fn default() -> Self {
+        Self::new(TraitRegistry::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{BasicBlock, Terminator, TypeDefinition};
+    use crate::trait_system::TraitImpl;
+
+    fn point_type() -> IRType {
+        IRType::Struct { fields: vec![IRType::I32, IRType::I32] }
+    }
+
+    fn module_with_clone_call(receiver_ty: IRType) -> Module {
+        let mut module = Module::new("test");
+        module.types.push(TypeDefinition { name: "Point".to_string(), ty: point_type() });
+        module.functions.push(IRFunction {
+            name: "main".to_string(),
+            params: vec![],
+            return_type: IRType::Void,
+            blocks: vec![BasicBlock {
+                label: "entry".to_string(),
+                instructions: vec![
+                    Instruction::Alloca { result: "%p".to_string(), ty: receiver_ty },
+                    Instruction::Call {
+                        result: Some("%q".to_string()),
+                        func: "Clone::clone".to_string(),
+                        args: vec!["%p".to_string()],
+                    },
+                ],
+                terminator: Terminator::Ret { value: None },
+            }],
+        });
+        module
+    }
+
+    fn devirtualizer() -> Devirtualizer {
+        let mut registry = TraitRegistry::new();
+        registry.register_impl(TraitImpl {
+            trait_name: "Clone".to_string(),
+            for_type: Type::Custom("Point".to_string()),
+            methods: HashMap::from([("clone".to_string(), "Point::clone".to_string())]),
+            associated_types: HashMap::new(),
+        });
+        Devirtualizer::new(registry)
+    }
+
+    fn called_function(module: &Module) -> &str {
+        match &module.functions[0].blocks[0].instructions[1] {
+            Instruction::Call { func, .. } => func,
+            other => panic!("expected call, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_clone_on_known_type_becomes_direct_call() {
+        let module = module_with_clone_call(point_type());
+        let optimized = devirtualizer().optimize(&module).unwrap();
+
+        assert_eq!(called_function(&optimized), "Point::clone");
+    }
+
+    #[test]
+    fn test_unknown_receiver_type_is_left_virtual() {
+        let module = module_with_clone_call(IRType::Struct { fields: vec![IRType::F64] });
+        let optimized = devirtualizer().optimize(&module).unwrap();
+
+        assert_eq!(called_function(&optimized), "Clone::clone");
+    }
+}
@@ -0,0 +1,175 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{BasicBlock, Instruction, IRType, Terminator};
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Fuses a float `Mul` feeding an `Add` into a single `FMA`.
+///
+/// Contraction changes rounding, so the pass only runs when fast-math is
+/// enabled. The `Mul` must be in the same block and have no other uses.
+pub struct FmaContraction {
+    fast_math: bool,
+}
+
+impl FmaContraction {
+    pub fn new(fast_math: bool) -> Self {
+        Self { fast_math }
+    }
+
+    fn is_float(ty: &IRType) -> bool {
+        matches!(ty, IRType::F32 | IRType::F64)
+    }
+
+    fn contract_block(block: &mut BasicBlock, use_counts: &HashMap<String, usize>) {
+        let mut products: HashMap<String, usize> = HashMap::new();
+        let mut fused = Vec::new();
+
+        for i in 0..block.instructions.len() {
+            let replacement = match &block.instructions[i] {
+                Instruction::Mul { result, ty, .. } if Self::is_float(ty) => {
+                    products.insert(result.clone(), i);
+                    None
+                }
+                Instruction::Add { result, left, right, ty } if Self::is_float(ty) => {
+                    let single_use = |name: &String| use_counts.get(name) == Some(&1);
+                    let (product, addend) = if products.contains_key(left) && single_use(left) {
+                        (left, right)
+                    } else if products.contains_key(right) && single_use(right) {
+                        (right, left)
+                    } else {
+                        continue;
+                    };
+
+                    let mul_index = products.remove(product).unwrap();
+                    match &block.instructions[mul_index] {
+                        Instruction::Mul { left: a, right: b, ty: mul_ty, .. } if mul_ty == ty => {
+                            fused.push(mul_index);
+                            Some(Instruction::FMA {
+                                result: result.clone(),
+                                a: a.clone(),
+                                b: b.clone(),
+                                c: addend.clone(),
+                                ty: ty.clone(),
+                            })
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+
+            if let Some(instruction) = replacement {
+                block.instructions[i] = instruction;
+            }
+        }
+
+        fused.sort_unstable_by(|a, b| b.cmp(a));
+        for index in fused {
+            block.instructions.remove(index);
+        }
+    }
+}
+
+impl OptimizationPass for FmaContraction {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+        if !self.fast_math {
+            return Ok(optimized);
+        }
+
+        for function in &mut optimized.functions {
+            let mut use_counts: HashMap<String, usize> = HashMap::new();
+            for block in &function.blocks {
+                for instruction in &block.instructions {
+                    for operand in instruction.get_operands() {
+                        *use_counts.entry(operand.to_string()).or_insert(0) += 1;
+                    }
+                }
+                match &block.terminator {
+                    Terminator::Ret { value: Some(operand) }
+                    | Terminator::CondBr { condition: operand, .. }
+                    | Terminator::Switch { value: operand, .. } => {
+                        *use_counts.entry(operand.clone()).or_insert(0) += 1;
+                    }
+                    _ => {}
+                }
+            }
+
+            for block in &mut function.blocks {
+                Self::contract_block(block, &use_counts);
+            }
+        }
+
+        Ok(optimized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::IRFunction;
+
+    fn mul_add_module(ty: IRType) -> Module {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "mul_add".to_string(),
+            params: vec![],
+            return_type: ty.clone(),
+            blocks: vec![BasicBlock {
+                label: "entry".to_string(),
+                instructions: vec![
+                    Instruction::Mul {
+                        result: "%t0".to_string(),
+                        left: "%a".to_string(),
+                        right: "%b".to_string(),
+                        ty: ty.clone(),
+                    },
+                    Instruction::Add {
+                        result: "%t1".to_string(),
+                        left: "%t0".to_string(),
+                        right: "%c".to_string(),
+                        ty,
+                    },
+                ],
+                terminator: Terminator::Ret { value: Some("%t1".to_string()) },
+            }],
+        });
+        module
+    }
+
+    fn instructions(module: &Module) -> &[Instruction] {
+        &module.functions[0].blocks[0].instructions
+    }
+
+    #[test]
+    fn test_fma_forms_with_fast_math() {
+        let optimized = FmaContraction::new(true).optimize(&mul_add_module(IRType::F64)).unwrap();
+
+        assert_eq!(
+            instructions(&optimized),
+            &[Instruction::FMA {
+                result: "%t1".to_string(),
+                a: "%a".to_string(),
+                b: "%b".to_string(),
+                c: "%c".to_string(),
+                ty: IRType::F64,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_fma_requires_fast_math() {
+        let module = mul_add_module(IRType::F64);
+        let optimized = FmaContraction::new(false).optimize(&module).unwrap();
+
+        assert_eq!(instructions(&optimized), instructions(&module));
+    }
+
+    #[test]
+    fn test_fma_ignores_integer_arithmetic() {
+        let module = mul_add_module(IRType::I32);
+        let optimized = FmaContraction::new(true).optimize(&module).unwrap();
+
+        assert_eq!(instructions(&optimized), instructions(&module));
+    }
+}
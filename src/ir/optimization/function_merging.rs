@@ -0,0 +1,241 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{IRFunction, Instruction, Terminator};
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Merges functions with identical bodies, such as monomorphized copies of a
+/// generic function that do not depend on their type arguments.
+///
+/// Each function is normalized by renaming its parameters, values and block
+/// labels in order of appearance, and calls to itself to a fixed name. Two
+/// functions are merged only when their signatures and normalized bodies are
+/// equal. The first one in the module is kept; the others are removed and
+/// every call to them is redirected to it. Redirecting calls can make the
+/// callers identical in turn, so merging repeats until nothing changes.
+pub struct FunctionMerging;
+
+/// Stands in for the function's own name in recursive calls.
+const SELF_CALL: &str = "<self>";
+
+impl FunctionMerging {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// A key that is equal for two functions exactly when merging them is safe.
+    fn normalized(function: &IRFunction) -> String {
+        let mut names: HashMap<String, String> = HashMap::new();
+        for (index, param) in function.params.iter().enumerate() {
+            names.insert(param.name.clone(), format!("%arg{}", index));
+        }
+        for (index, block) in function.blocks.iter().enumerate() {
+            names.insert(block.label.clone(), format!("bb{}", index));
+        }
+        let mut next_value = 0;
+        for instruction in function.blocks.iter().flat_map(|block| &block.instructions) {
+            if let Some(result) = instruction.get_result() {
+                names.insert(result.to_string(), format!("%v{}", next_value));
+                next_value += 1;
+            }
+        }
+
+        let rename = |name: &mut String| {
+            if let Some(normalized) = names.get(name.as_str()) {
+                *name = normalized.clone();
+            }
+        };
+
+        let mut blocks = function.blocks.clone();
+        for block in &mut blocks {
+            rename(&mut block.label);
+            for instruction in &mut block.instructions {
+                if let Some(result) = instruction.result_mut() {
+                    rename(result);
+                }
+                for operand in instruction.operands_mut() {
+                    rename(operand);
+                }
+                match instruction {
+                    Instruction::Phi { incoming, .. } => {
+                        for (_, label) in incoming {
+                            rename(label);
+                        }
+                    }
+                    Instruction::Call { func, .. } if *func == function.name => *func = SELF_CALL.to_string(),
+                    _ => {}
+                }
+            }
+            match &mut block.terminator {
+                Terminator::Ret { value: Some(value) } => rename(value),
+                Terminator::Br { target } => rename(target),
+                Terminator::CondBr { condition, true_target, false_target } => {
+                    rename(condition);
+                    rename(true_target);
+                    rename(false_target);
+                }
+                Terminator::Switch { value, cases, default } => {
+                    rename(value);
+                    for (_, target) in cases {
+                        rename(target);
+                    }
+                    rename(default);
+                }
+                _ => {}
+            }
+        }
+
+        let params: Vec<_> = function.params.iter().map(|param| &param.ty).collect();
+        format!("{:?} -> {:?} {:?}", params, function.return_type, blocks)
+    }
+
+    /// Maps each duplicate function to the function replacing it.
+    fn find_duplicates(module: &Module) -> HashMap<String, String> {
+        let mut canonical: HashMap<String, &str> = HashMap::new();
+        let mut duplicates = HashMap::new();
+
+        // Declarations have no body to compare, and `main` is the entry
+        // point, so neither is ever merged away.
+        for function in module.functions.iter().filter(|f| !f.blocks.is_empty() && f.name != "main") {
+            let key = Self::normalized(function);
+            match canonical.get(&key) {
+                Some(kept) => {
+                    duplicates.insert(function.name.clone(), kept.to_string());
+                }
+                None => {
+                    canonical.insert(key, &function.name);
+                }
+            }
+        }
+
+        duplicates
+    }
+}
+
+impl OptimizationPass for FunctionMerging {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        loop {
+            let duplicates = Self::find_duplicates(&optimized);
+            if duplicates.is_empty() {
+                break;
+            }
+
+            optimized.functions.retain(|function| !duplicates.contains_key(&function.name));
+            for function in &mut optimized.functions {
+                for instruction in function.blocks.iter_mut().flat_map(|block| &mut block.instructions) {
+                    if let Instruction::Call { func, .. } = instruction {
+                        if let Some(kept) = duplicates.get(func.as_str()) {
+                            *func = kept.clone();
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(optimized)
+    }
+}
+
+impl Default for FunctionMerging {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{BasicBlock, IRType, Parameter};
+
+    /// `fn name(x: ty) -> ty { return x * x; }` with value names from `prefix`.
+    fn square(name: &str, ty: IRType, prefix: &str) -> IRFunction {
+        let (x, product) = (format!("%{}x", prefix), format!("%{}sq", prefix));
+        IRFunction {
+            name: name.to_string(),
+            params: vec![Parameter { name: x.clone(), ty: ty.clone() }],
+            return_type: ty.clone(),
+            blocks: vec![BasicBlock {
+                label: format!("{}entry", prefix),
+                instructions: vec![Instruction::Mul { result: product.clone(), left: x.clone(), right: x, ty }],
+                terminator: Terminator::Ret { value: Some(product) },
+            }],
+        }
+    }
+
+    fn caller(name: &str, callees: &[&str]) -> IRFunction {
+        IRFunction {
+            name: name.to_string(),
+            params: vec![Parameter { name: "%n".to_string(), ty: IRType::I32 }],
+            return_type: IRType::Void,
+            blocks: vec![BasicBlock {
+                label: "entry".to_string(),
+                instructions: callees
+                    .iter()
+                    .enumerate()
+                    .map(|(i, callee)| Instruction::Call {
+                        result: Some(format!("%r{}", i)),
+                        func: callee.to_string(),
+                        args: vec!["%n".to_string()],
+                    })
+                    .collect(),
+                terminator: Terminator::Ret { value: None },
+            }],
+        }
+    }
+
+    fn calls(function: &IRFunction) -> Vec<&str> {
+        function
+            .blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .filter_map(|instruction| match instruction {
+                Instruction::Call { func, .. } => Some(func.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn names(module: &Module) -> Vec<&str> {
+        module.functions.iter().map(|function| function.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_identical_monomorphizations_are_merged() {
+        let mut module = Module::new("test");
+        module.functions.push(square("square_Meters", IRType::I32, "a"));
+        module.functions.push(square("square_Seconds", IRType::I32, "b"));
+        module.functions.push(caller("main", &["square_Meters", "square_Seconds"]));
+
+        let optimized = FunctionMerging::new().optimize(&module).unwrap();
+
+        assert_eq!(names(&optimized), vec!["square_Meters", "main"]);
+        assert_eq!(calls(&optimized.functions[1]), vec!["square_Meters", "square_Meters"]);
+    }
+
+    #[test]
+    fn test_functions_with_different_signatures_are_kept() {
+        let mut module = Module::new("test");
+        module.functions.push(square("square_i32", IRType::I32, "a"));
+        module.functions.push(square("square_i64", IRType::I64, "a"));
+
+        let optimized = FunctionMerging::new().optimize(&module).unwrap();
+
+        assert_eq!(names(&optimized), vec!["square_i32", "square_i64"]);
+    }
+
+    #[test]
+    fn test_callers_become_identical_after_merging() {
+        let mut module = Module::new("test");
+        module.functions.push(square("square_A", IRType::I32, "a"));
+        module.functions.push(square("square_B", IRType::I32, "b"));
+        module.functions.push(caller("use_A", &["square_A"]));
+        module.functions.push(caller("use_B", &["square_B"]));
+        module.functions.push(caller("main", &["use_A", "use_B"]));
+
+        let optimized = FunctionMerging::new().optimize(&module).unwrap();
+
+        assert_eq!(names(&optimized), vec!["square_A", "use_A", "main"]);
+        assert_eq!(calls(&optimized.functions[2]), vec!["use_A", "use_A"]);
+    }
+}
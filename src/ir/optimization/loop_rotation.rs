@@ -0,0 +1,412 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{BasicBlock, IRFunction, Instruction, Terminator};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+/// Rotates test-at-top loops into guarded bottom-tested loops.
+///
+/// ```text
+/// header: cond = ...; br cond, body, exit      header.guard: cond' = ...; br cond', header.preheader, exit
+/// body:   ...; br header                  =>    header.preheader: br body
+///                                               body:   ...; br header
+///                                               header: cond = ...; br cond, body, exit
+/// ```
+///
+/// The header's instructions are copied into the guard rather than moved, so
+/// the condition is still evaluated exactly once per test and side effects
+/// in it are preserved. The new preheader gives LICM a single block to hoist
+/// into. Loops are left alone when values defined in the header are used
+/// elsewhere or when phis depend on the header as a predecessor, since
+/// rotating those would need new phis.
+pub struct LoopRotation;
+
+impl LoopRotation {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn successors(terminator: &Terminator) -> Vec<&str> {
+        match terminator {
+            Terminator::Br { target } => vec![target],
+            Terminator::CondBr { true_target, false_target, .. } => vec![true_target, false_target],
+            Terminator::Switch { cases, default, .. } => {
+                cases.iter().map(|(_, target)| target.as_str()).chain([default.as_str()]).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Whether `to` can be reached from `from` without passing through `avoid`.
+    fn reaches(function: &IRFunction, from: &str, to: &str, avoid: &str) -> bool {
+        let blocks: HashMap<&str, &BasicBlock> = function.blocks.iter().map(|b| (b.label.as_str(), b)).collect();
+        let mut visited = HashSet::new();
+        let mut worklist = vec![from];
+
+        while let Some(label) = worklist.pop() {
+            if label == to {
+                return true;
+            }
+            if label == avoid || !visited.insert(label) {
+                continue;
+            }
+            if let Some(block) = blocks.get(label) {
+                worklist.extend(Self::successors(&block.terminator));
+            }
+        }
+
+        false
+    }
+
+    fn rotate_one(function: &mut IRFunction) -> bool {
+        for index in 0..function.blocks.len() {
+            if let Some(rotated) = Self::try_rotate(function, index) {
+                *function = rotated;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn try_rotate(function: &IRFunction, index: usize) -> Option<IRFunction> {
+        let header = &function.blocks[index];
+        let (condition, true_target, false_target) = match &header.terminator {
+            Terminator::CondBr { condition, true_target, false_target } => (condition, true_target, false_target),
+            _ => return None,
+        };
+        if header.label.ends_with(".guard")
+            || true_target == &header.label
+            || false_target == &header.label
+        {
+            return None;
+        }
+
+        let loops_through = |target: &str| Self::reaches(function, target, &header.label, "");
+        let (body, exit) = match (loops_through(true_target), loops_through(false_target)) {
+            (true, false) => (true_target, false_target),
+            (false, true) => (false_target, true_target),
+            _ => return None,
+        };
+
+        let mut latches = Vec::new();
+        let mut entries = Vec::new();
+        for block in &function.blocks {
+            if Self::successors(&block.terminator).contains(&header.label.as_str()) {
+                if Self::reaches(function, body, &block.label, &header.label) {
+                    latches.push(block.label.clone());
+                } else {
+                    entries.push(block.label.clone());
+                }
+            }
+        }
+        if latches.is_empty() || (entries.is_empty() && index != 0) {
+            return None;
+        }
+
+        if !Self::header_is_self_contained(function, header) {
+            return None;
+        }
+
+        let guard_label = format!("{}.guard", header.label);
+        let preheader_label = format!("{}.preheader", header.label);
+        let renames: HashMap<String, String> = header
+            .instructions
+            .iter()
+            .filter_map(Instruction::get_result)
+            .map(|result| (result.to_string(), format!("{}.guard", result)))
+            .collect();
+
+        let guard_target = |target: &String| {
+            if target == body {
+                preheader_label.clone()
+            } else {
+                target.clone()
+            }
+        };
+        let guard = BasicBlock {
+            label: guard_label.clone(),
+            instructions: header.instructions.iter().map(|i| rename_instruction(i, &renames)).collect(),
+            terminator: Terminator::CondBr {
+                condition: renames.get(condition).cloned().unwrap_or_else(|| condition.clone()),
+                true_target: guard_target(true_target),
+                false_target: guard_target(false_target),
+            },
+        };
+        let preheader = BasicBlock {
+            label: preheader_label,
+            instructions: Vec::new(),
+            terminator: Terminator::Br { target: body.clone() },
+        };
+
+        let mut rotated = function.clone();
+        let header_label = header.label.clone();
+        let exit = exit.clone();
+        for block in &mut rotated.blocks {
+            if entries.contains(&block.label) {
+                redirect(&mut block.terminator, &header_label, &guard_label);
+            }
+        }
+
+        // The guard takes the header's place in layout; the header moves to
+        // the bottom of the loop, just before the exit.
+        let header = rotated.blocks.remove(index);
+        rotated.blocks.insert(index, guard);
+        rotated.blocks.insert(index + 1, preheader);
+        let exit_position = rotated
+            .blocks
+            .iter()
+            .position(|b| b.label == exit)
+            .filter(|&position| position > index + 1)
+            .unwrap_or(rotated.blocks.len());
+        rotated.blocks.insert(exit_position, header);
+
+        Some(rotated)
+    }
+
+    /// The header may only define values it consumes itself, and no phi may
+    /// name it as an incoming block.
+    fn header_is_self_contained(function: &IRFunction, header: &BasicBlock) -> bool {
+        if header.instructions.iter().any(|i| matches!(i, Instruction::Phi { .. })) {
+            return false;
+        }
+
+        let defined: HashSet<&str> = header.instructions.iter().filter_map(Instruction::get_result).collect();
+        function.blocks.iter().filter(|b| b.label != header.label).all(|block| {
+            block.instructions.iter().all(|instruction| {
+                let phi_from_header = matches!(
+                    instruction,
+                    Instruction::Phi { incoming, .. } if incoming.iter().any(|(_, from)| from == &header.label)
+                );
+                !phi_from_header && !instruction.get_operands().iter().any(|op| defined.contains(op))
+            }) && match &block.terminator {
+                Terminator::Ret { value: Some(value) } => !defined.contains(value.as_str()),
+                Terminator::CondBr { condition: value, .. } | Terminator::Switch { value, .. } => {
+                    !defined.contains(value.as_str())
+                }
+                _ => true,
+            }
+        })
+    }
+}
+
+fn redirect(terminator: &mut Terminator, from: &str, to: &str) {
+    match terminator {
+        Terminator::Br { target } if target == from => *target = to.to_string(),
+        Terminator::CondBr { true_target, false_target, .. } => {
+            if true_target == from {
+                *true_target = to.to_string();
+            }
+            if false_target == from {
+                *false_target = to.to_string();
+            }
+        }
+        Terminator::Switch { cases, default, .. } => {
+            for target in cases.iter_mut().map(|(_, target)| target).chain([default]) {
+                if target == from {
+                    *target = to.to_string();
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn rename_instruction(instruction: &Instruction, renames: &HashMap<String, String>) -> Instruction {
+    let r = |name: &String| renames.get(name).cloned().unwrap_or_else(|| name.clone());
+
+    match instruction {
+        Instruction::Alloca { result, ty } => Instruction::Alloca { result: r(result), ty: ty.clone() },
+        Instruction::Store { value, ptr } => Instruction::Store { value: r(value), ptr: r(ptr) },
+        Instruction::Load { result, ptr } => Instruction::Load { result: r(result), ptr: r(ptr) },
+        Instruction::Const { result, ty, value } => {
+            Instruction::Const { result: r(result), ty: ty.clone(), value: value.clone() }
+        }
+        Instruction::Add { result, left, right, ty } => {
+            Instruction::Add { result: r(result), left: r(left), right: r(right), ty: ty.clone() }
+        }
+        Instruction::Sub { result, left, right, ty } => {
+            Instruction::Sub { result: r(result), left: r(left), right: r(right), ty: ty.clone() }
+        }
+        Instruction::Mul { result, left, right, ty } => {
+            Instruction::Mul { result: r(result), left: r(left), right: r(right), ty: ty.clone() }
+        }
+        Instruction::Div { result, left, right, ty } => {
+            Instruction::Div { result: r(result), left: r(left), right: r(right), ty: ty.clone() }
+        }
+        Instruction::Mod { result, left, right, ty } => {
+            Instruction::Mod { result: r(result), left: r(left), right: r(right), ty: ty.clone() }
+        }
+        Instruction::FMA { result, a, b, c, ty } => {
+            Instruction::FMA { result: r(result), a: r(a), b: r(b), c: r(c), ty: ty.clone() }
+        }
+        Instruction::ICmp { result, condition, left, right } => Instruction::ICmp {
+            result: r(result),
+            condition: condition.clone(),
+            left: r(left),
+            right: r(right),
+        },
+        Instruction::FCmp { result, condition, left, right } => Instruction::FCmp {
+            result: r(result),
+            condition: condition.clone(),
+            left: r(left),
+            right: r(right),
+        },
+        Instruction::Call { result, func, args } => Instruction::Call {
+            result: result.as_ref().map(r),
+            func: func.clone(),
+            args: args.iter().map(r).collect(),
+        },
+        Instruction::GetElementPtr { result, ptr, indices } => Instruction::GetElementPtr {
+            result: r(result),
+            ptr: r(ptr),
+            indices: indices.iter().map(r).collect(),
+        },
+        Instruction::BitCast { result, value, ty } => {
+            Instruction::BitCast { result: r(result), value: r(value), ty: ty.clone() }
+        }
+        Instruction::SExt { result, value, from_ty, to_ty } => Instruction::SExt {
+            result: r(result),
+            value: r(value),
+            from_ty: from_ty.clone(),
+            to_ty: to_ty.clone(),
+        },
+        Instruction::ZExt { result, value, from_ty, to_ty } => Instruction::ZExt {
+            result: r(result),
+            value: r(value),
+            from_ty: from_ty.clone(),
+            to_ty: to_ty.clone(),
+        },
+        Instruction::Trunc { result, value, from_ty, to_ty } => Instruction::Trunc {
+            result: r(result),
+            value: r(value),
+            from_ty: from_ty.clone(),
+            to_ty: to_ty.clone(),
+        },
+        Instruction::Phi { result, ty, incoming } => Instruction::Phi {
+            result: r(result),
+            ty: ty.clone(),
+            incoming: incoming.iter().map(|(value, from)| (r(value), from.clone())).collect(),
+        },
+    }
+}
+
+impl OptimizationPass for LoopRotation {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            while Self::rotate_one(function) {}
+        }
+
+        Ok(optimized)
+    }
+}
+
+impl Default for LoopRotation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{ICmpCondition, IRType};
+
+    fn block(label: &str, instructions: Vec<Instruction>, terminator: Terminator) -> BasicBlock {
+        BasicBlock { label: label.to_string(), instructions, terminator }
+    }
+
+    fn br(target: &str) -> Terminator {
+        Terminator::Br { target: target.to_string() }
+    }
+
+    fn cond_br(condition: &str, true_target: &str, false_target: &str) -> Terminator {
+        Terminator::CondBr {
+            condition: condition.to_string(),
+            true_target: true_target.to_string(),
+            false_target: false_target.to_string(),
+        }
+    }
+
+    /// `while next() < limit { work(); }`
+    fn while_loop(body: Vec<Instruction>) -> Module {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "run".to_string(),
+            params: vec![],
+            return_type: IRType::Void,
+            blocks: vec![
+                block("entry", vec![], br("header")),
+                block(
+                    "header",
+                    vec![
+                        Instruction::Call { result: Some("%n".to_string()), func: "next".to_string(), args: vec![] },
+                        Instruction::ICmp {
+                            result: "%cond".to_string(),
+                            condition: ICmpCondition::Slt,
+                            left: "%n".to_string(),
+                            right: "%limit".to_string(),
+                        },
+                    ],
+                    cond_br("%cond", "body", "exit"),
+                ),
+                block("body", body, br("header")),
+                block("exit", vec![], Terminator::Ret { value: None }),
+            ],
+        });
+        module
+    }
+
+    fn labels(module: &Module) -> Vec<&str> {
+        module.functions[0].blocks.iter().map(|b| b.label.as_str()).collect()
+    }
+
+    fn find<'a>(module: &'a Module, label: &str) -> &'a BasicBlock {
+        module.functions[0].blocks.iter().find(|b| b.label == label).unwrap()
+    }
+
+    #[test]
+    fn test_while_loop_is_rotated() {
+        let work = Instruction::Call { result: None, func: "work".to_string(), args: vec![] };
+        let optimized = LoopRotation::new().optimize(&while_loop(vec![work])).unwrap();
+
+        assert_eq!(labels(&optimized), vec!["entry", "header.guard", "header.preheader", "body", "header", "exit"]);
+
+        // Single entry test, reached only from the original entry.
+        assert_eq!(find(&optimized, "entry").terminator, br("header.guard"));
+        let guard = find(&optimized, "header.guard");
+        assert_eq!(guard.terminator, cond_br("%cond.guard", "header.preheader", "exit"));
+        assert_eq!(guard.instructions.len(), 2);
+
+        // Back-edge test at the bottom of the loop.
+        assert_eq!(find(&optimized, "header.preheader").terminator, br("body"));
+        assert_eq!(find(&optimized, "body").terminator, br("header"));
+        assert_eq!(find(&optimized, "header").terminator, cond_br("%cond", "body", "exit"));
+    }
+
+    #[test]
+    fn test_empty_body_keeps_condition_side_effects() {
+        let optimized = LoopRotation::new().optimize(&while_loop(vec![])).unwrap();
+
+        // `next()` still runs once for the entry test and once per back edge.
+        let calls_next = |label: &str| {
+            find(&optimized, label)
+                .instructions
+                .iter()
+                .any(|i| matches!(i, Instruction::Call { func, .. } if func == "next"))
+        };
+        assert!(calls_next("header.guard"));
+        assert!(calls_next("header"));
+        assert_eq!(find(&optimized, "body").instructions, vec![]);
+    }
+
+    #[test]
+    fn test_header_value_used_in_body_is_not_rotated() {
+        let use_n = Instruction::Call { result: None, func: "work".to_string(), args: vec!["%n".to_string()] };
+        let module = while_loop(vec![use_n]);
+        let optimized = LoopRotation::new().optimize(&module).unwrap();
+
+        assert_eq!(labels(&optimized), labels(&module));
+        assert_eq!(find(&optimized, "entry").terminator, br("header"));
+    }
+}
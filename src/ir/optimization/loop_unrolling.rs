@@ -0,0 +1,371 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{ICmpCondition, IRFunction, Instruction, Terminator};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+/// Fully unrolls counted loops whose trip count is a small constant.
+///
+/// ```text
+/// header: %i = phi [0, pre], [%i.next, body]        header: <body with %i = 0>
+///         %c = icmp slt %i, 4                  =>           <body with %i = 1>
+///         br %c, body, exit                                 <body with %i = 2>
+/// body:   ...; %i.next = add %i, 1; br header               <body with %i = 3>
+///                                                           br exit
+/// ```
+///
+/// Only single-block bodies are unrolled, and only when nothing outside the
+/// loop reads a value it defines. Loops with a bound that is not a constant,
+/// or that run more than `max_trip_count` times, are left alone.
+pub struct LoopUnrolling {
+    max_trip_count: u64,
+}
+
+/// A loop `header -> body -> header` counting `induction` from `start` by
+/// `step` while `condition` holds against `bound`.
+struct CountedLoop {
+    header: usize,
+    body: usize,
+    exit: String,
+    induction: String,
+    increment: String,
+    start: i64,
+    step: i64,
+    trip_count: u64,
+}
+
+impl LoopUnrolling {
+    pub fn new() -> Self {
+        Self { max_trip_count: 8 }
+    }
+
+    pub fn with_max_trip_count(mut self, max_trip_count: u64) -> Self {
+        self.max_trip_count = max_trip_count;
+        self
+    }
+
+    /// An operand's value if it is an integer literal or the result of an
+    /// integer `Const`.
+    fn constant(function: &IRFunction, operand: &str) -> Option<i64> {
+        operand.parse().ok().or_else(|| {
+            function.blocks.iter().flat_map(|block| &block.instructions).find_map(|instruction| match instruction {
+                Instruction::Const { result, value, .. } if result == operand => value.parse().ok(),
+                _ => None,
+            })
+        })
+    }
+
+    /// Iterations of `i = start; i <cond> bound; i += step`, or `None` when
+    /// the loop might not terminate or the count overflows.
+    fn trip_count(condition: &ICmpCondition, start: i64, bound: i64, step: i64) -> Option<u64> {
+        if step <= 0 {
+            return None;
+        }
+        let span = match condition {
+            ICmpCondition::Slt | ICmpCondition::Ult => bound.checked_sub(start)?,
+            ICmpCondition::Sle | ICmpCondition::Ule => bound.checked_sub(start)?.checked_add(1)?,
+            ICmpCondition::Ne if bound >= start => {
+                let span = bound.checked_sub(start)?;
+                if span % step != 0 {
+                    return None;
+                }
+                span
+            }
+            _ => return None,
+        };
+        if matches!(condition, ICmpCondition::Ult | ICmpCondition::Ule) && (start < 0 || bound < 0) {
+            return None;
+        }
+        Some(if span <= 0 { 0 } else { (span.checked_add(step - 1)? / step) as u64 })
+    }
+
+    fn find_loop(&self, function: &IRFunction) -> Option<CountedLoop> {
+        let index_of: HashMap<&str, usize> =
+            function.blocks.iter().enumerate().map(|(i, block)| (block.label.as_str(), i)).collect();
+
+        for (header, block) in function.blocks.iter().enumerate() {
+            let (phi, compare) = match block.instructions.as_slice() {
+                [phi @ Instruction::Phi { .. }, compare @ Instruction::ICmp { .. }] => (phi, compare),
+                _ => continue,
+            };
+            let (induction, incoming) = match phi {
+                Instruction::Phi { result, incoming, .. } if incoming.len() == 2 => (result, incoming),
+                _ => continue,
+            };
+            let (tested, condition, counter, bound) = match compare {
+                Instruction::ICmp { result, condition, left, right } => (result, condition, left, right),
+                _ => continue,
+            };
+            let (body_label, exit) = match &block.terminator {
+                Terminator::CondBr { condition: c, true_target, false_target } if c == tested => (true_target, false_target),
+                _ => continue,
+            };
+            if counter != induction || body_label == &block.label || exit == &block.label {
+                continue;
+            }
+
+            let body = match index_of.get(body_label.as_str()) {
+                Some(&body) => body,
+                None => continue,
+            };
+            let body_block = &function.blocks[body];
+            if body_block.terminator != (Terminator::Br { target: block.label.clone() }) {
+                continue;
+            }
+
+            let (start, increment) = match incoming.as_slice() {
+                [(start, from), (next, latch)] | [(next, latch), (start, from)] if latch == body_label && from != body_label => {
+                    (start, next)
+                }
+                _ => continue,
+            };
+            let step = body_block.instructions.iter().find_map(|instruction| match instruction {
+                Instruction::Add { result, left, right, .. } if result == increment && left == induction => {
+                    Self::constant(function, right)
+                }
+                _ => None,
+            });
+
+            let (start, bound, step) = match (Self::constant(function, start), Self::constant(function, bound), step) {
+                (Some(start), Some(bound), Some(step)) => (start, bound, step),
+                _ => continue,
+            };
+            let trip_count = match Self::trip_count(condition, start, bound, step) {
+                Some(count) if count > 0 && count <= self.max_trip_count => count,
+                _ => continue,
+            };
+
+            let candidate = CountedLoop {
+                header,
+                body,
+                exit: exit.clone(),
+                induction: induction.clone(),
+                increment: increment.clone(),
+                start,
+                step,
+                trip_count,
+            };
+            if Self::is_self_contained(function, &candidate) {
+                return Some(candidate);
+            }
+        }
+
+        None
+    }
+
+    /// Whether only the loop itself reads what it defines, and the increment
+    /// feeds nothing but the phi.
+    fn is_self_contained(function: &IRFunction, counted: &CountedLoop) -> bool {
+        let header = &function.blocks[counted.header];
+        let body = &function.blocks[counted.body];
+        let defined: HashSet<&str> = header
+            .instructions
+            .iter()
+            .chain(&body.instructions)
+            .filter_map(|instruction| instruction.get_result())
+            .collect();
+
+        let outside_uses = function.blocks.iter().enumerate().filter(|(i, _)| *i != counted.header && *i != counted.body);
+        for (_, block) in outside_uses {
+            let in_terminator = match &block.terminator {
+                Terminator::Ret { value: Some(value) } => Some(value.as_str()),
+                Terminator::CondBr { condition: value, .. } | Terminator::Switch { value, .. } => Some(value.as_str()),
+                _ => None,
+            };
+            let mut operands = block.instructions.iter().flat_map(|instruction| instruction.get_operands());
+            if operands.any(|operand| defined.contains(operand)) || in_terminator.is_some_and(|v| defined.contains(v)) {
+                return false;
+            }
+        }
+
+        !body
+            .instructions
+            .iter()
+            .flat_map(|instruction| instruction.get_operands())
+            .any(|operand| operand == counted.increment)
+    }
+
+    fn unroll(function: &mut IRFunction, counted: CountedLoop) {
+        let body = function.blocks[counted.body].instructions.clone();
+        let mut unrolled = Vec::with_capacity(body.len() * counted.trip_count as usize);
+
+        for iteration in 0..counted.trip_count {
+            let mut renames: HashMap<String, String> = HashMap::new();
+            renames.insert(counted.induction.clone(), (counted.start + iteration as i64 * counted.step).to_string());
+
+            for instruction in &body {
+                if instruction.get_result() == Some(counted.increment.as_str()) {
+                    continue;
+                }
+                let mut copy = instruction.clone();
+                for operand in copy.operands_mut() {
+                    if let Some(renamed) = renames.get(operand.as_str()) {
+                        *operand = renamed.clone();
+                    }
+                }
+                if let Some(result) = copy.result_mut() {
+                    let renamed = format!("{}.unroll{}", result, iteration);
+                    renames.insert(result.clone(), renamed.clone());
+                    *result = renamed;
+                }
+                unrolled.push(copy);
+            }
+        }
+
+        let header = &mut function.blocks[counted.header];
+        header.instructions = unrolled;
+        header.terminator = Terminator::Br { target: counted.exit };
+        function.blocks.remove(counted.body);
+    }
+}
+
+impl Default for LoopUnrolling {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OptimizationPass for LoopUnrolling {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            while let Some(counted) = self.find_loop(function) {
+                Self::unroll(function, counted);
+            }
+        }
+
+        Ok(optimized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{BasicBlock, IRType};
+
+    /// `for i in 0..bound { work(i) }` in header/body form.
+    fn counting_loop(bound: &str) -> Module {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "count".to_string(),
+            params: vec![],
+            return_type: IRType::Void,
+            blocks: vec![
+                BasicBlock {
+                    label: "entry".to_string(),
+                    instructions: vec![],
+                    terminator: Terminator::Br { target: "header".to_string() },
+                },
+                BasicBlock {
+                    label: "header".to_string(),
+                    instructions: vec![
+                        Instruction::Phi {
+                            result: "%i".to_string(),
+                            ty: IRType::I32,
+                            incoming: vec![
+                                ("0".to_string(), "entry".to_string()),
+                                ("%i.next".to_string(), "body".to_string()),
+                            ],
+                        },
+                        Instruction::ICmp {
+                            result: "%c".to_string(),
+                            condition: ICmpCondition::Slt,
+                            left: "%i".to_string(),
+                            right: bound.to_string(),
+                        },
+                    ],
+                    terminator: Terminator::CondBr {
+                        condition: "%c".to_string(),
+                        true_target: "body".to_string(),
+                        false_target: "exit".to_string(),
+                    },
+                },
+                BasicBlock {
+                    label: "body".to_string(),
+                    instructions: vec![
+                        Instruction::Mul {
+                            result: "%sq".to_string(),
+                            left: "%i".to_string(),
+                            right: "%i".to_string(),
+                            ty: IRType::I32,
+                        },
+                        Instruction::Call { result: None, func: "work".to_string(), args: vec!["%sq".to_string()] },
+                        Instruction::Add {
+                            result: "%i.next".to_string(),
+                            left: "%i".to_string(),
+                            right: "1".to_string(),
+                            ty: IRType::I32,
+                        },
+                    ],
+                    terminator: Terminator::Br { target: "header".to_string() },
+                },
+                BasicBlock {
+                    label: "exit".to_string(),
+                    instructions: vec![],
+                    terminator: Terminator::Ret { value: None },
+                },
+            ],
+        });
+        module
+    }
+
+    fn blocks(module: &Module) -> Vec<(&str, &[Instruction], &Terminator)> {
+        module.functions[0]
+            .blocks
+            .iter()
+            .map(|block| (block.label.as_str(), block.instructions.as_slice(), &block.terminator))
+            .collect()
+    }
+
+    #[test]
+    fn test_constant_loop_body_is_duplicated_per_iteration() {
+        let unrolled = LoopUnrolling::new().optimize(&counting_loop("4")).unwrap();
+        let function = &unrolled.functions[0];
+
+        let labels: Vec<&str> = function.blocks.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, vec!["entry", "header", "exit"]);
+        assert_eq!(function.blocks[1].terminator, Terminator::Br { target: "exit".to_string() });
+
+        let mut expected = Vec::new();
+        for i in 0..4 {
+            let square = format!("%sq.unroll{}", i);
+            expected.push(Instruction::Mul {
+                result: square.clone(),
+                left: i.to_string(),
+                right: i.to_string(),
+                ty: IRType::I32,
+            });
+            expected.push(Instruction::Call { result: None, func: "work".to_string(), args: vec![square] });
+        }
+        assert_eq!(function.blocks[1].instructions, expected);
+    }
+
+    #[test]
+    fn test_dynamic_bound_loop_is_unchanged() {
+        let module = counting_loop("%n");
+        let unrolled = LoopUnrolling::new().optimize(&module).unwrap();
+
+        assert_eq!(blocks(&unrolled), blocks(&module));
+    }
+
+    #[test]
+    fn test_loop_above_the_trip_count_limit_is_unchanged() {
+        let module = counting_loop("100");
+        let unrolled = LoopUnrolling::new().optimize(&module).unwrap();
+
+        assert_eq!(blocks(&unrolled), blocks(&module));
+    }
+
+    #[test]
+    fn test_trip_count_with_extreme_bounds_is_not_computed() {
+        assert_eq!(LoopUnrolling::trip_count(&ICmpCondition::Slt, i64::MIN, i64::MAX, 1), None);
+        assert_eq!(LoopUnrolling::trip_count(&ICmpCondition::Sle, 0, i64::MAX, 1), None);
+        assert_eq!(LoopUnrolling::trip_count(&ICmpCondition::Ne, -1, i64::MAX, 1), None);
+        assert_eq!(LoopUnrolling::trip_count(&ICmpCondition::Slt, 0, i64::MAX, 2), None);
+        assert_eq!(LoopUnrolling::trip_count(&ICmpCondition::Slt, 0, 7, 2), Some(4));
+
+        let module = counting_loop(&i64::MAX.to_string());
+        let unrolled = LoopUnrolling::new().optimize(&module).unwrap();
+        assert_eq!(blocks(&unrolled), blocks(&module));
+    }
+}
@@ -3,14 +3,46 @@ pub mod dead_code_elimination;
 pub mod inlining;
 pub mod peephole;
 pub mod aggressive_opts;
+pub mod devirtualization;
+pub mod fma_contraction;
+pub mod bounds_check_elimination;
+pub mod loop_rotation;
+pub mod cast_combining;
+pub mod check_fusion;
+pub mod phi_simplification;
+pub mod function_merging;
+pub mod switch_conversion;
+pub mod unreachable_defaults;
+pub mod tail_calls;
+pub mod refcount_elision;
+pub mod partial_redundancy;
+pub mod constant_pooling;
+pub mod loop_unrolling;
 
 pub use constant_folding::*;
 pub use dead_code_elimination::*;
 pub use inlining::*;
 pub use peephole::*;
 pub use aggressive_opts::*;
+pub use devirtualization::*;
+pub use fma_contraction::*;
+pub use bounds_check_elimination::*;
+pub use loop_rotation::*;
+pub use cast_combining::*;
+pub use check_fusion::*;
+pub use phi_simplification::*;
+pub use function_merging::*;
+pub use switch_conversion::*;
+pub use unreachable_defaults::*;
+pub use tail_calls::*;
+pub use refcount_elision::*;
+pub use partial_redundancy::*;
+pub use constant_pooling::*;
+pub use loop_unrolling::*;
 
 use super::Module;
+use crate::optimizer::OptimizationOptions;
+use crate::trait_system::TraitRegistry;
 use anyhow::Result;
 
 pub struct Optimizer {
@@ -19,6 +51,22 @@ pub struct Optimizer {
     inliner: Inliner,
     peephole_optimizer: PeepholeOptimizer,
     aggressive_optimizer: AggressiveOptimizer,
+    devirtualizer: Devirtualizer,
+    fma_contraction: FmaContraction,
+    bounds_check_eliminator: BoundsCheckElimination,
+    loop_rotation: LoopRotation,
+    cast_combiner: CastCombiner,
+    check_fusion: CheckFusion,
+    phi_simplification: PhiSimplification,
+    function_merging: FunctionMerging,
+    switch_conversion: SwitchConversion,
+    unreachable_defaults: UnreachableDefaults,
+    tail_calls: TailCallElimination,
+    refcount_elision: RefcountElision,
+    partial_redundancy: PartialRedundancyElimination,
+    constant_pooling: ConstantPooling,
+    loop_unrolling: LoopUnrolling,
+    unroll_loops: bool,
 }
 
 impl Optimizer {
@@ -29,9 +77,49 @@ impl Optimizer {
             inliner: Inliner::new(),
             peephole_optimizer: PeepholeOptimizer::new(),
             aggressive_optimizer: AggressiveOptimizer::new(),
+            devirtualizer: Devirtualizer::default(),
+            fma_contraction: FmaContraction::new(false),
+            bounds_check_eliminator: BoundsCheckElimination::new(),
+            loop_rotation: LoopRotation::new(),
+            cast_combiner: CastCombiner::new(),
+            check_fusion: CheckFusion::new(),
+            phi_simplification: PhiSimplification::new(),
+            function_merging: FunctionMerging::new(),
+            switch_conversion: SwitchConversion::new(),
+            unreachable_defaults: UnreachableDefaults::new(),
+            tail_calls: TailCallElimination::new(),
+            refcount_elision: RefcountElision::new(),
+            partial_redundancy: PartialRedundancyElimination::new(),
+            constant_pooling: ConstantPooling::new(),
+            loop_unrolling: LoopUnrolling::new(),
+            unroll_loops: false,
         }
     }
 
+    /// Builds the pipeline for the passes `options` turns on or off.
+    pub fn from_options(options: &OptimizationOptions) -> Self {
+        Self::new()
+            .with_fast_math(options.fast_math)
+            .with_unroll_loops(options.unroll_loops)
+    }
+
+    /// Allows floating-point contraction of `a * b + c` into `FMA`.
+    pub fn with_fast_math(mut self, fast_math: bool) -> Self {
+        self.fma_contraction = FmaContraction::new(fast_math);
+        self
+    }
+
+    /// Fully unrolls loops with a small constant trip count at level 3.
+    pub fn with_unroll_loops(mut self, unroll_loops: bool) -> Self {
+        self.unroll_loops = unroll_loops;
+        self
+    }
+
+    pub fn with_trait_registry(mut self, registry: TraitRegistry) -> Self {
+        self.devirtualizer = Devirtualizer::new(registry);
+        self
+    }
+
     pub fn optimize(&mut self, module: &Module, opt_level: u8) -> Result<Module> {
         let mut optimized_module = module.clone();
 
@@ -45,17 +133,50 @@ impl Optimizer {
             }
             2 => {
                 optimized_module = self.constant_folder.optimize(&optimized_module)?;
+                optimized_module = self.phi_simplification.optimize(&optimized_module)?;
                 optimized_module = self.dead_code_eliminator.optimize(&optimized_module)?;
+                optimized_module = self.switch_conversion.optimize(&optimized_module)?;
+                optimized_module = self.unreachable_defaults.optimize(&optimized_module)?;
+                optimized_module = self.tail_calls.optimize(&optimized_module)?;
+                optimized_module = self.loop_rotation.optimize(&optimized_module)?;
+                optimized_module = self.devirtualizer.optimize(&optimized_module)?;
                 optimized_module = self.peephole_optimizer.optimize(&optimized_module)?;
+                optimized_module = self.cast_combiner.optimize(&optimized_module)?;
+                optimized_module = self.refcount_elision.optimize(&optimized_module)?;
+                optimized_module = self.bounds_check_eliminator.optimize(&optimized_module)?;
+                optimized_module = self.check_fusion.optimize(&optimized_module)?;
+                optimized_module = self.fma_contraction.optimize(&optimized_module)?;
+                optimized_module = self.function_merging.optimize(&optimized_module)?;
+                optimized_module = self.constant_pooling.optimize(&optimized_module)?;
             }
             3 => {
+                optimized_module = self.devirtualizer.optimize(&optimized_module)?;
+                // Devirtualized calls may turn out to be self calls in tail position.
+                optimized_module = self.tail_calls.optimize(&optimized_module)?;
+                // Unroll before rotation, which rewrites the loop shape it matches.
+                if self.unroll_loops {
+                    optimized_module = self.loop_unrolling.optimize(&optimized_module)?;
+                }
+                // Rotate first so LICM inside the loop below has preheaders to hoist into.
+                optimized_module = self.loop_rotation.optimize(&optimized_module)?;
                 for _ in 0..3 {
                     optimized_module = self.constant_folder.optimize(&optimized_module)?;
+                    optimized_module = self.phi_simplification.optimize(&optimized_module)?;
                     optimized_module = self.dead_code_eliminator.optimize(&optimized_module)?;
                     optimized_module = self.inliner.optimize(&optimized_module)?;
                     optimized_module = self.peephole_optimizer.optimize(&optimized_module)?;
+                    optimized_module = self.cast_combiner.optimize(&optimized_module)?;
                     self.aggressive_optimizer.optimize(&mut optimized_module)?;
                 }
+                optimized_module = self.partial_redundancy.optimize(&optimized_module)?;
+                optimized_module = self.switch_conversion.optimize(&optimized_module)?;
+                optimized_module = self.unreachable_defaults.optimize(&optimized_module)?;
+                optimized_module = self.refcount_elision.optimize(&optimized_module)?;
+                optimized_module = self.bounds_check_eliminator.optimize(&optimized_module)?;
+                optimized_module = self.check_fusion.optimize(&optimized_module)?;
+                optimized_module = self.fma_contraction.optimize(&optimized_module)?;
+                optimized_module = self.function_merging.optimize(&optimized_module)?;
+                optimized_module = self.constant_pooling.optimize(&optimized_module)?;
             }
             _ => {
                 return Err(anyhow::anyhow!("Invalid optimization level: {}", opt_level));
@@ -70,3 +191,62 @@ pub trait OptimizationPass {
     fn optimize(&mut self, module: &Module) -> Result<Module>;
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{BasicBlock, IRFunction, IRType, Instruction, Terminator};
+
+    #[test]
+    fn test_loop_unrolling_follows_unroll_loops_option() {
+        assert!(!Optimizer::new().unroll_loops);
+        assert!(!Optimizer::from_options(&OptimizationOptions::level_2()).unroll_loops);
+        assert!(Optimizer::from_options(&OptimizationOptions::level_3()).unroll_loops);
+
+        let options = OptimizationOptions { unroll_loops: false, ..OptimizationOptions::level_3() };
+        assert!(!Optimizer::from_options(&options).unroll_loops);
+    }
+
+    /// `%a * %b + %c` on `f64`, returned.
+    fn mul_add_module() -> Module {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "mul_add".to_string(),
+            params: vec![],
+            return_type: IRType::F64,
+            blocks: vec![BasicBlock {
+                label: "entry".to_string(),
+                instructions: vec![
+                    Instruction::Mul {
+                        result: "%t0".to_string(),
+                        left: "%a".to_string(),
+                        right: "%b".to_string(),
+                        ty: IRType::F64,
+                    },
+                    Instruction::Add {
+                        result: "%t1".to_string(),
+                        left: "%t0".to_string(),
+                        right: "%c".to_string(),
+                        ty: IRType::F64,
+                    },
+                ],
+                terminator: Terminator::Ret { value: Some("%t1".to_string()) },
+            }],
+        });
+        module
+    }
+
+    #[test]
+    fn test_fma_contraction_follows_fast_math_option() {
+        let contracted = |fast_math: bool| {
+            let options = OptimizationOptions { fast_math, ..OptimizationOptions::level_2() };
+            let module = Optimizer::from_options(&options).fma_contraction.optimize(&mul_add_module()).unwrap();
+            module.functions[0].blocks[0]
+                .instructions
+                .iter()
+                .any(|instruction| matches!(instruction, Instruction::FMA { .. }))
+        };
+
+        assert!(contracted(true));
+        assert!(!contracted(false));
+    }
+}
@@ -0,0 +1,337 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{IRFunction, IRType, Instruction, Terminator};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+/// Partial redundancy elimination at join points.
+///
+/// When an expression at a join is already computed on some incoming paths
+/// but not others, a copy is inserted at the end of each predecessor that
+/// lacks it, and the expression at the join becomes a phi of the values
+/// reaching it:
+///
+/// ```text
+/// then: %x = add %a, %b; br join            then: %x = add %a, %b; br join
+/// else: br join                       =>    else: %y.pre.else = add %a, %b; br join
+/// join: %y = add %a, %b                     join: %y = phi [%x, then], [%y.pre.else, else]
+/// ```
+///
+/// A value counts as available at the end of a predecessor if the same
+/// expression is computed there or in a block dominating it. Only arithmetic,
+/// comparisons and casts are moved; loads and calls never are. Division and
+/// remainder may trap, so they are only inserted into predecessors that
+/// always continue to the join and only when nothing before them in the
+/// join could leave the function.
+pub struct PartialRedundancyElimination;
+
+/// A partially redundant expression at `position` in `join`. Predecessors in
+/// `incoming` without a value get a copy of `expression`; the original then
+/// becomes a phi over all of them.
+struct Insertion {
+    join: usize,
+    position: usize,
+    result: String,
+    expression: Instruction,
+    incoming: Vec<(Option<String>, String)>,
+}
+
+impl PartialRedundancyElimination {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// `instruction` with its result cleared, so equal expressions compare
+    /// equal whatever they are named.
+    fn expression(instruction: &Instruction) -> Option<Instruction> {
+        let movable = matches!(
+            instruction,
+            Instruction::Add { .. }
+                | Instruction::Sub { .. }
+                | Instruction::Mul { .. }
+                | Instruction::Div { .. }
+                | Instruction::Mod { .. }
+                | Instruction::ICmp { .. }
+                | Instruction::FCmp { .. }
+                | Instruction::BitCast { .. }
+                | Instruction::SExt { .. }
+                | Instruction::ZExt { .. }
+                | Instruction::Trunc { .. }
+        );
+        if !movable {
+            return None;
+        }
+        let mut expression = instruction.clone();
+        *expression.result_mut()? = String::new();
+        Some(expression)
+    }
+
+    fn may_trap(expression: &Instruction) -> bool {
+        matches!(expression, Instruction::Div { .. } | Instruction::Mod { .. })
+    }
+
+    fn successors(terminator: &Terminator) -> Vec<&str> {
+        match terminator {
+            Terminator::Br { target } => vec![target],
+            Terminator::CondBr { true_target, false_target, .. } => vec![true_target, false_target],
+            Terminator::Switch { cases, default, .. } => {
+                cases.iter().map(|(_, target)| target.as_str()).chain([default.as_str()]).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Blocks dominating each block, itself included, by the usual iterative
+    /// data-flow over the predecessors.
+    fn dominators(function: &IRFunction, predecessors: &[Vec<usize>]) -> Vec<HashSet<usize>> {
+        let all: HashSet<usize> = (0..function.blocks.len()).collect();
+        let mut dominators: Vec<HashSet<usize>> = vec![all; function.blocks.len()];
+        if let Some(entry) = dominators.first_mut() {
+            *entry = [0].into_iter().collect();
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in 1..function.blocks.len() {
+                let mut incoming = predecessors[block].iter().map(|&pred| &dominators[pred]);
+                let mut updated = match incoming.next() {
+                    Some(first) => incoming.fold(first.clone(), |acc, doms| &acc & doms),
+                    None => HashSet::new(),
+                };
+                updated.insert(block);
+                if updated != dominators[block] {
+                    dominators[block] = updated;
+                    changed = true;
+                }
+            }
+        }
+        dominators
+    }
+
+    fn find_insertion(function: &IRFunction) -> Option<Insertion> {
+        let index_of: HashMap<&str, usize> =
+            function.blocks.iter().enumerate().map(|(i, block)| (block.label.as_str(), i)).collect();
+        let mut predecessors = vec![Vec::new(); function.blocks.len()];
+        for (index, block) in function.blocks.iter().enumerate() {
+            for successor in Self::successors(&block.terminator) {
+                if let Some(&target) = index_of.get(successor) {
+                    predecessors[target].push(index);
+                }
+            }
+        }
+        let dominators = Self::dominators(function, &predecessors);
+
+        for (join, block) in function.blocks.iter().enumerate() {
+            let preds = &predecessors[join];
+            let distinct: HashSet<&usize> = preds.iter().collect();
+            // Skip entries, duplicate edges and loop headers.
+            if preds.len() < 2 || distinct.len() != preds.len() || preds.iter().any(|p| dominators[*p].contains(&join)) {
+                continue;
+            }
+            let defined_here: HashSet<&str> = block.instructions.iter().filter_map(|i| i.get_result()).collect();
+
+            for (position, instruction) in block.instructions.iter().enumerate() {
+                let expression = match Self::expression(instruction) {
+                    Some(expression) => expression,
+                    None => continue,
+                };
+                if expression.get_operands().iter().any(|operand| defined_here.contains(operand)) {
+                    continue;
+                }
+
+                let available_in = |pred: usize| {
+                    function
+                        .blocks
+                        .iter()
+                        .enumerate()
+                        .filter(|(index, _)| *index != join && dominators[pred].contains(index))
+                        .flat_map(|(_, block)| &block.instructions)
+                        .find(|other| Self::expression(other).as_ref() == Some(&expression))
+                        .and_then(|other| other.get_result())
+                        .map(str::to_string)
+                };
+                let incoming: Vec<(Option<String>, String)> =
+                    preds.iter().map(|&pred| (available_in(pred), function.blocks[pred].label.clone())).collect();
+                let available = incoming.iter().filter(|(value, _)| value.is_some()).count();
+                if available == 0 || available == incoming.len() {
+                    continue;
+                }
+
+                if Self::may_trap(&expression) {
+                    let always_reaches_join = preds.iter().zip(&incoming).all(|(&pred, (value, _))| {
+                        value.is_some() || Self::successors(&function.blocks[pred].terminator).len() == 1
+                    });
+                    let may_leave_first = block.instructions[..position]
+                        .iter()
+                        .any(|earlier| matches!(earlier, Instruction::Call { .. }));
+                    if !always_reaches_join || may_leave_first {
+                        continue;
+                    }
+                }
+
+                return Some(Insertion {
+                    join,
+                    position,
+                    result: instruction.get_result()?.to_string(),
+                    expression,
+                    incoming,
+                });
+            }
+        }
+
+        None
+    }
+
+    fn apply(function: &mut IRFunction, insertion: Insertion) {
+        let mut incoming = Vec::with_capacity(insertion.incoming.len());
+        for (value, label) in insertion.incoming {
+            let value = match value {
+                Some(value) => value,
+                None => {
+                    let inserted = format!("{}.pre.{}", insertion.result, label);
+                    let mut copy = insertion.expression.clone();
+                    if let Some(result) = copy.result_mut() {
+                        *result = inserted.clone();
+                    }
+                    if let Some(pred) = function.blocks.iter_mut().find(|block| block.label == label) {
+                        pred.instructions.push(copy);
+                    }
+                    inserted
+                }
+            };
+            incoming.push((value, label));
+        }
+
+        let ty = Self::result_type(&insertion.expression);
+        let join = &mut function.blocks[insertion.join];
+        join.instructions.remove(insertion.position);
+        let after_phis = join.instructions.iter().take_while(|i| matches!(i, Instruction::Phi { .. })).count();
+        join.instructions.insert(after_phis, Instruction::Phi { result: insertion.result, ty, incoming });
+    }
+
+    /// The type a movable expression produces.
+    fn result_type(expression: &Instruction) -> IRType {
+        match expression {
+            Instruction::Add { ty, .. }
+            | Instruction::Sub { ty, .. }
+            | Instruction::Mul { ty, .. }
+            | Instruction::Div { ty, .. }
+            | Instruction::Mod { ty, .. }
+            | Instruction::BitCast { ty, .. } => ty.clone(),
+            Instruction::SExt { to_ty, .. } | Instruction::ZExt { to_ty, .. } | Instruction::Trunc { to_ty, .. } => {
+                to_ty.clone()
+            }
+            Instruction::ICmp { .. } | Instruction::FCmp { .. } => IRType::I1,
+            _ => unreachable!("only movable expressions are inserted"),
+        }
+    }
+}
+
+impl Default for PartialRedundancyElimination {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OptimizationPass for PartialRedundancyElimination {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            while let Some(insertion) = Self::find_insertion(function) {
+                Self::apply(function, insertion);
+            }
+        }
+
+        Ok(optimized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{BasicBlock, Parameter};
+
+    fn block(label: &str, instructions: Vec<Instruction>, terminator: Terminator) -> BasicBlock {
+        BasicBlock { label: label.to_string(), instructions, terminator }
+    }
+
+    fn br(target: &str) -> Terminator {
+        Terminator::Br { target: target.to_string() }
+    }
+
+    fn cond_br(if_true: &str, if_false: &str) -> Terminator {
+        Terminator::CondBr {
+            condition: "%c".to_string(),
+            true_target: if_true.to_string(),
+            false_target: if_false.to_string(),
+        }
+    }
+
+    fn add(result: &str) -> Instruction {
+        Instruction::Add { result: result.to_string(), left: "%a".to_string(), right: "%b".to_string(), ty: IRType::I32 }
+    }
+
+    fn div(result: &str) -> Instruction {
+        Instruction::Div { result: result.to_string(), left: "%a".to_string(), right: "%b".to_string(), ty: IRType::I32 }
+    }
+
+    fn function(blocks: Vec<BasicBlock>) -> Module {
+        let param = |name: &str, ty: IRType| Parameter { name: name.to_string(), ty };
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "pick".to_string(),
+            params: vec![param("%c", IRType::I1), param("%a", IRType::I32), param("%b", IRType::I32)],
+            return_type: IRType::I32,
+            blocks,
+        });
+        module
+    }
+
+    fn ret(value: &str) -> Terminator {
+        Terminator::Ret { value: Some(value.to_string()) }
+    }
+
+    #[test]
+    fn test_expression_from_one_branch_is_made_available_at_the_join() {
+        let module = function(vec![
+            block("entry", vec![], cond_br("then", "else")),
+            block("then", vec![add("%x")], br("join")),
+            block("else", vec![], br("join")),
+            block("join", vec![add("%y")], ret("%y")),
+        ]);
+
+        let optimized = PartialRedundancyElimination::new().optimize(&module).unwrap();
+        let blocks = &optimized.functions[0].blocks;
+
+        assert_eq!(blocks[1].instructions, vec![add("%x")]);
+        assert_eq!(blocks[2].instructions, vec![add("%y.pre.else")]);
+        assert_eq!(
+            blocks[3].instructions,
+            vec![Instruction::Phi {
+                result: "%y".to_string(),
+                ty: IRType::I32,
+                incoming: vec![
+                    ("%x".to_string(), "then".to_string()),
+                    ("%y.pre.else".to_string(), "else".to_string()),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_division_is_not_inserted_on_an_edge_that_can_skip_it() {
+        // entry branches straight to the join or through `then`, which divides.
+        let module = function(vec![
+            block("entry", vec![], cond_br("then", "join")),
+            block("then", vec![div("%q")], br("join")),
+            block("join", vec![div("%r")], ret("%r")),
+        ]);
+
+        let optimized = PartialRedundancyElimination::new().optimize(&module).unwrap();
+
+        let instructions: Vec<&[Instruction]> =
+            optimized.functions[0].blocks.iter().map(|b| b.instructions.as_slice()).collect();
+        assert_eq!(instructions, vec![&[][..], &[div("%q")][..], &[div("%r")][..]]);
+    }
+}
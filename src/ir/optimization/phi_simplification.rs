@@ -0,0 +1,260 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{IRFunction, IRType, Instruction, Terminator};
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Removes trivial phis left behind by SSA construction.
+///
+/// A phi is trivial when, ignoring references to itself, all of its incoming
+/// values are the same value; its uses are rewritten to that value and the
+/// phi is dropped. A phi whose incoming values are distinct constants with
+/// the same type and value becomes that constant. Removing one phi can make
+/// another trivial, so the pass repeats until nothing changes.
+pub struct PhiSimplification;
+
+enum Simplified {
+    Value(String),
+    Constant(IRType, String),
+}
+
+impl PhiSimplification {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn simplify(
+        result: &str,
+        incoming: &[(String, String)],
+        constants: &HashMap<&str, (&IRType, &str)>,
+    ) -> Option<Simplified> {
+        let mut values: Vec<&str> = incoming
+            .iter()
+            .map(|(value, _)| value.as_str())
+            .filter(|value| *value != result)
+            .collect();
+        values.sort_unstable();
+        values.dedup();
+
+        match values.as_slice() {
+            // Only self-references: the value is undefined, leave it alone.
+            [] => None,
+            [value] => Some(Simplified::Value(value.to_string())),
+            [first, rest @ ..] => {
+                let constant = constants.get(first)?;
+                if rest.iter().all(|value| constants.get(value) == Some(constant)) {
+                    Some(Simplified::Constant(constant.0.clone(), constant.1.to_string()))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Runs one round and reports whether anything changed.
+    fn simplify_round(function: &mut IRFunction) -> bool {
+        let mut replacements: HashMap<String, String> = HashMap::new();
+        let mut folded: HashMap<String, (IRType, String)> = HashMap::new();
+
+        {
+            let constants: HashMap<&str, (&IRType, &str)> = function
+                .blocks
+                .iter()
+                .flat_map(|block| &block.instructions)
+                .filter_map(|instruction| match instruction {
+                    Instruction::Const { result, ty, value } => Some((result.as_str(), (ty, value.as_str()))),
+                    _ => None,
+                })
+                .collect();
+
+            for instruction in function.blocks.iter().flat_map(|block| &block.instructions) {
+                if let Instruction::Phi { result, incoming, .. } = instruction {
+                    match Self::simplify(result, incoming, &constants) {
+                        Some(Simplified::Value(value)) => {
+                            replacements.insert(result.clone(), value);
+                        }
+                        Some(Simplified::Constant(ty, value)) => {
+                            folded.insert(result.clone(), (ty, value));
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+
+        if replacements.is_empty() && folded.is_empty() {
+            return false;
+        }
+
+        let resolve = |value: &str| {
+            let mut value = value;
+            while let Some(next) = replacements.get(value) {
+                value = next;
+            }
+            value.to_string()
+        };
+
+        for block in &mut function.blocks {
+            block.instructions.retain(|instruction| {
+                !matches!(instruction, Instruction::Phi { result, .. } if replacements.contains_key(result))
+            });
+
+            for instruction in &mut block.instructions {
+                if let Instruction::Phi { result, .. } = instruction {
+                    if let Some((ty, value)) = folded.remove(result.as_str()) {
+                        *instruction = Instruction::Const { result: result.clone(), ty, value };
+                        continue;
+                    }
+                }
+                for operand in instruction.operands_mut() {
+                    *operand = resolve(operand);
+                }
+            }
+
+            match &mut block.terminator {
+                Terminator::Ret { value: Some(value) }
+                | Terminator::CondBr { condition: value, .. }
+                | Terminator::Switch { value, .. } => {
+                    *value = resolve(value);
+                }
+                _ => {}
+            }
+        }
+
+        true
+    }
+}
+
+impl OptimizationPass for PhiSimplification {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            while Self::simplify_round(function) {}
+        }
+
+        Ok(optimized)
+    }
+}
+
+impl Default for PhiSimplification {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{BasicBlock, Parameter};
+
+    fn constant(result: &str, value: i64) -> Instruction {
+        Instruction::Const { result: result.to_string(), ty: IRType::I32, value: value.to_string() }
+    }
+
+    fn phi(result: &str, incoming: &[(&str, &str)]) -> Instruction {
+        Instruction::Phi {
+            result: result.to_string(),
+            ty: IRType::I32,
+            incoming: incoming.iter().map(|(value, block)| (value.to_string(), block.to_string())).collect(),
+        }
+    }
+
+    fn block(label: &str, instructions: Vec<Instruction>, terminator: Terminator) -> BasicBlock {
+        BasicBlock { label: label.to_string(), instructions, terminator }
+    }
+
+    fn br(target: &str) -> Terminator {
+        Terminator::Br { target: target.to_string() }
+    }
+
+    fn ret(value: &str) -> Terminator {
+        Terminator::Ret { value: Some(value.to_string()) }
+    }
+
+    fn simplify(blocks: Vec<BasicBlock>) -> IRFunction {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "f".to_string(),
+            params: vec![
+                Parameter { name: "%c".to_string(), ty: IRType::I1 },
+                Parameter { name: "%v".to_string(), ty: IRType::I32 },
+            ],
+            return_type: IRType::I32,
+            blocks,
+        });
+
+        let mut optimized = PhiSimplification::new().optimize(&module).unwrap();
+        optimized.functions.remove(0)
+    }
+
+    /// `let x = if c { 1 } else { 1 }; return x;`
+    #[test]
+    fn test_phi_of_equal_constants_folds_to_constant() {
+        let function = simplify(vec![
+            block(
+                "entry",
+                vec![],
+                Terminator::CondBr {
+                    condition: "%c".to_string(),
+                    true_target: "then".to_string(),
+                    false_target: "else".to_string(),
+                },
+            ),
+            block("then", vec![constant("%a", 1)], br("merge")),
+            block("else", vec![constant("%b", 1)], br("merge")),
+            block("merge", vec![phi("%x", &[("%a", "then"), ("%b", "else")])], ret("%x")),
+        ]);
+
+        assert_eq!(function.blocks[3].instructions, vec![constant("%x", 1)]);
+        assert_eq!(function.blocks[3].terminator, ret("%x"));
+    }
+
+    #[test]
+    fn test_self_referential_phis_are_replaced_by_their_value() {
+        let function = simplify(vec![
+            block("entry", vec![], br("header")),
+            block(
+                "header",
+                vec![
+                    phi("%i", &[("%v", "entry"), ("%i", "body")]),
+                    phi("%j", &[("%i", "entry"), ("%j", "body"), ("%i", "body")]),
+                ],
+                br("body"),
+            ),
+            block(
+                "body",
+                vec![Instruction::Add {
+                    result: "%sum".to_string(),
+                    left: "%i".to_string(),
+                    right: "%j".to_string(),
+                    ty: IRType::I32,
+                }],
+                ret("%j"),
+            ),
+        ]);
+
+        assert_eq!(function.blocks[1].instructions, vec![]);
+        assert_eq!(
+            function.blocks[2].instructions,
+            vec![Instruction::Add {
+                result: "%sum".to_string(),
+                left: "%v".to_string(),
+                right: "%v".to_string(),
+                ty: IRType::I32,
+            }]
+        );
+        assert_eq!(function.blocks[2].terminator, ret("%v"));
+    }
+
+    #[test]
+    fn test_phi_merging_different_values_is_kept() {
+        let merge = vec![phi("%x", &[("%a", "then"), ("%v", "else")])];
+        let function = simplify(vec![
+            block("then", vec![constant("%a", 1)], br("merge")),
+            block("else", vec![], br("merge")),
+            block("merge", merge.clone(), ret("%x")),
+        ]);
+
+        assert_eq!(function.blocks[2].instructions, merge);
+    }
+}
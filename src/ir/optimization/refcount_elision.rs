@@ -0,0 +1,188 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{BasicBlock, IRFunction, Instruction, Terminator};
+use anyhow::Result;
+
+/// Runtime entry points that reference-counted values are lowered to.
+const RC_ALLOC: &str = "blaze_rc_alloc";
+const RC_INC: &str = "blaze_rc_inc";
+const RC_DEC: &str = "blaze_rc_dec";
+const RC_FREE: &str = "blaze_rc_free";
+
+/// Removes reference-count traffic on values that cannot escape.
+///
+/// Within a block, a `blaze_rc_inc` followed by a `blaze_rc_dec` of the same
+/// value cancels out when nothing in between lets the value escape: passing
+/// it to a call, storing it, returning it, or aliasing it through a phi,
+/// cast or GEP. Loads and stores through the value are not escapes.
+///
+/// Once pairs are gone, a value from `blaze_rc_alloc` that never escapes
+/// and is decremented exactly once must have a count of one there, so that
+/// decrement is replaced by a direct `blaze_rc_free`.
+pub struct RefcountElision;
+
+impl RefcountElision {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The value an `rc` runtime call of `kind` operates on.
+    fn rc_operand<'a>(instruction: &'a Instruction, kind: &str) -> Option<&'a str> {
+        match instruction {
+            Instruction::Call { func, args, .. } if func == kind && args.len() == 1 => Some(&args[0]),
+            _ => None,
+        }
+    }
+
+    fn is_rc_op(instruction: &Instruction, value: &str) -> bool {
+        [RC_INC, RC_DEC, RC_FREE].iter().any(|kind| Self::rc_operand(instruction, kind) == Some(value))
+    }
+
+    fn escapes(instruction: &Instruction, value: &str) -> bool {
+        match instruction {
+            _ if Self::is_rc_op(instruction, value) => false,
+            Instruction::Load { .. } => false,
+            Instruction::Store { value: stored, .. } => stored == value,
+            Instruction::ICmp { .. } | Instruction::FCmp { .. } => false,
+            other => other.get_operands().contains(&value),
+        }
+    }
+
+    /// Removes one balanced inc/dec pair from `block`, if there is one.
+    fn remove_pair(block: &mut BasicBlock) -> bool {
+        for (inc, instruction) in block.instructions.iter().enumerate() {
+            let value = match Self::rc_operand(instruction, RC_INC) {
+                Some(value) => value,
+                None => continue,
+            };
+            let rest = &block.instructions[inc + 1..];
+            let next = match rest.iter().position(|other| Self::is_rc_op(other, value)) {
+                Some(offset) => inc + 1 + offset,
+                None => continue,
+            };
+            let balanced = Self::rc_operand(&block.instructions[next], RC_DEC) == Some(value)
+                && !block.instructions[inc + 1..next].iter().any(|between| Self::escapes(between, value));
+            if balanced {
+                block.instructions.remove(next);
+                block.instructions.remove(inc);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Replaces the only decrement of a non-escaping allocation with a free.
+    fn free_directly(function: &mut IRFunction) {
+        let allocations: Vec<String> = function
+            .blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .filter_map(|instruction| match instruction {
+                Instruction::Call { result: Some(result), func, .. } if func == RC_ALLOC => Some(result.clone()),
+                _ => None,
+            })
+            .collect();
+
+        for value in allocations {
+            let instructions = || function.blocks.iter().flat_map(|block| &block.instructions);
+            let returned = function
+                .blocks
+                .iter()
+                .any(|block| block.terminator == Terminator::Ret { value: Some(value.clone()) });
+            let incremented = instructions().any(|instruction| Self::rc_operand(instruction, RC_INC) == Some(&value));
+            let decrements = instructions()
+                .filter(|instruction| Self::rc_operand(instruction, RC_DEC) == Some(&value))
+                .count();
+            if returned || incremented || decrements != 1 || instructions().any(|i| Self::escapes(i, &value)) {
+                continue;
+            }
+
+            for instruction in function.blocks.iter_mut().flat_map(|block| &mut block.instructions) {
+                if Self::rc_operand(instruction, RC_DEC) == Some(&value) {
+                    *instruction = Instruction::Call { result: None, func: RC_FREE.to_string(), args: vec![value.clone()] };
+                }
+            }
+        }
+    }
+}
+
+impl Default for RefcountElision {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OptimizationPass for RefcountElision {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            for block in &mut function.blocks {
+                while Self::remove_pair(block) {}
+            }
+            Self::free_directly(function);
+        }
+
+        Ok(optimized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::IRType;
+
+    fn call(func: &str, value: &str) -> Instruction {
+        Instruction::Call { result: None, func: func.to_string(), args: vec![value.to_string()] }
+    }
+
+    fn load(result: &str) -> Instruction {
+        Instruction::Load { result: result.to_string(), ptr: "%t".to_string() }
+    }
+
+    /// A function whose single block is `%t = blaze_rc_alloc(); <body>; ret`.
+    fn with_temporary(body: Vec<Instruction>) -> Module {
+        let mut instructions = vec![Instruction::Call {
+            result: Some("%t".to_string()),
+            func: RC_ALLOC.to_string(),
+            args: vec!["16".to_string()],
+        }];
+        instructions.extend(body);
+
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "temporary".to_string(),
+            params: vec![],
+            return_type: IRType::Void,
+            blocks: vec![BasicBlock {
+                label: "entry".to_string(),
+                instructions,
+                terminator: Terminator::Ret { value: None },
+            }],
+        });
+        module
+    }
+
+    fn body(module: &Module) -> &[Instruction] {
+        &module.functions[0].blocks[0].instructions[1..]
+    }
+
+    #[test]
+    fn test_inc_dec_pair_on_temporary_is_removed() {
+        // inc %t; %a = load %t; dec %t; dec %t
+        let module = with_temporary(vec![call(RC_INC, "%t"), load("%a"), call(RC_DEC, "%t"), call(RC_DEC, "%t")]);
+
+        let optimized = RefcountElision::new().optimize(&module).unwrap();
+
+        assert_eq!(body(&optimized), &[load("%a"), call(RC_FREE, "%t")]);
+    }
+
+    #[test]
+    fn test_escaping_value_keeps_its_counts() {
+        // inc %t; consume(%t); dec %t; dec %t
+        let module = with_temporary(vec![call(RC_INC, "%t"), call("consume", "%t"), call(RC_DEC, "%t"), call(RC_DEC, "%t")]);
+
+        let optimized = RefcountElision::new().optimize(&module).unwrap();
+
+        assert_eq!(body(&optimized), body(&module));
+    }
+}
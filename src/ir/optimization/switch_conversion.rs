@@ -0,0 +1,325 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{BasicBlock, ICmpCondition, IRFunction, Instruction, Terminator};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+/// Turns `if x == 0 {..} else if x == 1 {..} else ..` chains into a single
+/// `Terminator::Switch`.
+///
+/// A chain starts at a block ending in a branch on `x == k` and follows the
+/// false edges through blocks that do nothing but compare the same `x`
+/// against another constant. Those blocks are folded into the first one and
+/// removed. A repeated constant can never be reached in the original chain,
+/// so only its first case is kept.
+pub struct SwitchConversion {
+    min_cases: usize,
+}
+
+/// A block that only tests `value == case` on the way down the chain.
+struct ChainLink<'a> {
+    value: &'a str,
+    case: i64,
+    true_target: &'a str,
+    false_target: &'a str,
+}
+
+impl SwitchConversion {
+    pub fn new() -> Self {
+        Self { min_cases: 3 }
+    }
+
+    /// Shorter chains are cheaper as plain comparisons than as a switch.
+    pub fn with_min_cases(mut self, min_cases: usize) -> Self {
+        self.min_cases = min_cases;
+        self
+    }
+
+    fn integer_constants(function: &IRFunction) -> HashMap<&str, i64> {
+        function
+            .blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .filter_map(|instruction| match instruction {
+                Instruction::Const { result, value, .. } => value.parse().ok().map(|value| (result.as_str(), value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The comparison at the end of `block`, if it branches on `x == constant`.
+    fn link<'a>(block: &'a BasicBlock, constants: &HashMap<&str, i64>) -> Option<ChainLink<'a>> {
+        let (condition, true_target, false_target) = match &block.terminator {
+            Terminator::CondBr { condition, true_target, false_target } => (condition, true_target, false_target),
+            _ => return None,
+        };
+
+        let constant = |operand: &str| constants.get(operand).copied().or_else(|| operand.parse().ok());
+        block.instructions.iter().rev().find_map(|instruction| match instruction {
+            Instruction::ICmp { result, condition: ICmpCondition::Eq, left, right } if result == condition => {
+                let (value, case) = match (constant(left), constant(right)) {
+                    (None, Some(case)) => (left, case),
+                    (Some(case), None) => (right, case),
+                    _ => return None,
+                };
+                Some(ChainLink { value, case, true_target, false_target })
+            }
+            _ => None,
+        })
+    }
+
+    /// Finds the first chain worth converting: the index of its head block,
+    /// the switch replacing the head's branch, and the blocks folded into it.
+    fn find_chain(&self, function: &IRFunction) -> Option<(usize, Terminator, Vec<usize>)> {
+        let constants = Self::integer_constants(function);
+        let index_of: HashMap<&str, usize> =
+            function.blocks.iter().enumerate().map(|(index, block)| (block.label.as_str(), index)).collect();
+
+        let mut predecessors: HashMap<&str, usize> = HashMap::new();
+        let mut uses: HashMap<&str, usize> = HashMap::new();
+        let mut phi_sources: HashSet<&str> = HashSet::new();
+        for block in &function.blocks {
+            for target in successors(&block.terminator) {
+                *predecessors.entry(target).or_insert(0) += 1;
+            }
+            for instruction in &block.instructions {
+                for operand in instruction.get_operands() {
+                    *uses.entry(operand).or_insert(0) += 1;
+                }
+                if let Instruction::Phi { incoming, .. } = instruction {
+                    phi_sources.extend(incoming.iter().map(|(_, label)| label.as_str()));
+                }
+            }
+            match &block.terminator {
+                Terminator::Ret { value: Some(value) }
+                | Terminator::CondBr { condition: value, .. }
+                | Terminator::Switch { value, .. } => *uses.entry(value).or_insert(0) += 1,
+                _ => {}
+            }
+        }
+
+        // A block can be folded away when the previous link is its only
+        // predecessor, no phi names it, and its comparison and constants are
+        // used nowhere else.
+        let foldable = |block: &'_ BasicBlock, value: &str| -> bool {
+            let local_uses = |name: &str| {
+                let in_block = block.instructions.iter().flat_map(|i| i.get_operands()).filter(|op| *op == name).count();
+                let by_branch = matches!(&block.terminator, Terminator::CondBr { condition, .. } if condition == name);
+                in_block + by_branch as usize
+            };
+            let self_contained = block.instructions.iter().all(|instruction| match instruction {
+                Instruction::ICmp { result, .. } | Instruction::Const { result, .. } => {
+                    uses.get(result.as_str()).copied().unwrap_or(0) == local_uses(result)
+                }
+                _ => false,
+            });
+            self_contained
+                && predecessors.get(block.label.as_str()) == Some(&1)
+                && !phi_sources.contains(block.label.as_str())
+                && Self::link(block, &constants).is_some_and(|link| link.value == value)
+        };
+
+        for (head, block) in function.blocks.iter().enumerate() {
+            let first = match Self::link(block, &constants) {
+                Some(link) => link,
+                None => continue,
+            };
+
+            let mut cases = vec![(first.case, first.true_target.to_string())];
+            let mut folded = Vec::new();
+            let mut default = first.false_target;
+            while let Some(&next) = index_of.get(default) {
+                let next_block = &function.blocks[next];
+                if next == head || folded.contains(&next) || !foldable(next_block, first.value) {
+                    break;
+                }
+                let link = Self::link(next_block, &constants)?;
+                if !cases.iter().any(|(case, _)| *case == link.case) {
+                    cases.push((link.case, link.true_target.to_string()));
+                }
+                folded.push(next);
+                default = link.false_target;
+            }
+
+            if cases.len() >= self.min_cases {
+                let switch = Terminator::Switch { value: first.value.to_string(), cases, default: default.to_string() };
+                return Some((head, switch, folded));
+            }
+        }
+
+        None
+    }
+
+    fn convert_function(&self, function: &mut IRFunction) -> bool {
+        let (head, switch, folded) = match self.find_chain(function) {
+            Some(chain) => chain,
+            None => return false,
+        };
+
+        function.blocks[head].terminator = switch;
+        let mut index = 0;
+        function.blocks.retain(|_| {
+            index += 1;
+            !folded.contains(&(index - 1))
+        });
+        true
+    }
+}
+
+fn successors(terminator: &Terminator) -> Vec<&str> {
+    match terminator {
+        Terminator::Br { target } => vec![target],
+        Terminator::CondBr { true_target, false_target, .. } => vec![true_target, false_target],
+        Terminator::Switch { cases, default, .. } => {
+            cases.iter().map(|(_, target)| target.as_str()).chain([default.as_str()]).collect()
+        }
+        _ => vec![],
+    }
+}
+
+impl OptimizationPass for SwitchConversion {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            while self.convert_function(function) {}
+        }
+
+        Ok(optimized)
+    }
+}
+
+impl Default for SwitchConversion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{IRType, Parameter};
+
+    fn eq(result: &str, left: &str, right: &str) -> Instruction {
+        Instruction::ICmp {
+            result: result.to_string(),
+            condition: ICmpCondition::Eq,
+            left: left.to_string(),
+            right: right.to_string(),
+        }
+    }
+
+    fn block(label: &str, instructions: Vec<Instruction>, terminator: Terminator) -> BasicBlock {
+        BasicBlock { label: label.to_string(), instructions, terminator }
+    }
+
+    fn cond_br(condition: &str, true_target: &str, false_target: &str) -> Terminator {
+        Terminator::CondBr {
+            condition: condition.to_string(),
+            true_target: true_target.to_string(),
+            false_target: false_target.to_string(),
+        }
+    }
+
+    fn ret(value: &str) -> Terminator {
+        Terminator::Ret { value: Some(value.to_string()) }
+    }
+
+    /// `if x == 0 { return 10; } else if x == 1 { return 11; } ... else { return -1; }`
+    fn equality_chain(arms: i64) -> Vec<BasicBlock> {
+        let mut blocks = Vec::new();
+        for k in 0..arms {
+            let label = if k == 0 { "entry".to_string() } else { format!("test{}", k) };
+            let next = if k + 1 == arms { "otherwise".to_string() } else { format!("test{}", k + 1) };
+            let condition = format!("%c{}", k);
+            let arm = format!("arm{}", k);
+            blocks.push(block(&label, vec![eq(&condition, "%x", &k.to_string())], cond_br(&condition, &arm, &next)));
+        }
+        for k in 0..arms {
+            blocks.push(block(&format!("arm{}", k), vec![], ret(&(10 + k).to_string())));
+        }
+        blocks.push(block("otherwise", vec![], ret("-1")));
+        blocks
+    }
+
+    fn convert(blocks: Vec<BasicBlock>) -> IRFunction {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "classify".to_string(),
+            params: vec![Parameter { name: "%x".to_string(), ty: IRType::I32 }],
+            return_type: IRType::I32,
+            blocks,
+        });
+
+        let mut optimized = SwitchConversion::new().optimize(&module).unwrap();
+        optimized.functions.remove(0)
+    }
+
+    fn labels(function: &IRFunction) -> Vec<&str> {
+        function.blocks.iter().map(|block| block.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_five_arm_equality_chain_becomes_switch() {
+        let function = convert(equality_chain(5));
+
+        assert_eq!(
+            function.blocks[0].terminator,
+            Terminator::Switch {
+                value: "%x".to_string(),
+                cases: (0..5).map(|k| (k, format!("arm{}", k))).collect(),
+                default: "otherwise".to_string(),
+            }
+        );
+        assert_eq!(labels(&function), vec!["entry", "arm0", "arm1", "arm2", "arm3", "arm4", "otherwise"]);
+    }
+
+    #[test]
+    fn test_constant_operands_and_repeated_cases() {
+        // `if 7 == x {..} else if x == SEVEN {..} else if x == 8 {..} else if x == 9 {..}`
+        let blocks = vec![
+            block("entry", vec![eq("%c0", "7", "%x")], cond_br("%c0", "a", "t1")),
+            block(
+                "t1",
+                vec![
+                    Instruction::Const { result: "%seven".to_string(), ty: IRType::I32, value: "7".to_string() },
+                    eq("%c1", "%x", "%seven"),
+                ],
+                cond_br("%c1", "unreachable_arm", "t2"),
+            ),
+            block("t2", vec![eq("%c2", "%x", "8")], cond_br("%c2", "b", "t3")),
+            block("t3", vec![eq("%c3", "%x", "9")], cond_br("%c3", "c", "d")),
+        ];
+
+        let function = convert(blocks);
+
+        assert_eq!(
+            function.blocks[0].terminator,
+            Terminator::Switch {
+                value: "%x".to_string(),
+                cases: vec![(7, "a".to_string()), (8, "b".to_string()), (9, "c".to_string())],
+                default: "d".to_string(),
+            }
+        );
+        assert_eq!(labels(&function), vec!["entry"]);
+    }
+
+    #[test]
+    fn test_chain_stops_at_blocks_doing_other_work() {
+        let mut blocks = equality_chain(4);
+        // `test2` does more than compare, so the chain cannot fold it.
+        blocks[2].instructions.insert(
+            0,
+            Instruction::Add {
+                result: "%y".to_string(),
+                left: "%x".to_string(),
+                right: "1".to_string(),
+                ty: IRType::I32,
+            },
+        );
+
+        let function = convert(blocks);
+
+        assert_eq!(function.blocks[0].terminator, cond_br("%c0", "arm0", "test1"));
+        assert_eq!(function.blocks[2].label, "test2");
+    }
+}
@@ -0,0 +1,230 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{BasicBlock, IRFunction, Instruction, Terminator};
+use anyhow::Result;
+
+/// Turns self-recursive tail calls into a loop back to the function entry.
+///
+/// ```text
+/// entry: ...                                tailcall.entry: br entry
+/// rec:   %r = call @sum(%n1, %acc1)    =>   entry: %n = phi [%n.arg, tailcall.entry], [%n1, rec]
+///        ret %r                                    %acc = phi [%acc.arg, tailcall.entry], [%acc1, rec]
+///                                                  ...
+///                                           rec:   br entry
+/// ```
+///
+/// Only a call that is the last instruction of its block, and whose result
+/// is returned as is, is in tail position. The parameters are renamed to
+/// `<name>.arg` so the phis in the old entry block can keep their names.
+pub struct TailCallElimination;
+
+const PREHEADER: &str = "tailcall.entry";
+
+impl TailCallElimination {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The arguments of the self call ending `block`, if it is a tail call.
+    fn tail_call_args(function: &IRFunction, block: &BasicBlock) -> Option<Vec<String>> {
+        match (block.instructions.last(), &block.terminator) {
+            (Some(Instruction::Call { result, func, args }), Terminator::Ret { value })
+                if func == &function.name && args.len() == function.params.len() && result == value =>
+            {
+                Some(args.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn eliminate(function: &mut IRFunction) {
+        let entry = match function.blocks.first() {
+            Some(entry) => entry.label.clone(),
+            None => return,
+        };
+        // A loop already entering at the entry block would need its own phi
+        // incomings; leave such functions alone.
+        let entry_has_predecessors = function.blocks.iter().any(|block| match &block.terminator {
+            Terminator::Br { target } => target == &entry,
+            Terminator::CondBr { true_target, false_target, .. } => true_target == &entry || false_target == &entry,
+            Terminator::Switch { cases, default, .. } => {
+                default == &entry || cases.iter().any(|(_, target)| target == &entry)
+            }
+            _ => false,
+        });
+        if entry_has_predecessors {
+            return;
+        }
+
+        let tail_calls: Vec<(usize, Vec<String>)> = function
+            .blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, block)| Self::tail_call_args(function, block).map(|args| (index, args)))
+            .collect();
+        if tail_calls.is_empty() {
+            return;
+        }
+
+        let mut phis = Vec::with_capacity(function.params.len());
+        for (position, param) in function.params.iter_mut().enumerate() {
+            let incoming_arg = format!("{}.arg", param.name);
+            let mut incoming = vec![(incoming_arg.clone(), PREHEADER.to_string())];
+            for (index, args) in &tail_calls {
+                incoming.push((args[position].clone(), function.blocks[*index].label.clone()));
+            }
+            phis.push(Instruction::Phi { result: param.name.clone(), ty: param.ty.clone(), incoming });
+            param.name = incoming_arg;
+        }
+
+        for (index, _) in &tail_calls {
+            let block = &mut function.blocks[*index];
+            block.instructions.pop();
+            block.terminator = Terminator::Br { target: entry.clone() };
+        }
+        function.blocks[0].instructions.splice(0..0, phis);
+        function.blocks.insert(
+            0,
+            BasicBlock {
+                label: PREHEADER.to_string(),
+                instructions: vec![],
+                terminator: Terminator::Br { target: entry },
+            },
+        );
+    }
+}
+
+impl Default for TailCallElimination {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OptimizationPass for TailCallElimination {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            Self::eliminate(function);
+        }
+
+        Ok(optimized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{ICmpCondition, IRType, Parameter};
+
+    fn block(label: &str, instructions: Vec<Instruction>, terminator: Terminator) -> BasicBlock {
+        BasicBlock { label: label.to_string(), instructions, terminator }
+    }
+
+    fn param(name: &str) -> Parameter {
+        Parameter { name: name.to_string(), ty: IRType::I64 }
+    }
+
+    fn ret(value: &str) -> Terminator {
+        Terminator::Ret { value: Some(value.to_string()) }
+    }
+
+    /// `fn sum(n, acc) { if n == 0 { acc } else { <recurse> } }`, where the
+    /// recursive block is built by `recurse`.
+    fn sum(recurse: Vec<Instruction>, returned: &str) -> Module {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "sum".to_string(),
+            params: vec![param("%n"), param("%acc")],
+            return_type: IRType::I64,
+            blocks: vec![
+                block(
+                    "entry",
+                    vec![Instruction::ICmp {
+                        result: "%done".to_string(),
+                        condition: ICmpCondition::Eq,
+                        left: "%n".to_string(),
+                        right: "0".to_string(),
+                    }],
+                    Terminator::CondBr {
+                        condition: "%done".to_string(),
+                        true_target: "base".to_string(),
+                        false_target: "rec".to_string(),
+                    },
+                ),
+                block("base", vec![], ret("%acc")),
+                block("rec", recurse, ret(returned)),
+            ],
+        });
+        module
+    }
+
+    fn step() -> Vec<Instruction> {
+        vec![
+            Instruction::Add {
+                result: "%acc1".to_string(),
+                left: "%acc".to_string(),
+                right: "%n".to_string(),
+                ty: IRType::I64,
+            },
+            Instruction::Sub { result: "%n1".to_string(), left: "%n".to_string(), right: "1".to_string(), ty: IRType::I64 },
+        ]
+    }
+
+    fn call_sum(result: &str) -> Instruction {
+        Instruction::Call {
+            result: Some(result.to_string()),
+            func: "sum".to_string(),
+            args: vec!["%n1".to_string(), "%acc1".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_tail_recursive_sum_becomes_a_loop() {
+        let mut body = step();
+        body.push(call_sum("%r"));
+
+        let optimized = TailCallElimination::new().optimize(&sum(body, "%r")).unwrap();
+        let function = &optimized.functions[0];
+
+        let params: Vec<&str> = function.params.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(params, vec!["%n.arg", "%acc.arg"]);
+
+        let labels: Vec<&str> = function.blocks.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, vec!["tailcall.entry", "entry", "base", "rec"]);
+        assert_eq!(function.blocks[0].terminator, Terminator::Br { target: "entry".to_string() });
+
+        assert_eq!(
+            function.blocks[1].instructions[0],
+            Instruction::Phi {
+                result: "%n".to_string(),
+                ty: IRType::I64,
+                incoming: vec![
+                    ("%n.arg".to_string(), "tailcall.entry".to_string()),
+                    ("%n1".to_string(), "rec".to_string()),
+                ],
+            }
+        );
+        assert_eq!(function.blocks[3].instructions, step());
+        assert_eq!(function.blocks[3].terminator, Terminator::Br { target: "entry".to_string() });
+    }
+
+    #[test]
+    fn test_call_whose_result_is_used_is_not_a_tail_call() {
+        // rec: %r = call @sum(%n1, %acc1); %r2 = add %r, 1; ret %r2
+        let mut body = step();
+        body.push(call_sum("%r"));
+        body.push(Instruction::Add {
+            result: "%r2".to_string(),
+            left: "%r".to_string(),
+            right: "1".to_string(),
+            ty: IRType::I64,
+        });
+        let module = sum(body, "%r2");
+
+        let optimized = TailCallElimination::new().optimize(&module).unwrap();
+
+        assert_eq!(optimized.functions[0].params[0].name, "%n");
+        assert_eq!(optimized.functions[0].blocks.len(), 3);
+        assert_eq!(optimized.functions[0].blocks[2].instructions, module.functions[0].blocks[2].instructions);
+    }
+}
@@ -0,0 +1,284 @@
+use super::{OptimizationPass, Module};
+use crate::ir::{IRFunction, IRType, Instruction, Terminator};
+use anyhow::Result;
+use std::collections::HashSet;
+
+/// Drops `Switch` defaults that can never be taken, and branches into
+/// blocks that are only `unreachable`.
+///
+/// A default is dead when the cases already cover every `i1` value, when it
+/// leads straight to `unreachable`, or when the front end has recorded the
+/// switch as lowered from an exhaustive `match`. The last case then becomes
+/// the default, so the final comparison disappears; a two-way switch on an
+/// `i1` turns back into a conditional branch and a one-way switch into an
+/// unconditional one. Default blocks left without predecessors are removed.
+///
+/// Runs after [`SwitchConversion`](super::SwitchConversion), which is what
+/// turns exhaustive if-chains into switches with a default.
+pub struct UnreachableDefaults {
+    /// `(function, block)` pairs whose switch covers every possible value.
+    exhaustive: HashSet<(String, String)>,
+}
+
+impl UnreachableDefaults {
+    pub fn new() -> Self {
+        Self { exhaustive: HashSet::new() }
+    }
+
+    /// Marks the switch ending `block` in `function` as exhaustive, e.g.
+    /// because it was lowered from a `match` the exhaustiveness checker
+    /// accepted.
+    pub fn with_exhaustive_switch(mut self, function: &str, block: &str) -> Self {
+        self.exhaustive.insert((function.to_string(), block.to_string()));
+        self
+    }
+
+    fn bool_values(function: &IRFunction) -> HashSet<&str> {
+        let params = function.params.iter().filter(|param| param.ty == IRType::I1).map(|param| param.name.as_str());
+        let results = function.blocks.iter().flat_map(|block| &block.instructions).filter_map(|instruction| {
+            match instruction {
+                Instruction::ICmp { result, .. } | Instruction::FCmp { result, .. } => Some(result.as_str()),
+                Instruction::Const { result, ty: IRType::I1, .. }
+                | Instruction::Phi { result, ty: IRType::I1, .. }
+                | Instruction::Trunc { result, to_ty: IRType::I1, .. } => Some(result.as_str()),
+                _ => None,
+            }
+        });
+        params.chain(results).collect()
+    }
+
+    fn unreachable_blocks(function: &IRFunction) -> HashSet<String> {
+        function
+            .blocks
+            .iter()
+            .filter(|block| block.instructions.is_empty() && block.terminator == Terminator::Unreachable)
+            .map(|block| block.label.clone())
+            .collect()
+    }
+
+    /// Rewrites `terminator` without its dead default, returning the label
+    /// it no longer branches to.
+    fn simplify(
+        &self,
+        function: &str,
+        block: &str,
+        terminator: &Terminator,
+        bools: &HashSet<&str>,
+        unreachable: &HashSet<String>,
+    ) -> Option<(Terminator, String)> {
+        match terminator {
+            Terminator::Switch { value, cases, default } if !cases.is_empty() => {
+                let is_bool = bools.contains(value.as_str());
+                let covers_bool = is_bool && [0, 1].iter().all(|v| cases.iter().any(|(case, _)| case == v));
+                let marked = self.exhaustive.contains(&(function.to_string(), block.to_string()));
+                if !(covers_bool || marked || unreachable.contains(default)) {
+                    return None;
+                }
+
+                let mut cases = cases.clone();
+                let (_, last) = cases.pop()?;
+                let simplified = match cases.as_slice() {
+                    [] => Terminator::Br { target: last },
+                    [(0, if_false)] if is_bool => Terminator::CondBr {
+                        condition: value.clone(),
+                        true_target: last,
+                        false_target: if_false.clone(),
+                    },
+                    [(1, if_true)] if is_bool => Terminator::CondBr {
+                        condition: value.clone(),
+                        true_target: if_true.clone(),
+                        false_target: last,
+                    },
+                    _ => Terminator::Switch { value: value.clone(), cases, default: last },
+                };
+                Some((simplified, default.clone()))
+            }
+            Terminator::CondBr { true_target, false_target, .. } => {
+                match (unreachable.contains(true_target), unreachable.contains(false_target)) {
+                    (true, false) => Some((Terminator::Br { target: false_target.clone() }, true_target.clone())),
+                    (false, true) => Some((Terminator::Br { target: true_target.clone() }, false_target.clone())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn optimize_function(&self, function: &mut IRFunction) {
+        let dropped: Vec<String> = {
+            let bools = Self::bool_values(function);
+            let unreachable = Self::unreachable_blocks(function);
+            let rewrites: Vec<(usize, Terminator, String)> = function
+                .blocks
+                .iter()
+                .enumerate()
+                .filter_map(|(index, block)| {
+                    self.simplify(&function.name, &block.label, &block.terminator, &bools, &unreachable)
+                        .map(|(terminator, dropped)| (index, terminator, dropped))
+                })
+                .collect();
+
+            rewrites
+                .into_iter()
+                .map(|(index, terminator, dropped)| {
+                    function.blocks[index].terminator = terminator;
+                    dropped
+                })
+                .collect()
+        };
+
+        let referenced: HashSet<String> = function
+            .blocks
+            .iter()
+            .flat_map(|block| successors(&block.terminator))
+            .collect();
+        let entry = function.blocks.first().map(|block| block.label.clone());
+        let removed: HashSet<String> = dropped
+            .into_iter()
+            .filter(|label| !referenced.contains(label) && Some(label) != entry.as_ref())
+            .collect();
+
+        function.blocks.retain(|block| !removed.contains(&block.label));
+        for instruction in function.blocks.iter_mut().flat_map(|block| &mut block.instructions) {
+            if let Instruction::Phi { incoming, .. } = instruction {
+                incoming.retain(|(_, from)| !removed.contains(from));
+            }
+        }
+    }
+}
+
+fn successors(terminator: &Terminator) -> Vec<String> {
+    match terminator {
+        Terminator::Br { target } => vec![target.clone()],
+        Terminator::CondBr { true_target, false_target, .. } => vec![true_target.clone(), false_target.clone()],
+        Terminator::Switch { cases, default, .. } => {
+            cases.iter().map(|(_, target)| target.clone()).chain([default.clone()]).collect()
+        }
+        _ => vec![],
+    }
+}
+
+impl OptimizationPass for UnreachableDefaults {
+    fn optimize(&mut self, module: &Module) -> Result<Module> {
+        let mut optimized = module.clone();
+
+        for function in &mut optimized.functions {
+            self.optimize_function(function);
+        }
+
+        Ok(optimized)
+    }
+}
+
+impl Default for UnreachableDefaults {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{BasicBlock, ICmpCondition, Parameter};
+
+    fn block(label: &str, instructions: Vec<Instruction>, terminator: Terminator) -> BasicBlock {
+        BasicBlock { label: label.to_string(), instructions, terminator }
+    }
+
+    fn ret(value: &str) -> Terminator {
+        Terminator::Ret { value: Some(value.to_string()) }
+    }
+
+    /// `match x == 0 { false => 10, true => 11, _ => -1 }` lowered to a switch.
+    fn bool_match(default: Terminator) -> Module {
+        let mut module = Module::new("test");
+        module.functions.push(IRFunction {
+            name: "classify".to_string(),
+            params: vec![Parameter { name: "%x".to_string(), ty: IRType::I32 }],
+            return_type: IRType::I32,
+            blocks: vec![
+                block(
+                    "entry",
+                    vec![Instruction::ICmp {
+                        result: "%c".to_string(),
+                        condition: ICmpCondition::Eq,
+                        left: "%x".to_string(),
+                        right: "0".to_string(),
+                    }],
+                    Terminator::Switch {
+                        value: "%c".to_string(),
+                        cases: vec![(0, "no".to_string()), (1, "yes".to_string())],
+                        default: "match.default".to_string(),
+                    },
+                ),
+                block("no", vec![], ret("10")),
+                block("yes", vec![], ret("11")),
+                block("match.default", vec![], default),
+            ],
+        });
+        module
+    }
+
+    fn labels(module: &Module) -> Vec<&str> {
+        module.functions[0].blocks.iter().map(|block| block.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_exhaustive_bool_switch_loses_its_default() {
+        let optimized = UnreachableDefaults::new().optimize(&bool_match(ret("-1"))).unwrap();
+
+        assert_eq!(labels(&optimized), vec!["entry", "no", "yes"]);
+        assert_eq!(
+            optimized.functions[0].blocks[0].terminator,
+            Terminator::CondBr {
+                condition: "%c".to_string(),
+                true_target: "yes".to_string(),
+                false_target: "no".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_marked_switch_makes_its_last_case_the_default() {
+        let mut module = bool_match(ret("-1"));
+        let function = &mut module.functions[0];
+        function.blocks[0].instructions.clear();
+        function.blocks[0].terminator = Terminator::Switch {
+            value: "%x".to_string(),
+            cases: vec![(0, "no".to_string()), (1, "yes".to_string())],
+            default: "match.default".to_string(),
+        };
+
+        let unmarked = UnreachableDefaults::new().optimize(&module).unwrap();
+        assert_eq!(unmarked.functions[0].blocks[0].terminator, module.functions[0].blocks[0].terminator);
+
+        let marked = UnreachableDefaults::new()
+            .with_exhaustive_switch("classify", "entry")
+            .optimize(&module)
+            .unwrap();
+        assert_eq!(
+            marked.functions[0].blocks[0].terminator,
+            Terminator::Switch {
+                value: "%x".to_string(),
+                cases: vec![(0, "no".to_string())],
+                default: "yes".to_string(),
+            }
+        );
+        assert_eq!(labels(&marked), vec!["entry", "no", "yes"]);
+    }
+
+    #[test]
+    fn test_branch_into_unreachable_becomes_unconditional() {
+        let mut module = bool_match(Terminator::Unreachable);
+        module.functions[0].blocks[0].terminator = Terminator::CondBr {
+            condition: "%c".to_string(),
+            true_target: "yes".to_string(),
+            false_target: "match.default".to_string(),
+        };
+
+        let optimized = UnreachableDefaults::new().optimize(&module).unwrap();
+
+        assert_eq!(optimized.functions[0].blocks[0].terminator, Terminator::Br { target: "yes".to_string() });
+        assert_eq!(labels(&optimized), vec!["entry", "no", "yes"]);
+    }
+}
@@ -51,6 +51,11 @@ fn validate_block(block: &BasicBlock, defined_values: &mut HashSet<String>) -> R
                 bail!("Undefined condition '{}' in block '{}'", condition, block.label);
             }
         }
+        Terminator::Switch { value, .. } => {
+            if !value.starts_with('%') && !defined_values.contains(value) {
+                bail!("Undefined switch value '{}' in block '{}'", value, block.label);
+            }
+        }
         _ => {}
     }
     
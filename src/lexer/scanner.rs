@@ -22,7 +22,7 @@ impl Scanner {
         let mut tokens = Vec::new();
 
         while !self.is_at_end() {
-            self.skip_whitespace_and_comments();
+            self.skip_whitespace_and_comments()?;
             if self.is_at_end() {
                 break;
             }
@@ -41,16 +41,36 @@ impl Scanner {
         let ch = self.advance();
 
         let token_type = match ch {
-            '+' => TokenType::Plus,
+            '+' => {
+                if self.match_char('=') {
+                    TokenType::PlusEqual
+                } else {
+                    TokenType::Plus
+                }
+            }
             '-' => {
                 if self.match_char('>') {
                     TokenType::Arrow
+                } else if self.match_char('=') {
+                    TokenType::MinusEqual
                 } else {
                     TokenType::Minus
                 }
             }
-            '*' => TokenType::Star,
-            '/' => TokenType::Slash,
+            '*' => {
+                if self.match_char('=') {
+                    TokenType::StarEqual
+                } else {
+                    TokenType::Star
+                }
+            }
+            '/' => {
+                if self.match_char('=') {
+                    TokenType::SlashEqual
+                } else {
+                    TokenType::Slash
+                }
+            }
             '%' => TokenType::Percent,
             '=' => {
                 if self.match_char('=') {
@@ -110,9 +130,27 @@ impl Scanner {
             ';' => TokenType::Semicolon,
             ':' => TokenType::Colon,
             ',' => TokenType::Comma,
-            '.' => TokenType::Dot,
+            '.' => {
+                if self.match_char('.') {
+                    if self.match_char('=') {
+                        TokenType::DotDotEqual
+                    } else {
+                        TokenType::DotDot
+                    }
+                } else {
+                    TokenType::Dot
+                }
+            }
+            '?' => TokenType::Question,
             '"' => self.scan_string()?,
-            '\'' => self.scan_char()?,
+            '\'' => {
+                if self.starts_label() {
+                    self.scan_label()
+                } else {
+                    self.scan_char()?
+                }
+            }
+            'r' if !self.is_at_end() && self.peek() == '#' => self.scan_raw_identifier()?,
             _ if ch.is_ascii_digit() => self.scan_number()?,
             _ if ch.is_alphabetic() || ch == '_' => self.scan_identifier(),
             _ => {
@@ -134,19 +172,11 @@ impl Scanner {
 
         while !self.is_at_end() && self.peek() != '"' {
             if self.peek() == '\\' {
-                self.advance();
-                if self.is_at_end() {
+                if self.current + 1 >= self.source.len() {
+                    self.advance();
                     break;
                 }
-                let escaped = self.advance();
-                value.push(match escaped {
-                    'n' => '\n',
-                    't' => '\t',
-                    'r' => '\r',
-                    '\\' => '\\',
-                    '"' => '"',
-                    _ => escaped,
-                });
+                value.push(self.scan_escape()?);
             } else {
                 value.push(self.advance());
             }
@@ -164,6 +194,85 @@ impl Scanner {
         Ok(TokenType::StringLit(value))
     }
 
+    /// Decodes the escape sequence starting at the `\` under the cursor.
+    fn scan_escape(&mut self) -> Result<char> {
+        let line = self.line;
+        let column = self.column;
+        self.advance();
+
+        let escaped = self.advance();
+        let decoded = match escaped {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            'u' => return self.scan_unicode_escape(line, column),
+            _ => {
+                return Err(CompileError::LexError {
+                    message: format!("Unknown escape sequence '\\{}'", escaped),
+                    line,
+                    column,
+                    source_snippet: None,
+                    suggestion: Some(format!(
+                        "valid escapes are \\n \\t \\r \\0 \\\\ \\\" \\' and \\u{{...}}; write '\\\\{}' for a literal backslash",
+                        escaped
+                    )),
+                })
+            }
+        };
+        Ok(decoded)
+    }
+
+    /// Decodes the `{XXXX}` part of `\u{XXXX}`: one to six hex digits naming
+    /// a Unicode scalar value.
+    fn scan_unicode_escape(&mut self, line: usize, column: usize) -> Result<char> {
+        let error = |message: String| CompileError::LexError {
+            message,
+            line,
+            column,
+            source_snippet: None,
+            suggestion: Some("write a code point as \\u{1F525}, with one to six hex digits up to 10FFFF".to_string()),
+        };
+
+        if !self.match_char('{') {
+            return Err(error("Expected '{' after '\\u'".to_string()));
+        }
+        let mut digits = String::new();
+        while !self.is_at_end() && self.peek() != '}' && self.peek() != '"' {
+            digits.push(self.advance());
+        }
+        if !self.match_char('}') {
+            return Err(error("Unterminated unicode escape".to_string()));
+        }
+
+        if digits.is_empty() || digits.len() > 6 || !digits.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            return Err(error(format!("Invalid unicode escape '\\u{{{}}}'", digits)));
+        }
+        let code = u32::from_str_radix(&digits, 16).unwrap();
+        char::from_u32(code).ok_or_else(|| {
+            error(format!("Unicode escape '\\u{{{}}}' is not a valid code point", digits))
+        })
+    }
+
+    /// `'a'` is a character literal, while `'a` followed by anything other
+    /// than a closing quote starts a label.
+    fn starts_label(&self) -> bool {
+        !self.is_at_end()
+            && (self.peek().is_alphabetic() || self.peek() == '_')
+            && self.source.get(self.current + 1) != Some(&'\'')
+    }
+
+    fn scan_label(&mut self) -> TokenType {
+        let mut name = String::new();
+        while !self.is_at_end() && (self.peek().is_alphanumeric() || self.peek() == '_') {
+            name.push(self.advance());
+        }
+        TokenType::Label(name)
+    }
+
     fn scan_char(&mut self) -> Result<TokenType> {
         let start_line = self.line;
         let start_column = self.column;
@@ -177,23 +286,14 @@ impl Scanner {
         }
 
         let ch = if self.peek() == '\\' {
-            self.advance();
-            if self.is_at_end() {
+            if self.current + 1 >= self.source.len() {
                 return Err(CompileError::LexError {
                     message: "Unterminated character literal".to_string(),
                     line: start_line,
                     column: start_column,
                 });
             }
-            let escaped = self.advance();
-            match escaped {
-                'n' => '\n',
-                't' => '\t',
-                'r' => '\r',
-                '\\' => '\\',
-                '\'' => '\'',
-                _ => escaped,
-            }
+            self.scan_escape()?
         } else {
             self.advance()
         };
@@ -211,28 +311,127 @@ impl Scanner {
     }
 
     fn scan_number(&mut self) -> Result<TokenType> {
-        let mut value = String::new();
-        value.push(self.source[self.current - 1]);
-
-        while !self.is_at_end() && self.peek().is_ascii_digit() {
-            value.push(self.advance());
+        let start_line = self.line;
+        let start_column = self.column - 1;
+        let first = self.source[self.current - 1];
+
+        if first == '0' && !self.is_at_end() {
+            let radix = match self.peek() {
+                'x' => Some((16, "hexadecimal")),
+                'o' => Some((8, "octal")),
+                'b' => Some((2, "binary")),
+                _ => None,
+            };
+            if let Some((radix, name)) = radix {
+                let prefix = self.advance();
+                return self.scan_radix_digits(radix, name, prefix, start_line, start_column);
+            }
         }
 
+        let mut value = String::new();
+        value.push(first);
+        self.scan_decimal_digits(&mut value);
+
         if !self.is_at_end()
             && self.peek() == '.'
             && self.current + 1 < self.source.len()
             && self.source[self.current + 1].is_ascii_digit()
         {
             value.push(self.advance());
-
-            while !self.is_at_end() && self.peek().is_ascii_digit() {
-                value.push(self.advance());
-            }
+            self.scan_decimal_digits(&mut value);
 
             Ok(TokenType::FloatLit(value.parse().unwrap()))
         } else {
-            Ok(TokenType::IntLit(value.parse().unwrap()))
+            value.parse().map(TokenType::IntLit).map_err(|_| CompileError::LexError {
+                message: format!("Integer literal '{}' is too large", value),
+                line: start_line,
+                column: start_column,
+                source_snippet: None,
+                suggestion: None,
+            })
+        }
+    }
+
+    /// Appends decimal digits to `value`, dropping `_` separators.
+    fn scan_decimal_digits(&mut self, value: &mut String) {
+        while !self.is_at_end() && (self.peek().is_ascii_digit() || self.peek() == '_') {
+            let ch = self.advance();
+            if ch != '_' {
+                value.push(ch);
+            }
+        }
+    }
+
+    /// Scans the digits after a `0x`, `0o` or `0b` prefix. Letters and
+    /// digits outside the radix are reported rather than left to start the
+    /// next token, so `0xG` and `0b102` are errors.
+    fn scan_radix_digits(
+        &mut self,
+        radix: u32,
+        name: &str,
+        prefix: char,
+        start_line: usize,
+        start_column: usize,
+    ) -> Result<TokenType> {
+        let mut digits = String::new();
+        while !self.is_at_end() && (self.peek().is_ascii_alphanumeric() || self.peek() == '_') {
+            let ch = self.advance();
+            if ch == '_' {
+                continue;
+            }
+            if !ch.is_digit(radix) {
+                return Err(CompileError::LexError {
+                    message: format!("Invalid digit '{}' in {} literal", ch, name),
+                    line: start_line,
+                    column: start_column,
+                    source_snippet: None,
+                    suggestion: None,
+                });
+            }
+            digits.push(ch);
+        }
+
+        if digits.is_empty() {
+            return Err(CompileError::LexError {
+                message: format!("Expected {} digits after '0{}'", name, prefix),
+                line: start_line,
+                column: start_column,
+                source_snippet: None,
+                suggestion: None,
+            });
+        }
+
+        i64::from_str_radix(&digits, radix).map(TokenType::IntLit).map_err(|_| CompileError::LexError {
+            message: format!("Integer literal '0{}{}' is too large", prefix, digits),
+            line: start_line,
+            column: start_column,
+            source_snippet: None,
+            suggestion: None,
+        })
+    }
+
+    /// Scans `r#name`, producing a plain identifier even when `name` is a keyword.
+    fn scan_raw_identifier(&mut self) -> Result<TokenType> {
+        let start_line = self.line;
+        let start_column = self.column - 1;
+        self.advance();
+
+        if self.is_at_end() || !(self.peek().is_alphabetic() || self.peek() == '_') {
+            return Err(CompileError::LexError {
+                message: "Expected identifier after 'r#'".to_string(),
+                line: start_line,
+                column: start_column,
+                source_snippet: None,
+                suggestion: None,
+            });
         }
+
+        let mut value = String::new();
+        while !self.is_at_end() && (self.peek().is_alphanumeric() || self.peek() == '_') {
+            value.push(self.advance());
+        }
+
+        Ok(TokenType::Ident(value))
     }
 
     fn scan_identifier(&mut self) -> TokenType {
@@ -257,6 +456,7 @@ impl Scanner {
             "continue" => TokenType::Continue,
             "struct" => TokenType::Struct,
             "impl" => TokenType::Impl,
+            "trait" => TokenType::Trait,
             "match" => TokenType::Match,
             "enum" => TokenType::Enum,
             "pub" => TokenType::Pub,
@@ -266,6 +466,10 @@ impl Scanner {
             "false" => TokenType::False,
             "i32" => TokenType::I32,
             "i64" => TokenType::I64,
+            "u8" => TokenType::U8,
+            "u16" => TokenType::U16,
+            "u32" => TokenType::U32,
+            "u64" => TokenType::U64,
             "f32" => TokenType::F32,
             "f64" => TokenType::F64,
             "bool" => TokenType::Bool,
@@ -282,7 +486,7 @@ impl Scanner {
         }
     }
 
-    fn skip_whitespace_and_comments(&mut self) {
+    fn skip_whitespace_and_comments(&mut self) -> Result<()> {
         loop {
             if self.is_at_end() {
                 break;
@@ -292,16 +496,55 @@ impl Scanner {
                 ' ' | '\r' | '\t' | '\n' => {
                     self.advance();
                 }
-                '/' if self.current + 1 < self.source.len()
-                    && self.source[self.current + 1] == '/' =>
-                {
+                '/' if self.peek_next() == Some('/') => {
                     while !self.is_at_end() && self.peek() != '\n' {
                         self.advance();
                     }
                 }
+                '/' if self.peek_next() == Some('*') => self.skip_block_comment()?,
                 _ => break,
             }
         }
+        Ok(())
+    }
+
+    /// Skips a `/* ... */` comment. Block comments nest as in Rust, so
+    /// commenting out code that already contains one keeps working.
+    fn skip_block_comment(&mut self) -> Result<()> {
+        let start_line = self.line;
+        let start_column = self.column;
+        self.advance();
+        self.advance();
+
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                return Err(CompileError::LexError {
+                    message: "Unterminated block comment".to_string(),
+                    line: start_line,
+                    column: start_column,
+                    source_snippet: None,
+                    suggestion: None,
+                });
+            }
+
+            match (self.peek(), self.peek_next()) {
+                ('/', Some('*')) => {
+                    self.advance();
+                    self.advance();
+                    depth += 1;
+                }
+                ('*', Some('/')) => {
+                    self.advance();
+                    self.advance();
+                    depth -= 1;
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+        Ok(())
     }
 
     fn advance(&mut self) -> char {
@@ -320,6 +563,10 @@ impl Scanner {
         self.source[self.current]
     }
 
+    fn peek_next(&self) -> Option<char> {
+        self.source.get(self.current + 1).copied()
+    }
+
     fn match_char(&mut self, expected: char) -> bool {
         if self.is_at_end() || self.source[self.current] != expected {
             false
@@ -6,15 +6,18 @@ pub enum TokenType {
     Trait, Async, Await, Unsafe, Where, Type, As, Dyn, Move,
     Crate, Super, Extern, Box, Underscore,
     
-    I32, I64, F32, F64, Bool, Char, Str, String,
+    I32, I64, U8, U16, U32, U64, F32, F64, Bool, Char, Str, String,
     
     Ident(String),
     IntLit(i64),
     FloatLit(f64),
     StringLit(String),
     CharLit(char),
+    /// A loop label such as `'outer`, stored without the quote.
+    Label(String),
     
     Plus, Minus, Star, Slash, Percent,
+    PlusEqual, MinusEqual, StarEqual, SlashEqual,
     Equal, EqualEqual, BangEqual,
     Less, LessEqual, Greater, GreaterEqual,
     And, Or, Bang,
@@ -24,8 +27,8 @@ pub enum TokenType {
     LeftParen, RightParen,
     LeftBrace, RightBrace,
     LeftBracket, RightBracket,
-    Semicolon, Colon, Comma, Dot,
-    Arrow, FatArrow,
+    Semicolon, Colon, Comma, Dot, DotDot, DotDotEqual,
+    Arrow, FatArrow, Question,
     
     Eof,
 }
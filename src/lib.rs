@@ -46,7 +46,7 @@ pub mod documentation;
 
 pub use error::{CompileError, Result};
 pub use lexer::{lex, Token, TokenType};
-pub use parser::{parse, Program};
+pub use parser::{parse, parse_all, Program};
 pub use semantic::SemanticAnalyzer;
 pub use ir::{Module as IRModule, generate as generate_ir};
 
@@ -98,7 +98,7 @@ pub fn compile_to_executable(source: &str, output: PathBuf, optimization_level:
     let tokens = lex(source)?;
     let program = parse(tokens)?;
     
-    let mut analyzer = SemanticAnalyzer::new();
+    let mut analyzer = SemanticAnalyzer::new().with_executable(true);
     analyzer.analyze(&program)
         .map_err(|e| CompileError::SemanticError { 
             message: format!("Semantic analysis failed: {}", e),
@@ -116,7 +116,8 @@ pub fn compile_to_executable(source: &str, output: PathBuf, optimization_level:
             suggestion: None,
         })?;
     
-    let mut optimizer = ir::optimization::Optimizer::new();
+    let options = optimizer::OptimizationOptions::for_level(optimization_level);
+    let mut optimizer = ir::optimization::Optimizer::from_options(&options);
     let optimized = optimizer.optimize(&ir_module, optimization_level)
         .map_err(|e| CompileError::CodegenError {
             message: format!("Optimization failed: {}", e),
@@ -1,5 +1,6 @@
-use std::collections::HashMap;
-use crate::parser::{Program, Function, Statement, Expression};
+use std::collections::{HashMap, HashSet};
+use crate::incremental::ItemDependencyGraph;
+use crate::parser::{Program, Item, Function, Statement, Expression, Visibility, BinaryOp, UnaryOp, Literal, Type};
 
 pub struct Linter {
     rules: HashMap<String, Box<dyn LintRule>>,
@@ -44,6 +45,9 @@ impl Linter {
     }
     
     fn register_default_rules(&mut self) {
+        self.add_rule(Box::new(SelfAssignmentRule));
+        self.add_rule(Box::new(DeadCodeRule));
+        self.add_rule(Box::new(FloatEqualityRule));
     }
     
     pub fn add_rule(&mut self, rule: Box<dyn LintRule>) {
@@ -100,15 +104,64 @@ impl LintRule for UnusedVariableRule {
     }
 }
 
+/// Warns about private functions that cannot be reached from `main`,
+/// public items, or functions marked `#[used]` / `#[no_dead_code]`.
 pub struct DeadCodeRule;
 
+impl DeadCodeRule {
+    fn is_root(function: &Function) -> bool {
+        function.name == "main"
+            || function.visibility != Visibility::Private
+            || function
+                .attributes
+                .iter()
+                .any(|attr| attr.name == "used" || attr.name == "no_dead_code")
+    }
+}
+
 impl LintRule for DeadCodeRule {
     fn name(&self) -> &str {
         "dead_code"
     }
     
-    fn check_program(&self, _program: &Program) -> Vec<LintDiagnostic> {
-        Vec::new()
+    fn check_program(&self, program: &Program) -> Vec<LintDiagnostic> {
+        let graph = ItemDependencyGraph::build(program);
+        let functions: Vec<&Function> = program
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Function(function) => Some(function),
+                _ => None,
+            })
+            .collect();
+        
+        let mut reachable = HashSet::new();
+        let mut worklist: Vec<String> = functions
+            .iter()
+            .filter(|f| Self::is_root(f))
+            .map(|f| f.name.clone())
+            .collect();
+        
+        while let Some(name) = worklist.pop() {
+            if !reachable.insert(name.clone()) {
+                continue;
+            }
+            if let Some(dependencies) = graph.dependencies_of(&name) {
+                worklist.extend(dependencies.iter().cloned());
+            }
+        }
+        
+        functions
+            .iter()
+            .filter(|f| !reachable.contains(&f.name))
+            .map(|f| LintDiagnostic {
+                message: format!("function `{}` is never used", f.name),
+                severity: Severity::Warning,
+                line: 0,
+                column: 0,
+                rule_name: "dead_code".to_string(),
+            })
+            .collect()
     }
     
     fn check_function(&self, _function: &Function) -> Vec<LintDiagnostic> {
@@ -119,3 +172,270 @@ impl LintRule for DeadCodeRule {
         Vec::new()
     }
 }
+
+pub struct SelfAssignmentRule;
+
+impl SelfAssignmentRule {
+    fn is_place_expression(expr: &Expression) -> bool {
+        match expr {
+            Expression::Ident(_) | Expression::Identifier(_) => true,
+            Expression::FieldAccess { object, .. } => Self::is_place_expression(object),
+            Expression::Index { object, index } => {
+                Self::is_place_expression(object) && Self::is_place_expression(index)
+            }
+            _ => false,
+        }
+    }
+
+    fn describe(expr: &Expression) -> String {
+        match expr {
+            Expression::Ident(name) | Expression::Identifier(name) => name.clone(),
+            Expression::FieldAccess { object, field } => format!("{}.{}", Self::describe(object), field),
+            Expression::Index { object, index } => {
+                format!("{}[{}]", Self::describe(object), Self::describe(index))
+            }
+            _ => "<expression>".to_string(),
+        }
+    }
+
+    fn diagnostic(message: String) -> LintDiagnostic {
+        LintDiagnostic {
+            message,
+            severity: Severity::Warning,
+            line: 0,
+            column: 0,
+            rule_name: "self_assignment".to_string(),
+        }
+    }
+
+    fn check_body(&self, body: &[Statement]) -> Vec<LintDiagnostic> {
+        body.iter().flat_map(|stmt| self.check_statement(stmt)).collect()
+    }
+}
+
+impl LintRule for SelfAssignmentRule {
+    fn name(&self) -> &str {
+        "self_assignment"
+    }
+
+    fn check_program(&self, program: &Program) -> Vec<LintDiagnostic> {
+        program
+            .items
+            .iter()
+            .flat_map(|item| match item {
+                Item::Function(function) => self.check_function(function),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    fn check_function(&self, function: &Function) -> Vec<LintDiagnostic> {
+        self.check_body(&function.body)
+    }
+
+    fn check_statement(&self, stmt: &Statement) -> Vec<LintDiagnostic> {
+        match stmt {
+            Statement::Let { name, value: Some(Expression::Ident(source)), .. } if source == name => {
+                vec![Self::diagnostic(format!(
+                    "`let {} = {};` rebinds the variable to itself and has no effect",
+                    name, source
+                ))]
+            }
+            Statement::Expression(Expression::Assignment { target, value })
+                if target == value && Self::is_place_expression(target) =>
+            {
+                let place = Self::describe(target);
+                vec![Self::diagnostic(format!(
+                    "`{} = {}` assigns a value to itself and has no effect",
+                    place, place
+                ))]
+            }
+            Statement::If { then_body, else_body, .. } => {
+                let mut diags = self.check_body(then_body);
+                if let Some(else_body) = else_body {
+                    diags.extend(self.check_body(else_body));
+                }
+                diags
+            }
+            Statement::While { body, .. }
+            | Statement::For { body, .. }
+            | Statement::Loop { body }
+            | Statement::Block(body) => self.check_body(body),
+            Statement::Labeled { body, .. } => self.check_statement(body),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Warns about `==` and `!=` between floating-point values, which rounding
+/// makes unreliable. Functions marked `#[exact_float_eq]` compare exactly on
+/// purpose and are skipped.
+pub struct FloatEqualityRule;
+
+impl FloatEqualityRule {
+    fn is_float_type(ty: &Type) -> bool {
+        matches!(ty, Type::F32 | Type::F64)
+    }
+
+    fn is_float(expr: &Expression, floats: &HashSet<String>) -> bool {
+        match expr {
+            Expression::FloatLit(_) | Expression::Literal(Literal::Float(_)) => true,
+            Expression::Ident(name) | Expression::Identifier(name) => floats.contains(name),
+            Expression::Binary { op, left, right } => {
+                matches!(op, BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod)
+                    && (Self::is_float(left, floats) || Self::is_float(right, floats))
+            }
+            Expression::Unary { op: UnaryOp::Neg, expr } => Self::is_float(expr, floats),
+            _ => false,
+        }
+    }
+
+    fn describe(expr: &Expression) -> String {
+        match expr {
+            Expression::FloatLit(value) | Expression::Literal(Literal::Float(value)) => format!("{:?}", value),
+            Expression::IntLit(value) => value.to_string(),
+            Expression::Ident(name) | Expression::Identifier(name) => name.clone(),
+            _ => "<expression>".to_string(),
+        }
+    }
+
+    fn check_body(body: &[Statement], floats: &mut HashSet<String>, diags: &mut Vec<LintDiagnostic>) {
+        for stmt in body {
+            Self::check_stmt(stmt, floats, diags);
+        }
+    }
+
+    fn check_stmt(stmt: &Statement, floats: &mut HashSet<String>, diags: &mut Vec<LintDiagnostic>) {
+        match stmt {
+            Statement::Let { name, ty, value, .. } => {
+                if let Some(value) = value {
+                    Self::check_expr(value, floats, diags);
+                }
+                let is_float = match (ty, value) {
+                    (Some(ty), _) => Self::is_float_type(ty),
+                    (None, Some(value)) => Self::is_float(value, floats),
+                    (None, None) => false,
+                };
+                if is_float {
+                    floats.insert(name.clone());
+                } else {
+                    floats.remove(name);
+                }
+            }
+            Statement::Return(Some(expr)) | Statement::Expression(expr) => Self::check_expr(expr, floats, diags),
+            Statement::Break { value: Some(expr), .. } => Self::check_expr(expr, floats, diags),
+            Statement::While { condition, body } => {
+                Self::check_expr(condition, floats, diags);
+                Self::check_body(body, &mut floats.clone(), diags);
+            }
+            Statement::If { condition, then_body, else_body } => {
+                Self::check_expr(condition, floats, diags);
+                Self::check_body(then_body, &mut floats.clone(), diags);
+                if let Some(else_body) = else_body {
+                    Self::check_body(else_body, &mut floats.clone(), diags);
+                }
+            }
+            Statement::For { iterable, body, .. } => {
+                Self::check_expr(iterable, floats, diags);
+                Self::check_body(body, &mut floats.clone(), diags);
+            }
+            Statement::Loop { body } | Statement::Block(body) => Self::check_body(body, &mut floats.clone(), diags),
+            Statement::Labeled { body, .. } => Self::check_stmt(body, floats, diags),
+            _ => {}
+        }
+    }
+
+    fn check_expr(expr: &Expression, floats: &HashSet<String>, diags: &mut Vec<LintDiagnostic>) {
+        match expr {
+            Expression::Binary { op, left, right } => {
+                if matches!(op, BinaryOp::Eq | BinaryOp::Ne)
+                    && (Self::is_float(left, floats) || Self::is_float(right, floats))
+                {
+                    let operator = if *op == BinaryOp::Eq { "==" } else { "!=" };
+                    let (left, right) = (Self::describe(left), Self::describe(right));
+                    diags.push(LintDiagnostic {
+                        message: format!(
+                            "`{} {} {}` compares floating-point values exactly; compare `({} - {}).abs()` against an epsilon instead",
+                            left, operator, right, left, right
+                        ),
+                        severity: Severity::Warning,
+                        line: 0,
+                        column: 0,
+                        rule_name: "float_equality".to_string(),
+                    });
+                }
+                Self::check_expr(left, floats, diags);
+                Self::check_expr(right, floats, diags);
+            }
+            Expression::Unary { expr, .. } => Self::check_expr(expr, floats, diags),
+            Expression::Call { func, args } | Expression::CallAlt { callee: func, args } => {
+                Self::check_expr(func, floats, diags);
+                for arg in args {
+                    Self::check_expr(arg, floats, diags);
+                }
+            }
+            Expression::MethodCall { object, args, .. } => {
+                Self::check_expr(object, floats, diags);
+                for arg in args {
+                    Self::check_expr(arg, floats, diags);
+                }
+            }
+            Expression::Assignment { target, value } => {
+                Self::check_expr(target, floats, diags);
+                Self::check_expr(value, floats, diags);
+            }
+            Expression::If { condition, then_branch, else_branch } => {
+                Self::check_expr(condition, floats, diags);
+                Self::check_expr(then_branch, floats, diags);
+                if let Some(else_branch) = else_branch {
+                    Self::check_expr(else_branch, floats, diags);
+                }
+            }
+            Expression::Block(body) => Self::check_body(body, &mut floats.clone(), diags),
+            Expression::TupleLiteral(elements) | Expression::ArrayLiteral(elements) => {
+                for element in elements {
+                    Self::check_expr(element, floats, diags);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl LintRule for FloatEqualityRule {
+    fn name(&self) -> &str {
+        "float_equality"
+    }
+
+    fn check_program(&self, program: &Program) -> Vec<LintDiagnostic> {
+        program
+            .items
+            .iter()
+            .flat_map(|item| match item {
+                Item::Function(function) => self.check_function(function),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    fn check_function(&self, function: &Function) -> Vec<LintDiagnostic> {
+        if function.attributes.iter().any(|attr| attr.name == "exact_float_eq") {
+            return Vec::new();
+        }
+        let mut floats: HashSet<String> = function
+            .params
+            .iter()
+            .filter(|param| Self::is_float_type(&param.ty))
+            .map(|param| param.name.clone())
+            .collect();
+        let mut diags = Vec::new();
+        Self::check_body(&function.body, &mut floats, &mut diags);
+        diags
+    }
+
+    fn check_statement(&self, stmt: &Statement) -> Vec<LintDiagnostic> {
+        let mut diags = Vec::new();
+        Self::check_stmt(stmt, &mut HashSet::new(), &mut diags);
+        diags
+    }
+}
@@ -1,10 +1,16 @@
 use blaze_compiler::cli::Cli;
 use clap::Parser;
+use tracing_subscriber::EnvFilter;
 
 fn main() {
-    tracing_subscriber::fmt::init();
+    // `BLAZE_LOG` takes precedence over `RUST_LOG`; both use the `EnvFilter` syntax.
+    let filter = EnvFilter::try_from_env("BLAZE_LOG")
+        .or_else(|_| EnvFilter::try_from_default_env())
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt().with_env_filter(filter).init();
     
     let cli = Cli::parse();
+    cli.color.apply();
     
     match cli.command {
         blaze_compiler::cli::Commands::Check { input, verbose } => {
@@ -13,14 +19,27 @@ fn main() {
                 println!("Verbose mode enabled");
             }
         }
-        blaze_compiler::cli::Commands::Build { input, output, optimization, verbose, .. } => {
+        blaze_compiler::cli::Commands::Build { input, output, optimization, verbose, pgo_use, relocation_model, .. } => {
             println!("Building: {:?}", input);
             if verbose {
                 println!("Optimization level: {}", optimization);
+                println!("Relocation model: {:?}", relocation_model);
                 if let Some(out) = output {
                     println!("Output: {:?}", out);
                 }
             }
+            let optimizer = match blaze_compiler::optimizer::Optimizer::for_build(optimization, pgo_use.as_deref()) {
+                Ok(optimizer) => optimizer,
+                Err(e) => {
+                    eprintln!("error: {:#}", e);
+                    std::process::exit(1);
+                }
+            };
+            if verbose {
+                if let (Some(path), Some(profile)) = (&pgo_use, optimizer.profile()) {
+                    println!("Profile data: {} functions from {:?}", profile.call_counts.len(), path);
+                }
+            }
         }
         blaze_compiler::cli::Commands::Run { input, args, .. } => {
             println!("Running: {:?}", input);
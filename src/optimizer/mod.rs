@@ -1,20 +1,37 @@
 use crate::ir::*;
+use crate::profiler::ProfileData;
 use anyhow::Result;
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Pure builtins that constant folding may evaluate, with their arity.
+const FOLDABLE_BUILTINS: &[(&str, usize)] = &[("min", 2), ("max", 2), ("abs", 1), ("pow", 2)];
 
 pub struct Optimizer {
     options: OptimizationOptions,
+    profile: Option<ProfileData>,
 }
 
 #[derive(Debug, Clone)]
 pub struct OptimizationOptions {
     pub level: u8,
     pub inline_threshold: usize,
+    pub inline_growth_budget: usize,
+    pub inline_max_call_sites: usize,
+    /// How many levels of calls may be inlined into one another along a
+    /// single call chain.
+    pub inline_max_depth: usize,
+    pub hot_call_threshold: u64,
+    pub hot_inline_threshold: usize,
+    /// Fully unrolls loops with a small constant trip count.
     pub unroll_loops: bool,
     pub dead_code_elimination: bool,
     pub constant_folding: bool,
     pub constant_propagation: bool,
+    pub copy_propagation: bool,
     pub common_subexpression_elimination: bool,
+    /// Permits value-changing float rewrites such as FMA contraction.
+    pub fast_math: bool,
 }
 
 impl Default for OptimizationOptions {
@@ -22,16 +39,33 @@ impl Default for OptimizationOptions {
         Self {
             level: 0,
             inline_threshold: 50,
+            inline_growth_budget: 200,
+            inline_max_call_sites: 8,
+            inline_max_depth: 3,
+            hot_call_threshold: 1000,
+            hot_inline_threshold: 200,
             unroll_loops: false,
             dead_code_elimination: true,
             constant_folding: true,
             constant_propagation: true,
+            copy_propagation: true,
             common_subexpression_elimination: false,
+            fast_math: false,
         }
     }
 }
 
 impl OptimizationOptions {
+    /// The preset for `-O<level>`; levels above 3 get the level 3 preset.
+    pub fn for_level(level: u8) -> Self {
+        match level {
+            0 => Self::level_0(),
+            1 => Self::level_1(),
+            2 => Self::level_2(),
+            _ => Self::level_3(),
+        }
+    }
+
     pub fn level_0() -> Self {
         Self::default()
     }
@@ -49,6 +83,7 @@ impl OptimizationOptions {
         Self {
             level: 2,
             inline_threshold: 75,
+            inline_growth_budget: 400,
             constant_folding: true,
             constant_propagation: true,
             dead_code_elimination: true,
@@ -61,28 +96,48 @@ impl OptimizationOptions {
         Self {
             level: 3,
             inline_threshold: 100,
+            inline_growth_budget: 800,
+            inline_max_call_sites: 16,
+            inline_max_depth: 4,
+            hot_call_threshold: 1000,
+            hot_inline_threshold: 400,
             unroll_loops: true,
             constant_folding: true,
             constant_propagation: true,
+            copy_propagation: true,
             dead_code_elimination: true,
             common_subexpression_elimination: true,
+            fast_math: false,
         }
     }
 }
 
 impl Optimizer {
     pub fn new(options: OptimizationOptions) -> Self {
-        Self { options }
+        Self { options, profile: None }
+    }
+
+    /// Uses runtime call counts to inline hot callees beyond `inline_threshold`.
+    pub fn with_profile(mut self, profile: ProfileData) -> Self {
+        self.profile = Some(profile);
+        self
     }
 
     pub fn with_level(level: u8) -> Self {
-        let options = match level {
-            0 => OptimizationOptions::level_0(),
-            1 => OptimizationOptions::level_1(),
-            2 => OptimizationOptions::level_2(),
-            _ => OptimizationOptions::level_3(),
-        };
-        Self::new(options)
+        Self::new(OptimizationOptions::for_level(level))
+    }
+
+    /// Builds the optimizer for `blaze build`, loading `--pgo-use` profile data when given.
+    pub fn for_build(level: u8, pgo_use: Option<&Path>) -> Result<Self> {
+        let optimizer = Self::with_level(level);
+        match pgo_use {
+            Some(path) => Ok(optimizer.with_profile(ProfileData::load(path)?)),
+            None => Ok(optimizer),
+        }
+    }
+
+    pub fn profile(&self) -> Option<&ProfileData> {
+        self.profile.as_ref()
     }
 
     pub fn optimize(&mut self, mut module: Module) -> Result<Module> {
@@ -101,6 +156,9 @@ impl Optimizer {
             2 => {
                 module = self.constant_folding(module)?;
                 module = self.constant_propagation(module)?;
+                if self.options.copy_propagation {
+                    module = self.copy_propagation(module)?;
+                }
                 module = self.dead_code_elimination(module)?;
                 module = self.inline_functions(module)?;
                 if self.options.common_subexpression_elimination {
@@ -126,6 +184,7 @@ impl Optimizer {
 
             module = self.constant_folding(module)?;
             module = self.constant_propagation(module)?;
+            module = self.copy_propagation(module)?;
             module = self.dead_code_elimination(module)?;
             module = self.inline_functions(module)?;
             module = self.common_subexpression_elimination(module)?;
@@ -173,11 +232,53 @@ impl Optimizer {
                     });
                 }
             }
+            Instruction::Call { function, args, result: Some(result) } => {
+                let constants = args
+                    .iter()
+                    .map(|arg| match arg {
+                        Value::Const(c) => Some(c.clone()),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<Constant>>>()?;
+                let folded_value = self.evaluate_builtin(function, &constants)?;
+                return Some(Instruction::Store {
+                    dest: result.clone(),
+                    value: Value::Const(folded_value),
+                });
+            }
             _ => {}
         }
         None
     }
 
+    /// Evaluates a call to one of `FOLDABLE_BUILTINS`. Returns `None` for
+    /// other functions and for results that would overflow at runtime.
+    fn evaluate_builtin(&self, function: &str, args: &[Constant]) -> Option<Constant> {
+        if !FOLDABLE_BUILTINS.contains(&(function, args.len())) {
+            return None;
+        }
+
+        match (function, args) {
+            ("min", [Constant::Int(a), Constant::Int(b)]) => Some(Constant::Int(*a.min(b))),
+            ("max", [Constant::Int(a), Constant::Int(b)]) => Some(Constant::Int(*a.max(b))),
+            ("min", [Constant::Float(a), Constant::Float(b)]) => Some(Constant::Float(a.min(*b))),
+            ("max", [Constant::Float(a), Constant::Float(b)]) => Some(Constant::Float(a.max(*b))),
+            ("abs", [Constant::Int(a)]) => Some(Constant::Int(a.checked_abs()?)),
+            ("abs", [Constant::Float(a)]) => Some(Constant::Float(a.abs())),
+            ("pow", [Constant::Int(base), Constant::Int(exp)]) => {
+                let exp = u32::try_from(*exp).ok()?;
+                Some(Constant::Int(base.checked_pow(exp)?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Folds `left op right`, or returns `None` to leave it for runtime.
+    ///
+    /// Integer operations that would overflow are not folded. Neither are
+    /// float operations with a NaN or infinite operand or result, so that
+    /// `0.0 / 0.0` and comparisons against NaN keep their IEEE behavior on
+    /// the target instead of being baked in at compile time.
     fn evaluate_binary_op(&self, op: BinaryOp, left: &Constant, right: &Constant) -> Option<Constant> {
         match (left, right) {
             (Constant::Int(l), Constant::Int(r)) => {
@@ -193,11 +294,21 @@ impl Optimizer {
                     BinaryOp::Le => return Some(Constant::Bool(l <= r)),
                     BinaryOp::Gt => return Some(Constant::Bool(l > r)),
                     BinaryOp::Ge => return Some(Constant::Bool(l >= r)),
+                    BinaryOp::BitwiseAnd => l & r,
+                    BinaryOp::BitwiseOr => l | r,
+                    BinaryOp::BitwiseXor => l ^ r,
+                    // Shifts by a negative amount or by the full width or more
+                    // are left for the runtime to handle.
+                    BinaryOp::LeftShift => l.checked_shl(u32::try_from(*r).ok()?)?,
+                    BinaryOp::RightShift => l.checked_shr(u32::try_from(*r).ok()?)?,
                     _ => return None,
                 };
                 Some(Constant::Int(result))
             }
             (Constant::Float(l), Constant::Float(r)) => {
+                if !l.is_finite() || !r.is_finite() {
+                    return None;
+                }
                 let result = match op {
                     BinaryOp::Add => l + r,
                     BinaryOp::Sub => l - r,
@@ -211,6 +322,9 @@ impl Optimizer {
                     BinaryOp::Ge => return Some(Constant::Bool(l >= r)),
                     _ => return None,
                 };
+                if !result.is_finite() {
+                    return None;
+                }
                 Some(Constant::Float(result))
             }
             (Constant::Bool(l), Constant::Bool(r)) => {
@@ -229,7 +343,8 @@ impl Optimizer {
 
     fn evaluate_unary_op(&self, op: UnaryOp, operand: &Constant) -> Option<Constant> {
         match (op, operand) {
-            (UnaryOp::Neg, Constant::Int(val)) => Some(Constant::Int(-val)),
+            (UnaryOp::Neg, Constant::Int(val)) => Some(Constant::Int(val.checked_neg()?)),
+            (UnaryOp::Not, Constant::Int(val)) => Some(Constant::Int(!val)),
             (UnaryOp::Neg, Constant::Float(val)) => Some(Constant::Float(-val)),
             (UnaryOp::Not, Constant::Bool(val)) => Some(Constant::Bool(!val)),
             _ => None,
@@ -269,6 +384,57 @@ impl Optimizer {
         Ok(module)
     }
 
+    /// Replaces uses of `dest` after `Store { dest, value: Variable(src) }`
+    /// with `src`, until either is redefined. Like CSE this stays within a
+    /// block, since a copy made on one branch says nothing about its
+    /// siblings or the join. The copy itself is left for dead code
+    /// elimination once nothing reads `dest`.
+    fn copy_propagation(&self, mut module: Module) -> Result<Module> {
+        for function in &mut module.functions {
+            for block in &mut function.blocks {
+                let mut copies: HashMap<String, String> = HashMap::new();
+
+                for instr in &mut block.instructions {
+                    for value in self.used_values_mut(instr) {
+                        if let Value::Variable(var) = value {
+                            if let Some(src) = copies.get(var) {
+                                *var = src.clone();
+                            }
+                        }
+                    }
+
+                    let defined = match instr {
+                        Instruction::Store { dest, .. } => Some(dest.clone()),
+                        Instruction::Binary { result, .. } | Instruction::Unary { result, .. } => Some(result.clone()),
+                        Instruction::Call { result, .. } => result.clone(),
+                        _ => None,
+                    };
+                    if let Some(defined) = defined {
+                        copies.retain(|dest, src| *dest != defined && *src != defined);
+                    }
+
+                    if let Instruction::Store { dest, value: Value::Variable(src) } = instr {
+                        if dest != src {
+                            copies.insert(dest.clone(), src.clone());
+                        }
+                    }
+                }
+            }
+        }
+        Ok(module)
+    }
+
+    fn used_values_mut<'a>(&self, instr: &'a mut Instruction) -> Vec<&'a mut Value> {
+        match instr {
+            Instruction::Binary { left, right, .. } => vec![left, right],
+            Instruction::Unary { operand, .. } => vec![operand],
+            Instruction::Call { args, .. } => args.iter_mut().collect(),
+            Instruction::Return { value } => value.iter_mut().collect(),
+            Instruction::Store { value, .. } => vec![value],
+            _ => Vec::new(),
+        }
+    }
+
     fn dead_code_elimination(&self, mut module: Module) -> Result<Module> {
         for function in &mut module.functions {
             let mut used_vars: HashSet<String> = HashSet::new();
@@ -317,6 +483,9 @@ impl Optimizer {
                     used.insert(var.clone());
                 }
             }
+            Instruction::Store { value: Value::Variable(var), .. } => {
+                used.insert(var.clone());
+            }
             _ => {}
         }
     }
@@ -334,40 +503,118 @@ impl Optimizer {
         block.predecessors.is_empty() && !block.is_entry
     }
 
+    /// Inlines callees whose estimated code growth (`(size - 1) * call sites`)
+    /// fits in the remaining growth budget. Recursive functions and callees
+    /// with more than `inline_max_call_sites` callers are never inlined.
+    /// With profile data, hot callees may be as large as `hot_inline_threshold`.
+    /// A callee that already holds `inline_max_depth` levels of inlined calls
+    /// is not inlined further.
     fn inline_functions(&self, mut module: Module) -> Result<Module> {
+        let mut budget = self.options.inline_growth_budget;
         let mut inlined_any = true;
+        // Levels of inlined calls nested in each function's body.
+        let mut depths: HashMap<String, usize> = HashMap::new();
 
         while inlined_any {
             inlined_any = false;
+            let recursive = self.recursive_functions(&module);
 
             for i in 0..module.functions.len() {
-                let function = &module.functions[i];
+                let function = module.functions[i].clone();
+
+                if recursive.contains(&function.name) {
+                    continue;
+                }
+
+                let depth = depths.get(&function.name).copied().unwrap_or(0) + 1;
+                if depth > self.options.inline_max_depth {
+                    continue;
+                }
 
-                let size = self.estimate_function_size(function);
-                if size > self.options.inline_threshold {
+                let size = self.estimate_function_size(&function);
+                if size > self.size_limit(&function.name) {
                     continue;
                 }
 
-                let call_sites = self.find_call_sites(&module, &function.name);
+                let mut call_sites = self.find_call_sites(&module, &function.name);
+                if call_sites.is_empty() || call_sites.len() > self.options.inline_max_call_sites {
+                    continue;
+                }
 
-                if !call_sites.is_empty() {
-                    for (caller_idx, block_idx, instr_idx) in call_sites {
-                        self.inline_call_site(
-                            &mut module,
-                            caller_idx,
-                            block_idx,
-                            instr_idx,
-                            &function.clone(),
-                        )?;
-                        inlined_any = true;
-                    }
+                let growth = size.saturating_sub(1) * call_sites.len();
+                if growth > budget {
+                    continue;
+                }
+                budget -= growth;
+
+                // Splice from the back so earlier indices in the same block stay valid.
+                call_sites.sort_unstable_by(|a, b| b.cmp(a));
+                for (caller_idx, block_idx, instr_idx) in call_sites {
+                    self.inline_call_site(&mut module, caller_idx, block_idx, instr_idx, &function)?;
+                    let caller_depth = depths.entry(module.functions[caller_idx].name.clone()).or_insert(0);
+                    *caller_depth = (*caller_depth).max(depth);
                 }
+                inlined_any = true;
             }
         }
 
         Ok(module)
     }
 
+    fn size_limit(&self, function: &str) -> usize {
+        let is_hot = self.profile.as_ref()
+            .map_or(false, |p| p.call_count(function) >= self.options.hot_call_threshold);
+
+        if is_hot {
+            self.options.hot_inline_threshold.max(self.options.inline_threshold)
+        } else {
+            self.options.inline_threshold
+        }
+    }
+
+    fn build_call_graph(&self, module: &Module) -> HashMap<String, HashSet<String>> {
+        let mut graph: HashMap<String, HashSet<String>> = HashMap::new();
+
+        for function in &module.functions {
+            let callees = graph.entry(function.name.clone()).or_default();
+            for block in &function.blocks {
+                for instr in &block.instructions {
+                    if let Instruction::Call { function: callee, .. } = instr {
+                        callees.insert(callee.clone());
+                    }
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Returns every function that can reach itself through the call graph.
+    fn recursive_functions(&self, module: &Module) -> HashSet<String> {
+        let graph = self.build_call_graph(module);
+        let mut recursive = HashSet::new();
+
+        for start in graph.keys() {
+            let mut visited = HashSet::new();
+            let mut stack: Vec<&String> = graph[start].iter().collect();
+
+            while let Some(current) = stack.pop() {
+                if current == start {
+                    recursive.insert(start.clone());
+                    break;
+                }
+                if !visited.insert(current) {
+                    continue;
+                }
+                if let Some(callees) = graph.get(current) {
+                    stack.extend(callees.iter());
+                }
+            }
+        }
+
+        recursive
+    }
+
     fn estimate_function_size(&self, function: &Function) -> usize {
         function.blocks.iter().map(|b| b.instructions.len()).sum()
     }
@@ -402,12 +649,39 @@ impl Optimizer {
         instr_idx: usize,
         callee: &Function,
     ) -> Result<()> {
-        let mut inlined_instrs = Vec::new();
+        let prefix = format!("_inlined_{}_", callee.name);
+        let (args, call_result) = match &module.functions[caller_idx].blocks[block_idx].instructions[instr_idx] {
+            Instruction::Call { args, result, .. } => (args.clone(), result.clone()),
+            other => return Err(anyhow::anyhow!("expected a call to '{}', found {:?}", callee.name, other)),
+        };
+        if args.len() != callee.params.len() {
+            return Err(anyhow::anyhow!(
+                "call to '{}' passes {} argument(s) but it takes {}",
+                callee.name,
+                args.len(),
+                callee.params.len()
+            ));
+        }
+
+        // Each parameter becomes a renamed local holding its argument.
+        let mut inlined_instrs: Vec<Instruction> = callee
+            .params
+            .iter()
+            .zip(args)
+            .map(|(param, arg)| Instruction::Store { dest: format!("{}{}", prefix, param), value: arg })
+            .collect();
 
         for block in &callee.blocks {
             for instr in &block.instructions {
-                let renamed = self.rename_instruction_vars(instr, &format!("_inlined_{}_", callee.name));
-                inlined_instrs.push(renamed);
+                match self.rename_instruction_vars(instr, &prefix) {
+                    // The callee's return value lands in the call's result.
+                    Instruction::Return { value } => {
+                        if let (Some(dest), Some(value)) = (&call_result, value) {
+                            inlined_instrs.push(Instruction::Store { dest: dest.clone(), value });
+                        }
+                    }
+                    renamed => inlined_instrs.push(renamed),
+                }
             }
         }
 
@@ -429,13 +703,25 @@ impl Optimizer {
                     result: format!("{}{}", prefix, result),
                 }
             }
+            Instruction::Unary { op, operand, result } => Instruction::Unary {
+                op: *op,
+                operand: self.rename_value(operand, prefix),
+                result: format!("{}{}", prefix, result),
+            },
             Instruction::Store { dest, value } => {
                 Instruction::Store {
                     dest: format!("{}{}", prefix, dest),
                     value: self.rename_value(value, prefix),
                 }
             }
-            _ => instr.clone(),
+            Instruction::Call { function, args, result } => Instruction::Call {
+                function: function.clone(),
+                args: args.iter().map(|arg| self.rename_value(arg, prefix)).collect(),
+                result: result.as_ref().map(|result| format!("{}{}", prefix, result)),
+            },
+            Instruction::Return { value } => Instruction::Return {
+                value: value.as_ref().map(|value| self.rename_value(value, prefix)),
+            },
         }
     }
 
@@ -446,30 +732,87 @@ impl Optimizer {
         }
     }
 
+    /// Replaces a repeated binary expression or constant with a copy of the
+    /// variable that already holds it. Only earlier instructions in the same
+    /// block are reused, since nothing proves an earlier block dominates a
+    /// later one, and an entry is forgotten as soon as its holder or one of
+    /// its operands is written again.
     fn common_subexpression_elimination(&self, mut module: Module) -> Result<Module> {
         for function in &mut module.functions {
-            let mut expr_map: HashMap<String, String> = HashMap::new();
-
             for block in &mut function.blocks {
+                // Expression key -> (holder, operand variables).
+                let mut expr_map: HashMap<String, (String, Vec<String>)> = HashMap::new();
+                let mut const_map: HashMap<String, String> = HashMap::new();
+
                 for instr in &mut block.instructions {
-                    if let Instruction::Binary { op, left, right, result } = instr {
-                        let expr_key = format!("{:?}_{:?}_{:?}", op, left, right);
-
-                        if let Some(existing_result) = expr_map.get(&expr_key) {
-                            *instr = Instruction::Store {
-                                dest: result.clone(),
-                                value: Value::Variable(existing_result.clone()),
-                            };
-                        } else {
-                            expr_map.insert(expr_key, result.clone());
+                    let mut available = None;
+                    let mut constant = None;
+
+                    match instr {
+                        Instruction::Binary { op, left, right, result } => {
+                            let expr_key = format!("{:?}_{:?}_{:?}", op, left, right);
+
+                            if let Some((existing_result, _)) = expr_map.get(&expr_key) {
+                                *instr = Instruction::Store {
+                                    dest: result.clone(),
+                                    value: Value::Variable(existing_result.clone()),
+                                };
+                            } else {
+                                let operands: Vec<String> = [&*left, &*right]
+                                    .into_iter()
+                                    .filter_map(|value| match value {
+                                        Value::Variable(name) => Some(name.clone()),
+                                        Value::Const(_) => None,
+                                    })
+                                    .collect();
+                                available = Some((expr_key, (result.clone(), operands)));
+                            }
+                        }
+                        Instruction::Store { dest, value: Value::Const(c) } => {
+                            let const_key = format!("{:?}", c);
+                            match const_map.get(&const_key) {
+                                Some(holder) if holder != dest => {
+                                    *instr = Instruction::Store {
+                                        dest: dest.clone(),
+                                        value: Value::Variable(holder.clone()),
+                                    };
+                                }
+                                _ => constant = Some((const_key, dest.clone())),
+                            }
+                        }
+                        _ => {}
+                    }
+
+                    if let Some(written) = Self::written_variable(instr) {
+                        expr_map.retain(|_, (holder, operands)| holder != written && !operands.iter().any(|o| o == written));
+                        const_map.retain(|_, holder| holder != written);
+                    }
+
+                    // `a = a + 1` computes from the old `a`, so it is not
+                    // available once `a` holds the result.
+                    if let Some((key, (holder, operands))) = available {
+                        if !operands.contains(&holder) {
+                            expr_map.insert(key, (holder, operands));
                         }
                     }
+                    if let Some((key, holder)) = constant {
+                        const_map.insert(key, holder);
+                    }
                 }
             }
         }
         Ok(module)
     }
 
+    fn written_variable(instr: &Instruction) -> Option<&str> {
+        match instr {
+            Instruction::Binary { result, .. } | Instruction::Unary { result, .. } => Some(result),
+            Instruction::Store { dest, .. } => Some(dest),
+            Instruction::Call { result, .. } => result.as_deref(),
+            _ => None,
+        }
+    }
+
     fn validate(&self, module: &Module) -> Result<()> {
         for function in &module.functions {
             if function.blocks.is_empty() {
@@ -525,4 +868,449 @@ mod tests {
         let optimizer = Optimizer::with_level(2);
         assert_eq!(optimizer.options.level, 2);
     }
+
+    fn block(label: &str, instructions: Vec<Instruction>) -> BasicBlock {
+        BasicBlock {
+            label: label.to_string(),
+            instructions,
+            predecessors: Vec::new(),
+            is_entry: label == "entry",
+        }
+    }
+
+    fn function(name: &str, params: &[&str], blocks: Vec<BasicBlock>) -> Function {
+        Function {
+            name: name.to_string(),
+            params: params.iter().map(|p| p.to_string()).collect(),
+            blocks,
+        }
+    }
+
+    fn module_with(functions: Vec<Function>) -> Module {
+        let mut module = Module::new("test");
+        module.functions = functions;
+        module
+    }
+
+    fn store_const(dest: &str, value: i64) -> Instruction {
+        Instruction::Store {
+            dest: dest.to_string(),
+            value: Value::Const(Constant::Int(value)),
+        }
+    }
+
+    fn var(name: &str) -> Value {
+        Value::Variable(name.to_string())
+    }
+
+    #[test]
+    fn test_cse_merges_identical_constant_stores() {
+        let optimizer = Optimizer::with_level(2);
+        let module = module_with(vec![function(
+            "main",
+            &[],
+            vec![block(
+                "entry",
+                vec![store_const("a", 5), store_const("b", 5), store_const("c", 5)],
+            )],
+        )]);
+
+        let module = optimizer.common_subexpression_elimination(module).unwrap();
+        let instrs = &module.functions[0].blocks[0].instructions;
+
+        assert_eq!(instrs[0], store_const("a", 5));
+        assert_eq!(instrs[1], Instruction::Store { dest: "b".to_string(), value: var("a") });
+        assert_eq!(instrs[2], Instruction::Store { dest: "c".to_string(), value: var("a") });
+    }
+
+    #[test]
+    fn test_cse_constant_holder_redefined() {
+        let optimizer = Optimizer::with_level(2);
+        let module = module_with(vec![function(
+            "main",
+            &[],
+            vec![block(
+                "entry",
+                vec![store_const("a", 5), store_const("a", 7), store_const("b", 5)],
+            )],
+        )]);
+
+        let module = optimizer.common_subexpression_elimination(module).unwrap();
+        let instrs = &module.functions[0].blocks[0].instructions;
+
+        assert_eq!(instrs[2], store_const("b", 5));
+    }
+
+    fn add(result: &str, left: Value, right: Value) -> Instruction {
+        Instruction::Binary { op: BinaryOp::Add, left, right, result: result.to_string() }
+    }
+
+    #[test]
+    fn test_cse_forgets_expression_when_operand_is_reassigned() {
+        let optimizer = Optimizer::with_level(2);
+        let second = add("t2", var("a"), var("b"));
+        let module = module_with(vec![function(
+            "main",
+            &["a", "b"],
+            vec![block(
+                "entry",
+                vec![add("t1", var("a"), var("b")), store_const("a", 9), second.clone()],
+            )],
+        )]);
+
+        let module = optimizer.common_subexpression_elimination(module).unwrap();
+
+        assert_eq!(module.functions[0].blocks[0].instructions[2], second);
+    }
+
+    #[test]
+    fn test_cse_does_not_reuse_self_updating_expression() {
+        let optimizer = Optimizer::with_level(2);
+        let next = add("b", var("a"), int(1));
+        let module = module_with(vec![function(
+            "main",
+            &["a"],
+            vec![block("entry", vec![add("a", var("a"), int(1)), next.clone()])],
+        )]);
+
+        let module = optimizer.common_subexpression_elimination(module).unwrap();
+
+        assert_eq!(module.functions[0].blocks[0].instructions[1], next);
+    }
+
+    #[test]
+    fn test_cse_stays_within_a_block() {
+        let optimizer = Optimizer::with_level(2);
+        let second = add("t2", var("a"), var("b"));
+        let module = module_with(vec![function(
+            "main",
+            &["a", "b"],
+            vec![
+                block("entry", vec![add("t1", var("a"), var("b"))]),
+                block("next", vec![second.clone()]),
+            ],
+        )]);
+
+        let module = optimizer.common_subexpression_elimination(module).unwrap();
+
+        assert_eq!(module.functions[0].blocks[1].instructions[0], second);
+    }
+
+    #[test]
+    fn test_copy_chain_collapses_to_source() {
+        let optimizer = Optimizer::with_level(2);
+        let use_c = Instruction::Call { function: "use".to_string(), args: vec![var("c")], result: None };
+        let module = module_with(vec![function(
+            "main",
+            &["a"],
+            vec![block(
+                "entry",
+                vec![
+                    Instruction::Store { dest: "b".to_string(), value: var("a") },
+                    Instruction::Store { dest: "c".to_string(), value: var("b") },
+                    use_c,
+                ],
+            )],
+        )]);
+
+        let module = optimizer.copy_propagation(module).unwrap();
+        let use_a = Instruction::Call { function: "use".to_string(), args: vec![var("a")], result: None };
+        assert_eq!(module.functions[0].blocks[0].instructions[2], use_a);
+
+        let module = optimizer.dead_code_elimination(module).unwrap();
+        assert_eq!(module.functions[0].blocks[0].instructions, vec![use_a]);
+    }
+
+    #[test]
+    fn test_copy_propagation_stops_at_redefinition() {
+        let optimizer = Optimizer::with_level(2);
+        let module = module_with(vec![function(
+            "main",
+            &["a"],
+            vec![block(
+                "entry",
+                vec![
+                    Instruction::Store { dest: "b".to_string(), value: var("a") },
+                    store_const("a", 9),
+                    Instruction::Return { value: Some(var("b")) },
+                ],
+            )],
+        )]);
+
+        let module = optimizer.copy_propagation(module).unwrap();
+
+        assert_eq!(module.functions[0].blocks[0].instructions[2], Instruction::Return { value: Some(var("b")) });
+    }
+
+    #[test]
+    fn test_copy_propagation_stays_within_a_block() {
+        // then: b = a; else: b = 1; join: return b
+        let optimizer = Optimizer::with_level(2);
+        let ret_b = Instruction::Return { value: Some(var("b")) };
+        let module = module_with(vec![function(
+            "main",
+            &["a"],
+            vec![
+                block("then", vec![Instruction::Store { dest: "b".to_string(), value: var("a") }]),
+                block("else", vec![store_const("b", 1)]),
+                block("join", vec![ret_b.clone()]),
+            ],
+        )]);
+
+        let module = optimizer.copy_propagation(module).unwrap();
+
+        assert_eq!(module.functions[0].blocks[2].instructions[0], ret_b);
+    }
+
+    fn call_builtin(callee: &str, args: Vec<Value>) -> Instruction {
+        Instruction::Call {
+            function: callee.to_string(),
+            args,
+            result: Some("r".to_string()),
+        }
+    }
+
+    fn int(value: i64) -> Value {
+        Value::Const(Constant::Int(value))
+    }
+
+    fn fold_single(instr: Instruction) -> Instruction {
+        let optimizer = Optimizer::with_level(1);
+        let module = module_with(vec![function("main", &["x"], vec![block("entry", vec![instr])])]);
+        let module = optimizer.constant_folding(module).unwrap();
+        module.functions[0].blocks[0].instructions[0].clone()
+    }
+
+    #[test]
+    fn test_builtin_calls_with_constant_arguments_fold() {
+        assert_eq!(fold_single(call_builtin("max", vec![int(3), int(5)])), store_const("r", 5));
+        assert_eq!(fold_single(call_builtin("min", vec![int(3), int(5)])), store_const("r", 3));
+        assert_eq!(fold_single(call_builtin("abs", vec![int(-4)])), store_const("r", 4));
+        assert_eq!(fold_single(call_builtin("pow", vec![int(2), int(10)])), store_const("r", 1024));
+    }
+
+    #[test]
+    fn test_builtin_calls_left_alone_when_not_foldable() {
+        let unfoldable = vec![
+            call_builtin("pow", vec![int(2), var("x")]),
+            call_builtin("pow", vec![int(2), int(64)]),
+            call_builtin("pow", vec![int(2), int(-1)]),
+            call_builtin("abs", vec![int(i64::MIN)]),
+            call_builtin("max", vec![int(1)]),
+            call_builtin("print", vec![int(1)]),
+        ];
+
+        for instr in unfoldable {
+            assert_eq!(fold_single(instr.clone()), instr);
+        }
+    }
+
+    fn binary(op: BinaryOp, left: i64, right: i64) -> Instruction {
+        Instruction::Binary { op, left: int(left), right: int(right), result: "r".to_string() }
+    }
+
+    #[test]
+    fn test_bitwise_and_shift_operations_fold() {
+        assert_eq!(fold_single(binary(BinaryOp::BitwiseAnd, 0xF0, 0x0F)), store_const("r", 0));
+        assert_eq!(fold_single(binary(BinaryOp::BitwiseOr, 0xF0, 0x0F)), store_const("r", 0xFF));
+        assert_eq!(fold_single(binary(BinaryOp::BitwiseXor, 0xFF, 0x0F)), store_const("r", 0xF0));
+        assert_eq!(fold_single(binary(BinaryOp::LeftShift, 1, 3)), store_const("r", 8));
+        assert_eq!(fold_single(binary(BinaryOp::RightShift, -16, 2)), store_const("r", -4));
+
+        let not = Instruction::Unary { op: UnaryOp::Not, operand: int(0x0F), result: "r".to_string() };
+        assert_eq!(fold_single(not), store_const("r", !0x0F));
+    }
+
+    #[test]
+    fn test_out_of_range_shifts_are_not_folded() {
+        for shift in [binary(BinaryOp::LeftShift, 1, 64), binary(BinaryOp::RightShift, 1, 100), binary(BinaryOp::LeftShift, 1, -1)] {
+            assert_eq!(fold_single(shift.clone()), shift);
+        }
+    }
+
+    #[test]
+    fn test_non_finite_float_operations_are_not_folded() {
+        let float = |value: f64| Value::Const(Constant::Float(value));
+        let unfoldable = vec![
+            Instruction::Binary { op: BinaryOp::Div, left: float(1.0), right: float(0.0), result: "r".to_string() },
+            Instruction::Binary { op: BinaryOp::Div, left: float(0.0), right: float(0.0), result: "r".to_string() },
+            Instruction::Binary { op: BinaryOp::Lt, left: float(f64::NAN), right: float(1.0), result: "r".to_string() },
+        ];
+
+        // NaN never compares equal, so check the shape rather than the value.
+        for instr in unfoldable {
+            assert!(matches!(fold_single(instr), Instruction::Binary { .. }));
+        }
+
+        let finite = Instruction::Binary { op: BinaryOp::Div, left: float(1.0), right: float(4.0), result: "r".to_string() };
+        assert_eq!(
+            fold_single(finite),
+            Instruction::Store { dest: "r".to_string(), value: float(0.25) }
+        );
+    }
+
+    fn call(callee: &str) -> Instruction {
+        Instruction::Call {
+            function: callee.to_string(),
+            args: Vec::new(),
+            result: None,
+        }
+    }
+
+    fn count_calls(function: &Function, callee: &str) -> usize {
+        function
+            .blocks
+            .iter()
+            .flat_map(|b| b.instructions.iter())
+            .filter(|i| matches!(i, Instruction::Call { function, .. } if function == callee))
+            .count()
+    }
+
+    #[test]
+    fn test_inline_small_single_call_function() {
+        let optimizer = Optimizer::with_level(2);
+        let module = module_with(vec![
+            function("helper", &[], vec![block("entry", vec![store_const("x", 1)])]),
+            function("main", &[], vec![block("entry", vec![call("helper"), store_const("y", 2)])]),
+        ]);
+
+        let module = optimizer.inline_functions(module).unwrap();
+        let main = &module.functions[1];
+
+        assert_eq!(count_calls(main, "helper"), 0);
+        assert_eq!(main.blocks[0].instructions[0], store_const("_inlined_helper_x", 1));
+    }
+
+    #[test]
+    fn test_inline_never_inlines_recursive_function() {
+        let optimizer = Optimizer::with_level(2);
+        let module = module_with(vec![
+            function("fact", &[], vec![block("entry", vec![store_const("n", 1), call("fact")])]),
+            function("main", &[], vec![block("entry", vec![call("fact")])]),
+        ]);
+
+        let module = optimizer.inline_functions(module).unwrap();
+
+        assert_eq!(count_calls(&module.functions[0], "fact"), 1);
+        assert_eq!(count_calls(&module.functions[1], "fact"), 1);
+    }
+
+    #[test]
+    fn test_inline_binds_arguments_and_return_value() {
+        let optimizer = Optimizer::with_level(2);
+        let double = function(
+            "double",
+            &["n"],
+            vec![block(
+                "entry",
+                vec![
+                    Instruction::Binary { op: BinaryOp::Add, left: var("n"), right: var("n"), result: "sum".to_string() },
+                    Instruction::Return { value: Some(var("sum")) },
+                ],
+            )],
+        );
+        let call_double = Instruction::Call {
+            function: "double".to_string(),
+            args: vec![var("a")],
+            result: Some("r".to_string()),
+        };
+        let module = module_with(vec![double, function("main", &["a"], vec![block("entry", vec![call_double])])]);
+
+        let module = optimizer.inline_functions(module).unwrap();
+
+        assert_eq!(
+            module.functions[1].blocks[0].instructions,
+            vec![
+                Instruction::Store { dest: "_inlined_double_n".to_string(), value: var("a") },
+                Instruction::Binary {
+                    op: BinaryOp::Add,
+                    left: var("_inlined_double_n"),
+                    right: var("_inlined_double_n"),
+                    result: "_inlined_double_sum".to_string(),
+                },
+                Instruction::Store { dest: "r".to_string(), value: var("_inlined_double_sum") },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_depth_is_capped_per_call_chain() {
+        let optimizer = Optimizer::new(OptimizationOptions {
+            inline_max_depth: 2,
+            ..OptimizationOptions::level_2()
+        });
+        let module = module_with(vec![
+            function("leaf", &[], vec![block("entry", vec![store_const("x", 1)])]),
+            function("middle", &[], vec![block("entry", vec![call("leaf")])]),
+            function("outer", &[], vec![block("entry", vec![call("middle")])]),
+            function("main", &[], vec![block("entry", vec![call("outer")])]),
+        ]);
+
+        let module = optimizer.inline_functions(module).unwrap();
+
+        assert_eq!(count_calls(&module.functions[1], "leaf"), 0);
+        assert_eq!(count_calls(&module.functions[2], "middle"), 0);
+        assert_eq!(count_calls(&module.functions[3], "outer"), 1);
+    }
+
+    #[test]
+    fn test_inline_respects_growth_budget() {
+        let optimizer = Optimizer::new(OptimizationOptions {
+            inline_growth_budget: 30,
+            ..OptimizationOptions::level_2()
+        });
+        let body = (0..10).map(|i| store_const(&format!("v{}", i), i)).collect();
+        let module = module_with(vec![
+            function("big", &[], vec![block("entry", body)]),
+            function("main", &[], vec![block("entry", vec![call("big"); 6])]),
+        ]);
+
+        let module = optimizer.inline_functions(module).unwrap();
+
+        assert_eq!(count_calls(&module.functions[1], "big"), 6);
+    }
+
+    #[test]
+    fn test_profile_guided_inlining_prefers_hot_callee() {
+        let profile = ProfileData::from_json(r#"{
+            "hot": { "calls": 50000, "total_ms": 12 },
+            "cold": { "calls": 3, "total_ms": 0 }
+        }"#).unwrap();
+        let optimizer = Optimizer::with_level(2).with_profile(profile);
+
+        let body = |prefix: &str| (0..100).map(|i| store_const(&format!("{}{}", prefix, i), i)).collect();
+        let module = module_with(vec![
+            function("hot", &[], vec![block("entry", body("h"))]),
+            function("cold", &[], vec![block("entry", body("c"))]),
+            function("main", &[], vec![block("entry", vec![call("hot"), call("cold")])]),
+        ]);
+
+        let module = optimizer.inline_functions(module).unwrap();
+        let main = &module.functions[2];
+
+        assert_eq!(count_calls(main, "hot"), 0);
+        assert_eq!(count_calls(main, "cold"), 1);
+    }
+
+    #[test]
+    fn test_build_optimizer_inlines_hot_call_site_from_pgo_use_file() {
+        let path = std::env::temp_dir().join("blaze_test_pgo_use.json");
+        std::fs::write(&path, r#"{ "hot": { "calls": 50000, "total_ms": 12 } }"#).unwrap();
+
+        let body = (0..100).map(|i| store_const(&format!("h{}", i), i)).collect();
+        let module = module_with(vec![
+            function("hot", &[], vec![block("entry", body)]),
+            function("main", &[], vec![block("entry", vec![call("hot")])]),
+        ]);
+
+        let without_profile = Optimizer::for_build(2, None).unwrap();
+        let unchanged = without_profile.inline_functions(module.clone()).unwrap();
+        assert_eq!(count_calls(&unchanged.functions[1], "hot"), 1);
+
+        let with_profile = Optimizer::for_build(2, Some(&path)).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(with_profile.profile().unwrap().call_count("hot"), 50000);
+        let inlined = with_profile.inline_functions(module).unwrap();
+        assert_eq!(count_calls(&inlined.functions[1], "hot"), 0);
+    }
 }
@@ -78,10 +78,16 @@ pub enum Type {
     Reference { mutable: bool, inner: Box<Type> },
     Pointer { mutable: bool, inner: Box<Type> },
     Array { element: Box<Type>, size: Option<usize> },
+    /// `[T]`, a dynamically sized view into an array.
+    Slice(Box<Type>),
     Tuple(Vec<Type>),
     Function { params: Vec<Type>, return_type: Box<Type> },
     TraitObject(String),
     Impl(String),
+    /// A concrete const-generic argument, e.g. the `4` in `Array<i32, 4>`.
+    ConstValue(i64),
+    /// `[T; N]` where `N` is a const generic parameter.
+    ParamArray { element: Box<Type>, size_param: String },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -92,8 +98,10 @@ pub enum Statement {
     While { condition: Expression, body: Vec<Statement> },
     For { variable: String, iterable: Expression, body: Vec<Statement> },
     Loop { body: Vec<Statement> },
-    Break(Option<Expression>),
-    Continue,
+    /// `'label: while/for/loop ...`; `body` is always one of the loop statements.
+    Labeled { label: String, body: Box<Statement> },
+    Break { label: Option<String>, value: Option<Expression> },
+    Continue { label: Option<String> },
     Block(Vec<Statement>),
     If { condition: Expression, then_body: Vec<Statement>, else_body: Option<Vec<Statement>> },
 }
@@ -125,6 +133,8 @@ pub enum Expression {
     If { condition: Box<Expression>, then_branch: Box<Expression>, else_branch: Option<Box<Expression>> },
     Match { expression: Box<Expression>, arms: Vec<MatchArm> },
     Closure { params: Vec<ClosureParam>, body: Box<Expression> },
+    /// `start..end`, or `start..=end` when `inclusive`.
+    Range { start: Box<Expression>, end: Box<Expression>, inclusive: bool },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -147,6 +157,7 @@ pub enum Pattern {
     Wildcard,
     Tuple(Vec<Pattern>),
     Struct { name: String, fields: Vec<(String, Pattern)> },
+    TupleStruct { name: String, fields: Vec<Pattern> },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -275,10 +286,18 @@ pub enum ImplItem {
 #[derive(Debug, Clone, PartialEq)]
 pub struct GenericParam {
     pub name: String,
+    pub kind: GenericParamKind,
     pub bounds: Vec<TypeBound>,
     pub default: Option<Type>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenericParamKind {
+    Type,
+    /// `const N: usize`; the payload is the parameter's value type.
+    Const(Type),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TypeBound {
     Trait(String),
@@ -8,11 +8,35 @@ use crate::error::{CompileError, Result};
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    /// Cleared while parsing the head of `if`, `while`, `for` and `match`,
+    /// where `Name {` opens the body rather than a struct literal.
+    struct_literals_allowed: bool,
+    /// Set by `parse_recovering`: syntax errors are collected in `errors`
+    /// and parsing resumes at the next statement or item.
+    recovering: bool,
+    errors: Vec<CompileError>,
+    /// Where the arms of the most recently parsed `if` end in a tail
+    /// expression, read back by the block that holds it.
+    if_tail: BlockTail,
+}
+
+/// Which statements at the end of a block produce its value: a trailing
+/// expression with no `;`, or the tail arms of a trailing if/else.
+#[derive(Debug, Clone, Default)]
+enum BlockTail {
+    #[default]
+    None,
+    Expression,
+    If { then_tail: Box<BlockTail>, else_tail: Option<Box<BlockTail>> },
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+    pub fn new(mut tokens: Vec<Token>) -> Self {
+        if !matches!(tokens.last().map(|t| &t.token_type), Some(TokenType::Eof)) {
+            let (line, column) = tokens.last().map_or((1, 1), |t| (t.line, t.column));
+            tokens.push(Token::new(TokenType::Eof, line, column));
+        }
+        Parser { tokens, current: 0, struct_literals_allowed: true, recovering: false, errors: Vec::new(), if_tail: BlockTail::None }
     }
     
     pub fn parse(&mut self) -> Result<Program> {
@@ -25,28 +49,128 @@ impl Parser {
         Ok(Program { items })
     }
     
+    /// Parses the whole input, reporting every syntax error instead of
+    /// stopping at the first. A broken statement is skipped up to the next
+    /// `;` or `}`, and a broken item up to the next item keyword; the
+    /// program holds everything that did parse.
+    pub fn parse_recovering(&mut self) -> (Program, Vec<CompileError>) {
+        self.recovering = true;
+        let mut items = Vec::new();
+        
+        while !self.is_at_end() {
+            match self.parse_item() {
+                Ok(item) => items.push(item),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize_item();
+                }
+            }
+        }
+        
+        self.recovering = false;
+        (Program { items }, std::mem::take(&mut self.errors))
+    }
+    
+    /// Parses one statement of a block. While recovering, a syntax error is
+    /// recorded and skipped, giving `None`.
+    fn parse_block_statement(&mut self) -> Result<Option<Statement>> {
+        match self.parse_statement() {
+            Ok(stmt) => Ok(Some(stmt)),
+            Err(error) if self.recovering => {
+                self.errors.push(error);
+                self.synchronize_statement();
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+    
+    /// Skips past the next `;`, or past a nested block that closes, stopping
+    /// before a `}` that ends the enclosing block.
+    fn synchronize_statement(&mut self) {
+        let mut depth = 0usize;
+        while !self.is_at_end() {
+            match self.peek().token_type {
+                TokenType::Semicolon if depth == 0 => {
+                    self.advance();
+                    return;
+                }
+                TokenType::RightBrace if depth == 0 => return,
+                TokenType::RightBrace => {
+                    self.advance();
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                TokenType::LeftBrace => {
+                    self.advance();
+                    depth += 1;
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+    
+    /// Skips to the next item keyword outside any braces.
+    fn synchronize_item(&mut self) {
+        let mut depth = 0usize;
+        while !self.is_at_end() {
+            match self.peek().token_type {
+                TokenType::Fn | TokenType::Const | TokenType::Struct | TokenType::Enum | TokenType::Trait | TokenType::Impl
+                    if depth == 0 =>
+                {
+                    return;
+                }
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+    
     fn parse_item(&mut self) -> Result<Item> {
         match &self.peek().token_type {
             TokenType::Fn => Ok(Item::Function(self.parse_function()?)),
+            TokenType::Const => {
+                self.advance();
+                if !self.check(TokenType::Fn) {
+                    return Err(self.error("Expected 'fn' after 'const'"));
+                }
+                let mut function = self.parse_function()?;
+                function.is_const = true;
+                Ok(Item::Function(function))
+            }
             TokenType::Struct => Ok(Item::Struct(self.parse_struct()?)),
-            _ => Err(self.error("Expected function or struct")),
+            TokenType::Enum => Ok(Item::Enum(self.parse_enum()?)),
+            TokenType::Trait => Ok(Item::Trait(self.parse_trait()?)),
+            TokenType::Impl => Ok(Item::Impl(self.parse_impl()?)),
+            _ => Err(self.error("Expected function, struct, enum, trait or impl")),
         }
     }
     
     fn parse_function(&mut self) -> Result<Function> {
+        let mut function = self.parse_signature()?;
+        function.body = self.parse_function_body(function.return_type.is_some())?;
+        Ok(function)
+    }
+    
+    /// Parses `fn name<generics>(params) -> ret`, leaving the body empty.
+    fn parse_signature(&mut self) -> Result<Function> {
         self.consume(TokenType::Fn)?;
         
         let name = self.consume_ident()?;
+        let generics = self.parse_generic_params()?;
         
         self.consume(TokenType::LeftParen)?;
         let mut params = Vec::new();
         
         if !self.check(TokenType::RightParen) {
             loop {
-                let param_name = self.consume_ident()?;
-                self.consume(TokenType::Colon)?;
-                let param_type = self.parse_type()?;
-                params.push(Param { name: param_name, ty: param_type });
+                params.push(self.parse_param()?);
                 
                 if !self.match_token(TokenType::Comma) {
                     break;
@@ -62,21 +186,205 @@ impl Parser {
             None
         };
         
+        Ok(Function {
+            attributes: Vec::new(),
+            visibility: Visibility::Private,
+            name,
+            generics,
+            params,
+            return_type,
+            where_clause: None,
+            body: Vec::new(),
+            is_async: false,
+            is_unsafe: false,
+            is_const: false,
+        })
+    }
+    
+    /// Parses `name: Type`, or a method receiver `self`, `&self` or
+    /// `&mut self`, whose type is written in terms of `Self`.
+    fn parse_param(&mut self) -> Result<Param> {
+        let self_type = Type::Custom("Self".to_string());
+        if self.match_token(TokenType::SelfValue) {
+            return Ok(Param { name: "self".to_string(), ty: self_type });
+        }
+        if self.match_token(TokenType::Ampersand) {
+            let mutable = self.match_token(TokenType::Mut);
+            self.consume(TokenType::SelfValue)?;
+            return Ok(Param {
+                name: "self".to_string(),
+                ty: Type::Reference { mutable, inner: Box::new(self_type) },
+            });
+        }
+        
+        let name = self.consume_ident()?;
+        self.consume(TokenType::Colon)?;
+        let ty = self.parse_type()?;
+        Ok(Param { name, ty })
+    }
+    
+    fn parse_function_body(&mut self, returns_value: bool) -> Result<Vec<Statement>> {
+        let (mut body, tail) = self.parse_block_body()?;
+        
+        // The value of a function body is its trailing expression, so desugar it
+        // (and the tail arms of a trailing if/else) into explicit returns.
+        if returns_value {
+            Self::make_implicit_return(&mut body, tail);
+        }
+        
+        Ok(body)
+    }
+    
+    /// Parses `{ statements }`, noting which trailing statements form the
+    /// block's value.
+    fn parse_block_body(&mut self) -> Result<(Vec<Statement>, BlockTail)> {
         self.consume(TokenType::LeftBrace)?;
         let mut body = Vec::new();
+        let mut tail = BlockTail::None;
         
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
-            body.push(self.parse_statement()?);
+            tail = BlockTail::None;
+            if let Some(stmt) = self.parse_block_statement()? {
+                let at_end = self.check(TokenType::RightBrace);
+                tail = match stmt {
+                    Statement::Expression(_)
+                        if at_end && !matches!(self.previous().token_type, TokenType::Semicolon) =>
+                    {
+                        BlockTail::Expression
+                    }
+                    Statement::If { .. } if at_end => std::mem::take(&mut self.if_tail),
+                    _ => BlockTail::None,
+                };
+                body.push(stmt);
+            }
         }
         
         self.consume(TokenType::RightBrace)?;
+        Ok((body, tail))
+    }
+    
+    /// Parses `trait Name: Super + Other { ... }`. Each method is a
+    /// signature ending in `;`, or has a default body.
+    fn parse_trait(&mut self) -> Result<Trait> {
+        self.consume(TokenType::Trait)?;
+        let name = self.consume_ident()?;
+        let generics = self.parse_generic_params()?;
         
-        Ok(Function { name, params, return_type, body })
+        let mut supertraits = Vec::new();
+        if self.match_token(TokenType::Colon) {
+            loop {
+                supertraits.push(self.consume_ident()?);
+                if !self.match_token(TokenType::Plus) {
+                    break;
+                }
+            }
+        }
+        
+        self.consume(TokenType::LeftBrace)?;
+        let mut items = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            if !self.check(TokenType::Fn) {
+                return Err(self.error("Expected 'fn' in trait body"));
+            }
+            let signature = self.parse_signature()?;
+            let default_body = if self.match_token(TokenType::Semicolon) {
+                None
+            } else {
+                Some(self.parse_function_body(signature.return_type.is_some())?)
+            };
+            items.push(TraitItem::Function(TraitFunction {
+                name: signature.name,
+                generics: signature.generics,
+                params: signature.params,
+                return_type: signature.return_type,
+                where_clause: None,
+                default_body,
+            }));
+        }
+        self.consume(TokenType::RightBrace)?;
+        
+        Ok(Trait {
+            attributes: Vec::new(),
+            visibility: Visibility::Private,
+            name,
+            generics,
+            supertraits,
+            items,
+            where_clause: None,
+        })
+    }
+    
+    /// Parses `impl Trait for Type { ... }` or an inherent `impl Type { ... }`.
+    fn parse_impl(&mut self) -> Result<Impl> {
+        self.consume(TokenType::Impl)?;
+        let generics = self.parse_generic_params()?;
+        
+        let first = self.parse_type()?;
+        let (trait_path, self_type) = if self.match_token(TokenType::For) {
+            match first {
+                Type::Custom(trait_name) => (Some(trait_name), self.parse_type()?),
+                _ => return Err(self.error("Expected a trait name before 'for'")),
+            }
+        } else {
+            (None, first)
+        };
+        
+        self.consume(TokenType::LeftBrace)?;
+        let mut items = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            if !self.check(TokenType::Fn) {
+                return Err(self.error("Expected 'fn' in impl body"));
+            }
+            items.push(ImplItem::Function(self.parse_function()?));
+        }
+        self.consume(TokenType::RightBrace)?;
+        
+        Ok(Impl {
+            attributes: Vec::new(),
+            generics,
+            trait_path,
+            self_type,
+            where_clause: None,
+            items,
+        })
+    }
+    
+    fn make_implicit_return(body: &mut [Statement], tail: BlockTail) {
+        match (body.last_mut(), tail) {
+            (
+                Some(Statement::If { then_body, else_body: Some(else_body), .. }),
+                BlockTail::If { then_tail, else_tail: Some(else_tail) },
+            ) => {
+                Self::make_implicit_return(then_body, *then_tail);
+                Self::make_implicit_return(else_body, *else_tail);
+            }
+            (Some(last @ Statement::Expression(_)), BlockTail::Expression) => {
+                if let Statement::Expression(expr) = std::mem::replace(last, Statement::Return(None)) {
+                    *last = Statement::Return(Some(expr));
+                }
+            }
+            _ => {}
+        }
     }
     
     fn parse_struct(&mut self) -> Result<Struct> {
         self.consume(TokenType::Struct)?;
         let name = self.consume_ident()?;
+        let generics = self.parse_generic_params()?;
+        let fields = self.parse_fields()?;
+        
+        Ok(Struct {
+            attributes: Vec::new(),
+            visibility: Visibility::Private,
+            name,
+            generics,
+            fields,
+            where_clause: None,
+        })
+    }
+    
+    /// Parses a braced `{ name: Type, ... }` field list.
+    fn parse_fields(&mut self) -> Result<Vec<Field>> {
         self.consume(TokenType::LeftBrace)?;
         
         let mut fields = Vec::new();
@@ -91,8 +399,98 @@ impl Parser {
         }
         
         self.consume(TokenType::RightBrace)?;
+        Ok(fields)
+    }
+    
+    /// Parses `enum Name { A, B(i32, i32), C { x: i32 } }`; a trailing comma
+    /// after the last variant is allowed.
+    fn parse_enum(&mut self) -> Result<Enum> {
+        self.consume(TokenType::Enum)?;
+        let name = self.consume_ident()?;
+        let generics = self.parse_generic_params()?;
+        self.consume(TokenType::LeftBrace)?;
         
-        Ok(Struct { name, fields })
+        let mut variants = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let variant_name = self.consume_ident()?;
+            let data = if self.check(TokenType::LeftBrace) {
+                EnumVariantData::Struct(self.parse_fields()?)
+            } else if self.match_token(TokenType::LeftParen) {
+                let mut types = Vec::new();
+                while !self.check(TokenType::RightParen) && !self.is_at_end() {
+                    types.push(self.parse_type()?);
+                    if !self.match_token(TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.consume(TokenType::RightParen)?;
+                EnumVariantData::Tuple(types)
+            } else {
+                EnumVariantData::Unit
+            };
+            variants.push(EnumVariant { name: variant_name, data });
+            
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+        
+        self.consume(TokenType::RightBrace)?;
+        
+        Ok(Enum {
+            attributes: Vec::new(),
+            visibility: Visibility::Private,
+            name,
+            generics,
+            variants,
+            where_clause: None,
+        })
+    }
+    
+    /// Parses an optional `<T: Bound, const N: usize>` parameter list.
+    fn parse_generic_params(&mut self) -> Result<Vec<GenericParam>> {
+        let mut generics = Vec::new();
+        if !self.match_token(TokenType::Less) {
+            return Ok(generics);
+        }
+        
+        while !self.check(TokenType::Greater) && !self.is_at_end() {
+            if self.match_token(TokenType::Const) {
+                let name = self.consume_ident()?;
+                self.consume(TokenType::Colon)?;
+                let ty = self.parse_type()?;
+                generics.push(GenericParam {
+                    name,
+                    kind: GenericParamKind::Const(ty),
+                    bounds: Vec::new(),
+                    default: None,
+                });
+            } else {
+                let name = self.consume_ident()?;
+                let mut bounds = Vec::new();
+                if self.match_token(TokenType::Colon) {
+                    loop {
+                        bounds.push(TypeBound::Trait(self.consume_ident()?));
+                        if !self.match_token(TokenType::Plus) {
+                            break;
+                        }
+                    }
+                }
+                generics.push(GenericParam {
+                    name,
+                    kind: GenericParamKind::Type,
+                    bounds,
+                    default: None,
+                });
+            }
+            
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+        
+        self.consume(TokenType::Greater)?;
+        Ok(generics)
     }
     
     fn parse_statement(&mut self) -> Result<Statement> {
@@ -100,6 +498,11 @@ impl Parser {
             TokenType::Let => self.parse_let(),
             TokenType::Return => self.parse_return(),
             TokenType::While => self.parse_while(),
+            TokenType::For => self.parse_for(),
+            TokenType::Loop => self.parse_loop(),
+            TokenType::Label(_) => self.parse_labeled(),
+            TokenType::Break => self.parse_break(),
+            TokenType::Continue => self.parse_continue(),
             TokenType::If => self.parse_if(),
             _ => {
                 let expr = self.parse_expression()?;
@@ -122,7 +525,7 @@ impl Parser {
         };
         
         self.consume(TokenType::Equal)?;
-        let value = self.parse_expression()?;
+        let value = Some(self.parse_expression()?);
         self.match_token(TokenType::Semicolon);
         
         Ok(Statement::Let { name, mutable, ty, value })
@@ -143,47 +546,183 @@ impl Parser {
     
     fn parse_while(&mut self) -> Result<Statement> {
         self.consume(TokenType::While)?;
-        let condition = self.parse_expression()?;
+        let condition = self.parse_condition()?;
         self.consume(TokenType::LeftBrace)?;
         
         let mut body = Vec::new();
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
-            body.push(self.parse_statement()?);
+            body.extend(self.parse_block_statement()?);
         }
         
         self.consume(TokenType::RightBrace)?;
         Ok(Statement::While { condition, body })
     }
     
-    fn parse_if(&mut self) -> Result<Statement> {
-        self.consume(TokenType::If)?;
-        let condition = self.parse_expression()?;
+    fn parse_for(&mut self) -> Result<Statement> {
+        self.consume(TokenType::For)?;
+        let variable = self.consume_ident()?;
+        self.consume(TokenType::In)?;
+        let iterable = self.parse_condition()?;
+        self.consume(TokenType::LeftBrace)?;
+        
+        let mut body = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            body.extend(self.parse_block_statement()?);
+        }
+        
+        self.consume(TokenType::RightBrace)?;
+        Ok(Statement::For { variable, iterable, body })
+    }
+    
+    fn parse_loop(&mut self) -> Result<Statement> {
+        self.consume(TokenType::Loop)?;
         self.consume(TokenType::LeftBrace)?;
         
-        let mut then_body = Vec::new();
+        let mut body = Vec::new();
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
-            then_body.push(self.parse_statement()?);
+            body.extend(self.parse_block_statement()?);
         }
         
         self.consume(TokenType::RightBrace)?;
+        Ok(Statement::Loop { body })
+    }
+    
+    fn parse_labeled(&mut self) -> Result<Statement> {
+        let label = self.parse_label().ok_or_else(|| self.error("Expected a label"))?;
+        self.consume(TokenType::Colon)?;
         
-        let else_body = if self.match_token(TokenType::Else) {
-            self.consume(TokenType::LeftBrace)?;
-            let mut body = Vec::new();
-            while !self.check(TokenType::RightBrace) && !self.is_at_end() {
-                body.push(self.parse_statement()?);
-            }
-            self.consume(TokenType::RightBrace)?;
-            Some(body)
+        let body = match self.peek().token_type {
+            TokenType::While => self.parse_while()?,
+            TokenType::For => self.parse_for()?,
+            TokenType::Loop => self.parse_loop()?,
+            _ => return Err(self.error("Expected a loop after label")),
+        };
+        
+        Ok(Statement::Labeled { label, body: Box::new(body) })
+    }
+    
+    fn parse_break(&mut self) -> Result<Statement> {
+        self.consume(TokenType::Break)?;
+        let label = self.parse_label();
+        
+        let value = if self.check(TokenType::Semicolon) || self.check(TokenType::RightBrace) || self.is_at_end() {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        
+        self.match_token(TokenType::Semicolon);
+        Ok(Statement::Break { label, value })
+    }
+    
+    fn parse_continue(&mut self) -> Result<Statement> {
+        self.consume(TokenType::Continue)?;
+        let label = self.parse_label();
+        self.match_token(TokenType::Semicolon);
+        Ok(Statement::Continue { label })
+    }
+    
+    fn parse_label(&mut self) -> Option<String> {
+        if let TokenType::Label(name) = &self.peek().token_type {
+            let name = name.clone();
+            self.advance();
+            Some(name)
         } else {
             None
+        }
+    }
+    
+    fn parse_if(&mut self) -> Result<Statement> {
+        self.consume(TokenType::If)?;
+        let condition = self.parse_condition()?;
+        let (then_body, then_tail) = self.parse_block_body()?;
+        
+        let (else_body, else_tail) = if self.match_token(TokenType::Else) {
+            if self.check(TokenType::If) {
+                // `else if` is sugar for an else block holding a single if.
+                let nested = self.parse_if()?;
+                let nested_tail = std::mem::take(&mut self.if_tail);
+                (Some(vec![nested]), Some(nested_tail))
+            } else {
+                let (body, tail) = self.parse_block_body()?;
+                (Some(body), Some(tail))
+            }
+        } else {
+            (None, None)
         };
         
+        self.if_tail = BlockTail::If { then_tail: Box::new(then_tail), else_tail: else_tail.map(Box::new) };
         Ok(Statement::If { condition, then_body, else_body })
     }
     
     fn parse_expression(&mut self) -> Result<Expression> {
-        self.parse_or()
+        self.parse_assignment()
+    }
+    
+    /// Parses an expression followed by a `{ ... }` body.
+    fn parse_condition(&mut self) -> Result<Expression> {
+        self.with_struct_literals(false, Self::parse_expression)
+    }
+    
+    fn with_struct_literals<T>(&mut self, allowed: bool, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let saved = std::mem::replace(&mut self.struct_literals_allowed, allowed);
+        let result = parse(self);
+        self.struct_literals_allowed = saved;
+        result
+    }
+    
+    /// Ranges bind looser than every binary operator and do not chain.
+    /// Assignment binds loosest and groups to the right, so `x = y = 3`
+    /// assigns 3 to `y` first. `x += e` is sugar for `x = x + e`.
+    fn parse_assignment(&mut self) -> Result<Expression> {
+        let target = self.parse_range()?;
+        
+        let op = match self.peek().token_type {
+            TokenType::Equal => None,
+            TokenType::PlusEqual => Some(BinaryOp::Add),
+            TokenType::MinusEqual => Some(BinaryOp::Sub),
+            TokenType::StarEqual => Some(BinaryOp::Mul),
+            TokenType::SlashEqual => Some(BinaryOp::Div),
+            _ => return Ok(target),
+        };
+        
+        if !Self::is_place(&target) {
+            return Err(self.error("Invalid assignment target: expected a variable, field, index or dereference"));
+        }
+        self.advance();
+        
+        let value = self.parse_assignment()?;
+        let value = match op {
+            Some(op) => Expression::Binary { op, left: Box::new(target.clone()), right: Box::new(value) },
+            None => value,
+        };
+        
+        Ok(Expression::Assignment { target: Box::new(target), value: Box::new(value) })
+    }
+    
+    /// Whether `expr` names a memory location that can be assigned to.
+    fn is_place(expr: &Expression) -> bool {
+        match expr {
+            Expression::Ident(_) | Expression::Identifier(_) => true,
+            Expression::FieldAccess { object, .. } | Expression::Index { object, .. } => Self::is_place(object),
+            Expression::Unary { op: UnaryOp::Deref, .. } => true,
+            _ => false,
+        }
+    }
+    
+    fn parse_range(&mut self) -> Result<Expression> {
+        let start = self.parse_or()?;
+        
+        let inclusive = if self.match_token(TokenType::DotDotEqual) {
+            true
+        } else if self.match_token(TokenType::DotDot) {
+            false
+        } else {
+            return Ok(start);
+        };
+        
+        let end = self.parse_or()?;
+        Ok(Expression::Range { start: Box::new(start), end: Box::new(end), inclusive })
     }
     
     fn parse_or(&mut self) -> Result<Expression> {
@@ -237,7 +776,7 @@ impl Parser {
     }
     
     fn parse_comparison(&mut self) -> Result<Expression> {
-        let mut left = self.parse_term()?;
+        let mut left = self.parse_bitwise_or()?;
         
         while let Some(op) = self.match_tokens(&[
             TokenType::Less,
@@ -252,6 +791,73 @@ impl Parser {
                 TokenType::GreaterEqual => BinaryOp::Ge,
                 _ => unreachable!(),
             };
+            let right = self.parse_bitwise_or()?;
+            left = Expression::Binary {
+                op: binary_op,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        
+        Ok(left)
+    }
+    
+    // As in Rust, the bitwise operators bind tighter than comparisons:
+    // `|` < `^` < `&` < shifts < `+`/`-`.
+    fn parse_bitwise_or(&mut self) -> Result<Expression> {
+        let mut left = self.parse_bitwise_xor()?;
+        
+        while self.match_token(TokenType::Pipe) {
+            let right = self.parse_bitwise_xor()?;
+            left = Expression::Binary {
+                op: BinaryOp::BitwiseOr,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        
+        Ok(left)
+    }
+    
+    fn parse_bitwise_xor(&mut self) -> Result<Expression> {
+        let mut left = self.parse_bitwise_and()?;
+        
+        while self.match_token(TokenType::Caret) {
+            let right = self.parse_bitwise_and()?;
+            left = Expression::Binary {
+                op: BinaryOp::BitwiseXor,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        
+        Ok(left)
+    }
+    
+    fn parse_bitwise_and(&mut self) -> Result<Expression> {
+        let mut left = self.parse_shift()?;
+        
+        while self.match_token(TokenType::Ampersand) {
+            let right = self.parse_shift()?;
+            left = Expression::Binary {
+                op: BinaryOp::BitwiseAnd,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        
+        Ok(left)
+    }
+    
+    fn parse_shift(&mut self) -> Result<Expression> {
+        let mut left = self.parse_term()?;
+        
+        while let Some(op) = self.match_tokens(&[TokenType::LeftShift, TokenType::RightShift]) {
+            let binary_op = match op {
+                TokenType::LeftShift => BinaryOp::LeftShift,
+                TokenType::RightShift => BinaryOp::RightShift,
+                _ => unreachable!(),
+            };
             let right = self.parse_term()?;
             left = Expression::Binary {
                 op: binary_op,
@@ -324,29 +930,84 @@ impl Parser {
     fn parse_call(&mut self) -> Result<Expression> {
         let mut expr = self.parse_primary()?;
         
-        while self.match_token(TokenType::LeftParen) {
+        loop {
+            if self.match_token(TokenType::LeftParen) {
+                let args = self.parse_arguments()?;
+                expr = Expression::Call {
+                    func: Box::new(expr),
+                    args,
+                };
+            } else if self.match_token(TokenType::Dot) {
+                let name = self.consume_ident()?;
+                expr = if self.match_token(TokenType::LeftParen) {
+                    let args = self.parse_arguments()?;
+                    Expression::MethodCall { object: Box::new(expr), method: name, args }
+                } else {
+                    Expression::FieldAccess { object: Box::new(expr), field: name }
+                };
+            } else if self.match_token(TokenType::Question) {
+                expr = Self::desugar_try(expr);
+            } else {
+                break;
+            }
+        }
+        
+        Ok(expr)
+    }
+    
+    /// Parses a comma-separated argument list after the opening `(`,
+    /// including the closing `)`.
+    fn parse_arguments(&mut self) -> Result<Vec<Expression>> {
+        let args = self.with_struct_literals(true, |parser| {
             let mut args = Vec::new();
-            
-            if !self.check(TokenType::RightParen) {
+            if !parser.check(TokenType::RightParen) {
                 loop {
-                    args.push(self.parse_expression()?);
-                    if !self.match_token(TokenType::Comma) {
+                    args.push(parser.parse_expression()?);
+                    if !parser.match_token(TokenType::Comma) {
                         break;
                     }
                 }
             }
-            
-            self.consume(TokenType::RightParen)?;
-            expr = Expression::Call {
-                func: Box::new(expr),
-                args,
-            };
-        }
+            Ok(args)
+        })?;
         
-        Ok(expr)
+        self.consume(TokenType::RightParen)?;
+        Ok(args)
+    }
+    
+    /// Desugars `expr?` into `match expr { Ok(v) => v, Err(e) => return Err(e) }`.
+    fn desugar_try(expr: Expression) -> Expression {
+        let value = "__try_value".to_string();
+        let error = "__try_error".to_string();
+        
+        Expression::Match {
+            expression: Box::new(expr),
+            arms: vec![
+                MatchArm {
+                    pattern: Pattern::TupleStruct {
+                        name: "Ok".to_string(),
+                        fields: vec![Pattern::Identifier(value.clone())],
+                    },
+                    guard: None,
+                    body: Expression::Ident(value),
+                },
+                MatchArm {
+                    pattern: Pattern::TupleStruct {
+                        name: "Err".to_string(),
+                        fields: vec![Pattern::Identifier(error.clone())],
+                    },
+                    guard: None,
+                    body: Expression::Block(vec![Statement::Return(Some(Expression::Call {
+                        func: Box::new(Expression::Ident("Err".to_string())),
+                        args: vec![Expression::Ident(error)],
+                    }))]),
+                },
+            ],
+        }
     }
     
     fn parse_primary(&mut self) -> Result<Expression> {
+        let start = self.current;
         let token = self.advance().clone();
         
         match &token.token_type {
@@ -356,32 +1017,287 @@ impl Parser {
             TokenType::CharLit(c) => Ok(Expression::CharLit(*c)),
             TokenType::True => Ok(Expression::BoolLit(true)),
             TokenType::False => Ok(Expression::BoolLit(false)),
+            TokenType::Ident(name) if self.struct_literals_allowed && self.check(TokenType::LeftBrace) => {
+                self.advance();
+                self.parse_struct_literal(name.clone())
+            }
             TokenType::Ident(name) => Ok(Expression::Ident(name.clone())),
+            TokenType::SelfValue => Ok(Expression::Ident("self".to_string())),
+            TokenType::Match => self.parse_match(),
+            TokenType::LeftParen => self.with_struct_literals(true, Self::parse_parenthesized),
+            _ => {
+                // Report, and leave for recovery, the offending token itself.
+                self.current = start;
+                Err(self.error("Expected expression"))
+            }
+        }
+    }
+    
+    /// Parses the fields of `Name { field: value, ... }` after the `{`.
+    /// `Name { x }` is shorthand for `Name { x: x }`.
+    fn parse_struct_literal(&mut self, name: String) -> Result<Expression> {
+        self.with_struct_literals(true, |parser| {
+            let mut fields = Vec::new();
+            while !parser.check(TokenType::RightBrace) && !parser.is_at_end() {
+                let field = parser.consume_ident()?;
+                let value = if parser.match_token(TokenType::Colon) {
+                    parser.parse_expression()?
+                } else {
+                    Expression::Ident(field.clone())
+                };
+                fields.push(FieldInit { name: field, value });
+                if !parser.match_token(TokenType::Comma) {
+                    break;
+                }
+            }
+            parser.consume(TokenType::RightBrace)?;
+            Ok(Expression::StructLiteral { name, fields })
+        })
+    }
+    
+    /// Parses a grouping or tuple after the opening `(`.
+    fn parse_parenthesized(&mut self) -> Result<Expression> {
+        if self.match_token(TokenType::RightParen) {
+            return Ok(Expression::TupleLiteral(Vec::new()));
+        }
+        let first = self.parse_expression()?;
+        // `(x)` is grouping; a comma, even a trailing one, makes a tuple.
+        if !self.match_token(TokenType::Comma) {
+            self.consume(TokenType::RightParen)?;
+            return Ok(first);
+        }
+        let mut elements = vec![first];
+        while !self.check(TokenType::RightParen) && !self.is_at_end() {
+            elements.push(self.parse_expression()?);
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightParen)?;
+        Ok(Expression::TupleLiteral(elements))
+    }
+    
+    /// Parses the rest of `match expr { pat [if guard] => body, ... }` after
+    /// the `match` keyword. An arm's body is an expression or a block; the
+    /// comma after a block body is optional.
+    fn parse_match(&mut self) -> Result<Expression> {
+        let expression = Box::new(self.parse_condition()?);
+        self.consume(TokenType::LeftBrace)?;
+        
+        let mut arms = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let pattern = self.parse_pattern()?;
+            let guard = if self.match_token(TokenType::If) {
+                Some(self.parse_expression()?)
+            } else {
+                None
+            };
+            self.consume(TokenType::FatArrow)?;
+            
+            let (body, is_block) = if self.match_token(TokenType::LeftBrace) {
+                let mut statements = Vec::new();
+                while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+                    statements.extend(self.parse_block_statement()?);
+                }
+                self.consume(TokenType::RightBrace)?;
+                (Expression::Block(statements), true)
+            } else {
+                (self.parse_expression()?, false)
+            };
+            arms.push(MatchArm { pattern, guard, body });
+            
+            if !self.match_token(TokenType::Comma) && !is_block {
+                break;
+            }
+        }
+        
+        self.consume(TokenType::RightBrace)?;
+        Ok(Expression::Match { expression, arms })
+    }
+    
+    fn parse_pattern(&mut self) -> Result<Pattern> {
+        let token = self.advance().clone();
+        
+        match &token.token_type {
+            TokenType::Underscore => Ok(Pattern::Wildcard),
+            TokenType::Ident(name) if name == "_" => Ok(Pattern::Wildcard),
+            TokenType::IntLit(n) => Ok(Pattern::Literal(Literal::Integer(*n))),
+            TokenType::FloatLit(f) => Ok(Pattern::Literal(Literal::Float(*f))),
+            TokenType::StringLit(s) => Ok(Pattern::Literal(Literal::String(s.clone()))),
+            TokenType::CharLit(c) => Ok(Pattern::Literal(Literal::Char(*c))),
+            TokenType::True => Ok(Pattern::Literal(Literal::Boolean(true))),
+            TokenType::False => Ok(Pattern::Literal(Literal::Boolean(false))),
+            TokenType::Minus => match self.advance().token_type.clone() {
+                TokenType::IntLit(n) => Ok(Pattern::Literal(Literal::Integer(-n))),
+                TokenType::FloatLit(f) => Ok(Pattern::Literal(Literal::Float(-f))),
+                _ => Err(self.error("Expected numeric literal after '-' in pattern")),
+            },
             TokenType::LeftParen => {
-                let expr = self.parse_expression()?;
+                if self.match_token(TokenType::RightParen) {
+                    return Ok(Pattern::Literal(Literal::Unit));
+                }
+                let first = self.parse_pattern()?;
+                if !self.match_token(TokenType::Comma) {
+                    self.consume(TokenType::RightParen)?;
+                    return Ok(first);
+                }
+                let mut elements = vec![first];
+                while !self.check(TokenType::RightParen) && !self.is_at_end() {
+                    elements.push(self.parse_pattern()?);
+                    if !self.match_token(TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.consume(TokenType::RightParen)?;
+                Ok(Pattern::Tuple(elements))
+            }
+            TokenType::Ident(name) if self.check(TokenType::LeftBrace) => {
+                self.advance();
+                let mut fields = Vec::new();
+                while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+                    let field = self.consume_ident()?;
+                    // `Point { x }` binds the field to a variable of the same name.
+                    let pattern = if self.match_token(TokenType::Colon) {
+                        self.parse_pattern()?
+                    } else {
+                        Pattern::Identifier(field.clone())
+                    };
+                    fields.push((field, pattern));
+                    if !self.match_token(TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.consume(TokenType::RightBrace)?;
+                Ok(Pattern::Struct { name: name.clone(), fields })
+            }
+            TokenType::Ident(name) if self.check(TokenType::LeftParen) => {
+                self.advance();
+                let mut fields = Vec::new();
+                while !self.check(TokenType::RightParen) && !self.is_at_end() {
+                    fields.push(self.parse_pattern()?);
+                    if !self.match_token(TokenType::Comma) {
+                        break;
+                    }
+                }
                 self.consume(TokenType::RightParen)?;
-                Ok(expr)
+                Ok(Pattern::TupleStruct { name: name.clone(), fields })
             }
-            _ => Err(self.error("Expected expression")),
+            TokenType::Ident(name) => Ok(Pattern::Identifier(name.clone())),
+            _ => Err(self.error("Expected pattern")),
         }
     }
     
     fn parse_type(&mut self) -> Result<Type> {
+        let start = self.current;
         let token = self.advance().clone();
         
         match &token.token_type {
             TokenType::I32 => Ok(Type::I32),
             TokenType::I64 => Ok(Type::I64),
+            TokenType::U8 => Ok(Type::U8),
+            TokenType::U16 => Ok(Type::U16),
+            TokenType::U32 => Ok(Type::U32),
+            TokenType::U64 => Ok(Type::U64),
             TokenType::F32 => Ok(Type::F32),
             TokenType::F64 => Ok(Type::F64),
             TokenType::Bool => Ok(Type::Bool),
             TokenType::Char => Ok(Type::Char),
             TokenType::String => Ok(Type::String),
-            TokenType::Ident(name) => Ok(Type::Custom(name.clone())),
-            _ => Err(self.error("Expected type")),
+            TokenType::SelfType => Ok(Type::Custom("Self".to_string())),
+            TokenType::Ident(name) => {
+                if !self.match_token(TokenType::Less) {
+                    return Ok(Type::Custom(name.clone()));
+                }
+                
+                let mut args = Vec::new();
+                while !self.check_generic_close() && !self.is_at_end() {
+                    args.push(self.parse_generic_arg()?);
+                    if !self.match_token(TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.consume_generic_close()?;
+                Ok(Type::Generic(name.clone(), args))
+            }
+            TokenType::LeftBracket => {
+                let element = Box::new(self.parse_type()?);
+                if self.match_token(TokenType::RightBracket) {
+                    return Ok(Type::Slice(element));
+                }
+                self.consume(TokenType::Semicolon)?;
+                let size_token = self.advance().clone();
+                let ty = match size_token.token_type {
+                    TokenType::IntLit(n) if n >= 0 => Type::Array { element, size: Some(n as usize) },
+                    TokenType::Ident(size_param) => Type::ParamArray { element, size_param },
+                    _ => return Err(self.error("Expected array length")),
+                };
+                self.consume(TokenType::RightBracket)?;
+                Ok(ty)
+            }
+            TokenType::LeftParen => {
+                if self.match_token(TokenType::RightParen) {
+                    return Ok(Type::Tuple(Vec::new()));
+                }
+                let first = self.parse_type()?;
+                if !self.match_token(TokenType::Comma) {
+                    self.consume(TokenType::RightParen)?;
+                    return Ok(first);
+                }
+                let mut elements = vec![first];
+                while !self.check(TokenType::RightParen) && !self.is_at_end() {
+                    elements.push(self.parse_type()?);
+                    if !self.match_token(TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.consume(TokenType::RightParen)?;
+                Ok(Type::Tuple(elements))
+            }
+            _ => {
+                self.current = start;
+                Err(self.error("Expected type"))
+            }
+        }
+    }
+    
+    /// Parses one generic argument. Const arguments must be integer literals
+    /// or const parameters in scope, which parse as plain type names.
+    fn parse_generic_arg(&mut self) -> Result<Type> {
+        let token_type = self.peek().token_type.clone();
+        match token_type {
+            TokenType::IntLit(n) => {
+                self.advance();
+                Ok(Type::ConstValue(n))
+            }
+            TokenType::Minus => {
+                self.advance();
+                let operand = self.advance().token_type.clone();
+                match operand {
+                    TokenType::IntLit(n) => Ok(Type::ConstValue(-n)),
+                    _ => Err(self.error("const generic arguments must be integer literals or const parameters")),
+                }
+            }
+            TokenType::FloatLit(_) | TokenType::StringLit(_) | TokenType::CharLit(_) | TokenType::LeftBrace => {
+                Err(self.error("const generic arguments must be integer literals or const parameters"))
+            }
+            _ => self.parse_type(),
         }
     }
     
+    fn check_generic_close(&self) -> bool {
+        self.check(TokenType::Greater) || self.check(TokenType::RightShift)
+    }
+    
+    /// Consumes one `>`, splitting a `>>` token so nested argument lists close.
+    fn consume_generic_close(&mut self) -> Result<()> {
+        if self.check(TokenType::RightShift) {
+            self.tokens[self.current].token_type = TokenType::Greater;
+            self.tokens[self.current].column += 1;
+            return Ok(());
+        }
+        self.consume(TokenType::Greater)
+    }
+    
     fn consume(&mut self, token_type: TokenType) -> Result<()> {
         if self.check(token_type.clone()) {
             self.advance();
@@ -430,13 +1346,17 @@ impl Parser {
         if !self.is_at_end() {
             self.current += 1;
         }
-        &self.tokens[self.current - 1]
+        self.previous()
     }
     
     fn peek(&self) -> &Token {
         &self.tokens[self.current]
     }
     
+    fn previous(&self) -> &Token {
+        &self.tokens[self.current.saturating_sub(1)]
+    }
+    
     fn is_at_end(&self) -> bool {
         matches!(self.peek().token_type, TokenType::Eof)
     }
@@ -454,4 +1374,11 @@ impl Parser {
 pub fn parse(tokens: Vec<Token>) -> Result<Program> {
     let mut parser = Parser::new(tokens);
     parser.parse()
+}
+
+/// Parses `tokens`, collecting every syntax error; see
+/// [`Parser::parse_recovering`].
+pub fn parse_all(tokens: Vec<Token>) -> (Program, Vec<CompileError>) {
+    let mut parser = Parser::new(tokens);
+    parser.parse_recovering()
 }
\ No newline at end of file
@@ -57,6 +57,23 @@ impl PatternMatcher {
                 })
             }
             
+            (Pattern::TupleStruct { name, fields }, MatchedValue::Enum(variant, payload)) => {
+                if name != variant {
+                    return false;
+                }
+                
+                match (fields.as_slice(), payload.as_ref()) {
+                    ([], _) => true,
+                    ([single], value) => self.match_pattern(single, value),
+                    (patterns, MatchedValue::Tuple(values)) if patterns.len() == values.len() => {
+                        patterns.iter()
+                            .zip(values.iter())
+                            .all(|(p, v)| self.match_pattern(p, v))
+                    }
+                    _ => false,
+                }
+            }
+            
             _ => false,
         }
     }
@@ -105,7 +122,8 @@ impl ExhaustivenessChecker {
     fn find_missing_patterns(&self) -> Vec<String> {
         let mut missing = Vec::new();
         
-        let has_wildcard = self.patterns.iter().any(|p| matches!(p, Pattern::Wildcard));
+        // A binding pattern matches anything, just like `_`.
+        let has_wildcard = self.patterns.iter().any(|p| matches!(p, Pattern::Wildcard | Pattern::Identifier(_)));
         
         if !has_wildcard {
             missing.push("_ (wildcard)".to_string());
@@ -1,4 +1,6 @@
+use anyhow::{Context, Result};
 use std::collections::HashMap;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
 pub struct Profiler {
@@ -145,6 +147,52 @@ impl Profiler {
     }
 }
 
+/// Per-function call counts loaded from a profile written by
+/// [`Profiler::export_json`], used for profile-guided optimization.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileData {
+    pub call_counts: HashMap<String, u64>,
+}
+
+impl ProfileData {
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .context("profile data is not valid JSON")?;
+        let entries = value.as_object()
+            .context("profile data must be a JSON object keyed by function name")?;
+
+        let mut call_counts = HashMap::new();
+        for (name, metric) in entries {
+            let calls = metric.get("calls")
+                .and_then(|c| c.as_u64())
+                .with_context(|| format!("profile entry '{}' has no 'calls' count", name))?;
+            call_counts.insert(name.clone(), calls);
+        }
+
+        Ok(Self { call_counts })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read profile data from {}", path.display()))?;
+        Self::from_json(&json)
+    }
+
+    pub fn call_count(&self, function: &str) -> u64 {
+        self.call_counts.get(function).copied().unwrap_or(0)
+    }
+}
+
+impl From<&Profiler> for ProfileData {
+    fn from(profiler: &Profiler) -> Self {
+        Self {
+            call_counts: profiler.metrics.iter()
+                .map(|(name, metric)| (name.clone(), metric.call_count))
+                .collect(),
+        }
+    }
+}
+
 impl<'a> Drop for ProfileScope<'a> {
     fn drop(&mut self) {
         let duration = self.start.elapsed();
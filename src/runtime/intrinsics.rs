@@ -1,3 +1,6 @@
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicI64, Ordering};
+
 // Memory intrinsics
 #[no_mangle]
 pub unsafe extern "C" fn blaze_memcpy(dest: *mut u8, src: *const u8, count: usize) -> *mut u8 {
@@ -275,6 +278,280 @@ pub extern "C" fn blaze_max_i64(x: i64, y: i64) -> i64 {
     x.max(y)
 }
 
+// Atomic intrinsics
+//
+// Ordering codes follow `concurrency::MemoryOrdering`:
+// 0 = Relaxed, 1 = Acquire, 2 = Release, 3 = AcqRel, 4 = SeqCst.
+// Unknown codes fall back to SeqCst.
+fn atomic_ordering(code: i32) -> Ordering {
+    match code {
+        0 => Ordering::Relaxed,
+        1 => Ordering::Acquire,
+        2 => Ordering::Release,
+        3 => Ordering::AcqRel,
+        _ => Ordering::SeqCst,
+    }
+}
+
+// Loads (and compare-exchange failures) cannot release, so drop the release half.
+fn atomic_load_ordering(code: i32) -> Ordering {
+    match atomic_ordering(code) {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        ordering => ordering,
+    }
+}
+
+// Stores cannot acquire, so drop the acquire half.
+fn atomic_store_ordering(code: i32) -> Ordering {
+    match atomic_ordering(code) {
+        Ordering::Acquire => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Release,
+        ordering => ordering,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_atomic_load(ptr: *const i64, ordering: i32) -> i64 {
+    let atomic = &*(ptr as *const AtomicI64);
+    atomic.load(atomic_load_ordering(ordering))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_atomic_store(ptr: *mut i64, value: i64, ordering: i32) {
+    let atomic = &*(ptr as *const AtomicI64);
+    atomic.store(value, atomic_store_ordering(ordering));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_atomic_fetch_add(ptr: *mut i64, value: i64, ordering: i32) -> i64 {
+    let atomic = &*(ptr as *const AtomicI64);
+    atomic.fetch_add(value, atomic_ordering(ordering))
+}
+
+/// Returns 1 if `*ptr` held `expected` and was replaced by `desired`, 0 otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_atomic_compare_exchange(
+    ptr: *mut i64,
+    expected: i64,
+    desired: i64,
+    success_ordering: i32,
+    failure_ordering: i32,
+) -> c_int {
+    let atomic = &*(ptr as *const AtomicI64);
+    match atomic.compare_exchange(
+        expected,
+        desired,
+        atomic_ordering(success_ordering),
+        atomic_load_ordering(failure_ordering),
+    ) {
+        Ok(_) => 1,
+        Err(_) => 0,
+    }
+}
+
+// Checked arithmetic intrinsics
+//
+// Each returns 0 and writes the result on success, or returns 1 and leaves
+// `out` untouched on overflow so debug builds can branch to `blaze_panic`.
+unsafe fn write_checked<T>(result: Option<T>, out: *mut T) -> c_int {
+    match result {
+        Some(value) if !out.is_null() => {
+            *out = value;
+            0
+        }
+        Some(_) => 0,
+        None => 1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_checked_add_i32(a: i32, b: i32, out: *mut i32) -> c_int {
+    write_checked(a.checked_add(b), out)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_checked_sub_i32(a: i32, b: i32, out: *mut i32) -> c_int {
+    write_checked(a.checked_sub(b), out)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_checked_mul_i32(a: i32, b: i32, out: *mut i32) -> c_int {
+    write_checked(a.checked_mul(b), out)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_checked_add_i64(a: i64, b: i64, out: *mut i64) -> c_int {
+    write_checked(a.checked_add(b), out)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_checked_sub_i64(a: i64, b: i64, out: *mut i64) -> c_int {
+    write_checked(a.checked_sub(b), out)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_checked_mul_i64(a: i64, b: i64, out: *mut i64) -> c_int {
+    write_checked(a.checked_mul(b), out)
+}
+
+// Encoding intrinsics
+//
+// Each returns the number of bytes written to `out_ptr`, or -1 if the input
+// is invalid or the output does not fit in `out_cap` bytes.
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+unsafe fn input_bytes<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if ptr.is_null() && len > 0 {
+        None
+    } else if len == 0 {
+        Some(&[])
+    } else {
+        Some(std::slice::from_raw_parts(ptr, len))
+    }
+}
+
+unsafe fn write_output(bytes: Option<Vec<u8>>, out_ptr: *mut u8, out_cap: usize) -> isize {
+    match bytes {
+        Some(bytes) if bytes.len() <= out_cap && (!out_ptr.is_null() || bytes.is_empty()) => {
+            if !bytes.is_empty() {
+                std::ptr::copy_nonoverlapping(bytes.as_ptr(), out_ptr, bytes.len());
+            }
+            bytes.len() as isize
+        }
+        _ => -1,
+    }
+}
+
+fn base64_encode(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity((input.len() + 2) / 3 * 4);
+
+    for chunk in input.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        out.push(BASE64_ALPHABET[(n >> 18) as usize & 63]);
+        out.push(BASE64_ALPHABET[(n >> 12) as usize & 63]);
+        out.push(if chunk.len() > 1 { BASE64_ALPHABET[(n >> 6) as usize & 63] } else { b'=' });
+        out.push(if chunk.len() > 2 { BASE64_ALPHABET[n as usize & 63] } else { b'=' });
+    }
+
+    out
+}
+
+fn base64_decode(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() % 4 != 0 {
+        return None;
+    }
+
+    let sextet = |c: u8| BASE64_ALPHABET.iter().position(|&a| a == c).map(|p| p as u32);
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+    let chunk_count = input.len() / 4;
+
+    for (i, chunk) in input.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && i + 1 != chunk_count) {
+            return None;
+        }
+
+        let mut n = 0u32;
+        for &c in &chunk[..4 - padding] {
+            n = n << 6 | sextet(c)?;
+        }
+        n <<= 6 * padding as u32;
+
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        out.extend_from_slice(&bytes[..3 - padding]);
+    }
+
+    Some(out)
+}
+
+fn hex_encode(input: &[u8]) -> Vec<u8> {
+    input
+        .iter()
+        .flat_map(|&b| [HEX_DIGITS[(b >> 4) as usize], HEX_DIGITS[(b & 0x0f) as usize]])
+        .collect()
+}
+
+fn hex_decode(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() % 2 != 0 {
+        return None;
+    }
+
+    let nibble = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    input
+        .chunks(2)
+        .map(|pair| Some(nibble(pair[0])? << 4 | nibble(pair[1])?))
+        .collect()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_base64_encode(in_ptr: *const u8, in_len: usize, out_ptr: *mut u8, out_cap: usize) -> isize {
+    write_output(input_bytes(in_ptr, in_len).map(base64_encode), out_ptr, out_cap)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_base64_decode(in_ptr: *const u8, in_len: usize, out_ptr: *mut u8, out_cap: usize) -> isize {
+    write_output(input_bytes(in_ptr, in_len).and_then(base64_decode), out_ptr, out_cap)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_hex_encode(in_ptr: *const u8, in_len: usize, out_ptr: *mut u8, out_cap: usize) -> isize {
+    write_output(input_bytes(in_ptr, in_len).map(hex_encode), out_ptr, out_cap)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_hex_decode(in_ptr: *const u8, in_len: usize, out_ptr: *mut u8, out_cap: usize) -> isize {
+    write_output(input_bytes(in_ptr, in_len).and_then(hex_decode), out_ptr, out_cap)
+}
+
+// Hashing and randomness intrinsics
+//
+// Each returns 0 on success or -1 on a null pointer or failure. Digests are
+// always 32 bytes.
+pub const BLAZE_DIGEST_LEN: usize = 32;
+
+unsafe fn write_digest(input: Option<&[u8]>, out_ptr: *mut u8, digest: impl FnOnce(&[u8]) -> [u8; BLAZE_DIGEST_LEN]) -> c_int {
+    match input {
+        Some(input) if !out_ptr.is_null() => {
+            let hash = digest(input);
+            std::ptr::copy_nonoverlapping(hash.as_ptr(), out_ptr, BLAZE_DIGEST_LEN);
+            0
+        }
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_sha256(in_ptr: *const u8, in_len: usize, out_ptr: *mut u8) -> c_int {
+    use sha2::{Digest, Sha256};
+    write_digest(input_bytes(in_ptr, in_len), out_ptr, |input| Sha256::digest(input).into())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn blaze_blake3(in_ptr: *const u8, in_len: usize, out_ptr: *mut u8) -> c_int {
+    write_digest(input_bytes(in_ptr, in_len), out_ptr, |input| *blake3::hash(input).as_bytes())
+}
+
+/// Fills `out_ptr[..len]` from the operating system's CSPRNG.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_random_bytes(out_ptr: *mut u8, len: usize) -> c_int {
+    if len == 0 {
+        return 0;
+    }
+    if out_ptr.is_null() {
+        return -1;
+    }
+
+    let out = std::slice::from_raw_parts_mut(out_ptr, len);
+    match getrandom::getrandom(out) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
 // I/O intrinsics
 #[no_mangle]
 pub unsafe extern "C" fn blaze_println_str(s: *const u8) {
@@ -352,3 +629,54 @@ pub extern "C" fn blaze_print_f64(x: f64) {
 pub extern "C" fn blaze_print_bool(x: bool) {
     print!("{}", x);
 }
+
+// Logging intrinsics
+//
+// Levels: 0 = error, 1 = warn, 2 = info, 3 = debug, 4 = trace. Messages pass
+// through the `tracing` subscriber installed by the host (which applies
+// `BLAZE_LOG`/`RUST_LOG`) after the runtime threshold set by
+// `blaze_log_set_level`. The threshold starts at trace, leaving all
+// filtering to the subscriber.
+pub const BLAZE_LOG_ERROR: c_int = 0;
+pub const BLAZE_LOG_WARN: c_int = 1;
+pub const BLAZE_LOG_INFO: c_int = 2;
+pub const BLAZE_LOG_DEBUG: c_int = 3;
+pub const BLAZE_LOG_TRACE: c_int = 4;
+
+static LOG_THRESHOLD: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(BLAZE_LOG_TRACE);
+
+/// Logs a UTF-8 message at `level`. Returns 0 on success (including when the
+/// message is below the threshold) or -1 for an invalid level or message.
+#[no_mangle]
+pub unsafe extern "C" fn blaze_log(level: c_int, msg_ptr: *const u8, msg_len: usize) -> c_int {
+    if !(BLAZE_LOG_ERROR..=BLAZE_LOG_TRACE).contains(&level) {
+        return -1;
+    }
+    let message = match input_bytes(msg_ptr, msg_len).map(std::str::from_utf8) {
+        Some(Ok(message)) => message,
+        _ => return -1,
+    };
+    if level > LOG_THRESHOLD.load(Ordering::Relaxed) {
+        return 0;
+    }
+
+    match level {
+        BLAZE_LOG_ERROR => tracing::error!(target: "blaze", "{}", message),
+        BLAZE_LOG_WARN => tracing::warn!(target: "blaze", "{}", message),
+        BLAZE_LOG_INFO => tracing::info!(target: "blaze", "{}", message),
+        BLAZE_LOG_DEBUG => tracing::debug!(target: "blaze", "{}", message),
+        _ => tracing::trace!(target: "blaze", "{}", message),
+    }
+    0
+}
+
+/// Sets the most verbose level that `blaze_log` emits. Returns -1 for an
+/// invalid level.
+#[no_mangle]
+pub extern "C" fn blaze_log_set_level(level: c_int) -> c_int {
+    if !(BLAZE_LOG_ERROR..=BLAZE_LOG_TRACE).contains(&level) {
+        return -1;
+    }
+    LOG_THRESHOLD.store(level, Ordering::Relaxed);
+    0
+}
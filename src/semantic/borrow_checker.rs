@@ -1,4 +1,4 @@
-use crate::parser::{Program, Item, Function, Statement, Expression};
+use crate::parser::{Program, Item, Function, Statement, Expression, Pattern, UnaryOp, UnaryOperator};
 use crate::semantic::SymbolTable;
 use anyhow::{Result, bail};
 use petgraph::graph::{DiGraph, NodeIndex};
@@ -6,6 +6,12 @@ use petgraph::algo::dominators;
 use petgraph::visit::EdgeRef;
 use std::collections::{HashMap, HashSet, VecDeque};
 
+/// Methods assumed to take `&self`, so calling them from a closure only
+/// borrows the receiver immutably.
+const READ_ONLY_METHODS: &[&str] = &[
+    "len", "is_empty", "get", "iter", "contains", "first", "last", "clone", "to_string", "as_str",
+];
+
 pub struct BorrowChecker {
     next_loan_id: usize,
     next_lifetime_id: usize,
@@ -17,6 +23,10 @@ pub struct BorrowChecker {
     kill_sets: HashMap<NodeIndex, HashSet<usize>>,
     active_loans: HashMap<String, Vec<Loan>>,
     all_loans: Vec<Loan>,
+    closure_captures: Vec<Loan>,
+    /// A scope past every statement of the function being checked; loans
+    /// held by closures bound with `let` last until here.
+    function_end: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -61,16 +71,15 @@ impl BorrowChecker {
             kill_sets: HashMap::new(),
             active_loans: HashMap::new(),
             all_loans: Vec::new(),
+            closure_captures: Vec::new(),
+            function_end: 0,
         }
     }
 
     pub fn check(&mut self, program: &Program, symbol_table: &SymbolTable) -> Result<()> {
         for item in &program.items {
-            match item {
-                Item::Function(func) => {
-                    self.check_function(func, symbol_table)?;
-                }
-                Item::Struct(_) => {}
+            if let Item::Function(func) = item {
+                self.check_function(func, symbol_table)?;
             }
         }
         Ok(())
@@ -116,10 +125,29 @@ impl BorrowChecker {
     }
 
     fn analyze_borrows_in_function(&mut self, func: &Function) -> Result<()> {
+        self.closure_captures.clear();
+        self.function_end = Self::count_statements(&func.body);
         self.analyze_statements_with_scope(&func.body, 0)?;
         Ok(())
     }
 
+    /// Counts statements at every nesting level. Each statement opens at most
+    /// one scope, so this bounds the scopes used while analyzing them.
+    fn count_statements(statements: &[Statement]) -> usize {
+        statements.iter().map(|stmt| match stmt {
+            Statement::If { then_body, else_body, .. } => {
+                1 + Self::count_statements(then_body)
+                    + else_body.as_deref().map_or(0, Self::count_statements)
+            }
+            Statement::While { body, .. }
+            | Statement::For { body, .. }
+            | Statement::Loop { body }
+            | Statement::Block(body) => 1 + Self::count_statements(body),
+            Statement::Labeled { body, .. } => 1 + Self::count_statements(std::slice::from_ref(body.as_ref())),
+            _ => 1,
+        }).sum()
+    }
+
     fn analyze_statements_with_scope(&mut self, statements: &[Statement], scope_start: usize) -> Result<usize> {
         let mut current_scope = scope_start;
         
@@ -132,10 +160,20 @@ impl BorrowChecker {
 
     fn analyze_statement_with_scope(&mut self, stmt: &Statement, scope_start: usize) -> Result<usize> {
         match stmt {
-            Statement::Let { name, value, mutable, .. } => {
-                let scope_end = scope_start + 1;
-                self.analyze_expression_with_lifetime(value, name, *mutable, scope_start, scope_end)?;
-                Ok(scope_end)
+            Statement::Let { name, value: Some(value), mutable, .. } => {
+                // A closure bound to a name may be called for the rest of the
+                // function, so its captures stay borrowed until the end;
+                // anything else is released when the statement ends.
+                let loan_end = if matches!(value, Expression::Closure { .. }) {
+                    self.function_end
+                } else {
+                    scope_start + 1
+                };
+                self.analyze_expression_with_lifetime(value, name, *mutable, scope_start, loan_end)?;
+                Ok(scope_start + 1)
+            }
+            Statement::Let { value: None, .. } => {
+                Ok(scope_start + 1)
             }
             Statement::Expression(expr) => {
                 let scope_end = scope_start + 1;
@@ -170,6 +208,7 @@ impl BorrowChecker {
             Statement::Return(None) => {
                 Ok(scope_start + 1)
             }
+            _ => Ok(scope_start + 1),
         }
     }
 
@@ -192,6 +231,7 @@ impl BorrowChecker {
                 };
                 self.next_loan_id += 1;
                 
+                self.check_closure_capture_conflicts(&loan)?;
                 self.active_loans.entry(location.to_string())
                     .or_insert_with(Vec::new)
                     .push(loan.clone());
@@ -203,8 +243,39 @@ impl BorrowChecker {
                 self.analyze_expression_with_lifetime(right, location, false, scope_start, scope_end)?;
             }
             
-            Expression::Unary { expr, .. } => {
-                self.analyze_expression_with_lifetime(expr, location, false, scope_start, scope_end)?;
+            Expression::Unary { op, expr } => {
+                let mutable = *op == UnaryOp::RefMut;
+                self.analyze_expression_with_lifetime(expr, location, mutable, scope_start, scope_end)?;
+            }
+            
+            Expression::Closure { params, body } => {
+                let params: HashSet<String> = params.iter().map(|p| p.name.clone()).collect();
+                let mut captures = HashMap::new();
+                Self::collect_captures(body, &params, &mut captures);
+                
+                let mut captures: Vec<_> = captures.into_iter().collect();
+                captures.sort();
+                
+                // The captures are borrowed for as long as the closure may be
+                // called: `scope_end` is the end of the function for a closure
+                // bound by `let`, and the end of the statement otherwise.
+                for (name, mutated) in captures {
+                    let lifetime = self.new_lifetime(scope_start, scope_end);
+                    let loan = Loan {
+                        id: self.next_loan_id,
+                        location: name,
+                        mutable: mutated,
+                        lifetime,
+                    };
+                    self.next_loan_id += 1;
+                    
+                    self.check_closure_capture_conflicts(&loan)?;
+                    self.active_loans.entry(location.to_string())
+                        .or_insert_with(Vec::new)
+                        .push(loan.clone());
+                    self.all_loans.push(loan.clone());
+                    self.closure_captures.push(loan);
+                }
             }
             
             Expression::Call { func, args } => {
@@ -219,6 +290,199 @@ impl BorrowChecker {
             Expression::BoolLit(_) => {
                 // Literals don't create borrows
             }
+            
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Records every free variable used in a closure body, marking it `true`
+    /// when the body may mutate it: by assigning to it or to one of its
+    /// fields or elements, by taking `&mut` of it, or by calling a method on
+    /// it. Receiver types aren't known here, so only methods in
+    /// `READ_ONLY_METHODS` are assumed to take `&self`.
+    fn collect_captures(expr: &Expression, bound: &HashSet<String>, captures: &mut HashMap<String, bool>) {
+        match expr {
+            Expression::Ident(name) | Expression::Identifier(name) => {
+                if !bound.contains(name) {
+                    captures.entry(name.clone()).or_insert(false);
+                }
+            }
+            Expression::Assignment { target, value } => {
+                Self::collect_mutated_place(target, bound, captures);
+                Self::collect_captures(value, bound, captures);
+            }
+            Expression::Unary { op: UnaryOp::RefMut, expr }
+            | Expression::UnaryOp { operator: UnaryOperator::MutableReference, operand: expr } => {
+                Self::collect_mutated_place(expr, bound, captures);
+            }
+            Expression::Unary { expr, .. } | Expression::UnaryOp { operand: expr, .. } => {
+                Self::collect_captures(expr, bound, captures);
+            }
+            Expression::Binary { left, right, .. } | Expression::BinaryOp { left, right, .. } => {
+                Self::collect_captures(left, bound, captures);
+                Self::collect_captures(right, bound, captures);
+            }
+            Expression::Call { func, args } | Expression::CallAlt { callee: func, args } => {
+                Self::collect_captures(func, bound, captures);
+                for arg in args {
+                    Self::collect_captures(arg, bound, captures);
+                }
+            }
+            Expression::MethodCall { object, method, args } => {
+                if READ_ONLY_METHODS.contains(&method.as_str()) {
+                    Self::collect_captures(object, bound, captures);
+                } else {
+                    Self::collect_mutated_place(object, bound, captures);
+                }
+                for arg in args {
+                    Self::collect_captures(arg, bound, captures);
+                }
+            }
+            Expression::FieldAccess { object, .. } => Self::collect_captures(object, bound, captures),
+            Expression::Index { object, index } => {
+                Self::collect_captures(object, bound, captures);
+                Self::collect_captures(index, bound, captures);
+            }
+            Expression::TupleLiteral(elements) | Expression::ArrayLiteral(elements) => {
+                for element in elements {
+                    Self::collect_captures(element, bound, captures);
+                }
+            }
+            Expression::StructLiteral { fields, .. } => {
+                for field in fields {
+                    Self::collect_captures(&field.value, bound, captures);
+                }
+            }
+            Expression::Range { start, end, .. } => {
+                Self::collect_captures(start, bound, captures);
+                Self::collect_captures(end, bound, captures);
+            }
+            Expression::If { condition, then_branch, else_branch } => {
+                Self::collect_captures(condition, bound, captures);
+                Self::collect_captures(then_branch, bound, captures);
+                if let Some(else_branch) = else_branch {
+                    Self::collect_captures(else_branch, bound, captures);
+                }
+            }
+            Expression::Match { expression, arms } => {
+                Self::collect_captures(expression, bound, captures);
+                for arm in arms {
+                    let mut bound = bound.clone();
+                    Self::bind_pattern(&arm.pattern, &mut bound);
+                    if let Some(guard) = &arm.guard {
+                        Self::collect_captures(guard, &bound, captures);
+                    }
+                    Self::collect_captures(&arm.body, &bound, captures);
+                }
+            }
+            Expression::Closure { params, body } => {
+                // Whatever a nested closure captures, the outer one must too.
+                let mut bound = bound.clone();
+                bound.extend(params.iter().map(|p| p.name.clone()));
+                Self::collect_captures(body, &bound, captures);
+            }
+            Expression::Block(statements) => {
+                Self::collect_statement_captures(statements, &mut bound.clone(), captures);
+            }
+            Expression::IntLit(_) | Expression::FloatLit(_) | Expression::StringLit(_) | Expression::CharLit(_)
+            | Expression::BoolLit(_) | Expression::Literal(_) => {}
+        }
+    }
+
+    /// Collects captures from `statements`, adding each `let` to `bound` for
+    /// the statements after it.
+    fn collect_statement_captures(
+        statements: &[Statement],
+        bound: &mut HashSet<String>,
+        captures: &mut HashMap<String, bool>,
+    ) {
+        for stmt in statements {
+            match stmt {
+                Statement::Let { name, value, .. } => {
+                    if let Some(value) = value {
+                        Self::collect_captures(value, bound, captures);
+                    }
+                    bound.insert(name.clone());
+                }
+                Statement::Expression(expr)
+                | Statement::Return(Some(expr))
+                | Statement::Break { value: Some(expr), .. } => {
+                    Self::collect_captures(expr, bound, captures);
+                }
+                Statement::While { condition, body } => {
+                    Self::collect_captures(condition, bound, captures);
+                    Self::collect_statement_captures(body, &mut bound.clone(), captures);
+                }
+                Statement::For { variable, iterable, body } => {
+                    Self::collect_captures(iterable, bound, captures);
+                    let mut bound = bound.clone();
+                    bound.insert(variable.clone());
+                    Self::collect_statement_captures(body, &mut bound, captures);
+                }
+                Statement::Loop { body } | Statement::Block(body) => {
+                    Self::collect_statement_captures(body, &mut bound.clone(), captures);
+                }
+                Statement::Labeled { body, .. } => {
+                    Self::collect_statement_captures(std::slice::from_ref(body.as_ref()), bound, captures);
+                }
+                Statement::If { condition, then_body, else_body } => {
+                    Self::collect_captures(condition, bound, captures);
+                    Self::collect_statement_captures(then_body, &mut bound.clone(), captures);
+                    if let Some(else_body) = else_body {
+                        Self::collect_statement_captures(else_body, &mut bound.clone(), captures);
+                    }
+                }
+                Statement::Return(None) | Statement::Break { value: None, .. } | Statement::Continue { .. } => {}
+            }
+        }
+    }
+
+    /// Marks the variable a place expression such as `x`, `x.field` or
+    /// `x[i]` is rooted in as mutated, and collects the rest normally.
+    fn collect_mutated_place(place: &Expression, bound: &HashSet<String>, captures: &mut HashMap<String, bool>) {
+        match place {
+            Expression::Ident(name) | Expression::Identifier(name) => {
+                if !bound.contains(name) {
+                    captures.insert(name.clone(), true);
+                }
+            }
+            Expression::FieldAccess { object, .. } => Self::collect_mutated_place(object, bound, captures),
+            Expression::Index { object, index } => {
+                Self::collect_mutated_place(object, bound, captures);
+                Self::collect_captures(index, bound, captures);
+            }
+            other => Self::collect_captures(other, bound, captures),
+        }
+    }
+
+    fn bind_pattern(pattern: &Pattern, bound: &mut HashSet<String>) {
+        match pattern {
+            Pattern::Identifier(name) => {
+                bound.insert(name.clone());
+            }
+            Pattern::Tuple(patterns) | Pattern::TupleStruct { fields: patterns, .. } => {
+                for pattern in patterns {
+                    Self::bind_pattern(pattern, bound);
+                }
+            }
+            Pattern::Struct { fields, .. } => {
+                for (_, pattern) in fields {
+                    Self::bind_pattern(pattern, bound);
+                }
+            }
+            Pattern::Literal(_) | Pattern::Wildcard => {}
+        }
+    }
+
+    fn check_closure_capture_conflicts(&self, loan: &Loan) -> Result<()> {
+        for capture in &self.closure_captures {
+            if capture.location == loan.location
+                && (capture.mutable || loan.mutable)
+                && Self::lifetimes_overlap(&capture.lifetime, &loan.lifetime)
+            {
+                return self.report_borrow_conflict(capture, loan);
+            }
         }
         Ok(())
     }
@@ -342,6 +606,14 @@ impl BorrowChecker {
                 self.cfg.add_edge(loop_exit_node, exit, ControlFlowEdge);
                 Ok(exit)
             }
+            
+            _ => {
+                if let Some(block) = self.cfg.node_weight_mut(entry) {
+                    block.statements.push("statement".to_string());
+                }
+                self.cfg.add_edge(entry, exit, ControlFlowEdge);
+                Ok(exit)
+            }
         }
     }
 
@@ -529,29 +801,41 @@ impl BorrowChecker {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::parser::{Expression, BinaryOp};
+    use crate::parser::{Expression, BinaryOp, ClosureParam, MatchArm, Visibility};
 
     fn create_test_symbol_table() -> SymbolTable {
         SymbolTable::new()
     }
 
+    /// A private, non-generic `fn test()` with the given body.
+    fn function_with_body(body: Vec<Statement>) -> Function {
+        Function {
+            attributes: Vec::new(),
+            visibility: Visibility::Private,
+            name: "test".to_string(),
+            generics: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            where_clause: None,
+            body,
+            is_async: false,
+            is_unsafe: false,
+            is_const: false,
+        }
+    }
+
     #[test]
     fn test_cfg_construction_simple() {
         let mut checker = BorrowChecker::new();
-        let func = Function {
-            name: "test".to_string(),
-            params: vec![],
-            return_type: None,
-            body: vec![
-                Statement::Let {
-                    name: "x".to_string(),
-                    mutable: false,
-                    ty: None,
-                    value: Expression::IntLit(42),
-                },
-                Statement::Return(Some(Expression::Ident("x".to_string()))),
-            ],
-        };
+        let func = function_with_body(vec![
+            Statement::Let {
+                name: "x".to_string(),
+                mutable: false,
+                ty: None,
+                value: Some(Expression::IntLit(42)),
+            },
+            Statement::Return(Some(Expression::Ident("x".to_string()))),
+        ]);
 
         let result = checker.build_cfg(&func);
         assert!(result.is_ok());
@@ -561,22 +845,17 @@ mod tests {
     #[test]
     fn test_cfg_construction_if_statement() {
         let mut checker = BorrowChecker::new();
-        let func = Function {
-            name: "test".to_string(),
-            params: vec![],
-            return_type: None,
-            body: vec![
-                Statement::If {
-                    condition: Expression::BoolLit(true),
-                    then_body: vec![
-                        Statement::Expression(Expression::IntLit(1)),
-                    ],
-                    else_body: Some(vec![
-                        Statement::Expression(Expression::IntLit(2)),
-                    ]),
-                },
-            ],
-        };
+        let func = function_with_body(vec![
+            Statement::If {
+                condition: Expression::BoolLit(true),
+                then_body: vec![
+                    Statement::Expression(Expression::IntLit(1)),
+                ],
+                else_body: Some(vec![
+                    Statement::Expression(Expression::IntLit(2)),
+                ]),
+            },
+        ]);
 
         let result = checker.build_cfg(&func);
         assert!(result.is_ok());
@@ -586,19 +865,14 @@ mod tests {
     #[test]
     fn test_cfg_construction_while_loop() {
         let mut checker = BorrowChecker::new();
-        let func = Function {
-            name: "test".to_string(),
-            params: vec![],
-            return_type: None,
-            body: vec![
-                Statement::While {
-                    condition: Expression::BoolLit(true),
-                    body: vec![
-                        Statement::Expression(Expression::IntLit(1)),
-                    ],
-                },
-            ],
-        };
+        let func = function_with_body(vec![
+            Statement::While {
+                condition: Expression::BoolLit(true),
+                body: vec![
+                    Statement::Expression(Expression::IntLit(1)),
+                ],
+            },
+        ]);
 
         let result = checker.build_cfg(&func);
         assert!(result.is_ok());
@@ -608,25 +882,20 @@ mod tests {
     #[test]
     fn test_loan_tracking_simple() {
         let mut checker = BorrowChecker::new();
-        let func = Function {
-            name: "test".to_string(),
-            params: vec![],
-            return_type: None,
-            body: vec![
-                Statement::Let {
-                    name: "x".to_string(),
-                    mutable: false,
-                    ty: None,
-                    value: Expression::IntLit(42),
-                },
-                Statement::Let {
-                    name: "y".to_string(),
-                    mutable: false,
-                    ty: None,
-                    value: Expression::Ident("x".to_string()),
-                },
-            ],
-        };
+        let func = function_with_body(vec![
+            Statement::Let {
+                name: "x".to_string(),
+                mutable: false,
+                ty: None,
+                value: Some(Expression::IntLit(42)),
+            },
+            Statement::Let {
+                name: "y".to_string(),
+                mutable: false,
+                ty: None,
+                value: Some(Expression::Ident("x".to_string())),
+            },
+        ]);
 
         let result = checker.analyze_borrows_in_function(&func);
         assert!(result.is_ok());
@@ -636,23 +905,18 @@ mod tests {
     #[test]
     fn test_loan_tracking_binary_expression() {
         let mut checker = BorrowChecker::new();
-        let func = Function {
-            name: "test".to_string(),
-            params: vec![],
-            return_type: None,
-            body: vec![
-                Statement::Let {
-                    name: "result".to_string(),
-                    mutable: false,
-                    ty: None,
-                    value: Expression::Binary {
-                        op: BinaryOp::Add,
-                        left: Box::new(Expression::Ident("x".to_string())),
-                        right: Box::new(Expression::Ident("y".to_string())),
-                    },
-                },
-            ],
-        };
+        let func = function_with_body(vec![
+            Statement::Let {
+                name: "result".to_string(),
+                mutable: false,
+                ty: None,
+                value: Some(Expression::Binary {
+                    op: BinaryOp::Add,
+                    left: Box::new(Expression::Ident("x".to_string())),
+                    right: Box::new(Expression::Ident("y".to_string())),
+                }),
+            },
+        ]);
 
         let result = checker.analyze_borrows_in_function(&func);
         assert!(result.is_ok());
@@ -664,25 +928,20 @@ mod tests {
         let mut checker = BorrowChecker::new();
         let symbol_table = create_test_symbol_table();
         
-        let func = Function {
-            name: "test".to_string(),
-            params: vec![],
-            return_type: None,
-            body: vec![
-                Statement::Let {
-                    name: "y".to_string(),
-                    mutable: false,
-                    ty: None,
-                    value: Expression::Ident("x".to_string()),
-                },
-                Statement::Let {
-                    name: "z".to_string(),
-                    mutable: false,
-                    ty: None,
-                    value: Expression::Ident("x".to_string()),
-                },
-            ],
-        };
+        let func = function_with_body(vec![
+            Statement::Let {
+                name: "y".to_string(),
+                mutable: false,
+                ty: None,
+                value: Some(Expression::Ident("x".to_string())),
+            },
+            Statement::Let {
+                name: "z".to_string(),
+                mutable: false,
+                ty: None,
+                value: Some(Expression::Ident("x".to_string())),
+            },
+        ]);
 
         let result = checker.check_function(&func, &symbol_table);
         assert!(result.is_ok());
@@ -693,25 +952,20 @@ mod tests {
         let mut checker = BorrowChecker::new();
         let symbol_table = create_test_symbol_table();
         
-        let func = Function {
-            name: "test".to_string(),
-            params: vec![],
-            return_type: None,
-            body: vec![
-                Statement::Let {
-                    name: "y".to_string(),
-                    mutable: false,
-                    ty: None,
-                    value: Expression::Ident("x".to_string()),
-                },
-                Statement::Let {
-                    name: "z".to_string(),
-                    mutable: true,
-                    ty: None,
-                    value: Expression::Ident("x".to_string()),
-                },
-            ],
-        };
+        let func = function_with_body(vec![
+            Statement::Let {
+                name: "y".to_string(),
+                mutable: false,
+                ty: None,
+                value: Some(Expression::Ident("x".to_string())),
+            },
+            Statement::Let {
+                name: "z".to_string(),
+                mutable: true,
+                ty: None,
+                value: Some(Expression::Ident("x".to_string())),
+            },
+        ]);
 
         let result = checker.check_function(&func, &symbol_table);
         assert!(result.is_err());
@@ -726,25 +980,20 @@ mod tests {
         let mut checker = BorrowChecker::new();
         let symbol_table = create_test_symbol_table();
         
-        let func = Function {
-            name: "test".to_string(),
-            params: vec![],
-            return_type: None,
-            body: vec![
-                Statement::Let {
-                    name: "y".to_string(),
-                    mutable: true,
-                    ty: None,
-                    value: Expression::Ident("x".to_string()),
-                },
-                Statement::Let {
-                    name: "z".to_string(),
-                    mutable: true,
-                    ty: None,
-                    value: Expression::Ident("x".to_string()),
-                },
-            ],
-        };
+        let func = function_with_body(vec![
+            Statement::Let {
+                name: "y".to_string(),
+                mutable: true,
+                ty: None,
+                value: Some(Expression::Ident("x".to_string())),
+            },
+            Statement::Let {
+                name: "z".to_string(),
+                mutable: true,
+                ty: None,
+                value: Some(Expression::Ident("x".to_string())),
+            },
+        ]);
 
         let result = checker.check_function(&func, &symbol_table);
         assert!(result.is_err());
@@ -757,30 +1006,25 @@ mod tests {
     #[test]
     fn test_dataflow_convergence() {
         let mut checker = BorrowChecker::new();
-        let func = Function {
-            name: "test".to_string(),
-            params: vec![],
-            return_type: None,
-            body: vec![
-                Statement::Let {
-                    name: "x".to_string(),
-                    mutable: false,
-                    ty: None,
-                    value: Expression::IntLit(42),
-                },
-                Statement::While {
-                    condition: Expression::BoolLit(true),
-                    body: vec![
-                        Statement::Let {
-                            name: "y".to_string(),
-                            mutable: false,
-                            ty: None,
-                            value: Expression::Ident("x".to_string()),
-                        },
-                    ],
-                },
-            ],
-        };
+        let func = function_with_body(vec![
+            Statement::Let {
+                name: "x".to_string(),
+                mutable: false,
+                ty: None,
+                value: Some(Expression::IntLit(42)),
+            },
+            Statement::While {
+                condition: Expression::BoolLit(true),
+                body: vec![
+                    Statement::Let {
+                        name: "y".to_string(),
+                        mutable: false,
+                        ty: None,
+                        value: Some(Expression::Ident("x".to_string())),
+                    },
+                ],
+            },
+        ]);
 
         checker.build_cfg(&func).unwrap();
         checker.analyze_borrows_in_function(&func).unwrap();
@@ -793,28 +1037,200 @@ mod tests {
     #[test]
     fn test_nested_control_flow() {
         let mut checker = BorrowChecker::new();
-        let func = Function {
-            name: "test".to_string(),
+        let func = function_with_body(vec![
+            Statement::If {
+                condition: Expression::BoolLit(true),
+                then_body: vec![
+                    Statement::While {
+                        condition: Expression::BoolLit(true),
+                        body: vec![
+                            Statement::Expression(Expression::IntLit(1)),
+                        ],
+                    },
+                ],
+                else_body: None,
+            },
+        ]);
+
+        let result = checker.build_cfg(&func);
+        assert!(result.is_ok());
+        assert!(checker.cfg.node_count() >= 6);
+    }
+
+    /// `|| x = x + 1`
+    fn incrementing_closure() -> Expression {
+        Expression::Closure {
             params: vec![],
-            return_type: None,
-            body: vec![
-                Statement::If {
-                    condition: Expression::BoolLit(true),
-                    then_body: vec![
-                        Statement::While {
-                            condition: Expression::BoolLit(true),
-                            body: vec![
-                                Statement::Expression(Expression::IntLit(1)),
-                            ],
-                        },
-                    ],
-                    else_body: None,
+            body: Box::new(Expression::Assignment {
+                target: ident("x"),
+                value: Box::new(Expression::Binary {
+                    op: BinaryOp::Add,
+                    left: ident("x"),
+                    right: Box::new(Expression::IntLit(1)),
+                }),
+            }),
+        }
+    }
+
+    /// `let r = &x;`
+    fn shared_borrow_of_x() -> Statement {
+        Statement::Let {
+            name: "r".to_string(),
+            mutable: false,
+            ty: None,
+            value: Some(Expression::Unary { op: UnaryOp::Ref, expr: ident("x") }),
+        }
+    }
+
+    #[test]
+    fn test_closure_mutable_capture_conflicts_with_later_borrow() {
+        let mut checker = BorrowChecker::new();
+        let func = function_with_body(vec![
+            Statement::Let {
+                name: "inc".to_string(),
+                mutable: false,
+                ty: None,
+                value: Some(incrementing_closure()),
+            },
+            shared_borrow_of_x(),
+        ]);
+
+        let error = checker.analyze_borrows_in_function(&func).unwrap_err().to_string();
+        assert!(error.contains("`x`"));
+        assert!(!error.contains(&usize::MAX.to_string()));
+    }
+
+    #[test]
+    fn test_temporary_closure_releases_captures_after_its_statement() {
+        let mut checker = BorrowChecker::new();
+        let func = function_with_body(vec![
+            Statement::Expression(Expression::Call {
+                func: Box::new(incrementing_closure()),
+                args: vec![],
+            }),
+            shared_borrow_of_x(),
+        ]);
+
+        assert!(checker.analyze_borrows_in_function(&func).is_ok());
+    }
+
+    #[test]
+    fn test_closure_immutable_capture_allows_shared_borrow() {
+        let mut checker = BorrowChecker::new();
+        let func = function_with_body(vec![
+            Statement::Let {
+                name: "get".to_string(),
+                mutable: false,
+                ty: None,
+                value: Some(Expression::Closure { params: vec![], body: ident("x") }),
+            },
+            shared_borrow_of_x(),
+        ]);
+
+        assert!(checker.analyze_borrows_in_function(&func).is_ok());
+    }
+
+    fn ident(name: &str) -> Box<Expression> {
+        Box::new(Expression::Ident(name.to_string()))
+    }
+
+    /// The captures of `|| body`, sorted by name.
+    fn captures_of(body: Expression) -> Vec<(String, bool)> {
+        let mut captures = HashMap::new();
+        BorrowChecker::collect_captures(&body, &HashSet::new(), &mut captures);
+        let mut captures: Vec<_> = captures.into_iter().collect();
+        captures.sort();
+        captures
+    }
+
+    fn captured(name: &str, mutated: bool) -> (String, bool) {
+        (name.to_string(), mutated)
+    }
+
+    #[test]
+    fn test_method_call_captures_receiver_mutably() {
+        // || v.push(n)
+        let push = Expression::MethodCall { object: ident("v"), method: "push".to_string(), args: vec![*ident("n")] };
+        assert_eq!(captures_of(push), vec![captured("n", false), captured("v", true)]);
+
+        // || v.len()
+        let len = Expression::MethodCall { object: ident("v"), method: "len".to_string(), args: vec![] };
+        assert_eq!(captures_of(len), vec![captured("v", false)]);
+    }
+
+    #[test]
+    fn test_field_and_index_assignment_capture_root_mutably() {
+        // || p.x = 1
+        let field = Expression::Assignment {
+            target: Box::new(Expression::FieldAccess { object: ident("p"), field: "x".to_string() }),
+            value: Box::new(Expression::IntLit(1)),
+        };
+        assert_eq!(captures_of(field), vec![captured("p", true)]);
+
+        // || a[i] = 0
+        let index = Expression::Assignment {
+            target: Box::new(Expression::Index { object: ident("a"), index: ident("i") }),
+            value: Box::new(Expression::IntLit(0)),
+        };
+        assert_eq!(captures_of(index), vec![captured("a", true), captured("i", false)]);
+
+        // || p.x
+        let read = Expression::FieldAccess { object: ident("p"), field: "x".to_string() };
+        assert_eq!(captures_of(read), vec![captured("p", false)]);
+    }
+
+    #[test]
+    fn test_ref_mut_captures_mutably() {
+        // || &mut x
+        let ref_mut = Expression::Unary { op: UnaryOp::RefMut, expr: ident("x") };
+        assert_eq!(captures_of(ref_mut), vec![captured("x", true)]);
+
+        // || &x
+        let shared = Expression::Unary { op: UnaryOp::Ref, expr: ident("x") };
+        assert_eq!(captures_of(shared), vec![captured("x", false)]);
+    }
+
+    #[test]
+    fn test_captures_inside_if_and_match() {
+        // || if c { x = 1 } else { y }
+        let branch = Expression::If {
+            condition: ident("c"),
+            then_branch: Box::new(Expression::Assignment { target: ident("x"), value: Box::new(Expression::IntLit(1)) }),
+            else_branch: Some(ident("y")),
+        };
+        assert_eq!(captures_of(branch), vec![captured("c", false), captured("x", true), captured("y", false)]);
+
+        // || match opt { Some(v) => v + k, _ => 0 }
+        let matched = Expression::Match {
+            expression: ident("opt"),
+            arms: vec![
+                MatchArm {
+                    pattern: Pattern::TupleStruct {
+                        name: "Some".to_string(),
+                        fields: vec![Pattern::Identifier("v".to_string())],
+                    },
+                    guard: None,
+                    body: Expression::Binary { op: BinaryOp::Add, left: ident("v"), right: ident("k") },
                 },
+                MatchArm { pattern: Pattern::Wildcard, guard: None, body: Expression::IntLit(0) },
             ],
         };
+        assert_eq!(captures_of(matched), vec![captured("k", false), captured("opt", false)]);
+    }
 
-        let result = checker.build_cfg(&func);
-        assert!(result.is_ok());
-        assert!(checker.cfg.node_count() >= 6);
+    #[test]
+    fn test_nested_closure_and_let_captures() {
+        // || { let t = total; |n| { total = t + n } }
+        let body = Expression::Block(vec![
+            Statement::Let { name: "t".to_string(), mutable: false, ty: None, value: Some(*ident("total")) },
+            Statement::Expression(Expression::Closure {
+                params: vec![ClosureParam { name: "n".to_string(), ty: None }],
+                body: Box::new(Expression::Assignment {
+                    target: ident("total"),
+                    value: Box::new(Expression::Binary { op: BinaryOp::Add, left: ident("t"), right: ident("n") }),
+                }),
+            }),
+        ]);
+        assert_eq!(captures_of(body), vec![captured("total", true)]);
     }
 }
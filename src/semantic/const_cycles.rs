@@ -0,0 +1,88 @@
+use crate::error::CompileError;
+use crate::incremental::item_graph::collect_expression;
+use crate::incremental::strongly_connected_components;
+use crate::parser::{Item, Program};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+/// Rejects const and static initializers that depend on themselves, such as
+/// `const A = B + 1; const B = A - 1;`, before anything tries to evaluate
+/// them.
+pub struct ConstCycleChecker;
+
+impl ConstCycleChecker {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn check(&self, program: &Program) -> Result<()> {
+        let graph = Self::initializer_graph(program);
+        let cycle = strongly_connected_components(&graph)
+            .into_iter()
+            .find(|component| component.len() > 1 || graph[&component[0]].contains(&component[0]));
+
+        match cycle {
+            Some(members) => {
+                let path = Self::cycle_path(&graph, &members);
+                Err(CompileError::SemanticError {
+                    message: format!("cyclic constant definitions: {}", path.join(" -> ")),
+                    line: None,
+                    column: None,
+                    source_snippet: None,
+                    suggestion: Some("a constant cannot depend on its own value; break the cycle with a literal".to_string()),
+                    related_info: Vec::new(),
+                }
+                .into())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Each const or static mapped to the consts and statics its initializer
+    /// reads.
+    fn initializer_graph(program: &Program) -> HashMap<String, HashSet<String>> {
+        let initializers: Vec<(&String, _)> = program
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Const(c) => Some((&c.name, &c.value)),
+                Item::Static(s) => Some((&s.name, &s.value)),
+                _ => None,
+            })
+            .collect();
+        let names: HashSet<&String> = initializers.iter().map(|(name, _)| *name).collect();
+
+        initializers
+            .into_iter()
+            .map(|(name, value)| {
+                let mut referenced = HashSet::new();
+                collect_expression(value, &mut referenced);
+                referenced.retain(|r| names.contains(r));
+                (name.clone(), referenced)
+            })
+            .collect()
+    }
+
+    /// Orders the members of a cycle as a path that returns to its start,
+    /// e.g. `A -> B -> C -> A`.
+    fn cycle_path(graph: &HashMap<String, HashSet<String>>, members: &[String]) -> Vec<String> {
+        let mut path = vec![members[0].clone()];
+        loop {
+            let current = &path[path.len() - 1];
+            let mut next: Vec<&String> = graph[current].iter().filter(|n| members.contains(*n)).collect();
+            next.sort();
+            match next.into_iter().find(|n| !path.contains(n)) {
+                Some(n) => path.push(n.clone()),
+                None => break,
+            }
+        }
+        path.push(members[0].clone());
+        path
+    }
+}
+
+impl Default for ConstCycleChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
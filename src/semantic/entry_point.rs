@@ -0,0 +1,96 @@
+use crate::error::CompileError;
+use crate::parser::{Function, Item, Program, Type};
+use anyhow::Result;
+
+/// Checks that an executable has exactly one `main` with a signature the
+/// runtime can call.
+///
+/// `main` takes either nothing or the command-line arguments as
+/// `Vec<String>` (or `&[String]`), and returns nothing or an `i32` exit
+/// code. Libraries have no entry point, so this only runs for executables.
+pub struct EntryPointChecker;
+
+impl EntryPointChecker {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn check(&self, program: &Program) -> Result<()> {
+        let mains: Vec<&Function> = program
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Function(func) if func.name == "main" => Some(func),
+                _ => None,
+            })
+            .collect();
+
+        match mains.as_slice() {
+            [] => Err(Self::error(
+                "executable has no `main` function".to_string(),
+                "add `fn main() {}`, or build the program as a library",
+            )),
+            [main] => Self::check_signature(main),
+            _ => Err(Self::error(
+                format!("`main` is defined {} times", mains.len()),
+                "an executable has exactly one entry point; rename or remove the others",
+            )),
+        }
+    }
+
+    fn check_signature(main: &Function) -> Result<()> {
+        if !main.generics.is_empty() {
+            return Err(Self::error(
+                "`main` cannot have generic parameters".to_string(),
+                "remove the generic parameters from `main`",
+            ));
+        }
+
+        match main.params.as_slice() {
+            [] => {}
+            [args] if Self::is_argv(&args.ty) => {}
+            params => {
+                let found: Vec<String> = params.iter().map(|param| format!("{}: {:?}", param.name, param.ty)).collect();
+                return Err(Self::error(
+                    format!("`main` has invalid parameters `({})`", found.join(", ")),
+                    "declare `fn main()` or `fn main(args: Vec<String>)`",
+                ));
+            }
+        }
+
+        match &main.return_type {
+            None | Some(Type::I32) => Ok(()),
+            Some(Type::Tuple(elements)) if elements.is_empty() => Ok(()),
+            Some(other) => Err(Self::error(
+                format!("`main` returns `{:?}`, but must return `()` or `i32`", other),
+                "return nothing, or an `i32` exit code",
+            )),
+        }
+    }
+
+    fn is_argv(ty: &Type) -> bool {
+        match ty {
+            Type::Generic(name, args) => name == "Vec" && args.as_slice() == [Type::String],
+            Type::Reference { mutable: false, inner } => **inner == Type::Slice(Box::new(Type::String)),
+            _ => false,
+        }
+    }
+
+    fn error(message: String, suggestion: &str) -> anyhow::Error {
+        CompileError::SemanticError {
+            message,
+            line: None,
+            column: None,
+            source_snippet: None,
+            suggestion: Some(suggestion.to_string()),
+            related_info: Vec::new(),
+        }
+        .into()
+    }
+}
+
+impl Default for EntryPointChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
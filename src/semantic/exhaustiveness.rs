@@ -0,0 +1,123 @@
+use crate::error::CompileError;
+use crate::parser::{Expression, ImplItem, Item, MatchArm, Program, Statement};
+use crate::pattern_matching::ExhaustivenessChecker;
+use anyhow::Result;
+
+/// Runs the [`ExhaustivenessChecker`] over every `match` in the program.
+///
+/// Guarded arms are left out: a guard can fail, so the arm does not cover
+/// its pattern on its own.
+pub struct MatchChecker;
+
+impl MatchChecker {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn check(&self, program: &Program) -> Result<()> {
+        for item in &program.items {
+            match item {
+                Item::Function(func) => self.check_block(&func.name, &func.body)?,
+                Item::Impl(impl_block) => {
+                    for impl_item in &impl_block.items {
+                        if let ImplItem::Function(method) = impl_item {
+                            self.check_block(&method.name, &method.body)?;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn check_block(&self, func: &str, body: &[Statement]) -> Result<()> {
+        for stmt in body {
+            self.check_statement(func, stmt)?;
+        }
+        Ok(())
+    }
+
+    fn check_statement(&self, func: &str, stmt: &Statement) -> Result<()> {
+        match stmt {
+            Statement::Expression(expr) | Statement::Let { value: Some(expr), .. } | Statement::Return(Some(expr)) => {
+                self.check_expression(func, expr)
+            }
+            Statement::While { condition: expr, body } | Statement::For { iterable: expr, body, .. } => {
+                self.check_expression(func, expr)?;
+                self.check_block(func, body)
+            }
+            Statement::Loop { body } | Statement::Block(body) => self.check_block(func, body),
+            Statement::Labeled { body, .. } => self.check_statement(func, body),
+            Statement::If { condition, then_body, else_body } => {
+                self.check_expression(func, condition)?;
+                self.check_block(func, then_body)?;
+                if let Some(else_body) = else_body {
+                    self.check_block(func, else_body)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_expression(&self, func: &str, expr: &Expression) -> Result<()> {
+        match expr {
+            Expression::Match { expression, arms } => {
+                self.check_expression(func, expression)?;
+                for arm in arms {
+                    self.check_expression(func, &arm.body)?;
+                }
+                self.check_arms(func, arms)
+            }
+            Expression::Block(body) => self.check_block(func, body),
+            Expression::If { condition, then_branch, else_branch } => {
+                self.check_expression(func, condition)?;
+                self.check_expression(func, then_branch)?;
+                if let Some(else_branch) = else_branch {
+                    self.check_expression(func, else_branch)?;
+                }
+                Ok(())
+            }
+            Expression::Binary { left, right, .. } => {
+                self.check_expression(func, left)?;
+                self.check_expression(func, right)
+            }
+            Expression::Unary { expr, .. } => self.check_expression(func, expr),
+            Expression::Call { args, .. } | Expression::TupleLiteral(args) | Expression::ArrayLiteral(args) => {
+                for arg in args {
+                    self.check_expression(func, arg)?;
+                }
+                Ok(())
+            }
+            Expression::Closure { body, .. } => self.check_expression(func, body),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_arms(&self, func: &str, arms: &[MatchArm]) -> Result<()> {
+        let mut checker = ExhaustivenessChecker::new();
+        for arm in arms.iter().filter(|arm| arm.guard.is_none()) {
+            checker.add_pattern(arm.pattern.clone());
+        }
+
+        match checker.check_exhaustive() {
+            Ok(()) => Ok(()),
+            Err(missing) => Err(CompileError::SemanticError {
+                message: format!("non-exhaustive patterns in `match` in `{}`: {} not covered", func, missing.join(", ")),
+                line: None,
+                column: None,
+                source_snippet: None,
+                suggestion: Some("add a `_ => ...` arm to handle the remaining values".to_string()),
+                related_info: Vec::new(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl Default for MatchChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,152 @@
+use crate::error::{CompileError, Diagnostic};
+use crate::parser::{Expression, ImplItem, Item, Program, Statement};
+use anyhow::Result;
+
+/// Checks that every `break 'label`/`continue 'label` names a loop that
+/// encloses it.
+///
+/// Labels are tracked on a stack while walking each function body. A label
+/// that is not on the stack is an error; a loop reusing the label of a loop
+/// around it is accepted but warned about, since the inner label hides the
+/// outer one. Closures start with an empty stack because a `break` cannot
+/// leave the closure body.
+pub struct LoopLabelChecker {
+    labels: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl LoopLabelChecker {
+    pub fn new() -> Self {
+        Self {
+            labels: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn check(&mut self, program: &Program) -> Result<Vec<Diagnostic>> {
+        self.diagnostics.clear();
+
+        for item in &program.items {
+            match item {
+                Item::Function(func) => self.check_function(&func.name, &func.body)?,
+                Item::Impl(impl_block) => {
+                    for impl_item in &impl_block.items {
+                        if let ImplItem::Function(method) = impl_item {
+                            self.check_function(&method.name, &method.body)?;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(std::mem::take(&mut self.diagnostics))
+    }
+
+    fn check_function(&mut self, func: &str, body: &[Statement]) -> Result<()> {
+        self.labels.clear();
+        self.check_block(func, body)
+    }
+
+    fn check_block(&mut self, func: &str, body: &[Statement]) -> Result<()> {
+        for stmt in body {
+            self.check_statement(func, stmt)?;
+        }
+        Ok(())
+    }
+
+    fn check_statement(&mut self, func: &str, stmt: &Statement) -> Result<()> {
+        match stmt {
+            Statement::Labeled { label, body } => {
+                if self.labels.contains(label) {
+                    self.diagnostics.push(
+                        Diagnostic::warning(format!("label `'{}` shadows a label of an enclosing loop in `{}`", label, func))
+                            .with_suggestion("give the inner loop a different label".to_string())
+                            .with_note(format!("`break '{}` and `continue '{}` now refer to the inner loop", label, label)),
+                    );
+                }
+                self.labels.push(label.clone());
+                let result = self.check_statement(func, body);
+                self.labels.pop();
+                result
+            }
+            Statement::Break { label, value } => {
+                if let Some(label) = label {
+                    self.check_label(func, "break", label)?;
+                }
+                match value {
+                    Some(value) => self.check_expression(func, value),
+                    None => Ok(()),
+                }
+            }
+            Statement::Continue { label: Some(label) } => self.check_label(func, "continue", label),
+            Statement::Expression(expr) | Statement::Let { value: Some(expr), .. } | Statement::Return(Some(expr)) => {
+                self.check_expression(func, expr)
+            }
+            Statement::While { condition: expr, body } | Statement::For { iterable: expr, body, .. } => {
+                self.check_expression(func, expr)?;
+                self.check_block(func, body)
+            }
+            Statement::Loop { body } | Statement::Block(body) => self.check_block(func, body),
+            Statement::If { condition, then_body, else_body } => {
+                self.check_expression(func, condition)?;
+                self.check_block(func, then_body)?;
+                if let Some(else_body) = else_body {
+                    self.check_block(func, else_body)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_expression(&mut self, func: &str, expr: &Expression) -> Result<()> {
+        match expr {
+            Expression::Block(body) => self.check_block(func, body),
+            Expression::If { condition, then_branch, else_branch } => {
+                self.check_expression(func, condition)?;
+                self.check_expression(func, then_branch)?;
+                if let Some(else_branch) = else_branch {
+                    self.check_expression(func, else_branch)?;
+                }
+                Ok(())
+            }
+            Expression::Match { expression, arms } => {
+                self.check_expression(func, expression)?;
+                for arm in arms {
+                    self.check_expression(func, &arm.body)?;
+                }
+                Ok(())
+            }
+            Expression::Closure { body, .. } => {
+                let enclosing = std::mem::take(&mut self.labels);
+                let result = self.check_expression(func, body);
+                self.labels = enclosing;
+                result
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_label(&self, func: &str, keyword: &str, label: &str) -> Result<()> {
+        if self.labels.iter().any(|active| active == label) {
+            return Ok(());
+        }
+
+        Err(CompileError::SemanticError {
+            message: format!("`{} '{}` in `{}` does not refer to an enclosing loop", keyword, label, func),
+            line: None,
+            column: None,
+            source_snippet: None,
+            suggestion: Some(format!("label an enclosing loop with `'{}:` or remove the label", label)),
+            related_info: Vec::new(),
+        }
+        .into())
+    }
+}
+
+impl Default for LoopLabelChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
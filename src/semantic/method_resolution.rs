@@ -0,0 +1,357 @@
+use crate::error::CompileError;
+use crate::parser::{
+    Expression, Function, GenericParamKind, ImplItem, Item, Program, Statement, TraitItem, Type, TypeBound,
+};
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Methods of standard traits that can appear as bounds without being
+/// declared in the program.
+const STD_TRAIT_METHODS: &[(&str, &[&str])] = &[
+    ("Clone", &["clone"]),
+    ("Debug", &["fmt"]),
+    ("Display", &["fmt"]),
+    ("Iterator", &["next"]),
+    ("PartialEq", &["eq", "ne"]),
+    ("PartialOrd", &["partial_cmp", "lt", "le", "gt", "ge"]),
+];
+
+/// Where a resolved method is defined.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MethodOwner {
+    Inherent,
+    Trait(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedMethod {
+    pub owner: MethodOwner,
+    pub self_type: Type,
+    pub return_type: Option<Type>,
+}
+
+/// Resolves `receiver.method()` calls against inherent and trait impls.
+///
+/// Resolution follows Rust's order: for the receiver type and then each
+/// type reached by dereferencing it, inherent methods are tried first and
+/// shadow trait methods of the same name. If no inherent method matches and
+/// more than one implemented trait provides the method, the call is ambiguous.
+/// The same holds inside generic bodies: a receiver of type `T: A + B` has
+/// only the methods of its bounds, so a method declared by both `A` and `B`
+/// must be called as `A::method(t)`.
+#[derive(Debug, Default)]
+pub struct MethodResolver {
+    inherent: HashMap<Type, HashMap<String, Option<Type>>>,
+    trait_methods: HashMap<Type, Vec<(String, String, Option<Type>)>>,
+    trait_declarations: HashMap<String, Vec<(String, Option<Type>)>>,
+}
+
+impl MethodResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn collect(program: &Program) -> Self {
+        let mut resolver = Self::new();
+
+        for (trait_name, methods) in STD_TRAIT_METHODS {
+            let methods = methods.iter().map(|name| (name.to_string(), None)).collect();
+            resolver.trait_declarations.insert(trait_name.to_string(), methods);
+        }
+
+        let mut trait_defaults: HashMap<&str, Vec<(&str, &Option<Type>)>> = HashMap::new();
+        for item in &program.items {
+            if let Item::Trait(t) = item {
+                let declared = t.items.iter().filter_map(|item| match item {
+                    TraitItem::Function(f) => Some((f.name.clone(), f.return_type.clone())),
+                    _ => None,
+                });
+                resolver.trait_declarations.insert(t.name.clone(), declared.collect());
+                let defaults = t.items.iter().filter_map(|item| match item {
+                    TraitItem::Function(f) if f.default_body.is_some() => Some((f.name.as_str(), &f.return_type)),
+                    _ => None,
+                });
+                trait_defaults.insert(&t.name, defaults.collect());
+            }
+        }
+
+        for item in &program.items {
+            let imp = match item {
+                Item::Impl(imp) => imp,
+                _ => continue,
+            };
+            let methods: Vec<(&str, &Option<Type>)> = imp
+                .items
+                .iter()
+                .filter_map(|item| match item {
+                    ImplItem::Function(f) => Some((f.name.as_str(), &f.return_type)),
+                    _ => None,
+                })
+                .collect();
+
+            match &imp.trait_path {
+                None => {
+                    let entry = resolver.inherent.entry(imp.self_type.clone()).or_default();
+                    for (name, return_type) in methods {
+                        entry.insert(name.to_string(), return_type.clone());
+                    }
+                }
+                Some(trait_name) => {
+                    let defaults = trait_defaults.get(trait_name.as_str()).cloned().unwrap_or_default();
+                    let inherited = defaults.into_iter().filter(|(name, _)| !methods.iter().any(|(m, _)| m == name));
+                    let entry = resolver.trait_methods.entry(imp.self_type.clone()).or_default();
+                    for (name, return_type) in methods.iter().copied().chain(inherited) {
+                        entry.push((trait_name.clone(), name.to_string(), return_type.clone()));
+                    }
+                }
+            }
+        }
+
+        resolver
+    }
+
+    /// Returns `Ok(None)` when no impl provides the method, so callers can
+    /// fall back to their own handling for receivers of unknown type.
+    pub fn resolve(&self, receiver: &Type, method: &str) -> Result<Option<ResolvedMethod>> {
+        let mut current = Some(receiver);
+
+        while let Some(ty) = current {
+            if let Some(return_type) = self.inherent.get(ty).and_then(|methods| methods.get(method)) {
+                return Ok(Some(ResolvedMethod {
+                    owner: MethodOwner::Inherent,
+                    self_type: ty.clone(),
+                    return_type: return_type.clone(),
+                }));
+            }
+
+            let candidates: Vec<&(String, String, Option<Type>)> = self
+                .trait_methods
+                .get(ty)
+                .map(|methods| methods.iter().filter(|(_, name, _)| name == method).collect())
+                .unwrap_or_default();
+
+            match candidates.as_slice() {
+                [] => {}
+                [(trait_name, _, return_type)] => {
+                    return Ok(Some(ResolvedMethod {
+                        owner: MethodOwner::Trait(trait_name.clone()),
+                        self_type: ty.clone(),
+                        return_type: return_type.clone(),
+                    }));
+                }
+                _ => return Err(Self::ambiguity_error(method, "receiver", &candidates)),
+            }
+
+            current = match ty {
+                Type::Reference { inner, .. } | Type::Pointer { inner, .. } => Some(inner),
+                _ => None,
+            };
+        }
+
+        Ok(None)
+    }
+
+    /// Resolves a method on a generic parameter from the traits bounding it.
+    /// Returns `Ok(None)` when no bound declares the method.
+    pub fn resolve_bounded(&self, param: &str, bounds: &[String], method: &str) -> Result<Option<ResolvedMethod>> {
+        let candidates: Vec<(String, String, Option<Type>)> = bounds
+            .iter()
+            .filter_map(|bound| {
+                let declared = self.trait_declarations.get(bound)?;
+                let (_, return_type) = declared.iter().find(|(name, _)| name == method)?;
+                Some((bound.clone(), method.to_string(), return_type.clone()))
+            })
+            .collect();
+
+        match candidates.as_slice() {
+            [] => Ok(None),
+            [(trait_name, _, return_type)] => Ok(Some(ResolvedMethod {
+                owner: MethodOwner::Trait(trait_name.clone()),
+                self_type: Type::Custom(param.to_string()),
+                return_type: return_type.clone(),
+            })),
+            _ => {
+                let candidates: Vec<&(String, String, Option<Type>)> = candidates.iter().collect();
+                Err(Self::ambiguity_error(method, param, &candidates))
+            }
+        }
+    }
+
+    /// Rejects method calls on generic parameters that more than one of the
+    /// parameter's bounds could satisfy.
+    pub fn check_generic_calls(&self, program: &Program) -> Result<()> {
+        for item in &program.items {
+            match item {
+                Item::Function(func) => self.check_generic_function(func)?,
+                Item::Impl(imp) => {
+                    for impl_item in &imp.items {
+                        if let ImplItem::Function(method) = impl_item {
+                            self.check_generic_function(method)?;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn check_generic_function(&self, func: &Function) -> Result<()> {
+        let mut bounds: HashMap<&str, Vec<String>> = HashMap::new();
+        for generic in &func.generics {
+            if generic.kind == GenericParamKind::Type {
+                bounds.entry(&generic.name).or_default().extend(trait_bounds(&generic.bounds));
+            }
+        }
+        for predicate in func.where_clause.iter().flat_map(|clause| &clause.predicates) {
+            bounds.entry(&predicate.type_param).or_default().extend(trait_bounds(&predicate.bounds));
+        }
+        if bounds.values().all(|traits| traits.len() < 2) {
+            return Ok(());
+        }
+
+        let mut bindings: HashMap<String, &str> = HashMap::new();
+        for param in &func.params {
+            if let Some(generic) = generic_name(&param.ty).filter(|name| bounds.contains_key(name)) {
+                bindings.insert(param.name.clone(), generic);
+            }
+        }
+
+        let mut checker = GenericCallChecker { resolver: self, bounds: &bounds, bindings };
+        checker.check_block(&func.body)
+    }
+
+    fn ambiguity_error(method: &str, receiver: &str, candidates: &[&(String, String, Option<Type>)]) -> anyhow::Error {
+        let traits: Vec<&str> = candidates.iter().map(|(trait_name, _, _)| trait_name.as_str()).collect();
+
+        CompileError::SemanticError {
+            message: format!("multiple applicable methods named `{}` in scope", method),
+            line: None,
+            column: None,
+            source_snippet: None,
+            suggestion: Some(format!(
+                "disambiguate the call, e.g. `{}::{}({})`",
+                traits[0], method, receiver
+            )),
+            related_info: traits
+                .iter()
+                .map(|t| format!("candidate defined in an impl of trait `{}`", t))
+                .collect(),
+        }
+        .into()
+    }
+}
+
+fn trait_bounds(bounds: &[TypeBound]) -> impl Iterator<Item = String> + '_ {
+    bounds.iter().filter_map(|bound| match bound {
+        TypeBound::Trait(name) => Some(name.clone()),
+        TypeBound::Lifetime(_) => None,
+    })
+}
+
+/// The generic parameter named by `T`, `&T` or `&mut T`.
+fn generic_name(ty: &Type) -> Option<&str> {
+    match ty {
+        Type::Custom(name) => Some(name),
+        Type::Reference { inner, .. } => generic_name(inner),
+        _ => None,
+    }
+}
+
+/// Walks a generic body, tracking which variables hold a generic parameter.
+struct GenericCallChecker<'a> {
+    resolver: &'a MethodResolver,
+    bounds: &'a HashMap<&'a str, Vec<String>>,
+    bindings: HashMap<String, &'a str>,
+}
+
+impl<'a> GenericCallChecker<'a> {
+    fn check_block(&mut self, body: &'a [Statement]) -> Result<()> {
+        let enclosing = self.bindings.clone();
+        let result = body.iter().try_for_each(|stmt| self.check_statement(stmt));
+        self.bindings = enclosing;
+        result
+    }
+
+    fn check_statement(&mut self, stmt: &'a Statement) -> Result<()> {
+        match stmt {
+            Statement::Let { name, ty, value, .. } => {
+                if let Some(value) = value {
+                    self.check_expression(value)?;
+                }
+                // A later `let` shadows the parameter binding of the same name.
+                match ty.as_ref().and_then(generic_name).filter(|generic| self.bounds.contains_key(generic)) {
+                    Some(generic) => self.bindings.insert(name.clone(), generic),
+                    None => self.bindings.remove(name),
+                };
+                Ok(())
+            }
+            Statement::Expression(expr)
+            | Statement::Return(Some(expr))
+            | Statement::Break { value: Some(expr), .. } => self.check_expression(expr),
+            Statement::While { condition: expr, body } | Statement::For { iterable: expr, body, .. } => {
+                self.check_expression(expr)?;
+                self.check_block(body)
+            }
+            Statement::Loop { body } | Statement::Block(body) => self.check_block(body),
+            Statement::Labeled { body, .. } => self.check_statement(body),
+            Statement::If { condition, then_body, else_body } => {
+                self.check_expression(condition)?;
+                self.check_block(then_body)?;
+                match else_body {
+                    Some(else_body) => self.check_block(else_body),
+                    None => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_expression(&mut self, expr: &'a Expression) -> Result<()> {
+        match expr {
+            Expression::MethodCall { object, method, args } => {
+                if let Expression::Ident(name) | Expression::Identifier(name) = object.as_ref() {
+                    if let Some(&generic) = self.bindings.get(name) {
+                        self.resolver.resolve_bounded(name, &self.bounds[generic], method)?;
+                    }
+                }
+                self.check_expression(object)?;
+                args.iter().try_for_each(|arg| self.check_expression(arg))
+            }
+            Expression::Call { func: callee, args } | Expression::CallAlt { callee, args } => {
+                self.check_expression(callee)?;
+                args.iter().try_for_each(|arg| self.check_expression(arg))
+            }
+            Expression::Binary { left, right, .. }
+            | Expression::BinaryOp { left, right, .. }
+            | Expression::Assignment { target: left, value: right }
+            | Expression::Index { object: left, index: right }
+            | Expression::Range { start: left, end: right, .. } => {
+                self.check_expression(left)?;
+                self.check_expression(right)
+            }
+            Expression::Unary { expr, .. }
+            | Expression::UnaryOp { operand: expr, .. }
+            | Expression::FieldAccess { object: expr, .. } => self.check_expression(expr),
+            Expression::TupleLiteral(items) | Expression::ArrayLiteral(items) => {
+                items.iter().try_for_each(|item| self.check_expression(item))
+            }
+            Expression::StructLiteral { fields, .. } => fields.iter().try_for_each(|field| self.check_expression(&field.value)),
+            Expression::Block(body) => self.check_block(body),
+            Expression::If { condition, then_branch, else_branch } => {
+                self.check_expression(condition)?;
+                self.check_expression(then_branch)?;
+                match else_branch {
+                    Some(else_branch) => self.check_expression(else_branch),
+                    None => Ok(()),
+                }
+            }
+            Expression::Match { expression, arms } => {
+                self.check_expression(expression)?;
+                arms.iter().try_for_each(|arm| self.check_expression(&arm.body))
+            }
+            Expression::Closure { body, .. } => self.check_expression(body),
+            _ => Ok(()),
+        }
+    }
+}
@@ -27,6 +27,12 @@ pub mod symbol_table;
 pub mod lifetime_analyzer;
 pub mod scope_resolver;
 pub mod type_inference;
+pub mod must_use;
+pub mod method_resolution;
+pub mod exhaustiveness;
+pub mod loop_labels;
+pub mod entry_point;
+pub mod const_cycles;
 
 pub use type_checker::TypeChecker;
 pub use borrow_checker::BorrowChecker;
@@ -34,8 +40,18 @@ pub use symbol_table::SymbolTable;
 pub use lifetime_analyzer::LifetimeAnalyzer;
 pub use scope_resolver::ScopeResolver;
 pub use type_inference::TypeInference;
+pub use must_use::MustUseChecker;
+pub use method_resolution::{MethodOwner, MethodResolver, ResolvedMethod};
+pub use exhaustiveness::MatchChecker;
+pub use loop_labels::LoopLabelChecker;
+pub use entry_point::EntryPointChecker;
+pub use const_cycles::ConstCycleChecker;
 
+use crate::const_eval::ConstFnChecker;
+use crate::error::{CompileError, Diagnostic};
+use crate::incremental::ItemDependencyGraph;
 use crate::parser::Program;
+use crate::trait_system::TraitRegistry;
 use anyhow::Result;
 
 /// Semantic analyzer that coordinates all semantic analysis phases.
@@ -51,6 +67,12 @@ use anyhow::Result;
 /// 3. **Lifetime Analysis**: Infers and validates reference lifetimes
 /// 4. **Type Checking**: Validates type correctness and performs inference
 /// 5. **Borrow Checking**: Enforces ownership and borrowing rules
+/// 6. **Must-Use Checking**: Flags discarded `Result`/`Option` values
+/// 7. **Const Fn Checking**: Rejects non-const operations in `const fn` bodies
+/// 8. **Match Exhaustiveness**: Rejects `match` expressions missing a catch-all arm
+/// 9. **Loop Labels**: Rejects `break`/`continue` labels that name no enclosing loop
+/// 10. **Generic Method Calls**: Rejects calls that several bounds of a generic parameter could satisfy
+/// 11. **Entry Point**: Requires a single well-formed `main` when building an executable
 pub struct SemanticAnalyzer {
     symbol_table: SymbolTable,
     type_checker: TypeChecker,
@@ -58,6 +80,11 @@ pub struct SemanticAnalyzer {
     lifetime_analyzer: LifetimeAnalyzer,
     scope_resolver: ScopeResolver,
     type_inference: TypeInference,
+    must_use_checker: MustUseChecker,
+    trait_registry: TraitRegistry,
+    executable: bool,
+    warnings: Vec<Diagnostic>,
+    item_dependencies: ItemDependencyGraph,
 }
 
 impl SemanticAnalyzer {
@@ -78,17 +105,61 @@ impl SemanticAnalyzer {
             lifetime_analyzer: LifetimeAnalyzer::new(),
             scope_resolver: ScopeResolver::new(),
             type_inference: TypeInference::new(),
+            must_use_checker: MustUseChecker::new(),
+            trait_registry: TraitRegistry::new(),
+            executable: false,
+            warnings: Vec::new(),
+            item_dependencies: ItemDependencyGraph::new(),
         }
     }
 
+    /// Turns warnings that have a strict variant (such as discarded
+    /// `Result` values) into hard errors.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.must_use_checker = self.must_use_checker.with_strict(strict);
+        self
+    }
+
+    /// Analyzes the program as an executable, which must define a valid
+    /// `main`. Libraries, the default, have no entry point to check.
+    pub fn with_executable(mut self, executable: bool) -> Self {
+        self.executable = executable;
+        self
+    }
+
+    /// Warnings collected by the most recent call to [`analyze`](Self::analyze).
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    /// Builtin traits plus the traits and impls declared by the last
+    /// analyzed program.
+    pub fn trait_registry(&self) -> &TraitRegistry {
+        &self.trait_registry
+    }
+
+    /// Item-level dependencies of the last analyzed program, used by
+    /// incremental compilation to recompile only affected items.
+    pub fn item_dependencies(&self) -> &ItemDependencyGraph {
+        &self.item_dependencies
+    }
+
     /// Performs complete semantic analysis on a program.
     ///
     /// This method runs all semantic analysis phases in the correct order:
     /// 1. Symbol table construction
-    /// 2. Scope resolution
-    /// 3. Lifetime analysis
-    /// 4. Type checking
-    /// 5. Borrow checking
+    /// 2. Trait and impl registration
+    /// 3. Scope resolution
+    /// 4. Lifetime analysis
+    /// 5. Type checking
+    /// 6. Borrow checking
+    /// 7. Must-use checking
+    /// 8. Const cycle checking
+    /// 9. Const fn checking
+    /// 10. Match exhaustiveness checking
+    /// 11. Loop label checking
+    /// 12. Generic method call checking
+    /// 13. Entry point checking (executables only)
     ///
     /// # Arguments
     ///
@@ -127,11 +198,53 @@ impl SemanticAnalyzer {
     /// ```
     pub fn analyze(&mut self, program: &Program) -> Result<()> {
         self.symbol_table.analyze(program)?;
+        self.trait_registry = TraitRegistry::new();
+        self.trait_registry.register_program(program);
         self.scope_resolver.resolve(program)?;
         self.type_inference.infer(program)?;
         self.lifetime_analyzer.analyze(program)?;
         self.type_checker.check(program, &self.symbol_table)?;
         self.borrow_checker.check(program, &self.symbol_table)?;
+        self.warnings = self.must_use_checker.check(program)?;
+        ConstCycleChecker::new().check(program)?;
+        Self::check_const_fns(program)?;
+        MatchChecker::new().check(program)?;
+        let label_warnings = LoopLabelChecker::new().check(program)?;
+        self.warnings.extend(label_warnings);
+        MethodResolver::collect(program).check_generic_calls(program)?;
+        if self.executable {
+            EntryPointChecker::new().check(program)?;
+        }
+        self.item_dependencies = ItemDependencyGraph::build(program);
         Ok(())
     }
+
+    /// Reports every non-const operation at once: a single violation is
+    /// returned as-is, several are folded into one error listing them all.
+    fn check_const_fns(program: &Program) -> Result<()> {
+        let mut errors = match ConstFnChecker::new(program).check_program(program) {
+            Ok(()) => return Ok(()),
+            Err(errors) => errors,
+        };
+
+        if errors.len() == 1 {
+            return Err(errors.remove(0).into());
+        }
+
+        Err(CompileError::SemanticError {
+            message: format!("{} non-const operations in const functions", errors.len()),
+            line: None,
+            column: None,
+            source_snippet: None,
+            suggestion: None,
+            related_info: errors
+                .iter()
+                .map(|error| match error {
+                    CompileError::SemanticError { message, .. } => message.clone(),
+                    other => other.to_string(),
+                })
+                .collect(),
+        }
+        .into())
+    }
 }
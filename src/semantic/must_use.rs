@@ -0,0 +1,170 @@
+use crate::error::{CompileError, Diagnostic};
+use crate::parser::{Expression, Item, Program, Statement, Type};
+use anyhow::Result;
+use std::collections::HashSet;
+
+/// Reports calls whose `Result`/`Option` return value is silently dropped.
+///
+/// A result counts as handled when it is matched, propagated with `?`,
+/// bound to a variable, or explicitly ignored with `let _ = ...`. In strict
+/// mode an unhandled result is a hard error instead of a warning.
+pub struct MustUseChecker {
+    strict: bool,
+    must_use_functions: HashSet<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl MustUseChecker {
+    pub fn new() -> Self {
+        Self {
+            strict: false,
+            must_use_functions: HashSet::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn check(&mut self, program: &Program) -> Result<Vec<Diagnostic>> {
+        self.must_use_functions.clear();
+        self.diagnostics.clear();
+
+        for item in &program.items {
+            if let Item::Function(func) = item {
+                if func.return_type.as_ref().map_or(false, Self::is_result_like) {
+                    self.must_use_functions.insert(func.name.clone());
+                }
+            }
+        }
+
+        for item in &program.items {
+            if let Item::Function(func) = item {
+                self.check_block(&func.body)?;
+            }
+        }
+
+        Ok(std::mem::take(&mut self.diagnostics))
+    }
+
+    fn is_result_like(ty: &Type) -> bool {
+        match ty {
+            Type::Custom(name) | Type::Generic(name, _) => name == "Result" || name == "Option",
+            _ => false,
+        }
+    }
+
+    fn check_block(&mut self, body: &[Statement]) -> Result<()> {
+        for stmt in body {
+            self.check_statement(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn check_statement(&mut self, stmt: &Statement) -> Result<()> {
+        match stmt {
+            Statement::Expression(expr) => {
+                if let Some(name) = self.unhandled_call(expr) {
+                    self.report(name)?;
+                }
+                self.check_expression(expr)
+            }
+            Statement::Let { value: Some(value), .. } | Statement::Return(Some(value)) => {
+                self.check_expression(value)
+            }
+            Statement::While { condition, body } => {
+                self.check_expression(condition)?;
+                self.check_block(body)
+            }
+            Statement::For { iterable, body, .. } => {
+                self.check_expression(iterable)?;
+                self.check_block(body)
+            }
+            Statement::Loop { body } | Statement::Block(body) => self.check_block(body),
+            Statement::Labeled { body, .. } => self.check_statement(body),
+            Statement::If { condition, then_body, else_body } => {
+                self.check_expression(condition)?;
+                self.check_block(then_body)?;
+                if let Some(else_body) = else_body {
+                    self.check_block(else_body)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Walks into nested blocks so statements inside `match` arms, closures
+    /// and block expressions are checked as well.
+    fn check_expression(&mut self, expr: &Expression) -> Result<()> {
+        match expr {
+            Expression::Block(body) => self.check_block(body),
+            Expression::If { condition, then_branch, else_branch } => {
+                self.check_expression(condition)?;
+                self.check_expression(then_branch)?;
+                if let Some(else_branch) = else_branch {
+                    self.check_expression(else_branch)?;
+                }
+                Ok(())
+            }
+            Expression::Match { expression, arms } => {
+                self.check_expression(expression)?;
+                for arm in arms {
+                    self.check_expression(&arm.body)?;
+                }
+                Ok(())
+            }
+            Expression::Closure { body, .. } => self.check_expression(body),
+            _ => Ok(()),
+        }
+    }
+
+    fn unhandled_call<'a>(&self, expr: &'a Expression) -> Option<&'a str> {
+        match expr {
+            Expression::Call { func, .. } | Expression::CallAlt { callee: func, .. } => match func.as_ref() {
+                Expression::Ident(name) | Expression::Identifier(name)
+                    if self.must_use_functions.contains(name) =>
+                {
+                    Some(name)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn report(&mut self, function: &str) -> Result<()> {
+        let message = format!("unused result of `{}()` must be handled", function);
+        let suggestion = format!(
+            "match on the result, propagate it with `{}()?`, or ignore it with `let _ = {}();`",
+            function, function
+        );
+
+        if self.strict {
+            return Err(CompileError::SemanticError {
+                message,
+                line: None,
+                column: None,
+                source_snippet: None,
+                suggestion: Some(suggestion),
+                related_info: Vec::new(),
+            }
+            .into());
+        }
+
+        self.diagnostics.push(
+            Diagnostic::warning(message)
+                .with_suggestion(suggestion)
+                .with_note(format!("`{}` returns a Result or Option", function)),
+        );
+        Ok(())
+    }
+}
+
+impl Default for MustUseChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
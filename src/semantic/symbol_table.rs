@@ -1,7 +1,16 @@
-use crate::parser::{Program, Type, Item, Function, Statement};
+use crate::error::CompileError;
+use crate::parser::{Program, Type, Item, Function, Statement, Struct};
+use crate::trait_system::TraitRegistry;
 use anyhow::{Result, bail};
 use std::collections::HashMap;
 
+/// Type names the compiler resolves itself; user items may not reuse them.
+const BUILTIN_TYPES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "isize",
+    "u8", "u16", "u32", "u64", "u128", "usize",
+    "f32", "f64", "bool", "char", "str", "String",
+];
+
 pub struct SymbolTable {
     scopes: Vec<HashMap<String, Symbol>>,
 }
@@ -22,15 +31,110 @@ impl SymbolTable {
     }
 
     pub fn analyze(&mut self, program: &Program) -> Result<()> {
+        Self::check_duplicate_items(program)?;
+
         for item in &program.items {
             match item {
                 Item::Function(func) => {
                     self.analyze_function(func)?;
                 }
-                Item::Struct(_) => {
+                Item::Struct(s) => {
+                    Self::check_duplicate_fields(s)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects top-level items defined twice in the same namespace. Functions,
+    /// constants and statics share the value namespace; structs, enums,
+    /// traits, type aliases and modules share the type namespace.
+    ///
+    /// Types, traits and the other type-namespace items also may not take
+    /// the name of a built-in type or trait, which would change what every
+    /// use of that name resolves to.
+    fn check_duplicate_items(program: &Program) -> Result<()> {
+        let mut values: HashMap<&str, (usize, &str)> = HashMap::new();
+        let mut types: HashMap<&str, (usize, &str)> = HashMap::new();
+        let builtin_traits = TraitRegistry::new();
+
+        for (index, item) in program.items.iter().enumerate() {
+            let (namespace, name, kind) = match item {
+                Item::Function(f) => (&mut values, &f.name, "function"),
+                Item::Const(c) => (&mut values, &c.name, "constant"),
+                Item::Static(s) => (&mut values, &s.name, "static"),
+                Item::Struct(s) => (&mut types, &s.name, "struct"),
+                Item::Enum(e) => (&mut types, &e.name, "enum"),
+                Item::Trait(t) => (&mut types, &t.name, "trait"),
+                Item::Type(t) => (&mut types, &t.name, "type alias"),
+                Item::Mod(m) => (&mut types, &m.name, "module"),
+                Item::Impl(_) | Item::Use(_) => continue,
+            };
+
+            let in_type_namespace =
+                matches!(item, Item::Struct(_) | Item::Enum(_) | Item::Trait(_) | Item::Type(_) | Item::Mod(_));
+            let shadowed = if !in_type_namespace {
+                None
+            } else if BUILTIN_TYPES.contains(&name.as_str()) {
+                Some("type")
+            } else if builtin_traits.get_trait(name).is_some() {
+                Some("trait")
+            } else {
+                None
+            };
+            if let Some(builtin) = shadowed {
+                return Err(CompileError::SemanticError {
+                    message: format!("cannot define {} `{}`: `{}` is a built-in {}", kind, name, name, builtin),
+                    line: None,
+                    column: None,
+                    source_snippet: None,
+                    suggestion: Some(format!("rename the {} so it does not shadow the built-in {}", kind, builtin)),
+                    related_info: vec![format!("{} `{}` is item {} of the program", kind, name, index + 1)],
+                }
+                .into());
+            }
+
+            if let Some(&(first_index, first_kind)) = namespace.get(name.as_str()) {
+                return Err(CompileError::SemanticError {
+                    message: format!("the name `{}` is defined multiple times", name),
+                    line: None,
+                    column: None,
+                    source_snippet: None,
+                    suggestion: Some(format!("rename or remove one of the definitions of `{}`", name)),
+                    related_info: vec![
+                        format!("previous definition of {} `{}` is item {} of the program", first_kind, name, first_index + 1),
+                        format!("`{}` redefined here as a {} at item {} of the program", name, kind, index + 1),
+                    ],
                 }
+                .into());
             }
+            namespace.insert(name.as_str(), (index, kind));
         }
+
+        Ok(())
+    }
+
+    fn check_duplicate_fields(s: &Struct) -> Result<()> {
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+
+        for (index, field) in s.fields.iter().enumerate() {
+            if let Some(first_index) = seen.insert(&field.name, index) {
+                return Err(CompileError::SemanticError {
+                    message: format!("field `{}` is already declared in struct `{}`", field.name, s.name),
+                    line: None,
+                    column: None,
+                    source_snippet: None,
+                    suggestion: Some(format!("rename one of the `{}` fields", field.name)),
+                    related_info: vec![
+                        format!("`{}` first declared as field {} of `{}`", field.name, first_index + 1, s.name),
+                        format!("`{}` declared again as field {}", field.name, index + 1),
+                    ],
+                }
+                .into());
+            }
+        }
+
         Ok(())
     }
 
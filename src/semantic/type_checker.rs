@@ -1,5 +1,5 @@
-use crate::parser::{Program, Type, Item, Function, Statement, Expression, BinaryOp, UnaryOp};
-use crate::semantic::SymbolTable;
+use crate::parser::{Program, Type, Item, Field, FieldInit, Function, Statement, Expression, BinaryOp, UnaryOp};
+use crate::semantic::{MethodResolver, SymbolTable};
 use crate::error::CompileError;
 use anyhow::{Result, bail};
 use parking_lot::RwLock;
@@ -10,6 +10,8 @@ pub struct TypeChecker {
     substitutions: Arc<RwLock<HashMap<TypeVar, Type>>>,
     occurs_check_cache: HashMap<(Type, TypeVar), bool>,
     next_type_var: usize,
+    structs: HashMap<String, Vec<Field>>,
+    methods: MethodResolver,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -24,10 +26,20 @@ impl TypeChecker {
             substitutions: Arc::new(RwLock::new(HashMap::new())),
             occurs_check_cache: HashMap::new(),
             next_type_var: 0,
+            structs: HashMap::new(),
+            methods: MethodResolver::new(),
         }
     }
 
     pub fn check(&mut self, program: &Program, symbol_table: &SymbolTable) -> Result<()> {
+        self.structs.clear();
+        for item in &program.items {
+            if let Item::Struct(s) = item {
+                self.structs.insert(s.name.clone(), s.fields.clone());
+            }
+        }
+        self.methods = MethodResolver::collect(program);
+
         for item in &program.items {
             match item {
                 Item::Function(func) => {
@@ -147,11 +159,14 @@ impl TypeChecker {
                 Ok(Type::Custom(field.clone()))
             }
             Expression::MethodCall { object, method, args } => {
-                let _object_type = self.infer_expression(object, symbol_table)?;
+                let object_type = self.infer_expression(object, symbol_table)?;
                 for arg in args {
                     self.infer_expression(arg, symbol_table)?;
                 }
-                Ok(Type::Custom(method.clone()))
+                match self.methods.resolve(&object_type, method)? {
+                    Some(resolved) => Ok(resolved.return_type.unwrap_or_else(|| Type::Custom("unit".to_string()))),
+                    None => Ok(Type::Custom(method.clone())),
+                }
             }
             Expression::Index { object, index } => {
                 let _object_type = self.infer_expression(object, symbol_table)?;
@@ -171,9 +186,7 @@ impl TypeChecker {
                 Ok(Type::Custom("array".to_string()))
             }
             Expression::StructLiteral { name, fields } => {
-                for field in fields {
-                    self.infer_expression(&field.value, symbol_table)?;
-                }
+                self.check_struct_literal(name, fields, symbol_table)?;
                 Ok(Type::Custom(name.clone()))
             }
             Expression::Block(statements) => {
@@ -212,6 +225,81 @@ impl TypeChecker {
         }
     }
 
+    /// Checks a struct literal against its declaration: every declared field
+    /// must be given exactly once, no undeclared fields may appear, and each
+    /// value must match its field's type.
+    fn check_struct_literal(
+        &mut self,
+        name: &str,
+        fields: &[FieldInit],
+        symbol_table: &SymbolTable,
+    ) -> Result<()> {
+        let declared = match self.structs.get(name) {
+            Some(declared) => declared.clone(),
+            None => {
+                return Err(self.type_error(
+                    format!("unknown struct '{}'", name),
+                    None,
+                    None,
+                    Some(format!("declare 'struct {} {{ ... }}' before using it", name)),
+                ));
+            }
+        };
+
+        let mut seen = HashSet::new();
+        for field in fields {
+            let declared_field = match declared.iter().find(|f| f.name == field.name) {
+                Some(declared_field) => declared_field,
+                None => {
+                    return Err(self.type_error(
+                        format!("struct '{}' has no field named '{}'", name, field.name),
+                        None,
+                        None,
+                        Some(format!(
+                            "available fields are: {}",
+                            declared.iter().map(|f| f.name.as_str()).collect::<Vec<_>>().join(", ")
+                        )),
+                    ));
+                }
+            };
+
+            if !seen.insert(field.name.as_str()) {
+                return Err(self.type_error(
+                    format!("field '{}' specified more than once in '{}' literal", field.name, name),
+                    None,
+                    None,
+                    Some("remove the duplicate field".to_string()),
+                ));
+            }
+
+            let value_type = self.infer_expression(&field.value, symbol_table)?;
+            if self.unify(&declared_field.ty, &value_type).is_err() {
+                return Err(self.type_error(
+                    format!("mismatched type for field '{}' of struct '{}'", field.name, name),
+                    Some(declared_field.ty.clone()),
+                    Some(value_type),
+                    None,
+                ));
+            }
+        }
+
+        let missing: Vec<&str> = declared
+            .iter()
+            .map(|f| f.name.as_str())
+            .filter(|f| !seen.contains(f))
+            .collect();
+        if !missing.is_empty() {
+            return Err(self.type_error(
+                format!("missing fields {} in initializer of '{}'", missing.join(", "), name),
+                None,
+                None,
+                Some(format!("add the missing fields to the '{}' literal", name)),
+            ));
+        }
+
+        Ok(())
+    }
+
     fn check_binary_operation(
         &mut self,
         op: &BinaryOp,
@@ -1132,4 +1220,69 @@ mod tests {
         let err_msg = err.to_string();
         assert!(err_msg.contains("type mismatch") || err_msg.contains("expected"));
     }
+    
+    fn checker_with_point() -> TypeChecker {
+        let mut checker = TypeChecker::new();
+        let point = crate::parser::Struct {
+            attributes: Vec::new(),
+            visibility: crate::parser::Visibility::Private,
+            name: "Point".to_string(),
+            generics: Vec::new(),
+            fields: vec![
+                Field { name: "x".to_string(), ty: Type::I32 },
+                Field { name: "y".to_string(), ty: Type::I32 },
+            ],
+            where_clause: None,
+        };
+        let program = Program { items: vec![Item::Struct(point)] };
+        checker.check(&program, &SymbolTable::new()).unwrap();
+        checker
+    }
+    
+    fn point_literal(fields: Vec<(&str, Expression)>) -> Expression {
+        Expression::StructLiteral {
+            name: "Point".to_string(),
+            fields: fields
+                .into_iter()
+                .map(|(name, value)| FieldInit { name: name.to_string(), value })
+                .collect(),
+        }
+    }
+    
+    #[test]
+    fn test_struct_literal_missing_field() {
+        let mut checker = checker_with_point();
+        let literal = point_literal(vec![("x", Expression::IntLit(1))]);
+        
+        let err = checker.infer_expression(&literal, &SymbolTable::new()).unwrap_err();
+        assert!(err.to_string().contains("missing fields y"));
+        
+        let complete = point_literal(vec![("x", Expression::IntLit(1)), ("y", Expression::IntLit(2))]);
+        assert_eq!(
+            checker.infer_expression(&complete, &SymbolTable::new()).unwrap(),
+            Type::Custom("Point".to_string())
+        );
+    }
+    
+    #[test]
+    fn test_struct_literal_unknown_field() {
+        let mut checker = checker_with_point();
+        let literal = point_literal(vec![
+            ("x", Expression::IntLit(1)),
+            ("y", Expression::IntLit(2)),
+            ("z", Expression::IntLit(3)),
+        ]);
+        
+        let err = checker.infer_expression(&literal, &SymbolTable::new()).unwrap_err();
+        assert!(err.to_string().contains("no field named 'z'"));
+    }
+    
+    #[test]
+    fn test_struct_literal_field_type_mismatch() {
+        let mut checker = checker_with_point();
+        let literal = point_literal(vec![("x", Expression::IntLit(1)), ("y", Expression::BoolLit(true))]);
+        
+        let err = checker.infer_expression(&literal, &SymbolTable::new()).unwrap_err();
+        assert!(err.to_string().contains("field 'y'"));
+    }
 }
@@ -0,0 +1,343 @@
+//! Property-test harness for the lexer and parser.
+//!
+//! Each `check_*` function returns `Err` only when the front end panics or
+//! breaks an invariant; ordinary lex and parse errors are expected outcomes
+//! for malformed input. The functions are plain so they can drive both
+//! `proptest` strategies and the corpus-based [`FuzzingEngine`].
+
+use super::fuzzing::{FuzzConfig, FuzzResult, FuzzingEngine};
+use crate::lexer::{lex, Token};
+use crate::parser::{
+    parse, BinaryOp, Enum, EnumVariantData, Expression, Function, GenericParam, GenericParamKind, Item, Program, Statement, Struct, Type,
+    TypeBound, UnaryOp,
+};
+use std::panic::{self, AssertUnwindSafe};
+
+fn catch<T>(stage: &str, f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        format!("{} panicked: {}", stage, message)
+    })
+}
+
+/// Lexes arbitrary bytes (decoded lossily as UTF-8).
+pub fn check_lex(input: &[u8]) -> Result<(), String> {
+    let source = String::from_utf8_lossy(input);
+    catch("lexer", || lex(&source)).map(|_| ())
+}
+
+/// Parses an arbitrary token stream, which need not end in `Eof`.
+pub fn check_parse(tokens: Vec<Token>) -> Result<(), String> {
+    catch("parser", || parse(tokens)).map(|_| ())
+}
+
+/// Lexes arbitrary bytes and parses whatever tokens come out.
+pub fn check_pipeline(input: &[u8]) -> Result<(), String> {
+    let source = String::from_utf8_lossy(input);
+    match catch("lexer", || lex(&source))? {
+        Ok(tokens) => check_parse(tokens),
+        Err(_) => Ok(()),
+    }
+}
+
+/// For source that parses, pretty-printing the AST and parsing the result
+/// must give back the same AST, and printing again must be a fixed point.
+pub fn check_round_trip(source: &str) -> Result<(), String> {
+    let program = match catch("lexer", || lex(source))? {
+        Ok(tokens) => match catch("parser", || parse(tokens))? {
+            Ok(program) => program,
+            Err(_) => return Ok(()),
+        },
+        Err(_) => return Ok(()),
+    };
+
+    let printed = pretty_print(&program);
+    let reparsed = lex(&printed)
+        .and_then(parse)
+        .map_err(|e| format!("pretty-printed program does not parse: {}\n{}", e, printed))?;
+
+    if reparsed != program {
+        return Err(format!("round trip changed the AST:\n{}", printed));
+    }
+    if pretty_print(&reparsed) != printed {
+        return Err(format!("pretty-printing is not idempotent:\n{}", printed));
+    }
+    Ok(())
+}
+
+/// Runs the corpus-driven mutation fuzzer over the lex/parse pipeline for a
+/// bounded number of iterations. Every execution is deterministic.
+pub fn fuzz_corpus(seeds: &[Vec<u8>], max_iterations: u64) -> FuzzResult {
+    let mut engine = FuzzingEngine::new(FuzzConfig {
+        max_iterations,
+        ..FuzzConfig::default()
+    });
+    for seed in seeds {
+        engine.add_seed(seed.clone());
+    }
+    engine.fuzz(check_pipeline)
+}
+
+/// Prints a program in the subset of the surface syntax the parser accepts.
+/// Expressions are fully parenthesized so the output does not depend on
+/// operator precedence.
+pub fn pretty_print(program: &Program) -> String {
+    let mut out = String::new();
+    for item in &program.items {
+        match item {
+            Item::Function(func) => print_function(func, &mut out),
+            Item::Struct(s) => print_struct(s, &mut out),
+            Item::Enum(e) => print_enum(e, &mut out),
+            other => out.push_str(&format!("// unsupported item: {:?}\n", other)),
+        }
+    }
+    out
+}
+
+fn print_function(func: &Function, out: &mut String) {
+    if func.is_const {
+        out.push_str("const ");
+    }
+    let params: Vec<String> = func.params.iter().map(|p| format!("{}: {}", p.name, print_type(&p.ty))).collect();
+    out.push_str(&format!("fn {}{}({})", func.name, print_generics(&func.generics), params.join(", ")));
+    if let Some(ty) = &func.return_type {
+        out.push_str(&format!(" -> {}", print_type(ty)));
+    }
+    out.push_str(" {\n");
+    print_block(&func.body, 1, out);
+    out.push_str("}\n");
+}
+
+fn print_struct(s: &Struct, out: &mut String) {
+    out.push_str(&format!("struct {}{} {{\n", s.name, print_generics(&s.generics)));
+    for field in &s.fields {
+        out.push_str(&format!("    {}: {},\n", field.name, print_type(&field.ty)));
+    }
+    out.push_str("}\n");
+}
+
+fn print_enum(e: &Enum, out: &mut String) {
+    out.push_str(&format!("enum {}{} {{\n", e.name, print_generics(&e.generics)));
+    for variant in &e.variants {
+        match &variant.data {
+            EnumVariantData::Unit => out.push_str(&format!("    {},\n", variant.name)),
+            EnumVariantData::Tuple(types) => {
+                let types: Vec<String> = types.iter().map(print_type).collect();
+                out.push_str(&format!("    {}({}),\n", variant.name, types.join(", ")));
+            }
+            EnumVariantData::Struct(fields) => {
+                let fields: Vec<String> =
+                    fields.iter().map(|field| format!("{}: {}", field.name, print_type(&field.ty))).collect();
+                out.push_str(&format!("    {} {{ {} }},\n", variant.name, fields.join(", ")));
+            }
+        }
+    }
+    out.push_str("}\n");
+}
+
+fn print_generics(generics: &[GenericParam]) -> String {
+    if generics.is_empty() {
+        return String::new();
+    }
+    let params: Vec<String> = generics
+        .iter()
+        .map(|param| match &param.kind {
+            GenericParamKind::Const(ty) => format!("const {}: {}", param.name, print_type(ty)),
+            GenericParamKind::Type if param.bounds.is_empty() => param.name.clone(),
+            GenericParamKind::Type => {
+                let bounds: Vec<&str> = param
+                    .bounds
+                    .iter()
+                    .map(|bound| match bound {
+                        TypeBound::Trait(name) | TypeBound::Lifetime(name) => name.as_str(),
+                    })
+                    .collect();
+                format!("{}: {}", param.name, bounds.join(" + "))
+            }
+        })
+        .collect();
+    format!("<{}>", params.join(", "))
+}
+
+fn print_type(ty: &Type) -> String {
+    match ty {
+        Type::I32 => "i32".to_string(),
+        Type::I64 => "i64".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::Bool => "bool".to_string(),
+        Type::Char => "char".to_string(),
+        Type::String => "String".to_string(),
+        Type::Custom(name) => name.clone(),
+        Type::Generic(name, args) => {
+            let args: Vec<String> = args.iter().map(print_type).collect();
+            format!("{}<{}>", name, args.join(", "))
+        }
+        Type::ConstValue(n) => n.to_string(),
+        Type::Array { element, size: Some(size) } => format!("[{}; {}]", print_type(element), size),
+        Type::Slice(element) => format!("[{}]", print_type(element)),
+        Type::ParamArray { element, size_param } => format!("[{}; {}]", print_type(element), size_param),
+        Type::Tuple(elements) => print_tuple(elements.iter().map(print_type).collect()),
+        other => format!("{:?}", other),
+    }
+}
+
+fn print_block(body: &[Statement], depth: usize, out: &mut String) {
+    for stmt in body {
+        print_statement(stmt, depth, out);
+    }
+}
+
+fn print_statement(stmt: &Statement, depth: usize, out: &mut String) {
+    let indent = "    ".repeat(depth);
+    match stmt {
+        Statement::Let { name, mutable, ty, value } => {
+            out.push_str(&format!("{}let {}{}", indent, if *mutable { "mut " } else { "" }, name));
+            if let Some(ty) = ty {
+                out.push_str(&format!(": {}", print_type(ty)));
+            }
+            if let Some(value) = value {
+                out.push_str(&format!(" = {}", print_expression(value)));
+            }
+            out.push_str(";\n");
+        }
+        Statement::Return(Some(value)) => out.push_str(&format!("{}return {};\n", indent, print_expression(value))),
+        Statement::Return(None) => out.push_str(&format!("{}return;\n", indent)),
+        Statement::Expression(expr) => out.push_str(&format!("{}{};\n", indent, print_expression(expr))),
+        Statement::While { condition, body } => {
+            out.push_str(&format!("{}while {} {{\n", indent, print_expression(condition)));
+            print_block(body, depth + 1, out);
+            out.push_str(&format!("{}}}\n", indent));
+        }
+        Statement::For { variable, iterable, body } => {
+            out.push_str(&format!("{}for {} in {} {{\n", indent, variable, print_expression(iterable)));
+            print_block(body, depth + 1, out);
+            out.push_str(&format!("{}}}\n", indent));
+        }
+        Statement::Loop { body } => {
+            out.push_str(&format!("{}loop {{\n", indent));
+            print_block(body, depth + 1, out);
+            out.push_str(&format!("{}}}\n", indent));
+        }
+        Statement::Labeled { label, body } => {
+            let mut loop_text = String::new();
+            print_statement(body, depth, &mut loop_text);
+            out.push_str(&format!("{}'{}: {}", indent, label, loop_text.trim_start()));
+        }
+        Statement::Break { label, value } => {
+            out.push_str(&format!("{}break", indent));
+            if let Some(label) = label {
+                out.push_str(&format!(" '{}", label));
+            }
+            if let Some(value) = value {
+                out.push_str(&format!(" {}", print_expression(value)));
+            }
+            out.push_str(";\n");
+        }
+        Statement::Continue { label: Some(label) } => out.push_str(&format!("{}continue '{};\n", indent, label)),
+        Statement::Continue { label: None } => out.push_str(&format!("{}continue;\n", indent)),
+        Statement::If { condition, then_body, else_body } => {
+            out.push_str(&format!("{}if {} {{\n", indent, print_expression(condition)));
+            print_block(then_body, depth + 1, out);
+            match else_body {
+                Some(else_body) => {
+                    out.push_str(&format!("{}}} else {{\n", indent));
+                    print_block(else_body, depth + 1, out);
+                    out.push_str(&format!("{}}}\n", indent));
+                }
+                None => out.push_str(&format!("{}}}\n", indent)),
+            }
+        }
+        other => out.push_str(&format!("{}// unsupported statement: {:?}\n", indent, other)),
+    }
+}
+
+fn print_expression(expr: &Expression) -> String {
+    match expr {
+        Expression::IntLit(n) => n.to_string(),
+        Expression::FloatLit(f) => format!("{:?}", f),
+        Expression::BoolLit(b) => b.to_string(),
+        Expression::StringLit(s) => format!("\"{}\"", s),
+        Expression::CharLit(c) => format!("'{}'", c),
+        Expression::Ident(name) | Expression::Identifier(name) => name.clone(),
+        Expression::Binary { op, left, right } => {
+            format!("({} {} {})", print_expression(left), binary_op(op), print_expression(right))
+        }
+        Expression::Unary { op, expr } => {
+            let op = match op {
+                UnaryOp::Neg => "-",
+                UnaryOp::Not => "!",
+                UnaryOp::Ref => "&",
+                UnaryOp::RefMut => "&mut ",
+                UnaryOp::Deref => "*",
+            };
+            format!("({}{})", op, print_expression(expr))
+        }
+        Expression::Call { func, args } => {
+            let args: Vec<String> = args.iter().map(print_expression).collect();
+            format!("{}({})", print_expression(func), args.join(", "))
+        }
+        Expression::Range { start, end, inclusive } => format!(
+            "({}{}{})",
+            print_expression(start),
+            if *inclusive { "..=" } else { ".." },
+            print_expression(end)
+        ),
+        Expression::Assignment { target, value } => {
+            format!("({} = {})", print_expression(target), print_expression(value))
+        }
+        Expression::FieldAccess { object, field } => format!("{}.{}", print_expression(object), field),
+        Expression::MethodCall { object, method, args } => {
+            let args: Vec<String> = args.iter().map(print_expression).collect();
+            format!("{}.{}({})", print_expression(object), method, args.join(", "))
+        }
+        // Parenthesized so the literal also parses in `if` and `while` conditions.
+        Expression::StructLiteral { name, fields } => {
+            let fields: Vec<String> =
+                fields.iter().map(|field| format!("{}: {}", field.name, print_expression(&field.value))).collect();
+            format!("({} {{ {} }})", name, fields.join(", "))
+        }
+        Expression::TupleLiteral(elements) => print_tuple(elements.iter().map(print_expression).collect()),
+        other => format!("/* unsupported expression: {:?} */", other),
+    }
+}
+
+/// A one-element tuple needs a trailing comma to differ from a parenthesized
+/// expression or type.
+fn print_tuple(elements: Vec<String>) -> String {
+    match elements.as_slice() {
+        [single] => format!("({},)", single),
+        _ => format!("({})", elements.join(", ")),
+    }
+}
+
+fn binary_op(op: &BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Mod => "%",
+        BinaryOp::Eq => "==",
+        BinaryOp::Ne => "!=",
+        BinaryOp::Lt => "<",
+        BinaryOp::Le => "<=",
+        BinaryOp::Gt => ">",
+        BinaryOp::Ge => ">=",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+        BinaryOp::BitwiseAnd => "&",
+        BinaryOp::BitwiseOr => "|",
+        BinaryOp::BitwiseXor => "^",
+        BinaryOp::LeftShift => "<<",
+        BinaryOp::RightShift => ">>",
+    }
+}
@@ -1,6 +1,7 @@
 pub mod framework;
 pub mod coverage;
 pub mod fuzzing;
+pub mod fuzz;
 
 pub use framework::{TestFramework, Test, TestConfig};
 pub use coverage::CoverageAnalyzer;
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use crate::parser::Type;
+use crate::parser::{ImplItem, Item, Program, TraitItem, Type};
 
 pub struct TraitRegistry {
     traits: HashMap<String, TraitDefinition>,
@@ -27,8 +27,24 @@ pub struct TraitImpl {
     pub trait_name: String,
     pub for_type: Type,
     pub methods: HashMap<String, String>,
+    /// Associated types bound by the impl, e.g. `Output` for `Add`.
+    pub associated_types: HashMap<String, Type>,
 }
 
+/// Operator traits as `(trait, method)`, e.g. `a + b` calls `Add::add`.
+pub const OPERATOR_TRAITS: &[(&str, &str)] = &[
+    ("Add", "add"),
+    ("Sub", "sub"),
+    ("Mul", "mul"),
+    ("Div", "div"),
+    ("Rem", "rem"),
+    ("BitAnd", "bitand"),
+    ("BitOr", "bitor"),
+    ("BitXor", "bitxor"),
+    ("Shl", "shl"),
+    ("Shr", "shr"),
+];
+
 impl TraitRegistry {
     pub fn new() -> Self {
         let mut registry = Self {
@@ -167,6 +183,25 @@ impl TraitRegistry {
             associated_types: vec![],
             super_traits: vec![],
         });
+        
+        for (name, method) in OPERATOR_TRAITS {
+            self.register_trait(TraitDefinition {
+                name: name.to_string(),
+                methods: vec![
+                    TraitMethod {
+                        name: method.to_string(),
+                        params: vec![
+                            ("self".to_string(), Type::Custom("Self".to_string())),
+                            ("rhs".to_string(), Type::Custom("Rhs".to_string())),
+                        ],
+                        return_type: Some(Type::Custom("Self".to_string())),
+                        default_impl: None,
+                    }
+                ],
+                associated_types: vec!["Output".to_string()],
+                super_traits: vec![],
+            });
+        }
     }
     
     pub fn register_trait(&mut self, trait_def: TraitDefinition) {
@@ -178,6 +213,82 @@ impl TraitRegistry {
         self.implementations.insert(key, impl_def);
     }
     
+    /// Registers the traits and trait impls declared in `program`. Impl
+    /// methods resolve to `Type::method`; methods the impl leaves out fall
+    /// back to the trait's default body, `Trait::method`. Inherent impls
+    /// implement no trait and are not recorded here.
+    pub fn register_program(&mut self, program: &Program) {
+        for item in &program.items {
+            if let Item::Trait(trait_decl) = item {
+                let mut methods = Vec::new();
+                let mut associated_types = Vec::new();
+                for trait_item in &trait_decl.items {
+                    match trait_item {
+                        TraitItem::Function(func) => methods.push(TraitMethod {
+                            name: func.name.clone(),
+                            params: func.params.iter().map(|param| (param.name.clone(), param.ty.clone())).collect(),
+                            return_type: func.return_type.clone(),
+                            default_impl: func
+                                .default_body
+                                .as_ref()
+                                .map(|_| format!("{}::{}", trait_decl.name, func.name)),
+                        }),
+                        TraitItem::Type(assoc) => associated_types.push(assoc.name.clone()),
+                        TraitItem::Const(_) => {}
+                    }
+                }
+                self.register_trait(TraitDefinition {
+                    name: trait_decl.name.clone(),
+                    methods,
+                    associated_types,
+                    super_traits: trait_decl.supertraits.clone(),
+                });
+            }
+        }
+        
+        for item in &program.items {
+            let (trait_name, impl_decl) = match item {
+                Item::Impl(impl_decl) => match &impl_decl.trait_path {
+                    Some(trait_name) => (trait_name, impl_decl),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            
+            let type_name = match &impl_decl.self_type {
+                Type::Custom(name) | Type::Generic(name, _) => name.clone(),
+                other => format!("{:?}", other),
+            };
+            let mut methods = HashMap::new();
+            let mut associated_types = HashMap::new();
+            for impl_item in &impl_decl.items {
+                match impl_item {
+                    ImplItem::Function(func) => {
+                        methods.insert(func.name.clone(), format!("{}::{}", type_name, func.name));
+                    }
+                    ImplItem::Type(alias) => {
+                        associated_types.insert(alias.name.clone(), alias.ty.clone());
+                    }
+                    ImplItem::Const(_) => {}
+                }
+            }
+            if let Some(trait_def) = self.get_trait(trait_name) {
+                for method in &trait_def.methods {
+                    if let Some(default) = &method.default_impl {
+                        methods.entry(method.name.clone()).or_insert_with(|| default.clone());
+                    }
+                }
+            }
+            
+            self.register_impl(TraitImpl {
+                trait_name: trait_name.clone(),
+                for_type: impl_decl.self_type.clone(),
+                methods,
+                associated_types,
+            });
+        }
+    }
+    
     pub fn get_trait(&self, name: &str) -> Option<&TraitDefinition> {
         self.traits.get(name)
     }
@@ -202,6 +313,22 @@ impl TraitRegistry {
         Ok(())
     }
     
+    /// The result type of an operator trait applied to `for_type`: the impl's
+    /// `Output` if it binds one, otherwise the trait method's return type
+    /// with `Self` replaced by `for_type`.
+    pub fn operator_output(&self, trait_name: &str, for_type: &Type) -> Option<Type> {
+        let impl_def = self.get_impl(trait_name, for_type)?;
+        if let Some(output) = impl_def.associated_types.get("Output") {
+            return Some(output.clone());
+        }
+        
+        let method = self.get_trait(trait_name)?.methods.first()?;
+        match &method.return_type {
+            Some(Type::Custom(name)) if name == "Self" => Some(for_type.clone()),
+            other => other.clone(),
+        }
+    }
+    
     pub fn get_method(&self, trait_name: &str, method_name: &str) -> Option<&TraitMethod> {
         self.traits
             .get(trait_name)?
@@ -1,10 +1,47 @@
 use std::collections::HashMap;
-use crate::parser::{Type, Expression, Statement, Function};
+use crate::parser::{Type, Expression, Statement, Function, BinaryOp, ClosureParam, GenericParamKind, ImplItem, Item, Program};
+use crate::trait_system::TraitRegistry;
+
+/// The type of an integer literal before context fixes it: it matches any
+/// integer type, and defaults to `i32` when nothing constrains it.
+const INTEGER_LITERAL: &str = "{integer}";
 
 pub struct TypeInferenceEngine {
     type_map: HashMap<String, Type>,
     constraints: Vec<TypeConstraint>,
     next_type_var: usize,
+    trait_registry: TraitRegistry,
+    /// Return type of the function being inferred; `None` outside a function.
+    return_type: Option<Type>,
+    /// Types of let-bound closures, generalized where the value restriction
+    /// allows it.
+    schemes: HashMap<String, TypeScheme>,
+    /// Parameters of the closures currently being inferred, innermost last.
+    closure_scopes: Vec<HashMap<String, InferredType>>,
+    substitutions: HashMap<usize, InferredType>,
+    /// Variables bound to an integer literal, each with the type variable
+    /// that the first concrete integer type it meets is solved into.
+    integer_vars: HashMap<String, usize>,
+    /// Signatures of the program's free functions, used to type calls.
+    signatures: HashMap<String, FunctionSignature>,
+}
+
+#[derive(Debug, Clone)]
+struct FunctionSignature {
+    /// Type parameters, which appear in `params` and `return_type` as
+    /// `Type::Custom(name)` and are bound afresh at every call.
+    generics: Vec<String>,
+    params: Vec<Type>,
+    return_type: Type,
+}
+
+/// A type with the variables in `vars` quantified, so each use of the
+/// binding gets fresh copies of them. A scheme with no `vars` is
+/// monomorphic: every use shares, and eventually fixes, its variables.
+#[derive(Debug, Clone)]
+struct TypeScheme {
+    vars: Vec<usize>,
+    ty: InferredType,
 }
 
 #[derive(Debug, Clone)]
@@ -26,7 +63,78 @@ impl TypeInferenceEngine {
             type_map: HashMap::new(),
             constraints: Vec::new(),
             next_type_var: 0,
+            trait_registry: TraitRegistry::new(),
+            return_type: None,
+            schemes: HashMap::new(),
+            closure_scopes: Vec::new(),
+            substitutions: HashMap::new(),
+            integer_vars: HashMap::new(),
+            signatures: HashMap::new(),
+        }
+    }
+    
+    /// Uses `registry` to resolve operators applied to user-defined types.
+    pub fn with_trait_registry(mut self, registry: TraitRegistry) -> Self {
+        self.trait_registry = registry;
+        self
+    }
+    
+    /// Infers every function and checks const and static initializers
+    /// against their declared types.
+    pub fn infer_program(&mut self, program: &Program) -> Result<(), String> {
+        for item in &program.items {
+            if let Item::Function(function) = item {
+                self.register_function(function);
+            }
+        }
+        
+        for item in &program.items {
+            match item {
+                Item::Function(function) => {
+                    self.infer_function(function)?;
+                }
+                Item::Impl(impl_block) => {
+                    for impl_item in &impl_block.items {
+                        if let ImplItem::Function(method) = impl_item {
+                            self.infer_function(method)?;
+                        }
+                    }
+                }
+                Item::Const(decl) => self.infer_initializer(&decl.name, &decl.ty, &decl.value)?,
+                Item::Static(decl) => self.infer_initializer(&decl.name, &decl.ty, &decl.value)?,
+                _ => {}
+            }
         }
+        Ok(())
+    }
+    
+    /// Records `function`'s signature so calls to it are checked against
+    /// its parameters and take its return type.
+    pub fn register_function(&mut self, function: &Function) {
+        self.signatures.insert(
+            function.name.clone(),
+            FunctionSignature {
+                generics: function
+                    .generics
+                    .iter()
+                    .filter(|param| param.kind == GenericParamKind::Type)
+                    .map(|param| param.name.clone())
+                    .collect(),
+                params: function.params.iter().map(|param| param.ty.clone()).collect(),
+                return_type: function.return_type.clone().unwrap_or(Type::Custom("()".to_string())),
+            },
+        );
+    }
+    
+    fn infer_initializer(&mut self, name: &str, ty: &Type, value: &Expression) -> Result<(), String> {
+        let value_type = self.check_expression(value, ty)?;
+        if !Self::is_unknown(&value_type) && !self.expect_type(ty, value, &value_type)? {
+            return Err(format!(
+                "Type mismatch in initializer of '{}': expected {:?}, found {:?}",
+                name, ty, value_type
+            ));
+        }
+        Ok(())
     }
     
     pub fn infer_function(&mut self, function: &Function) -> Result<Type, String> {
@@ -34,14 +142,27 @@ impl TypeInferenceEngine {
             self.type_map.insert(param.name.clone(), param.ty.clone());
         }
         
-        let mut last_type = Type::Custom("()".to_string());
+        let return_type = function.return_type.clone().unwrap_or(Type::Custom("()".to_string()));
+        let enclosing = self.return_type.replace(return_type);
         
-        for stmt in &function.body {
-            last_type = self.infer_statement(stmt)?;
+        let mut last_type = Ok(Type::Custom("()".to_string()));
+        for (index, stmt) in function.body.iter().enumerate() {
+            last_type = match (stmt, &function.return_type) {
+                (Statement::Expression(expr), Some(ret_type)) if index + 1 == function.body.len() => {
+                    self.check_expression(expr, ret_type)
+                }
+                _ => self.infer_statement(stmt),
+            };
+            if last_type.is_err() {
+                break;
+            }
         }
         
+        self.return_type = enclosing;
+        let last_type = last_type?;
+        
         if let Some(ret_type) = &function.return_type {
-            if !self.types_match(ret_type, &last_type) {
+            if !Self::is_unknown(&last_type) && !self.types_match(ret_type, &last_type) {
                 return Err(format!(
                     "Return type mismatch: expected {:?}, found {:?}",
                     ret_type, last_type
@@ -49,33 +170,83 @@ impl TypeInferenceEngine {
             }
             Ok(ret_type.clone())
         } else {
-            Ok(last_type)
+            Ok(Self::default_integer(last_type))
         }
     }
     
     fn infer_statement(&mut self, stmt: &Statement) -> Result<Type, String> {
         match stmt {
-            Statement::Let { name, ty, value, .. } => {
-                let inferred_type = if let Some(expr) = value {
-                    self.infer_expression(expr)?
+            Statement::Let { name, ty: None, value: Some(expr), .. } if self.is_higher_order(expr) => {
+                let inferred = self.infer_inferred(expr)?;
+                let inferred_type = self.to_type(&inferred);
+                if matches!(self.resolve(&inferred), InferredType::Function(..)) {
+                    // Value restriction: only a syntactic value is generalized.
+                    // The result of a call shares its variables with every
+                    // other use, so `let f = id(id);` stays monomorphic.
+                    let scheme = if Self::is_syntactic_value(expr) {
+                        self.generalize(&inferred)
+                    } else {
+                        TypeScheme { vars: Vec::new(), ty: inferred }
+                    };
+                    self.schemes.insert(name.clone(), scheme);
+                    self.type_map.remove(name);
                 } else {
-                    ty.clone().unwrap_or(Type::Custom("()".to_string()))
+                    self.schemes.remove(name);
+                    self.type_map.insert(name.clone(), inferred_type.clone());
+                }
+                Ok(inferred_type)
+            }
+            
+            Statement::Let { name, ty, value, .. } => {
+                let inferred_type = match (ty, value) {
+                    (Some(declared_type), Some(expr)) => self.check_expression(expr, declared_type)?,
+                    (None, Some(expr)) => self.infer_expression(expr)?,
+                    _ => ty.clone().unwrap_or(Type::Custom("()".to_string())),
                 };
                 
-                if let Some(declared_type) = ty {
-                    if !self.types_match(declared_type, &inferred_type) {
-                        return Err(format!(
-                            "Type mismatch in let binding '{}': expected {:?}, found {:?}",
-                            name, declared_type, inferred_type
-                        ));
+                let inferred_type = match (ty, value) {
+                    (Some(declared_type), Some(expr)) => {
+                        if !self.expect_type(declared_type, expr, &inferred_type)? {
+                            return Err(format!(
+                                "Type mismatch in let binding '{}': expected {:?}, found {:?}",
+                                name, declared_type, inferred_type
+                            ));
+                        }
+                        declared_type.clone()
                     }
-                }
+                    _ => inferred_type,
+                };
                 
+                self.schemes.remove(name);
+                self.integer_vars.remove(name);
+                if Self::is_integer_literal(&inferred_type) {
+                    if let InferredType::Variable(var) = self.new_type_var() {
+                        self.integer_vars.insert(name.clone(), var);
+                    }
+                }
                 self.type_map.insert(name.clone(), inferred_type.clone());
                 Ok(inferred_type)
             }
             
-            Statement::Return(Some(expr)) => self.infer_expression(expr),
+            Statement::Return(value) => {
+                let expected = self.return_type.clone().ok_or("'return' outside of a function")?;
+                let value_type = match value {
+                    Some(expr) => self.check_expression(expr, &expected)?,
+                    None => Type::Custom("()".to_string()),
+                };
+                
+                let matches = match value {
+                    Some(expr) => self.expect_type(&expected, expr, &value_type)?,
+                    None => self.types_match(&expected, &value_type),
+                };
+                if !Self::is_unknown(&value_type) && !matches {
+                    return Err(format!(
+                        "Return type mismatch: expected {:?}, found {:?}",
+                        expected, value_type
+                    ));
+                }
+                Ok(value_type)
+            }
             
             Statement::Expression(expr) => self.infer_expression(expr),
             
@@ -120,19 +291,61 @@ impl TypeInferenceEngine {
                 Ok(Type::Custom("()".to_string()))
             }
             
+            Statement::For { variable, iterable, body } => {
+                let element_type = match self.infer_expression(iterable)? {
+                    Type::Array { element, .. } | Type::Slice(element) => *element,
+                    _ => match iterable {
+                        Expression::Range { start, .. } => self.infer_expression(start)?,
+                        _ => Type::Custom("unknown".to_string()),
+                    },
+                };
+                self.type_map.insert(variable.clone(), element_type);
+                
+                for stmt in body {
+                    self.infer_statement(stmt)?;
+                }
+                
+                Ok(Type::Custom("()".to_string()))
+            }
+            
+            Statement::Loop { body } | Statement::Block(body) => {
+                for stmt in body {
+                    self.infer_statement(stmt)?;
+                }
+                
+                Ok(Type::Custom("()".to_string()))
+            }
+            
+            Statement::Labeled { body, .. } => self.infer_statement(body),
+            
+            Statement::Break { value: Some(value), .. } => {
+                self.infer_expression(value)?;
+                Ok(Type::Custom("()".to_string()))
+            }
+            
             _ => Ok(Type::Custom("()".to_string())),
         }
     }
     
     fn infer_expression(&mut self, expr: &Expression) -> Result<Type, String> {
         match expr {
-            Expression::IntLit(_) => Ok(Type::I64),
+            Expression::IntLit(_) => Ok(Type::Custom(INTEGER_LITERAL.to_string())),
             Expression::FloatLit(_) => Ok(Type::F64),
             Expression::BoolLit(_) => Ok(Type::Bool),
             Expression::StringLit(_) => Ok(Type::String),
             Expression::CharLit(_) => Ok(Type::Char),
             
+            Expression::Ident(name) | Expression::Identifier(name) if self.schemes.contains_key(name) => {
+                let inferred = self.infer_inferred(expr)?;
+                Ok(self.to_type(&inferred))
+            }
+            
             Expression::Ident(name) | Expression::Identifier(name) => {
+                if let Some(&var) = self.integer_vars.get(name) {
+                    if let InferredType::Known(ty) = self.resolve(&InferredType::Variable(var)) {
+                        return Ok(ty);
+                    }
+                }
                 self.type_map
                     .get(name)
                     .cloned()
@@ -143,6 +356,10 @@ impl TypeInferenceEngine {
                 let left_type = self.infer_expression(left)?;
                 let right_type = self.infer_expression(right)?;
                 
+                if let Some(trait_name) = Self::operator_trait(op).filter(|_| Self::is_user_type(&left_type)) {
+                    return self.infer_operator_trait(trait_name, &left_type, &right_type);
+                }
+                
                 if !self.types_match(&left_type, &right_type) {
                     return Err(format!(
                         "Binary operation type mismatch: {:?} vs {:?}",
@@ -150,6 +367,15 @@ impl TypeInferenceEngine {
                     ));
                 }
                 
+                // A literal operand takes the type of the other side.
+                let left_type = if Self::is_integer_literal(&left_type) {
+                    self.expect_type(&right_type, left, &left_type)?;
+                    right_type
+                } else {
+                    self.expect_type(&left_type, right, &right_type)?;
+                    left_type
+                };
+                
                 use crate::parser::BinaryOp::*;
                 match op {
                     Add | Sub | Mul | Div | Mod => Ok(left_type),
@@ -161,7 +387,7 @@ impl TypeInferenceEngine {
                         Ok(Type::Bool)
                     }
                     BitwiseAnd | BitwiseOr | BitwiseXor | LeftShift | RightShift => {
-                        if !matches!(left_type, Type::I32 | Type::I64 | Type::U32 | Type::U64) {
+                        if !Self::is_integer(&left_type) {
                             return Err(format!("Bitwise operation requires integer operands"));
                         }
                         Ok(left_type)
@@ -175,7 +401,7 @@ impl TypeInferenceEngine {
                 use crate::parser::UnaryOp::*;
                 match op {
                     Neg => {
-                        if !matches!(expr_type, Type::I32 | Type::I64 | Type::F32 | Type::F64) {
+                        if !Self::is_integer(&expr_type) && !matches!(expr_type, Type::F32 | Type::F64) {
                             return Err(format!("Negation requires numeric type"));
                         }
                         Ok(expr_type)
@@ -190,12 +416,23 @@ impl TypeInferenceEngine {
                 }
             }
             
+            Expression::Call { .. } | Expression::CallAlt { .. } | Expression::Closure { .. }
+                if self.is_higher_order(expr) =>
+            {
+                let inferred = self.infer_inferred(expr)?;
+                Ok(self.to_type(&inferred))
+            }
+            
             Expression::Call { func, args } | Expression::CallAlt { callee: func, args } => {
-                for arg in args {
-                    self.infer_expression(arg)?;
+                match self.call_signature(func) {
+                    Some((name, signature)) => self.infer_call(&name, &signature, args, None),
+                    None => {
+                        for arg in args {
+                            self.infer_expression(arg)?;
+                        }
+                        Ok(Type::Custom("unknown".to_string()))
+                    }
                 }
-                
-                Ok(Type::Custom("unknown".to_string()))
             }
             
             Expression::If { condition, then_branch, else_branch } => {
@@ -220,26 +457,625 @@ impl TypeInferenceEngine {
                 Ok(then_type)
             }
             
+            Expression::Block(statements) => {
+                let mut block_type = Type::Custom("()".to_string());
+                for stmt in statements {
+                    block_type = self.infer_statement(stmt)?;
+                }
+                Ok(block_type)
+            }
+            
+            Expression::ArrayLiteral(elements) => {
+                // The first element fixes the element type; an integer literal
+                // gives way to the first concrete integer type after it.
+                let mut element_type = Type::Custom("unknown".to_string());
+                for (position, value) in elements.iter().enumerate() {
+                    let found = self.infer_expression(value)?;
+                    if Self::is_unknown(&found) {
+                        continue;
+                    }
+                    if Self::is_unknown(&element_type) {
+                        element_type = found;
+                        continue;
+                    }
+                    if !self.types_match(&element_type, &found) {
+                        return Err(format!(
+                            "Array element {}: expected {:?}, found {:?}",
+                            position + 1,
+                            element_type,
+                            found
+                        ));
+                    }
+                    if Self::is_integer_literal(&element_type) {
+                        element_type = found;
+                    }
+                }
+                Ok(Type::Array { element: Box::new(element_type), size: Some(elements.len()) })
+            }
+            
+            Expression::TupleLiteral(elements) => {
+                let types = elements
+                    .iter()
+                    .map(|element| self.infer_expression(element))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Type::Tuple(types))
+            }
+            
             _ => Ok(Type::Custom("unknown".to_string())),
         }
     }
     
+    /// Infers `expr` with `expected` pushed inward, so literals, empty arrays
+    /// and generic calls take their type from context instead of defaulting
+    /// or failing. Returns the type found; callers still compare it with
+    /// `expected` to report the mismatch in their own terms.
+    fn check_expression(&mut self, expr: &Expression, expected: &Type) -> Result<Type, String> {
+        match (expr, expected) {
+            (Expression::IntLit(_), _) if Self::is_integer(expected) && !Self::is_integer_literal(expected) => {
+                Ok(expected.clone())
+            }
+            
+            (Expression::ArrayLiteral(elements), Type::Array { element, size }) => {
+                if let Some(size) = size.filter(|&size| size != elements.len()) {
+                    return Err(format!(
+                        "Array literal has {} element(s) but {:?} expects {}",
+                        elements.len(),
+                        expected,
+                        size
+                    ));
+                }
+                for (position, value) in elements.iter().enumerate() {
+                    let found = self.check_expression(value, element)?;
+                    if !Self::is_unknown(&found) && !self.expect_type(element, value, &found)? {
+                        return Err(format!(
+                            "Array element {}: expected {:?}, found {:?}",
+                            position + 1,
+                            element,
+                            found
+                        ));
+                    }
+                }
+                Ok(expected.clone())
+            }
+            
+            (Expression::Call { func, args } | Expression::CallAlt { callee: func, args }, _)
+                if !self.is_higher_order(expr) =>
+            {
+                match self.call_signature(func) {
+                    Some((name, signature)) => self.infer_call(&name, &signature, args, Some(expected)),
+                    None => self.infer_expression(expr),
+                }
+            }
+            
+            (Expression::If { condition, then_branch, else_branch: Some(else_branch) }, _) => {
+                let cond_type = self.infer_expression(condition)?;
+                if cond_type != Type::Bool {
+                    return Err("Condition must be boolean".to_string());
+                }
+                let then_type = self.check_expression(then_branch, expected)?;
+                let else_type = self.check_expression(else_branch, expected)?;
+                if !self.types_match(&then_type, &else_type) {
+                    return Err(format!(
+                        "If-else expression branches have different types: {:?} vs {:?}",
+                        then_type, else_type
+                    ));
+                }
+                Ok(then_type)
+            }
+            
+            (Expression::Block(statements), _) => {
+                let mut block_type = Type::Custom("()".to_string());
+                for (index, stmt) in statements.iter().enumerate() {
+                    block_type = match stmt {
+                        Statement::Expression(value) if index + 1 == statements.len() => {
+                            self.check_expression(value, expected)?
+                        }
+                        _ => self.infer_statement(stmt)?,
+                    };
+                }
+                Ok(block_type)
+            }
+            
+            _ => self.infer_expression(expr),
+        }
+    }
+    
+    /// The signature of a call's callee when it names a known function that
+    /// no local variable shadows.
+    fn call_signature(&self, func: &Expression) -> Option<(String, FunctionSignature)> {
+        match func {
+            Expression::Ident(name) | Expression::Identifier(name) if !self.type_map.contains_key(name) => {
+                self.signatures.get(name).map(|signature| (name.clone(), signature.clone()))
+            }
+            _ => None,
+        }
+    }
+    
+    /// Checks a call's arguments against `signature` and returns its result
+    /// type. Type parameters are bound from `expected` first, when the call
+    /// has one, then from the arguments in order.
+    fn infer_call(
+        &mut self,
+        name: &str,
+        signature: &FunctionSignature,
+        args: &[Expression],
+        expected: Option<&Type>,
+    ) -> Result<Type, String> {
+        if args.len() != signature.params.len() {
+            return Err(format!(
+                "Function '{}' takes {} argument(s) but {} were supplied",
+                name,
+                signature.params.len(),
+                args.len()
+            ));
+        }
+        
+        let mut bindings = HashMap::new();
+        if let Some(expected) = expected {
+            // A return type that cannot match is reported by the caller.
+            let mut attempt = HashMap::new();
+            if self.bind_generics(&signature.return_type, expected, &signature.generics, &mut attempt) {
+                bindings = attempt;
+            }
+        }
+        
+        for (position, (arg, declared)) in args.iter().zip(&signature.params).enumerate() {
+            let param_type = Self::substitute_generics(declared, &bindings);
+            let unbound = Self::unbound_generic(declared, &signature.generics, &bindings).is_some();
+            let arg_type = if unbound {
+                self.infer_expression(arg)?
+            } else {
+                self.check_expression(arg, &param_type)?
+            };
+            if Self::is_unknown(&arg_type) {
+                continue;
+            }
+            let matches = if unbound {
+                self.bind_generics(declared, &arg_type, &signature.generics, &mut bindings)
+            } else {
+                self.expect_type(&param_type, arg, &arg_type)?
+            };
+            if !matches {
+                return Err(format!(
+                    "Argument {} of call to '{}': expected {:?}, found {:?}",
+                    position + 1,
+                    name,
+                    param_type,
+                    arg_type
+                ));
+            }
+        }
+        
+        match Self::unbound_generic(&signature.return_type, &signature.generics, &bindings) {
+            Some(param) => Err(format!(
+                "Cannot infer type parameter '{}' of call to '{}'; annotate the expected type",
+                param, name
+            )),
+            None => Ok(Self::substitute_generics(&signature.return_type, &bindings)),
+        }
+    }
+    
+    /// Matches `pattern`, which may mention the type parameters in
+    /// `generics`, against `actual`, recording what each parameter stands for.
+    fn bind_generics(
+        &self,
+        pattern: &Type,
+        actual: &Type,
+        generics: &[String],
+        bindings: &mut HashMap<String, Type>,
+    ) -> bool {
+        match (pattern, actual) {
+            (Type::Custom(param), _) if generics.contains(param) => match bindings.get(param) {
+                Some(bound) => self.types_match(bound, actual),
+                None => {
+                    bindings.insert(param.clone(), actual.clone());
+                    true
+                }
+            },
+            (Type::Generic(a, a_args), Type::Generic(b, b_args)) => {
+                a == b
+                    && a_args.len() == b_args.len()
+                    && a_args.iter().zip(b_args).all(|(p, t)| self.bind_generics(p, t, generics, bindings))
+            }
+            (Type::Array { element: p, size: a }, Type::Array { element: t, size: b }) => {
+                a == b && self.bind_generics(p, t, generics, bindings)
+            }
+            (Type::Slice(p), Type::Slice(t)) => self.bind_generics(p, t, generics, bindings),
+            (Type::Reference { mutable: a, inner: p }, Type::Reference { mutable: b, inner: t }) => {
+                a == b && self.bind_generics(p, t, generics, bindings)
+            }
+            (Type::Tuple(ps), Type::Tuple(ts)) => {
+                ps.len() == ts.len() && ps.iter().zip(ts).all(|(p, t)| self.bind_generics(p, t, generics, bindings))
+            }
+            _ => self.types_match(pattern, actual),
+        }
+    }
+    
+    fn substitute_generics(ty: &Type, bindings: &HashMap<String, Type>) -> Type {
+        let sub = |ty: &Type| Self::substitute_generics(ty, bindings);
+        match ty {
+            Type::Custom(name) => bindings.get(name).cloned().unwrap_or_else(|| ty.clone()),
+            Type::Generic(name, args) => Type::Generic(name.clone(), args.iter().map(sub).collect()),
+            Type::Array { element, size } => Type::Array { element: Box::new(sub(element)), size: *size },
+            Type::Slice(element) => Type::Slice(Box::new(sub(element))),
+            Type::Reference { mutable, inner } => Type::Reference { mutable: *mutable, inner: Box::new(sub(inner)) },
+            Type::Tuple(elements) => Type::Tuple(elements.iter().map(sub).collect()),
+            _ => ty.clone(),
+        }
+    }
+    
+    /// The first type parameter from `generics` that `ty` mentions and
+    /// `bindings` does not yet fix.
+    fn unbound_generic<'a>(
+        ty: &Type,
+        generics: &'a [String],
+        bindings: &HashMap<String, Type>,
+    ) -> Option<&'a String> {
+        match ty {
+            Type::Custom(name) => generics.iter().find(|param| *param == name && !bindings.contains_key(*param)),
+            Type::Generic(_, args) | Type::Tuple(args) => {
+                args.iter().find_map(|arg| Self::unbound_generic(arg, generics, bindings))
+            }
+            Type::Array { element: inner, .. } | Type::Slice(inner) | Type::Reference { inner, .. } => {
+                Self::unbound_generic(inner, generics, bindings)
+            }
+            _ => None,
+        }
+    }
+    
+    /// The trait an arithmetic or bitwise operator dispatches to on user types.
+    fn operator_trait(op: &BinaryOp) -> Option<&'static str> {
+        match op {
+            BinaryOp::Add => Some("Add"),
+            BinaryOp::Sub => Some("Sub"),
+            BinaryOp::Mul => Some("Mul"),
+            BinaryOp::Div => Some("Div"),
+            BinaryOp::Mod => Some("Rem"),
+            BinaryOp::BitwiseAnd => Some("BitAnd"),
+            BinaryOp::BitwiseOr => Some("BitOr"),
+            BinaryOp::BitwiseXor => Some("BitXor"),
+            BinaryOp::LeftShift => Some("Shl"),
+            BinaryOp::RightShift => Some("Shr"),
+            _ => None,
+        }
+    }
+    
+    /// Named types other than the placeholders used for unit and unknown
+    /// expressions.
+    fn is_user_type(ty: &Type) -> bool {
+        match ty {
+            Type::Custom(name) => name != "()" && name != "unknown" && name != INTEGER_LITERAL,
+            Type::Generic(..) => true,
+            _ => false,
+        }
+    }
+    
+    /// The placeholder for expressions whose type is not inferred yet, such
+    /// as calls; it is not compared against declared types.
+    fn is_unknown(ty: &Type) -> bool {
+        matches!(ty, Type::Custom(name) if name == "unknown")
+    }
+    
+    fn is_integer_literal(ty: &Type) -> bool {
+        matches!(ty, Type::Custom(name) if name == INTEGER_LITERAL)
+    }
+    
+    /// Concrete integer types, and integer literals not yet given one.
+    fn is_integer(ty: &Type) -> bool {
+        Self::is_integer_literal(ty)
+            || matches!(
+                ty,
+                Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::I128
+                    | Type::U8 | Type::U16 | Type::U32 | Type::U64 | Type::U128
+                    | Type::Isize | Type::Usize
+            )
+    }
+    
+    fn default_integer(ty: Type) -> Type {
+        if Self::is_integer_literal(&ty) {
+            Type::I32
+        } else {
+            ty
+        }
+    }
+    
+    /// Checks `value`, of type `found`, where `expected` is required. A
+    /// variable still holding an integer literal is constrained to
+    /// `expected`, so every later use sees that type.
+    fn expect_type(&mut self, expected: &Type, value: &Expression, found: &Type) -> Result<bool, String> {
+        if !self.types_match(expected, found) {
+            return Ok(false);
+        }
+        if let Expression::Ident(name) | Expression::Identifier(name) = value {
+            if let Some(&var) = self.integer_vars.get(name) {
+                if Self::is_integer(expected) && !Self::is_integer_literal(expected) {
+                    self.add_constraint(InferredType::Variable(var), InferredType::Known(expected.clone()));
+                    self.solve_constraints()?;
+                }
+            }
+        }
+        Ok(true)
+    }
+    
+    fn infer_operator_trait(&self, trait_name: &str, left: &Type, right: &Type) -> Result<Type, String> {
+        let impl_def = self.trait_registry.get_impl(trait_name, left).ok_or_else(|| {
+            format!("No implementation of '{}' for {:?}", trait_name, left)
+        })?;
+        
+        let rhs = impl_def.associated_types.get("Rhs").unwrap_or(left);
+        if rhs != right {
+            return Err(format!(
+                "'{}' for {:?} expects a right-hand side of {:?}, found {:?}",
+                trait_name, left, rhs, right
+            ));
+        }
+        
+        self.trait_registry
+            .operator_output(trait_name, left)
+            .ok_or_else(|| format!("'{}' for {:?} has no output type", trait_name, left))
+    }
+    
     fn types_match(&self, a: &Type, b: &Type) -> bool {
         match (a, b) {
+            (Type::I8, Type::I8) |
+            (Type::I16, Type::I16) |
             (Type::I32, Type::I32) |
             (Type::I64, Type::I64) |
+            (Type::I128, Type::I128) |
+            (Type::U128, Type::U128) |
+            (Type::Isize, Type::Isize) |
+            (Type::Usize, Type::Usize) |
+            (Type::U8, Type::U8) |
+            (Type::U16, Type::U16) |
+            (Type::U32, Type::U32) |
+            (Type::U64, Type::U64) |
             (Type::F32, Type::F32) |
             (Type::F64, Type::F64) |
             (Type::Bool, Type::Bool) |
             (Type::Char, Type::Char) |
             (Type::String, Type::String) => true,
             
+            (Type::Custom(literal), other) | (other, Type::Custom(literal)) if literal == INTEGER_LITERAL => {
+                Self::is_integer(other)
+            }
+            
             (Type::Custom(a), Type::Custom(b)) => a == b,
+            (Type::Generic(a, a_args), Type::Generic(b, b_args)) => {
+                a == b && a_args.len() == b_args.len() && a_args.iter().zip(b_args).all(|(a, b)| self.types_match(a, b))
+            }
+            
+            (Type::Array { element: a, size: size_a }, Type::Array { element: b, size: size_b }) => {
+                size_a == size_b && self.types_match(a, b)
+            }
+            (Type::Slice(a), Type::Slice(b)) => self.types_match(a, b),
+            (Type::Tuple(a), Type::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| self.types_match(a, b))
+            }
+            // `()` written in source is the empty tuple; statements without a
+            // value use the `Custom("()")` placeholder.
+            (Type::Tuple(unit), Type::Custom(name)) | (Type::Custom(name), Type::Tuple(unit)) => {
+                unit.is_empty() && name == "()"
+            }
             
             _ => false,
         }
     }
     
+    /// Closures, and calls or references to let-bound closures, are inferred
+    /// with type variables instead of concrete types.
+    fn is_higher_order(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::Closure { .. } => true,
+            Expression::Ident(name) | Expression::Identifier(name) => {
+                self.schemes.contains_key(name) || self.closure_scopes.iter().any(|scope| scope.contains_key(name))
+            }
+            Expression::Call { func, .. } | Expression::CallAlt { callee: func, .. } => self.is_higher_order(func),
+            _ => false,
+        }
+    }
+    
+    fn is_syntactic_value(expr: &Expression) -> bool {
+        matches!(expr, Expression::Closure { .. } | Expression::Ident(_) | Expression::Identifier(_))
+    }
+    
+    /// Infers `expr` allowing type variables, unifying as it goes.
+    fn infer_inferred(&mut self, expr: &Expression) -> Result<InferredType, String> {
+        match expr {
+            Expression::Closure { params, body } => self.infer_closure(params, body),
+            
+            Expression::Ident(name) | Expression::Identifier(name) => {
+                if let Some(ty) = self.closure_scopes.iter().rev().find_map(|scope| scope.get(name)) {
+                    return Ok(ty.clone());
+                }
+                match self.schemes.get(name).cloned() {
+                    Some(scheme) => Ok(self.instantiate(&scheme)),
+                    None => self.infer_expression(expr).map(InferredType::Known),
+                }
+            }
+            
+            Expression::Call { func, args } | Expression::CallAlt { callee: func, args } => {
+                let callee = self.infer_inferred(func)?;
+                let arg_types = args.iter().map(|arg| self.infer_inferred(arg)).collect::<Result<Vec<_>, _>>()?;
+                let result = self.new_type_var();
+                self.unify_now(&callee, &InferredType::Function(arg_types, Box::new(result.clone())))?;
+                Ok(result)
+            }
+            
+            Expression::Binary { op, left, right } => {
+                let left_type = self.infer_inferred(left)?;
+                let right_type = self.infer_inferred(right)?;
+                self.unify_now(&left_type, &right_type)?;
+                
+                use crate::parser::BinaryOp::*;
+                match op {
+                    Eq | Ne | Lt | Le | Gt | Ge => Ok(InferredType::Known(Type::Bool)),
+                    And | Or => {
+                        self.unify_now(&left_type, &InferredType::Known(Type::Bool))?;
+                        Ok(left_type)
+                    }
+                    _ => Ok(left_type),
+                }
+            }
+            
+            Expression::If { condition, then_branch, else_branch } => {
+                let cond_type = self.infer_inferred(condition)?;
+                self.unify_now(&cond_type, &InferredType::Known(Type::Bool))?;
+                let then_type = self.infer_inferred(then_branch)?;
+                if let Some(else_expr) = else_branch {
+                    let else_type = self.infer_inferred(else_expr)?;
+                    self.unify_now(&then_type, &else_type)?;
+                }
+                Ok(then_type)
+            }
+            
+            Expression::Block(statements) if !self.closure_scopes.is_empty() => {
+                let mut block_type = InferredType::Known(Type::Custom("()".to_string()));
+                for stmt in statements {
+                    block_type = match stmt {
+                        Statement::Expression(expr) => self.infer_inferred(expr)?,
+                        Statement::Let { name, value: Some(value), .. } => {
+                            let ty = self.infer_inferred(value)?;
+                            if let Some(scope) = self.closure_scopes.last_mut() {
+                                scope.insert(name.clone(), ty);
+                            }
+                            InferredType::Known(Type::Custom("()".to_string()))
+                        }
+                        _ => InferredType::Known(self.infer_statement(stmt)?),
+                    };
+                }
+                Ok(block_type)
+            }
+            
+            _ => self.infer_expression(expr).map(InferredType::Known),
+        }
+    }
+    
+    fn infer_closure(&mut self, params: &[ClosureParam], body: &Expression) -> Result<InferredType, String> {
+        let mut scope = HashMap::new();
+        let mut param_types = Vec::new();
+        for param in params {
+            let ty = match &param.ty {
+                Some(ty) => InferredType::Known(ty.clone()),
+                None => self.new_type_var(),
+            };
+            scope.insert(param.name.clone(), ty.clone());
+            param_types.push(ty);
+        }
+        
+        self.closure_scopes.push(scope);
+        let body_type = self.infer_inferred(body);
+        self.closure_scopes.pop();
+        
+        Ok(InferredType::Function(param_types, Box::new(body_type?)))
+    }
+    
+    /// Quantifies the variables of `ty` that nothing in scope still refers
+    /// to: enclosing closure parameters and monomorphic bindings keep theirs.
+    fn generalize(&self, ty: &InferredType) -> TypeScheme {
+        let ty = self.resolve(ty);
+        let mut in_scope = Vec::new();
+        for scope_type in self.closure_scopes.iter().flat_map(|scope| scope.values()) {
+            Self::free_vars(&self.resolve(scope_type), &mut in_scope);
+        }
+        for scheme in self.schemes.values().filter(|scheme| scheme.vars.is_empty()) {
+            Self::free_vars(&self.resolve(&scheme.ty), &mut in_scope);
+        }
+        
+        let mut vars = Vec::new();
+        Self::free_vars(&ty, &mut vars);
+        vars.retain(|var| !in_scope.contains(var));
+        TypeScheme { vars, ty }
+    }
+    
+    fn instantiate(&mut self, scheme: &TypeScheme) -> InferredType {
+        let fresh: HashMap<usize, InferredType> =
+            scheme.vars.iter().map(|&var| (var, self.new_type_var())).collect();
+        Self::substitute(&self.resolve(&scheme.ty), &fresh)
+    }
+    
+    fn substitute(ty: &InferredType, mapping: &HashMap<usize, InferredType>) -> InferredType {
+        match ty {
+            InferredType::Variable(var) => mapping.get(var).cloned().unwrap_or_else(|| ty.clone()),
+            InferredType::Function(params, ret) => InferredType::Function(
+                params.iter().map(|param| Self::substitute(param, mapping)).collect(),
+                Box::new(Self::substitute(ret, mapping)),
+            ),
+            InferredType::Known(_) => ty.clone(),
+        }
+    }
+    
+    fn free_vars(ty: &InferredType, vars: &mut Vec<usize>) {
+        match ty {
+            InferredType::Variable(var) if !vars.contains(var) => vars.push(*var),
+            InferredType::Function(params, ret) => {
+                for param in params {
+                    Self::free_vars(param, vars);
+                }
+                Self::free_vars(ret, vars);
+            }
+            _ => {}
+        }
+    }
+    
+    /// Applies the substitutions found so far throughout `ty`.
+    fn resolve(&self, ty: &InferredType) -> InferredType {
+        match ty {
+            InferredType::Variable(var) => match self.substitutions.get(var) {
+                Some(bound) => self.resolve(bound),
+                None => ty.clone(),
+            },
+            InferredType::Function(params, ret) => InferredType::Function(
+                params.iter().map(|param| self.resolve(param)).collect(),
+                Box::new(self.resolve(ret)),
+            ),
+            InferredType::Known(_) => ty.clone(),
+        }
+    }
+    
+    /// The concrete type of `ty`; variables not fixed yet are unknown.
+    fn to_type(&self, ty: &InferredType) -> Type {
+        match self.resolve(ty) {
+            InferredType::Known(ty) => ty,
+            InferredType::Variable(_) => Type::Custom("unknown".to_string()),
+            InferredType::Function(params, ret) => Type::Function {
+                params: params.iter().map(|param| self.to_type(param)).collect(),
+                return_type: Box::new(self.to_type(&ret)),
+            },
+        }
+    }
+    
+    fn unify_now(&mut self, a: &InferredType, b: &InferredType) -> Result<(), String> {
+        let (a, b) = (self.resolve(a), self.resolve(b));
+        match (&a, &b) {
+            (InferredType::Known(ty), _) | (_, InferredType::Known(ty)) if Self::is_unknown(ty) => Ok(()),
+            (InferredType::Variable(x), InferredType::Variable(y)) if x == y => Ok(()),
+            (InferredType::Variable(var), other) | (other, InferredType::Variable(var)) => {
+                let mut occurring = Vec::new();
+                Self::free_vars(other, &mut occurring);
+                if occurring.contains(var) {
+                    return Err(format!("Cannot construct infinite type {:?} = {:?}", var, other));
+                }
+                self.substitutions.insert(*var, other.clone());
+                Ok(())
+            }
+            (InferredType::Function(params1, ret1), InferredType::Function(params2, ret2)) => {
+                if params1.len() != params2.len() {
+                    return Err(format!(
+                        "Closure expects {} argument(s), found {}",
+                        params1.len(),
+                        params2.len()
+                    ));
+                }
+                for (p1, p2) in params1.iter().zip(params2) {
+                    self.unify_now(p1, p2)?;
+                }
+                self.unify_now(ret1, ret2)
+            }
+            (InferredType::Known(t1), InferredType::Known(t2)) if self.types_match(t1, t2) => Ok(()),
+            _ => Err(format!("Cannot unify types {:?} and {:?}", self.to_type(&a), self.to_type(&b))),
+        }
+    }
+    
     fn new_type_var(&mut self) -> InferredType {
         let var = InferredType::Variable(self.next_type_var);
         self.next_type_var += 1;
@@ -250,14 +1086,18 @@ impl TypeInferenceEngine {
         self.constraints.push(TypeConstraint { left, right });
     }
     
+    /// Solves the constraints added since the last call into the engine's
+    /// substitutions.
     pub fn solve_constraints(&mut self) -> Result<(), String> {
-        let mut substitutions: HashMap<usize, InferredType> = HashMap::new();
+        let constraints = std::mem::take(&mut self.constraints);
+        let mut substitutions = std::mem::take(&mut self.substitutions);
         
-        for constraint in &self.constraints {
-            self.unify(&constraint.left, &constraint.right, &mut substitutions)?;
-        }
+        let result = constraints
+            .iter()
+            .try_for_each(|constraint| self.unify(&constraint.left, &constraint.right, &mut substitutions));
         
-        Ok(())
+        self.substitutions = substitutions;
+        result
     }
     
     fn unify(
@@ -275,8 +1115,8 @@ impl TypeInferenceEngine {
             }
             
             (InferredType::Variable(v), t) | (t, InferredType::Variable(v)) => {
-                if let Some(existing) = substitutions.get(v) {
-                    self.unify(existing, t, substitutions)
+                if let Some(existing) = substitutions.get(v).cloned() {
+                    self.unify(&existing, t, substitutions)
                 } else {
                     substitutions.insert(*v, t.clone());
                     Ok(())
@@ -1,10 +1,13 @@
 use std::collections::HashMap;
 
 pub struct WASMGenerator {
+    types: Vec<WASMFuncType>,
+    imports: Vec<WASMImport>,
     functions: Vec<WASMFunction>,
     globals: Vec<WASMGlobal>,
     memory: WASMMemory,
     exports: Vec<WASMExport>,
+    data_segments: Vec<WASMDataSegment>,
 }
 
 #[derive(Debug, Clone)]
@@ -16,6 +19,21 @@ pub struct WASMFunction {
     pub instructions: Vec<WASMInstruction>,
 }
 
+/// A function signature declared on its own, for imports to refer to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WASMFuncType {
+    pub params: Vec<WASMType>,
+    pub results: Vec<WASMType>,
+}
+
+/// A host function, such as a BLAZE runtime entry point, called from WASM.
+#[derive(Debug, Clone)]
+pub struct WASMImport {
+    pub module: String,
+    pub name: String,
+    pub type_index: u32,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WASMType {
     I32,
@@ -40,6 +58,17 @@ pub enum WASMInstruction {
     I32Mul,
     I32DivS,
     I32DivU,
+    I64Add,
+    I64Sub,
+    I64Mul,
+    F32Add,
+    F32Sub,
+    F32Mul,
+    F32Div,
+    F64Add,
+    F64Sub,
+    F64Mul,
+    F64Div,
     Call(u32),
     Return,
     If,
@@ -65,6 +94,14 @@ pub struct WASMMemory {
     pub maximum_pages: Option<u32>,
 }
 
+/// Bytes copied into linear memory at `offset` when the module is
+/// instantiated.
+#[derive(Debug, Clone)]
+pub struct WASMDataSegment {
+    pub offset: u32,
+    pub bytes: Vec<u8>,
+}
+
 #[derive(Debug, Clone)]
 pub struct WASMExport {
     pub name: String,
@@ -83,6 +120,8 @@ pub enum WASMExportKind {
 impl WASMGenerator {
     pub fn new() -> Self {
         Self {
+            types: Vec::new(),
+            imports: Vec::new(),
             functions: Vec::new(),
             globals: Vec::new(),
             memory: WASMMemory {
@@ -90,17 +129,39 @@ impl WASMGenerator {
                 maximum_pages: None,
             },
             exports: Vec::new(),
+            data_segments: Vec::new(),
         }
     }
     
-    pub fn add_function(&mut self, func: WASMFunction) {
+    /// Declares a signature and returns its type index. Declared types come
+    /// before the ones generated for each function.
+    pub fn add_type(&mut self, params: Vec<WASMType>, results: Vec<WASMType>) -> u32 {
+        self.types.push(WASMFuncType { params, results });
+        (self.types.len() - 1) as u32
+    }
+    
+    /// Imports `module.name` with the signature at `type_index` and returns
+    /// its function index. Imports take the lowest function indices, so
+    /// every import must be added before the first function.
+    pub fn add_import(&mut self, module: String, name: String, type_index: u32) -> u32 {
+        self.imports.push(WASMImport { module, name, type_index });
+        (self.imports.len() - 1) as u32
+    }
+    
+    /// Adds a function and returns its index, which counts the imports.
+    pub fn add_function(&mut self, func: WASMFunction) -> u32 {
         self.functions.push(func);
+        (self.imports.len() + self.functions.len() - 1) as u32
     }
     
     pub fn add_global(&mut self, global: WASMGlobal) {
         self.globals.push(global);
     }
     
+    pub fn add_data_segment(&mut self, offset: u32, bytes: Vec<u8>) {
+        self.data_segments.push(WASMDataSegment { offset, bytes });
+    }
+    
     pub fn export_function(&mut self, name: String, func_index: u32) {
         self.exports.push(WASMExport {
             name,
@@ -116,34 +177,112 @@ impl WASMGenerator {
         bytes.extend_from_slice(&[0x01, 0x00, 0x00, 0x00]);
         
         self.encode_type_section(&mut bytes);
+        self.encode_import_section(&mut bytes);
         self.encode_function_section(&mut bytes);
         self.encode_memory_section(&mut bytes);
         self.encode_export_section(&mut bytes);
         self.encode_code_section(&mut bytes);
+        self.encode_data_section(&mut bytes);
         
         bytes
     }
     
+    /// Renders the module in the WebAssembly text format, one instruction per
+    /// line and indented by block nesting. Meant for reading generated code;
+    /// `generate` is the output that is actually run.
+    pub fn generate_wat(&self) -> String {
+        let mut output = String::from("(module\n");
+        
+        for ty in &self.types {
+            output.push_str(&format!("  (type (func{}))\n", self.signature_to_wat(&ty.params, &ty.results)));
+        }
+        
+        for import in &self.imports {
+            output.push_str(&format!(
+                "  (import {} {} (func (type {})))\n",
+                self.string_to_wat(import.module.as_bytes()),
+                self.string_to_wat(import.name.as_bytes()),
+                import.type_index
+            ));
+        }
+        
+        for func in &self.functions {
+            output.push_str(&format!("  (func ${}{}\n", func.name, self.signature_to_wat(&func.params, &func.results)));
+            if !func.locals.is_empty() {
+                let locals: Vec<&str> = func.locals.iter().map(|local| self.type_to_wat(*local)).collect();
+                output.push_str(&format!("    (local {})\n", locals.join(" ")));
+            }
+            
+            let mut depth = 2;
+            for instr in &func.instructions {
+                if matches!(instr, WASMInstruction::Else | WASMInstruction::End) {
+                    depth -= 1;
+                }
+                output.push_str(&format!("{}{}\n", "  ".repeat(depth), self.instruction_to_wat(instr)));
+                if matches!(
+                    instr,
+                    WASMInstruction::Block | WASMInstruction::Loop | WASMInstruction::If | WASMInstruction::Else
+                ) {
+                    depth += 1;
+                }
+            }
+            output.push_str("  )\n");
+        }
+        
+        match self.memory.maximum_pages {
+            Some(max) => output.push_str(&format!("  (memory {} {})\n", self.memory.initial_pages, max)),
+            None => output.push_str(&format!("  (memory {})\n", self.memory.initial_pages)),
+        }
+        
+        for export in &self.exports {
+            let kind = match export.kind {
+                WASMExportKind::Function => "func",
+                WASMExportKind::Table => "table",
+                WASMExportKind::Memory => "memory",
+                WASMExportKind::Global => "global",
+            };
+            output.push_str(&format!(
+                "  (export {} ({} {}))\n",
+                self.string_to_wat(export.name.as_bytes()),
+                kind,
+                export.index
+            ));
+        }
+        
+        for segment in &self.data_segments {
+            output.push_str(&format!(
+                "  (data (i32.const {}) {})\n",
+                segment.offset as i32,
+                self.string_to_wat(&segment.bytes)
+            ));
+        }
+        
+        output.push_str(")\n");
+        output
+    }
+    
     fn encode_type_section(&self, bytes: &mut Vec<u8>) {
-        if self.functions.is_empty() {
+        if self.types.is_empty() && self.functions.is_empty() {
             return;
         }
         
         bytes.push(0x01);
         
         let mut section_bytes = Vec::new();
-        self.encode_varuint(&mut section_bytes, self.functions.len() as u32);
+        self.encode_varuint(&mut section_bytes, (self.types.len() + self.functions.len()) as u32);
         
-        for func in &self.functions {
+        let declared = self.types.iter().map(|ty| (&ty.params, &ty.results));
+        let generated = self.functions.iter().map(|func| (&func.params, &func.results));
+        for (params, results) in declared.chain(generated) {
             section_bytes.push(0x60);
             
-            self.encode_varuint(&mut section_bytes, func.params.len() as u32);
-            for param in &func.params {
+            self.encode_varuint(&mut section_bytes, params.len() as u32);
+            for param in params {
                 section_bytes.push(self.type_to_byte(*param));
             }
             
-            self.encode_varuint(&mut section_bytes, func.results.len() as u32);
-            for result in &func.results {
+            self.encode_varuint(&mut section_bytes, results.len() as u32);
+            for result in results {
                 section_bytes.push(self.type_to_byte(*result));
             }
         }
@@ -152,6 +291,27 @@ impl WASMGenerator {
         bytes.extend_from_slice(&section_bytes);
     }
     
+    fn encode_import_section(&self, bytes: &mut Vec<u8>) {
+        if self.imports.is_empty() {
+            return;
+        }
+        
+        bytes.push(0x02);
+        
+        let mut section_bytes = Vec::new();
+        self.encode_varuint(&mut section_bytes, self.imports.len() as u32);
+        
+        for import in &self.imports {
+            self.encode_string(&mut section_bytes, &import.module);
+            self.encode_string(&mut section_bytes, &import.name);
+            section_bytes.push(0x00);
+            self.encode_varuint(&mut section_bytes, import.type_index);
+        }
+        
+        self.encode_varuint(bytes, section_bytes.len() as u32);
+        bytes.extend_from_slice(&section_bytes);
+    }
+    
     fn encode_function_section(&self, bytes: &mut Vec<u8>) {
         if self.functions.is_empty() {
             return;
@@ -163,7 +323,7 @@ impl WASMGenerator {
         self.encode_varuint(&mut section_bytes, self.functions.len() as u32);
         
         for i in 0..self.functions.len() {
-            self.encode_varuint(&mut section_bytes, i as u32);
+            self.encode_varuint(&mut section_bytes, (self.types.len() + i) as u32);
         }
         
         self.encode_varuint(bytes, section_bytes.len() as u32);
@@ -249,7 +409,36 @@ impl WASMGenerator {
         bytes.extend_from_slice(&section_bytes);
     }
     
+    /// Emits every segment as active in memory 0, placed by an `i32.const`
+    /// offset expression.
+    fn encode_data_section(&self, bytes: &mut Vec<u8>) {
+        if self.data_segments.is_empty() {
+            return;
+        }
+        
+        bytes.push(0x0B);
+        
+        let mut section_bytes = Vec::new();
+        self.encode_varuint(&mut section_bytes, self.data_segments.len() as u32);
+        
+        for segment in &self.data_segments {
+            section_bytes.push(0x00);
+            self.encode_instruction(&mut section_bytes, &WASMInstruction::I32Const(segment.offset as i32));
+            section_bytes.push(0x0B);
+            
+            self.encode_varuint(&mut section_bytes, segment.bytes.len() as u32);
+            section_bytes.extend_from_slice(&segment.bytes);
+        }
+        
+        self.encode_varuint(bytes, section_bytes.len() as u32);
+        bytes.extend_from_slice(&section_bytes);
+    }
+    
+    /// Structured instructions (`If`, `Loop`, `Block`) are emitted with the
+    /// empty block type, so they neither take nor leave values on the stack.
     fn encode_instruction(&self, bytes: &mut Vec<u8>, instr: &WASMInstruction) {
+        const EMPTY_BLOCK_TYPE: u8 = 0x40;
+
         match instr {
             WASMInstruction::LocalGet(idx) => {
                 bytes.push(0x20);
@@ -259,17 +448,156 @@ impl WASMGenerator {
                 bytes.push(0x21);
                 self.encode_varuint(bytes, *idx);
             }
+            WASMInstruction::LocalTee(idx) => {
+                bytes.push(0x22);
+                self.encode_varuint(bytes, *idx);
+            }
+            WASMInstruction::GlobalGet(idx) => {
+                bytes.push(0x23);
+                self.encode_varuint(bytes, *idx);
+            }
+            WASMInstruction::GlobalSet(idx) => {
+                bytes.push(0x24);
+                self.encode_varuint(bytes, *idx);
+            }
             WASMInstruction::I32Const(val) => {
                 bytes.push(0x41);
                 self.encode_varint(bytes, *val as i64);
             }
+            WASMInstruction::I64Const(val) => {
+                bytes.push(0x42);
+                self.encode_varint(bytes, *val);
+            }
+            WASMInstruction::F32Const(val) => {
+                bytes.push(0x43);
+                bytes.extend_from_slice(&val.to_le_bytes());
+            }
+            WASMInstruction::F64Const(val) => {
+                bytes.push(0x44);
+                bytes.extend_from_slice(&val.to_le_bytes());
+            }
             WASMInstruction::I32Add => bytes.push(0x6A),
             WASMInstruction::I32Sub => bytes.push(0x6B),
             WASMInstruction::I32Mul => bytes.push(0x6C),
+            WASMInstruction::I32DivS => bytes.push(0x6D),
+            WASMInstruction::I32DivU => bytes.push(0x6E),
+            WASMInstruction::I64Add => bytes.push(0x7C),
+            WASMInstruction::I64Sub => bytes.push(0x7D),
+            WASMInstruction::I64Mul => bytes.push(0x7E),
+            WASMInstruction::F32Add => bytes.push(0x92),
+            WASMInstruction::F32Sub => bytes.push(0x93),
+            WASMInstruction::F32Mul => bytes.push(0x94),
+            WASMInstruction::F32Div => bytes.push(0x95),
+            WASMInstruction::F64Add => bytes.push(0xA0),
+            WASMInstruction::F64Sub => bytes.push(0xA1),
+            WASMInstruction::F64Mul => bytes.push(0xA2),
+            WASMInstruction::F64Div => bytes.push(0xA3),
+            WASMInstruction::Call(idx) => {
+                bytes.push(0x10);
+                self.encode_varuint(bytes, *idx);
+            }
             WASMInstruction::Return => bytes.push(0x0F),
+            WASMInstruction::If => bytes.extend_from_slice(&[0x04, EMPTY_BLOCK_TYPE]),
+            WASMInstruction::Else => bytes.push(0x05),
             WASMInstruction::End => bytes.push(0x0B),
-            _ => {}
+            WASMInstruction::Loop => bytes.extend_from_slice(&[0x03, EMPTY_BLOCK_TYPE]),
+            WASMInstruction::Block => bytes.extend_from_slice(&[0x02, EMPTY_BLOCK_TYPE]),
+            WASMInstruction::Br(depth) => {
+                bytes.push(0x0C);
+                self.encode_varuint(bytes, *depth);
+            }
+            WASMInstruction::BrIf(depth) => {
+                bytes.push(0x0D);
+                self.encode_varuint(bytes, *depth);
+            }
+        }
+    }
+    
+    fn instruction_to_wat(&self, instr: &WASMInstruction) -> String {
+        match instr {
+            WASMInstruction::LocalGet(idx) => format!("local.get {}", idx),
+            WASMInstruction::LocalSet(idx) => format!("local.set {}", idx),
+            WASMInstruction::LocalTee(idx) => format!("local.tee {}", idx),
+            WASMInstruction::GlobalGet(idx) => format!("global.get {}", idx),
+            WASMInstruction::GlobalSet(idx) => format!("global.set {}", idx),
+            WASMInstruction::I32Const(val) => format!("i32.const {}", val),
+            WASMInstruction::I64Const(val) => format!("i64.const {}", val),
+            WASMInstruction::F32Const(val) => format!("f32.const {}", self.float_to_wat(*val as f64)),
+            WASMInstruction::F64Const(val) => format!("f64.const {}", self.float_to_wat(*val)),
+            WASMInstruction::I32Add => "i32.add".to_string(),
+            WASMInstruction::I32Sub => "i32.sub".to_string(),
+            WASMInstruction::I32Mul => "i32.mul".to_string(),
+            WASMInstruction::I32DivS => "i32.div_s".to_string(),
+            WASMInstruction::I32DivU => "i32.div_u".to_string(),
+            WASMInstruction::I64Add => "i64.add".to_string(),
+            WASMInstruction::I64Sub => "i64.sub".to_string(),
+            WASMInstruction::I64Mul => "i64.mul".to_string(),
+            WASMInstruction::F32Add => "f32.add".to_string(),
+            WASMInstruction::F32Sub => "f32.sub".to_string(),
+            WASMInstruction::F32Mul => "f32.mul".to_string(),
+            WASMInstruction::F32Div => "f32.div".to_string(),
+            WASMInstruction::F64Add => "f64.add".to_string(),
+            WASMInstruction::F64Sub => "f64.sub".to_string(),
+            WASMInstruction::F64Mul => "f64.mul".to_string(),
+            WASMInstruction::F64Div => "f64.div".to_string(),
+            WASMInstruction::Call(idx) => format!("call {}", idx),
+            WASMInstruction::Return => "return".to_string(),
+            WASMInstruction::If => "if".to_string(),
+            WASMInstruction::Else => "else".to_string(),
+            WASMInstruction::End => "end".to_string(),
+            WASMInstruction::Loop => "loop".to_string(),
+            WASMInstruction::Block => "block".to_string(),
+            WASMInstruction::Br(depth) => format!("br {}", depth),
+            WASMInstruction::BrIf(depth) => format!("br_if {}", depth),
+        }
+    }
+    
+    /// ` (param ...) (result ...)`, leaving out whichever is empty.
+    fn signature_to_wat(&self, params: &[WASMType], results: &[WASMType]) -> String {
+        let mut signature = String::new();
+        for (keyword, types) in [("param", params), ("result", results)] {
+            if !types.is_empty() {
+                let names: Vec<&str> = types.iter().map(|ty| self.type_to_wat(*ty)).collect();
+                signature.push_str(&format!(" ({} {})", keyword, names.join(" ")));
+            }
+        }
+        signature
+    }
+    
+    fn type_to_wat(&self, ty: WASMType) -> &'static str {
+        match ty {
+            WASMType::I32 => "i32",
+            WASMType::I64 => "i64",
+            WASMType::F32 => "f32",
+            WASMType::F64 => "f64",
+        }
+    }
+    
+    fn float_to_wat(&self, val: f64) -> String {
+        if val.is_nan() {
+            "nan".to_string()
+        } else if val.is_infinite() {
+            if val > 0.0 { "inf" } else { "-inf" }.to_string()
+        } else {
+            format!("{}", val)
+        }
+    }
+    
+    /// A quoted string, escaping quotes, backslashes and non-printable bytes.
+    fn string_to_wat(&self, bytes: &[u8]) -> String {
+        let mut quoted = String::from("\"");
+        for &byte in bytes {
+            match byte {
+                b'"' | b'\\' => {
+                    quoted.push('\\');
+                    quoted.push(byte as char);
+                }
+                0x20..=0x7E => quoted.push(byte as char),
+                _ => quoted.push_str(&format!("\\{:02x}", byte)),
+            }
         }
+        quoted.push('"');
+        quoted
     }
     
     fn type_to_byte(&self, ty: WASMType) -> u8 {
@@ -324,3 +652,178 @@ impl Default for WASMGenerator {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use WASMInstruction::*;
+
+    #[test]
+    fn test_float_and_control_flow_function_encodes_to_reference_bytes() {
+        // fn halve(n: i32, x: f64) -> f64 { while n != 0 { x = x * 0.5; n = n - 1 } x }
+        // The reference module validates and runs under a WebAssembly engine.
+        let mut generator = WASMGenerator::new();
+        generator.add_function(WASMFunction {
+            name: "halve".to_string(),
+            params: vec![WASMType::I32, WASMType::F64],
+            results: vec![WASMType::F64],
+            locals: vec![],
+            instructions: vec![
+                Block,
+                Loop,
+                LocalGet(0),
+                If,
+                LocalGet(1),
+                F64Const(0.5),
+                F64Mul,
+                LocalSet(1),
+                LocalGet(0),
+                I32Const(1),
+                I32Sub,
+                LocalSet(0),
+                Br(1),
+                Else,
+                Br(2),
+                End,
+                End,
+                End,
+                LocalGet(1),
+            ],
+        });
+        generator.export_function("halve".to_string(), 0);
+
+        #[rustfmt::skip]
+        let expected: Vec<u8> = vec![
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00,
+            // type: (i32, f64) -> f64
+            0x01, 0x07, 0x01, 0x60, 0x02, 0x7F, 0x7C, 0x01, 0x7C,
+            // function
+            0x03, 0x02, 0x01, 0x00,
+            // memory: 1 page
+            0x05, 0x03, 0x01, 0x00, 0x01,
+            // export "halve"
+            0x07, 0x09, 0x01, 0x05, b'h', b'a', b'l', b'v', b'e', 0x00, 0x00,
+            // code
+            0x0A, 0x2B, 0x01, 0x29, 0x00,
+            0x02, 0x40,                                     // block
+            0x03, 0x40,                                     //   loop
+            0x20, 0x00,                                     //     local.get 0
+            0x04, 0x40,                                     //     if
+            0x20, 0x01,                                     //       local.get 1
+            0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xE0, 0x3F, // f64.const 0.5
+            0xA2,                                           //       f64.mul
+            0x21, 0x01,                                     //       local.set 1
+            0x20, 0x00, 0x41, 0x01, 0x6B, 0x21, 0x00,       //       n = n - 1
+            0x0C, 0x01,                                     //       br 1
+            0x05,                                           //     else
+            0x0C, 0x02,                                     //       br 2
+            0x0B, 0x0B, 0x0B,                               // end end end
+            0x20, 0x01,                                     // local.get 1
+            0x0B,
+        ];
+        assert_eq!(generator.generate(), expected);
+    }
+
+    #[test]
+    fn test_wide_immediates_use_leb128_and_little_endian_floats() {
+        let generator = WASMGenerator::new();
+        let encode = |instr: WASMInstruction| {
+            let mut bytes = Vec::new();
+            generator.encode_instruction(&mut bytes, &instr);
+            bytes
+        };
+
+        assert_eq!(encode(I64Const(-129)), vec![0x42, 0xFF, 0x7E]);
+        assert_eq!(encode(I64Const(i64::MAX)), vec![0x42, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00]);
+        assert_eq!(encode(F32Const(1.0)), vec![0x43, 0x00, 0x00, 0x80, 0x3F]);
+        assert_eq!(encode(BrIf(200)), vec![0x0D, 0xC8, 0x01]);
+        assert_eq!(encode(GlobalSet(3)), vec![0x24, 0x03]);
+        assert_eq!(encode(Call(1)), vec![0x10, 0x01]);
+    }
+
+    #[test]
+    fn test_call_to_import_targets_the_lowest_function_index() {
+        let mut generator = WASMGenerator::new();
+        let print_type = generator.add_type(vec![WASMType::I32], vec![]);
+        let print = generator.add_import("env".to_string(), "print".to_string(), print_type);
+        let main = generator.add_function(WASMFunction {
+            name: "main".to_string(),
+            params: vec![],
+            results: vec![],
+            locals: vec![],
+            instructions: vec![I32Const(42), Call(print)],
+        });
+        generator.export_function("main".to_string(), main);
+        assert_eq!((print, main), (0, 1));
+
+        #[rustfmt::skip]
+        let expected: Vec<u8> = vec![
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00,
+            // types: 0 = (i32) -> (), 1 = () -> () for main
+            0x01, 0x08, 0x02, 0x60, 0x01, 0x7F, 0x00, 0x60, 0x00, 0x00,
+            // import "env" "print" as function 0 of type 0
+            0x02, 0x0D, 0x01, 0x03, b'e', b'n', b'v', 0x05, b'p', b'r', b'i', b'n', b't', 0x00, 0x00,
+            // function 1 has type 1
+            0x03, 0x02, 0x01, 0x01,
+            0x05, 0x03, 0x01, 0x00, 0x01,
+            0x07, 0x08, 0x01, 0x04, b'm', b'a', b'i', b'n', 0x00, 0x01,
+            // i32.const 42; call 0
+            0x0A, 0x08, 0x01, 0x06, 0x00, 0x41, 0x2A, 0x10, 0x00, 0x0B,
+        ];
+        assert_eq!(generator.generate(), expected);
+    }
+
+    #[test]
+    fn test_wat_for_add_function() {
+        let mut generator = WASMGenerator::new();
+        let add = generator.add_function(WASMFunction {
+            name: "add".to_string(),
+            params: vec![WASMType::I32, WASMType::I32],
+            results: vec![WASMType::I32],
+            locals: vec![WASMType::F64],
+            instructions: vec![LocalGet(0), LocalGet(1), I32Add, LocalGet(0), If, F64Const(0.5), LocalSet(2), End],
+        });
+        generator.export_function("add".to_string(), add);
+        generator.add_data_segment(16, b"\"hi\"\n".to_vec());
+
+        let expected = r#"(module
+  (func $add (param i32 i32) (result i32)
+    (local f64)
+    local.get 0
+    local.get 1
+    i32.add
+    local.get 0
+    if
+      f64.const 0.5
+      local.set 2
+    end
+  )
+  (memory 1)
+  (export "add" (func 0))
+  (data (i32.const 16) "\"hi\"\0a")
+)
+"#;
+        assert_eq!(generator.generate_wat(), expected);
+    }
+
+    #[test]
+    fn test_data_segment_is_emitted_after_the_code_section() {
+        let mut generator = WASMGenerator::new();
+        generator.add_data_segment(1024, b"hello\n".to_vec());
+
+        let bytes = generator.generate();
+
+        #[rustfmt::skip]
+        let data_section = [
+            0x0B, 0x0D, 0x01,
+            0x00, 0x41, 0x80, 0x08, 0x0B,           // memory 0, offset i32.const 1024
+            0x06, b'h', b'e', b'l', b'l', b'o', b'\n',
+        ];
+        assert!(bytes.ends_with(&data_section), "{:02X?}", bytes);
+
+        // Decoding the segment gives back what was added.
+        let segment = &bytes[bytes.len() - data_section.len() + 3..];
+        let len = segment[5] as usize;
+        assert_eq!(&segment[6..6 + len], b"hello\n");
+    }
+}
@@ -342,3 +342,32 @@ fn test_borrow_checker_single_statement() {
     let result = check(source);
     assert!(result.is_ok(), "Single statement should pass borrow checking");
 }
+
+fn borrow_check(source: &str) -> Result<(), String> {
+    let program = parse(lex(source).unwrap()).unwrap();
+    match &program.items[0] {
+        parser::Item::Function(func) => borrow_checker::BorrowChecker::new().check_function(func),
+        _ => panic!("expected a function"),
+    }
+}
+
+/// Test that a `for` loop variable is only visible inside the loop body
+#[test]
+fn test_for_loop_variable_is_scoped_to_body() {
+    assert_eq!(borrow_check("fn main() { let n = 3; for i in 0..n { let x = i; } }"), Ok(()));
+
+    let err = borrow_check("fn main() { for i in 0..3 { } let x = i; }").unwrap_err();
+    assert!(err.contains("undeclared variable 'i'"), "{}", err);
+}
+
+/// Test that field accesses and method calls check their receiver
+#[test]
+fn test_field_access_and_method_call_receivers_are_checked() {
+    assert_eq!(borrow_check("fn main() { let p = 1; let x = p.x; p.reset(x); }"), Ok(()));
+
+    let err = borrow_check("fn main() { let x = q.x; }").unwrap_err();
+    assert!(err.contains("undeclared variable 'q'"), "{}", err);
+
+    let err = borrow_check("fn main() { let p = 1; p.reset(r.y); }").unwrap_err();
+    assert!(err.contains("undeclared variable 'r'"), "{}", err);
+}
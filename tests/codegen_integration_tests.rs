@@ -512,3 +512,51 @@ fn test_linker_integration() {
         }
     }
 }
+
+#[test]
+fn test_relocation_model_defaults_to_pic_on_unix() {
+    use blaze_compiler::codegen::RelocModel;
+
+    let codegen = blaze_compiler::codegen::CodeGenerator::new();
+    if cfg!(unix) {
+        assert_eq!(codegen.relocation_model(), RelocModel::PIC);
+    } else {
+        assert_eq!(codegen.relocation_model(), RelocModel::Static);
+    }
+}
+
+#[test]
+fn test_relocation_model_cli_flag() {
+    use blaze_compiler::cli::{Cli, Commands};
+    use blaze_compiler::codegen::RelocModel;
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["blaze", "build", "lib.blz", "--relocation-model", "dynamic-no-pic"]).unwrap();
+    match cli.command {
+        Commands::Build { relocation_model, .. } => assert_eq!(relocation_model, RelocModel::DynamicNoPic),
+        other => panic!("expected build command, got {:?}", other),
+    }
+
+    assert!(Cli::try_parse_from(["blaze", "build", "lib.blz", "--relocation-model", "ropi"]).is_err());
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_pic_relocation_model_reaches_target_machine() {
+    use blaze_compiler::codegen::{LLVMCodegen, RelocModel};
+    use inkwell::context::Context;
+    use inkwell::targets::RelocMode;
+
+    let context = Context::create();
+    let mut backend = LLVMCodegen::new(&context, "shared_lib");
+    backend.set_relocation_model(RelocModel::Static);
+    assert_eq!(backend.reloc_mode(), RelocMode::Static);
+
+    backend.set_relocation_model(RelocModel::PIC);
+    assert_eq!(backend.reloc_mode(), RelocMode::PIC);
+
+    let temp_dir = create_temp_dir();
+    let object = temp_dir.path().join("libshared.o");
+    backend.generate(&ir::Module::new("shared_lib"), Some(object.clone()), None).unwrap();
+    assert!(fs::metadata(&object).unwrap().len() > 0);
+}
@@ -0,0 +1,171 @@
+use blaze_compiler::lexer::{Token, TokenType};
+use blaze_compiler::parser::{BinaryOp, Expression, Function, Item, Param, Program, Statement, Type, UnaryOp, Visibility};
+use blaze_compiler::testing::fuzz::{check_lex, check_parse, check_pipeline, check_round_trip, fuzz_corpus, pretty_print};
+use proptest::prelude::*;
+use proptest::test_runner::{Config, RngAlgorithm, TestRng, TestRunner};
+use std::fs;
+use std::path::Path;
+
+/// A runner with a fixed seed, so a failure reproduces on every run.
+fn runner(cases: u32) -> TestRunner {
+    TestRunner::new_with_rng(
+        Config { cases, failure_persistence: None, ..Config::default() },
+        TestRng::deterministic_rng(RngAlgorithm::ChaCha),
+    )
+}
+
+fn seed_corpus() -> Vec<(String, Vec<u8>)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fuzz_corpus");
+    let mut seeds: Vec<(String, Vec<u8>)> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "blz"))
+        .map(|path| (path.display().to_string(), fs::read(&path).unwrap()))
+        .collect();
+    seeds.sort();
+    seeds
+}
+
+fn token_type() -> impl Strategy<Value = TokenType> {
+    prop_oneof![
+        any::<i64>().prop_map(TokenType::IntLit),
+        "[a-z]{1,4}".prop_map(TokenType::Ident),
+        "[a-z ]{0,4}".prop_map(TokenType::StringLit),
+        prop::sample::select(vec![
+            TokenType::Fn, TokenType::Let, TokenType::Mut, TokenType::Return, TokenType::If,
+            TokenType::Else, TokenType::While, TokenType::Struct, TokenType::Const, TokenType::True,
+            TokenType::False, TokenType::I32, TokenType::I64, TokenType::Bool, TokenType::String,
+            TokenType::Plus, TokenType::Minus, TokenType::Star, TokenType::Slash, TokenType::Bang,
+            TokenType::Equal, TokenType::EqualEqual, TokenType::Less, TokenType::Greater,
+            TokenType::RightShift, TokenType::And, TokenType::Or, TokenType::Question,
+            TokenType::Arrow, TokenType::Colon, TokenType::Semicolon, TokenType::Comma,
+            TokenType::LeftParen, TokenType::RightParen, TokenType::LeftBrace, TokenType::RightBrace,
+            TokenType::LeftBracket, TokenType::RightBracket, TokenType::Eof,
+        ]),
+    ]
+}
+
+fn token_stream() -> impl Strategy<Value = Vec<Token>> {
+    prop::collection::vec(token_type(), 0..64)
+        .prop_map(|types| types.into_iter().enumerate().map(|(i, ty)| Token::new(ty, 1, i + 1)).collect())
+}
+
+const IDENTS: &[&str] = &["a", "b", "count", "total", "value", "x1", "y_2"];
+
+fn ident() -> impl Strategy<Value = String> {
+    prop::sample::select(IDENTS).prop_map(str::to_string)
+}
+
+fn expression() -> impl Strategy<Value = Expression> {
+    let leaf = prop_oneof![
+        (0..i64::MAX).prop_map(Expression::IntLit),
+        any::<bool>().prop_map(Expression::BoolLit),
+        "[a-z ]{0,8}".prop_map(Expression::StringLit),
+        ident().prop_map(Expression::Ident),
+    ];
+    leaf.prop_recursive(4, 32, 3, |inner| {
+        prop_oneof![
+            (
+                prop::sample::select(vec![
+                    BinaryOp::Add, BinaryOp::Sub, BinaryOp::Mul, BinaryOp::Div, BinaryOp::Mod,
+                    BinaryOp::Eq, BinaryOp::Ne, BinaryOp::Lt, BinaryOp::Le, BinaryOp::Gt,
+                    BinaryOp::Ge, BinaryOp::And, BinaryOp::Or,
+                ]),
+                inner.clone(),
+                inner.clone(),
+            )
+                .prop_map(|(op, left, right)| Expression::Binary { op, left: Box::new(left), right: Box::new(right) }),
+            (prop::sample::select(vec![UnaryOp::Neg, UnaryOp::Not]), inner.clone())
+                .prop_map(|(op, expr)| Expression::Unary { op, expr: Box::new(expr) }),
+            (ident(), prop::collection::vec(inner, 0..3))
+                .prop_map(|(name, args)| Expression::Call { func: Box::new(Expression::Ident(name)), args }),
+        ]
+    })
+}
+
+fn statement() -> impl Strategy<Value = Statement> {
+    let simple = prop_oneof![
+        (ident(), any::<bool>(), prop::option::of(Just(Type::I64)), expression())
+            .prop_map(|(name, mutable, ty, value)| Statement::Let { name, mutable, ty, value: Some(value) }),
+        prop::option::of(expression()).prop_map(Statement::Return),
+        expression().prop_map(Statement::Expression),
+    ];
+    simple.prop_recursive(2, 16, 3, |inner| {
+        let block = prop::collection::vec(inner, 0..3);
+        prop_oneof![
+            (expression(), block.clone()).prop_map(|(condition, body)| Statement::While { condition, body }),
+            (expression(), block.clone(), prop::option::of(block))
+                .prop_map(|(condition, then_body, else_body)| Statement::If { condition, then_body, else_body }),
+        ]
+    })
+}
+
+fn program() -> impl Strategy<Value = Program> {
+    let function = (ident(), prop::collection::vec((ident(), Just(Type::I32)), 0..3), prop::collection::vec(statement(), 0..4))
+        .prop_map(|(name, params, body)| {
+            Item::Function(Function {
+                attributes: Vec::new(),
+                visibility: Visibility::Private,
+                name,
+                generics: Vec::new(),
+                params: params.into_iter().map(|(name, ty)| Param { name, ty }).collect(),
+                return_type: None,
+                where_clause: None,
+                body,
+                is_async: false,
+                is_unsafe: false,
+                is_const: false,
+            })
+        });
+    prop::collection::vec(function, 1..3).prop_map(|items| Program { items })
+}
+
+#[test]
+fn test_lexer_never_panics_on_random_bytes() {
+    runner(512)
+        .run(&prop::collection::vec(any::<u8>(), 0..256), |bytes| {
+            prop_assert_eq!(check_lex(&bytes), Ok(()));
+            prop_assert_eq!(check_pipeline(&bytes), Ok(()));
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_parser_never_panics_on_random_token_streams() {
+    runner(1024)
+        .run(&token_stream(), |tokens| {
+            prop_assert_eq!(check_parse(tokens), Ok(()));
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_pretty_printed_programs_round_trip() {
+    runner(256)
+        .run(&program(), |program| {
+            let source = pretty_print(&program);
+            prop_assert_eq!(check_round_trip(&source), Ok(()));
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_seed_corpus_round_trips_and_survives_mutation() {
+    let seeds = seed_corpus();
+    assert!(!seeds.is_empty(), "no seeds in tests/fuzz_corpus");
+
+    for (path, seed) in &seeds {
+        let source = String::from_utf8(seed.clone()).unwrap();
+        blaze_compiler::parse(blaze_compiler::lex(&source).unwrap())
+            .unwrap_or_else(|e| panic!("seed {} does not parse: {}", path, e));
+        assert_eq!(check_round_trip(&source), Ok(()), "seed {}", path);
+    }
+
+    let seeds: Vec<Vec<u8>> = seeds.into_iter().map(|(_, seed)| seed).collect();
+    let result = fuzz_corpus(&seeds, 2000);
+    assert_eq!(result.total_executions, 2000);
+    assert_eq!(result.crashes, 0);
+}
@@ -0,0 +1,40 @@
+use blaze_compiler::incremental::{IncrementalCompiler, ItemDependencyGraph};
+use blaze_compiler::{lex, parse};
+use std::collections::HashSet;
+
+fn graph(source: &str) -> ItemDependencyGraph {
+    ItemDependencyGraph::build(&parse(lex(source).unwrap()).unwrap())
+}
+
+fn names(items: &[&str]) -> HashSet<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_item_graph_records_callees() {
+    let graph = graph("fn a() { b(); } fn b() { c(); } fn c() { }");
+
+    assert_eq!(graph.dependencies_of("a"), Some(&names(&["b"])));
+    assert_eq!(graph.get_affected_items("c"), names(&["a", "b"]));
+}
+
+#[test]
+fn test_changing_leaf_only_dirties_its_callers() {
+    let mut compiler = IncrementalCompiler::new();
+    compiler.dirty_items(graph("fn a() { let x = 1; } fn b() { leaf(); } fn leaf() { let y = 1; }"));
+
+    let dirty = compiler.dirty_items(graph("fn a() { let x = 1; } fn b() { leaf(); } fn leaf() { let y = 2; }"));
+
+    assert_eq!(dirty, names(&["leaf", "b"]));
+    assert_eq!(compiler.get_affected_items("leaf"), names(&["b"]));
+}
+
+#[test]
+fn test_mutually_recursive_items_share_a_component() {
+    let graph = graph("fn a() { b(); } fn b() { a(); c(); } fn c() { }");
+
+    assert_eq!(
+        graph.strongly_connected_components(),
+        vec![vec!["c".to_string()], vec!["a".to_string(), "b".to_string()]]
+    );
+}
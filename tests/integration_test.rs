@@ -192,7 +192,7 @@ fn test_references() {
 #[test]
 fn test_traits() {
     let source = r#"
-        trait Display {
+        trait Show {
             fn show(&self);
         }
         
@@ -201,7 +201,7 @@ fn test_traits() {
             y: i32,
         }
         
-        impl Display for Point {
+        impl Show for Point {
             fn show(&self) {
                 // Implementation
             }
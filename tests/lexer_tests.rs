@@ -1,3 +1,4 @@
+use blaze_compiler::error::CompileError;
 use blaze_compiler::lexer::{lex, TokenType};
 
 #[test]
@@ -23,6 +24,43 @@ fn test_numbers() {
     assert_eq!(tokens[1].token_type, TokenType::FloatLit(3.14));
 }
 
+#[test]
+fn test_prefixed_integer_literals() {
+    let tokens = lex("0xFF 0x1f 0o17 0b1010 0").unwrap();
+
+    assert_eq!(tokens[0].token_type, TokenType::IntLit(255));
+    assert_eq!(tokens[1].token_type, TokenType::IntLit(31));
+    assert_eq!(tokens[2].token_type, TokenType::IntLit(15));
+    assert_eq!(tokens[3].token_type, TokenType::IntLit(10));
+    assert_eq!(tokens[4].token_type, TokenType::IntLit(0));
+}
+
+#[test]
+fn test_digit_separators() {
+    let tokens = lex("1_000_000 0xFF_FF 0b1111_0000 1_000.5").unwrap();
+
+    assert_eq!(tokens[0].token_type, TokenType::IntLit(1_000_000));
+    assert_eq!(tokens[1].token_type, TokenType::IntLit(0xFFFF));
+    assert_eq!(tokens[2].token_type, TokenType::IntLit(0b1111_0000));
+    assert_eq!(tokens[3].token_type, TokenType::FloatLit(1000.5));
+}
+
+#[test]
+fn test_invalid_digit_for_radix_is_rejected() {
+    match lex("let x = 0xG;").unwrap_err() {
+        CompileError::LexError { message, line, column, .. } => {
+            assert_eq!(message, "Invalid digit 'G' in hexadecimal literal");
+            assert_eq!((line, column), (1, 9));
+        }
+        other => panic!("expected a lex error, got {:?}", other),
+    }
+
+    assert!(lex("0b102").is_err());
+    assert!(lex("0o8").is_err());
+    assert!(lex("0x").is_err());
+    assert!(lex("99999999999999999999").is_err());
+}
+
 #[test]
 fn test_strings() {
     let source = r#""hello world""#;
@@ -31,6 +69,50 @@ fn test_strings() {
     assert_eq!(tokens[0].token_type, TokenType::StringLit("hello world".to_string()));
 }
 
+#[test]
+fn test_string_escapes_are_decoded() {
+    let source = r#""a\nb\tc\rd\0e\\f\"g\'h\u{48}\u{1F525}""#;
+    let tokens = lex(source).unwrap();
+
+    assert_eq!(tokens[0].token_type, TokenType::StringLit("a\nb\tc\rd\0e\\f\"g'hH\u{1F525}".to_string()));
+}
+
+#[test]
+fn test_char_escapes_are_decoded() {
+    let tokens = lex(r"'\n' '\'' '\u{e9}'").unwrap();
+
+    assert_eq!(tokens[0].token_type, TokenType::CharLit('\n'));
+    assert_eq!(tokens[1].token_type, TokenType::CharLit('\''));
+    assert_eq!(tokens[2].token_type, TokenType::CharLit('é'));
+}
+
+#[test]
+fn test_unknown_escape_is_rejected_with_suggestion() {
+    match lex(r#"let s = "ab\q";"#).unwrap_err() {
+        CompileError::LexError { message, line, column, suggestion, .. } => {
+            assert_eq!(message, r"Unknown escape sequence '\q'");
+            assert_eq!((line, column), (1, 12));
+            assert!(suggestion.unwrap().contains(r"'\\q'"));
+        }
+        other => panic!("expected a lex error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_unicode_escape_out_of_range_is_rejected() {
+    match lex(r#""\u{110000}""#).unwrap_err() {
+        CompileError::LexError { message, .. } => {
+            assert_eq!(message, r"Unicode escape '\u{110000}' is not a valid code point");
+        }
+        other => panic!("expected a lex error, got {:?}", other),
+    }
+
+    assert!(lex(r#""\u{D800}""#).is_err());
+    assert!(lex(r#""\u{}""#).is_err());
+    assert!(lex(r#""\u{zz}""#).is_err());
+    assert!(lex(r#""\u41""#).is_err());
+}
+
 #[test]
 fn test_operators() {
     let source = "+ - * / == != < >";
@@ -62,4 +144,62 @@ fn test_comments() {
     
     assert!(matches!(tokens[0].token_type, TokenType::Let));
     assert_eq!(tokens[1].token_type, TokenType::Ident("x".to_string()));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_nested_block_comments() {
+    let source = "let /* outer /* inner */ still outer */ x\n/* two\nlines */ y";
+    let tokens = lex(source).unwrap();
+
+    assert!(matches!(tokens[0].token_type, TokenType::Let));
+    assert_eq!(tokens[1].token_type, TokenType::Ident("x".to_string()));
+    assert_eq!(tokens[2].token_type, TokenType::Ident("y".to_string()));
+    assert_eq!((tokens[2].line, tokens[2].column), (3, 10));
+}
+
+#[test]
+fn test_comment_at_end_of_file() {
+    let tokens = lex("x // no trailing newline").unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert!(matches!(tokens[1].token_type, TokenType::Eof));
+
+    let tokens = lex("x /* closed at the very end */").unwrap();
+    assert_eq!(tokens.len(), 2);
+}
+
+#[test]
+fn test_unterminated_block_comment_points_at_opening() {
+    let err = lex("let x = 1;\n  /* open /* nested */ never closed").unwrap_err();
+
+    match err {
+        CompileError::LexError { message, line, column, .. } => {
+            assert_eq!(message, "Unterminated block comment");
+            assert_eq!((line, column), (2, 3));
+        }
+        other => panic!("expected a lex error, got {:?}", other),
+    }
+}
+#[test]
+fn test_question_mark() {
+    let source = "f()?;";
+    let tokens = lex(source).unwrap();
+    
+    assert!(matches!(tokens[3].token_type, TokenType::Question));
+    assert!(matches!(tokens[4].token_type, TokenType::Semicolon));
+}
+
+#[test]
+fn test_raw_identifier_bypasses_keywords() {
+    let source = "let r#if = 1; r#match";
+    let tokens = lex(source).unwrap();
+    
+    assert_eq!(tokens[1].token_type, TokenType::Ident("if".to_string()));
+    assert_eq!(tokens[5].token_type, TokenType::Ident("match".to_string()));
+}
+
+#[test]
+fn test_raw_identifier_requires_identifier() {
+    assert!(lex("r#1").is_err());
+    assert!(lex("r# x").is_err());
+    assert_eq!(lex("r").unwrap()[0].token_type, TokenType::Ident("r".to_string()));
+}
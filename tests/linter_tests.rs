@@ -0,0 +1,167 @@
+use blaze_compiler::linter::{DeadCodeRule, FloatEqualityRule, LintRule, Linter, SelfAssignmentRule, Severity};
+use blaze_compiler::parser::{Attribute, BinaryOp, Expression, Function, Item, Param, Program, Statement, Type, Visibility};
+
+fn function(name: &str, body: Vec<Statement>) -> Function {
+    Function {
+        attributes: vec![],
+        visibility: Visibility::Private,
+        name: name.to_string(),
+        generics: vec![],
+        params: vec![],
+        return_type: None,
+        where_clause: None,
+        body,
+        is_async: false,
+        is_unsafe: false,
+        is_const: false,
+    }
+}
+
+fn program_with_body(body: Vec<Statement>) -> Program {
+    Program {
+        items: vec![Item::Function(function("main", body))],
+    }
+}
+
+fn call(name: &str) -> Statement {
+    Statement::Expression(Expression::Call {
+        func: Box::new(ident(name)),
+        args: vec![],
+    })
+}
+
+fn ident(name: &str) -> Expression {
+    Expression::Ident(name.to_string())
+}
+
+fn assign(target: Expression, value: Expression) -> Statement {
+    Statement::Expression(Expression::Assignment {
+        target: Box::new(target),
+        value: Box::new(value),
+    })
+}
+
+#[test]
+fn test_self_assignment_warns_on_variable() {
+    let program = program_with_body(vec![assign(ident("x"), ident("x"))]);
+    let diags = SelfAssignmentRule.check_program(&program);
+
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].severity, Severity::Warning);
+    assert!(diags[0].message.contains("x = x"));
+}
+
+#[test]
+fn test_self_assignment_warns_on_field() {
+    let field = || Expression::FieldAccess {
+        object: Box::new(ident("p")),
+        field: "x".to_string(),
+    };
+    let program = program_with_body(vec![assign(field(), field())]);
+    let diags = SelfAssignmentRule.check_program(&program);
+
+    assert_eq!(diags.len(), 1);
+    assert!(diags[0].message.contains("p.x = p.x"));
+}
+
+#[test]
+fn test_self_assignment_ignores_real_update() {
+    let increment = Expression::Binary {
+        op: BinaryOp::Add,
+        left: Box::new(ident("x")),
+        right: Box::new(Expression::IntLit(1)),
+    };
+    let program = program_with_body(vec![assign(ident("x"), increment)]);
+
+    assert!(SelfAssignmentRule.check_program(&program).is_empty());
+}
+
+#[test]
+fn test_linter_runs_self_assignment_by_default() {
+    let program = program_with_body(vec![Statement::Let {
+        name: "x".to_string(),
+        mutable: false,
+        ty: None,
+        value: Some(ident("x")),
+    }]);
+
+    let mut linter = Linter::new();
+    let diags = linter.lint(&program);
+
+    assert!(diags.iter().any(|d| d.rule_name == "self_assignment"));
+    assert!(!linter.has_errors());
+}
+
+#[test]
+fn test_dead_code_warns_on_uncalled_private_function() {
+    let program = Program {
+        items: vec![
+            Item::Function(function("main", vec![call("used_helper")])),
+            Item::Function(function("used_helper", vec![])),
+            Item::Function(function("unused_helper", vec![])),
+        ],
+    };
+    let diags = DeadCodeRule.check_program(&program);
+
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].severity, Severity::Warning);
+    assert!(diags[0].message.contains("unused_helper"));
+}
+
+#[test]
+fn test_dead_code_respects_roots_and_opt_out() {
+    let mut exported = function("api", vec![call("internal")]);
+    exported.visibility = Visibility::Public;
+    let mut kept = function("kept", vec![]);
+    kept.attributes.push(Attribute { name: "used".to_string(), args: vec![] });
+
+    let program = Program {
+        items: vec![
+            Item::Function(exported),
+            Item::Function(function("internal", vec![])),
+            Item::Function(kept),
+        ],
+    };
+
+    assert!(DeadCodeRule.check_program(&program).is_empty());
+}
+
+fn equals(left: Expression, right: Expression) -> Statement {
+    Statement::Expression(Expression::Binary {
+        op: BinaryOp::Eq,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+}
+
+fn function_with_param(name: &str, ty: Type, body: Vec<Statement>) -> Function {
+    let mut function = function("check", body);
+    function.params.push(Param { name: name.to_string(), ty });
+    function
+}
+
+#[test]
+fn test_float_equality_warns_on_float_operand() {
+    let function = function_with_param("x", Type::F64, vec![equals(ident("x"), Expression::FloatLit(1.5))]);
+    let diags = FloatEqualityRule.check_function(&function);
+
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].severity, Severity::Warning);
+    assert!(diags[0].message.contains("`x == 1.5`"), "{}", diags[0].message);
+    assert!(diags[0].message.contains("epsilon"));
+}
+
+#[test]
+fn test_float_equality_ignores_integer_comparison() {
+    let function = function_with_param("n", Type::I32, vec![equals(ident("n"), Expression::IntLit(3))]);
+
+    assert!(FloatEqualityRule.check_function(&function).is_empty());
+}
+
+#[test]
+fn test_float_equality_can_be_allowed() {
+    let mut function = function_with_param("x", Type::F32, vec![equals(ident("x"), Expression::FloatLit(0.0))]);
+    function.attributes.push(Attribute { name: "exact_float_eq".to_string(), args: vec![] });
+
+    assert!(FloatEqualityRule.check_function(&function).is_empty());
+}
@@ -1,5 +1,9 @@
-use blaze_compiler::{lex, parse};
-use blaze_compiler::parser::{Statement, Expression, BinaryOp};
+use blaze_compiler::{lex, parse, parse_all};
+use blaze_compiler::error::CompileError;
+use blaze_compiler::parser::{
+    Item, Statement, Expression, BinaryOp, EnumVariant, EnumVariantData, Field, FieldInit, GenericParamKind, ImplItem,
+    Literal, Param, Pattern, TraitItem, Type, TypeBound,
+};
 
 #[test]
 fn test_parse_let_statement() {
@@ -44,4 +48,792 @@ fn test_parse_while_loop() {
     let program = parse(tokens).unwrap();
     
     assert_eq!(program.items.len(), 1);
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_implicit_return() {
+    let source = "fn f() -> i32 { 1 + 2 }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let func = match &program.items[0] {
+        Item::Function(func) => func,
+        _ => panic!("expected function"),
+    };
+    
+    assert_eq!(func.body.len(), 1);
+    assert!(matches!(
+        &func.body[0],
+        Statement::Return(Some(Expression::Binary { op: BinaryOp::Add, .. }))
+    ));
+}
+
+#[test]
+fn test_parse_implicit_return_from_if_else() {
+    let source = "fn f(c: bool) -> i32 { let x = 1; if c { x } else { 2 } }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let func = match &program.items[0] {
+        Item::Function(func) => func,
+        _ => panic!("expected function"),
+    };
+    
+    match func.body.last() {
+        Some(Statement::If { then_body, else_body: Some(else_body), .. }) => {
+            assert!(matches!(then_body.last(), Some(Statement::Return(Some(_)))));
+            assert!(matches!(else_body.last(), Some(Statement::Return(Some(_)))));
+        }
+        other => panic!("expected trailing if/else, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_trailing_semicolon_is_not_implicit_return() {
+    let source = "fn f() -> i32 { 1 + 2; }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let func = match &program.items[0] {
+        Item::Function(func) => func,
+        _ => panic!("expected function"),
+    };
+    
+    assert!(matches!(&func.body[0], Statement::Expression(_)));
+}
+
+#[test]
+fn test_if_else_arm_with_trailing_semicolon_is_not_implicit_return() {
+    let source = "fn f(c: bool) -> i32 { if c { g(); } else if c { 1 } else { 2 } }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let func = match &program.items[0] {
+        Item::Function(func) => func,
+        _ => panic!("expected function"),
+    };
+    
+    match func.body.last() {
+        Some(Statement::If { then_body, else_body: Some(else_body), .. }) => {
+            assert!(matches!(then_body.last(), Some(Statement::Expression(Expression::Call { .. }))));
+            match else_body.last() {
+                Some(Statement::If { then_body, else_body: Some(else_body), .. }) => {
+                    assert!(matches!(then_body.last(), Some(Statement::Return(Some(_)))));
+                    assert!(matches!(else_body.last(), Some(Statement::Return(Some(_)))));
+                }
+                other => panic!("expected else-if, got {:?}", other),
+            }
+        }
+        other => panic!("expected trailing if/else, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_try_operator_desugars_to_match() {
+    let source = "fn f() -> Result { let x = g()?; return Ok(x); }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let func = match &program.items[0] {
+        Item::Function(func) => func,
+        _ => panic!("expected function"),
+    };
+    
+    match &func.body[0] {
+        Statement::Let { value: Some(Expression::Match { expression, arms }), .. } => {
+            assert!(matches!(expression.as_ref(), Expression::Call { .. }));
+            assert_eq!(arms.len(), 2);
+            match &arms[1].body {
+                Expression::Block(stmts) => {
+                    assert!(matches!(&stmts[0], Statement::Return(Some(Expression::Call { .. }))));
+                }
+                other => panic!("expected early return block, got {:?}", other),
+            }
+        }
+        other => panic!("expected desugared match, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_raw_identifier_usable_as_variable() {
+    let source = "fn main() { let r#if = 1; let y = r#if + 1; }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let func = match &program.items[0] {
+        Item::Function(func) => func,
+        _ => panic!("expected function"),
+    };
+    
+    assert!(matches!(&func.body[0], Statement::Let { name, .. } if name == "if"));
+    match &func.body[1] {
+        Statement::Let { value: Some(Expression::Binary { left, .. }), .. } => {
+            assert_eq!(left.as_ref(), &Expression::Ident("if".to_string()));
+        }
+        other => panic!("expected binary let, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_const_generic_struct() {
+    let source = "struct Array<T, const N: usize> { data: [T; N] }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let s = match &program.items[0] {
+        Item::Struct(s) => s,
+        _ => panic!("expected struct"),
+    };
+    
+    assert_eq!(s.generics.len(), 2);
+    assert_eq!(s.generics[0].kind, GenericParamKind::Type);
+    assert_eq!(s.generics[1].name, "N");
+    assert!(matches!(s.generics[1].kind, GenericParamKind::Const(_)));
+    assert_eq!(
+        s.fields[0].ty,
+        Type::ParamArray {
+            element: Box::new(Type::Custom("T".to_string())),
+            size_param: "N".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_parse_const_generic_use_site() {
+    let source = "fn main() { let a: Array<i32, 4> = make(); }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let func = match &program.items[0] {
+        Item::Function(func) => func,
+        _ => panic!("expected function"),
+    };
+    
+    match &func.body[0] {
+        Statement::Let { ty: Some(ty), .. } => {
+            assert_eq!(ty, &Type::Generic("Array".to_string(), vec![Type::I32, Type::ConstValue(4)]));
+        }
+        other => panic!("expected typed let, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_const_generic_function() {
+    let source = "fn zeros<const N: usize>() -> [i32; N] { return make(); }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let func = match &program.items[0] {
+        Item::Function(func) => func,
+        _ => panic!("expected function"),
+    };
+    
+    assert!(matches!(func.generics[0].kind, GenericParamKind::Const(_)));
+    assert!(matches!(func.return_type, Some(Type::ParamArray { .. })));
+}
+
+#[test]
+fn test_parse_generic_function_with_bounds() {
+    let source = "fn pick<T: Ord + Clone, U>(a: T, b: U) -> T { return a; }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let func = match &program.items[0] {
+        Item::Function(func) => func,
+        _ => panic!("expected function"),
+    };
+    
+    let params: Vec<(&str, &[TypeBound])> = func.generics.iter().map(|g| (g.name.as_str(), g.bounds.as_slice())).collect();
+    assert_eq!(
+        params,
+        vec![
+            ("T", &[TypeBound::Trait("Ord".to_string()), TypeBound::Trait("Clone".to_string())][..]),
+            ("U", &[][..]),
+        ]
+    );
+    assert_eq!(func.params[1].ty, Type::Custom("U".to_string()));
+    assert_eq!(func.return_type, Some(Type::Custom("T".to_string())));
+}
+
+#[test]
+fn test_parse_generic_struct_with_bounded_params() {
+    let source = "struct Pair<K: Hash, V> { key: K, value: V }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let s = match &program.items[0] {
+        Item::Struct(s) => s,
+        _ => panic!("expected struct"),
+    };
+    
+    let names: Vec<&str> = s.generics.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(names, vec!["K", "V"]);
+    assert_eq!(s.generics[0].bounds, vec![TypeBound::Trait("Hash".to_string())]);
+    assert_eq!(s.fields[1].ty, Type::Custom("V".to_string()));
+}
+
+#[test]
+fn test_parse_nested_generic_arguments() {
+    let source = "fn main() { let m: HashMap<String, Vec<Option<i32>>> = make(); }";
+    let tokens = lex(source).unwrap();
+    let program = parse(tokens).unwrap();
+    
+    let func = match &program.items[0] {
+        Item::Function(func) => func,
+        _ => panic!("expected function"),
+    };
+    
+    let option = Type::Generic("Option".to_string(), vec![Type::I32]);
+    let vec = Type::Generic("Vec".to_string(), vec![option]);
+    match &func.body[0] {
+        Statement::Let { ty: Some(ty), .. } => {
+            assert_eq!(ty, &Type::Generic("HashMap".to_string(), vec![Type::String, vec]));
+        }
+        other => panic!("expected typed let, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_const_generic_argument_must_be_const() {
+    let source = "fn main() { let a: Array<i32, 2.5> = make(); }";
+    let tokens = lex(source).unwrap();
+    
+    assert!(parse(tokens).is_err());
+}
+
+fn first_statement(source: &str) -> Statement {
+    let program = parse(lex(source).unwrap()).unwrap();
+    match &program.items[0] {
+        Item::Function(func) => func.body[0].clone(),
+        _ => panic!("expected function"),
+    }
+}
+
+#[test]
+fn test_parse_else_if_chain() {
+    let stmt = first_statement("fn main() { if a { f(); } else if b { g(); } }");
+
+    let else_body = match stmt {
+        Statement::If { else_body: Some(else_body), .. } => else_body,
+        other => panic!("expected if/else, got {:?}", other),
+    };
+    assert_eq!(else_body.len(), 1);
+    assert!(matches!(
+        &else_body[0],
+        Statement::If { condition: Expression::Ident(name), else_body: None, .. } if name == "b"
+    ));
+}
+
+#[test]
+fn test_parse_three_arm_chain_with_trailing_else() {
+    let stmt = first_statement("fn main() { if a { f(); } else if b { g(); } else { h(); } }");
+
+    let nested = match stmt {
+        Statement::If { else_body: Some(mut else_body), .. } if else_body.len() == 1 => else_body.remove(0),
+        other => panic!("expected else-if, got {:?}", other),
+    };
+    match nested {
+        Statement::If { condition: Expression::Ident(name), then_body, else_body: Some(else_body) } => {
+            assert_eq!(name, "b");
+            assert_eq!(then_body.len(), 1);
+            assert_eq!(else_body.len(), 1);
+            assert!(matches!(else_body[0], Statement::Expression(Expression::Call { .. })));
+        }
+        other => panic!("expected nested if with else, got {:?}", other),
+    }
+}
+
+fn param_types(source: &str) -> Vec<Type> {
+    let program = parse(lex(source).unwrap()).unwrap();
+    match &program.items[0] {
+        Item::Function(func) => func.params.iter().map(|param| param.ty.clone()).collect(),
+        _ => panic!("expected function"),
+    }
+}
+
+#[test]
+fn test_parse_array_and_slice_types() {
+    let types = param_types("fn f(a: [i32; 8], s: [u8]) {}");
+
+    assert_eq!(types[0], Type::Array { element: Box::new(Type::I32), size: Some(8) });
+    assert_eq!(types[1], Type::Slice(Box::new(Type::U8)));
+}
+
+#[test]
+fn test_parse_nested_array_type() {
+    let types = param_types("fn det(m: [[f32; 4]; 4], rows: [[f32; 4]]) -> f32 { return 0.0; }");
+
+    let row = Type::Array { element: Box::new(Type::F32), size: Some(4) };
+    assert_eq!(types[0], Type::Array { element: Box::new(row.clone()), size: Some(4) });
+    assert_eq!(types[1], Type::Slice(Box::new(row)));
+    assert!(parse(lex("fn f(a: [i32; 4) {}").unwrap()).is_err());
+}
+
+#[test]
+fn test_parse_tuple_types() {
+    let types = param_types("fn f(unit: (), one: (i32,), grouped: (i32), pair: (i32, (bool, String))) {}");
+
+    assert_eq!(types[0], Type::Tuple(vec![]));
+    assert_eq!(types[1], Type::Tuple(vec![Type::I32]));
+    assert_eq!(types[2], Type::I32);
+    assert_eq!(types[3], Type::Tuple(vec![Type::I32, Type::Tuple(vec![Type::Bool, Type::String])]));
+}
+
+#[test]
+fn test_parse_tuple_literals() {
+    let value = |source: &str| match first_statement(source) {
+        Statement::Let { value: Some(value), .. } => value,
+        other => panic!("expected let with a value, got {:?}", other),
+    };
+
+    assert_eq!(value("fn main() { let u = (); }"), Expression::TupleLiteral(vec![]));
+    assert_eq!(value("fn main() { let x = (1); }"), Expression::IntLit(1));
+    assert_eq!(value("fn main() { let x = (1,); }"), Expression::TupleLiteral(vec![Expression::IntLit(1)]));
+    assert_eq!(
+        value("fn main() { let t = (1, (true, x)); }"),
+        Expression::TupleLiteral(vec![
+            Expression::IntLit(1),
+            Expression::TupleLiteral(vec![Expression::BoolLit(true), Expression::Ident("x".to_string())]),
+        ])
+    );
+}
+
+#[test]
+fn test_parse_for_over_range() {
+    let stmt = first_statement("fn main() { for i in 0..n + 1 { print(i); } }");
+
+    match stmt {
+        Statement::For { variable, iterable, body } => {
+            assert_eq!(variable, "i");
+            assert_eq!(
+                iterable,
+                Expression::Range {
+                    start: Box::new(Expression::IntLit(0)),
+                    end: Box::new(Expression::Binary {
+                        op: BinaryOp::Add,
+                        left: Box::new(Expression::Ident("n".to_string())),
+                        right: Box::new(Expression::IntLit(1)),
+                    }),
+                    inclusive: false,
+                }
+            );
+            assert_eq!(body.len(), 1);
+        }
+        other => panic!("expected for loop, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_for_with_empty_body() {
+    let stmt = first_statement("fn main() { for x in items {} }");
+    assert_eq!(
+        stmt,
+        Statement::For { variable: "x".to_string(), iterable: Expression::Ident("items".to_string()), body: vec![] }
+    );
+
+    let stmt = first_statement("fn main() { for i in 1..=10 {} }");
+    assert!(matches!(
+        stmt,
+        Statement::For { iterable: Expression::Range { inclusive: true, .. }, ref body, .. } if body.is_empty()
+    ));
+    assert!(parse(lex("fn main() { for in items {} }").unwrap()).is_err());
+}
+
+#[test]
+fn test_parse_match_patterns_and_guards() {
+    let source = r#"
+        fn main() {
+            match p {
+                (0, _) => 1,
+                Point { x, y: -1 } => x,
+                Some(v) if v > 0 => { print(v); }
+                n => n,
+            }
+        }
+    "#;
+
+    let arms = match first_statement(source) {
+        Statement::Expression(Expression::Match { expression, arms }) => {
+            assert_eq!(*expression, Expression::Ident("p".to_string()));
+            arms
+        }
+        other => panic!("expected match, got {:?}", other),
+    };
+
+    let patterns: Vec<&Pattern> = arms.iter().map(|arm| &arm.pattern).collect();
+    assert_eq!(
+        patterns,
+        vec![
+            &Pattern::Tuple(vec![Pattern::Literal(Literal::Integer(0)), Pattern::Wildcard]),
+            &Pattern::Struct {
+                name: "Point".to_string(),
+                fields: vec![
+                    ("x".to_string(), Pattern::Identifier("x".to_string())),
+                    ("y".to_string(), Pattern::Literal(Literal::Integer(-1))),
+                ],
+            },
+            &Pattern::TupleStruct { name: "Some".to_string(), fields: vec![Pattern::Identifier("v".to_string())] },
+            &Pattern::Identifier("n".to_string()),
+        ]
+    );
+    assert!(arms[2].guard.is_some());
+    assert!(matches!(arms[2].body, Expression::Block(ref body) if body.len() == 1));
+    assert!(arms.iter().enumerate().all(|(i, arm)| i == 2 || arm.guard.is_none()));
+}
+
+fn field(name: &str, value: Expression) -> FieldInit {
+    FieldInit { name: name.to_string(), value }
+}
+
+#[test]
+fn test_parse_nested_struct_literal() {
+    let stmt = first_statement("fn main() { let r = Rect { origin: Point { x: 0, y }, width: w + 1, }; }");
+
+    let expected = Expression::StructLiteral {
+        name: "Rect".to_string(),
+        fields: vec![
+            field(
+                "origin",
+                Expression::StructLiteral {
+                    name: "Point".to_string(),
+                    fields: vec![field("x", Expression::IntLit(0)), field("y", Expression::Ident("y".to_string()))],
+                },
+            ),
+            field(
+                "width",
+                Expression::Binary {
+                    op: BinaryOp::Add,
+                    left: Box::new(Expression::Ident("w".to_string())),
+                    right: Box::new(Expression::IntLit(1)),
+                },
+            ),
+        ],
+    };
+    assert!(matches!(stmt, Statement::Let { value: Some(ref value), .. } if *value == expected), "{:?}", stmt);
+}
+
+#[test]
+fn test_struct_literal_is_not_parsed_in_conditions() {
+    match first_statement("fn main() { if done { stop(); } }") {
+        Statement::If { condition, then_body, .. } => {
+            assert_eq!(condition, Expression::Ident("done".to_string()));
+            assert_eq!(then_body.len(), 1);
+        }
+        other => panic!("expected if, got {:?}", other),
+    }
+
+    match first_statement("fn main() { while p == (Point { x: 0 }) { step(Point { x: 1 }); } }") {
+        Statement::While { condition: Expression::Binary { right, .. }, body } => {
+            assert!(matches!(*right, Expression::StructLiteral { .. }));
+            assert!(matches!(
+                &body[0],
+                Statement::Expression(Expression::Call { args, .. }) if matches!(args[0], Expression::StructLiteral { .. })
+            ));
+        }
+        other => panic!("expected while, got {:?}", other),
+    }
+
+    assert!(matches!(
+        first_statement("fn main() { for p in points {} }"),
+        Statement::For { iterable: Expression::Ident(_), .. }
+    ));
+}
+
+#[test]
+fn test_parse_field_and_method_chain() {
+    let stmt = first_statement("fn main() { a.b.c(1, x.y).d; }");
+
+    let ident = |name: &str| Box::new(Expression::Ident(name.to_string()));
+    let expected = Expression::FieldAccess {
+        object: Box::new(Expression::MethodCall {
+            object: Box::new(Expression::FieldAccess { object: ident("a"), field: "b".to_string() }),
+            method: "c".to_string(),
+            args: vec![Expression::IntLit(1), Expression::FieldAccess { object: ident("x"), field: "y".to_string() }],
+        }),
+        field: "d".to_string(),
+    };
+    assert_eq!(stmt, Statement::Expression(expected));
+}
+
+#[test]
+fn test_parse_call_result_method_and_field_operands() {
+    let stmt = first_statement("fn main() { let n = make().len() + p.x; }");
+
+    match stmt {
+        Statement::Let { value: Some(Expression::Binary { op: BinaryOp::Add, left, right }), .. } => {
+            assert!(matches!(
+                *left,
+                Expression::MethodCall { ref object, ref method, ref args }
+                    if method == "len" && args.is_empty() && matches!(**object, Expression::Call { .. })
+            ));
+            assert!(matches!(*right, Expression::FieldAccess { ref field, .. } if field == "x"));
+        }
+        other => panic!("expected addition, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_labeled_loops_and_labeled_jumps() {
+    let stmt = first_statement("fn main() { 'outer: while x { loop { break 'outer; continue; } } }");
+
+    let inner = Statement::Loop {
+        body: vec![
+            Statement::Break { label: Some("outer".to_string()), value: None },
+            Statement::Continue { label: None },
+        ],
+    };
+    let expected = Statement::Labeled {
+        label: "outer".to_string(),
+        body: Box::new(Statement::While { condition: Expression::Ident("x".to_string()), body: vec![inner] }),
+    };
+    assert_eq!(stmt, expected);
+}
+
+#[test]
+fn test_char_literals_are_not_labels() {
+    let stmt = first_statement("fn main() { let c = 'a'; }");
+    assert!(matches!(stmt, Statement::Let { value: Some(Expression::CharLit('a')), .. }));
+
+    let stmt = first_statement("fn main() { 'a: loop { break 'a 1; } }");
+    let expected = Statement::Labeled {
+        label: "a".to_string(),
+        body: Box::new(Statement::Loop {
+            body: vec![Statement::Break { label: Some("a".to_string()), value: Some(Expression::IntLit(1)) }],
+        }),
+    };
+    assert_eq!(stmt, expected);
+}
+
+#[test]
+fn test_parse_chained_assignment_is_right_associative() {
+    let stmt = first_statement("fn main() { x = y = 3; }");
+
+    let ident = |name: &str| Box::new(Expression::Ident(name.to_string()));
+    let expected = Expression::Assignment {
+        target: ident("x"),
+        value: Box::new(Expression::Assignment { target: ident("y"), value: Box::new(Expression::IntLit(3)) }),
+    };
+    assert_eq!(stmt, Statement::Expression(expected));
+}
+
+#[test]
+fn test_parse_compound_assignment_desugars_to_binary() {
+    let cases = [("+=", BinaryOp::Add), ("-=", BinaryOp::Sub), ("*=", BinaryOp::Mul), ("/=", BinaryOp::Div)];
+
+    for (token, op) in cases {
+        let stmt = first_statement(&format!("fn main() {{ p.total {} n + 1; }}", token));
+
+        let target = Expression::FieldAccess {
+            object: Box::new(Expression::Ident("p".to_string())),
+            field: "total".to_string(),
+        };
+        let sum = Expression::Binary {
+            op: BinaryOp::Add,
+            left: Box::new(Expression::Ident("n".to_string())),
+            right: Box::new(Expression::IntLit(1)),
+        };
+        let expected = Expression::Assignment {
+            target: Box::new(target.clone()),
+            value: Box::new(Expression::Binary { op, left: Box::new(target), right: Box::new(sum) }),
+        };
+        assert_eq!(stmt, Statement::Expression(expected), "{}", token);
+    }
+}
+
+#[test]
+fn test_assignment_to_non_place_is_rejected() {
+    for source in ["fn main() { 1 = x; }", "fn main() { a + b += 1; }", "fn main() { f() = 2; }"] {
+        let err = parse(lex(source).unwrap()).unwrap_err().to_string();
+        assert!(err.contains("Invalid assignment target"), "{}: {}", source, err);
+    }
+}
+
+fn binary(op: BinaryOp, left: Expression, right: Expression) -> Expression {
+    Expression::Binary { op, left: Box::new(left), right: Box::new(right) }
+}
+
+#[test]
+fn test_parse_shift_binds_tighter_than_bitwise_or() {
+    let stmt = first_statement("fn main() { 1 << 2 | 3; }");
+
+    let expected = binary(
+        BinaryOp::BitwiseOr,
+        binary(BinaryOp::LeftShift, Expression::IntLit(1), Expression::IntLit(2)),
+        Expression::IntLit(3),
+    );
+    assert_eq!(stmt, Statement::Expression(expected));
+}
+
+#[test]
+fn test_parse_bitwise_precedence_levels() {
+    let ident = |name: &str| Expression::Ident(name.to_string());
+
+    // `^` sits between `|` and `&`, and `>>` binds looser than `+`.
+    let stmt = first_statement("fn main() { a | b ^ c & d >> e + 1; }");
+    let shift = binary(BinaryOp::RightShift, ident("d"), binary(BinaryOp::Add, ident("e"), Expression::IntLit(1)));
+    let expected = binary(
+        BinaryOp::BitwiseOr,
+        ident("a"),
+        binary(BinaryOp::BitwiseXor, ident("b"), binary(BinaryOp::BitwiseAnd, ident("c"), shift)),
+    );
+    assert_eq!(stmt, Statement::Expression(expected));
+
+    // Bitwise operators bind tighter than comparisons, and `&`/`|` stay
+    // distinct from `&&`/`||`.
+    let stmt = first_statement("fn main() { a & 1 == 0 && b | c; }");
+    let expected = binary(
+        BinaryOp::And,
+        binary(BinaryOp::Eq, binary(BinaryOp::BitwiseAnd, ident("a"), Expression::IntLit(1)), Expression::IntLit(0)),
+        binary(BinaryOp::BitwiseOr, ident("b"), ident("c")),
+    );
+    assert_eq!(stmt, Statement::Expression(expected));
+}
+
+#[test]
+fn test_parse_unsigned_integer_types() {
+    let stmt = first_statement("fn main() { let x: u8 = 255; }");
+    assert_eq!(
+        stmt,
+        Statement::Let { name: "x".to_string(), mutable: false, ty: Some(Type::U8), value: Some(Expression::IntLit(255)) }
+    );
+
+    assert_eq!(param_types("fn f(a: u16, b: u32, c: u64) {}"), vec![Type::U16, Type::U32, Type::U64]);
+}
+
+fn enum_variants(source: &str) -> Vec<EnumVariant> {
+    let program = parse(lex(source).unwrap()).unwrap();
+    match &program.items[0] {
+        Item::Enum(e) => e.variants.clone(),
+        _ => panic!("expected enum"),
+    }
+}
+
+#[test]
+fn test_parse_enum_variant_shapes() {
+    let variants = enum_variants("enum Shape { Empty, Line(i32, i32), Circle { radius: f64 } }");
+
+    assert_eq!(
+        variants,
+        vec![
+            EnumVariant { name: "Empty".to_string(), data: EnumVariantData::Unit },
+            EnumVariant { name: "Line".to_string(), data: EnumVariantData::Tuple(vec![Type::I32, Type::I32]) },
+            EnumVariant {
+                name: "Circle".to_string(),
+                data: EnumVariantData::Struct(vec![Field { name: "radius".to_string(), ty: Type::F64 }]),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_enum_with_trailing_comma() {
+    let variants = enum_variants("enum Color { Red, Green, Blue, }");
+
+    let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["Red", "Green", "Blue"]);
+    assert!(parse(lex("enum Color { Red Green }").unwrap()).is_err());
+}
+
+#[test]
+fn test_parse_trait_with_default_method() {
+    let source = r#"
+        trait Shape: Debug + Clone {
+            fn area(&self) -> f64;
+            fn describe(&self) -> i32 { 0 }
+        }
+    "#;
+    let program = parse(lex(source).unwrap()).unwrap();
+
+    let shape = match &program.items[0] {
+        Item::Trait(t) => t,
+        _ => panic!("expected trait"),
+    };
+    assert_eq!(shape.name, "Shape");
+    assert_eq!(shape.supertraits, vec!["Debug".to_string(), "Clone".to_string()]);
+
+    let methods: Vec<(&str, bool)> = shape
+        .items
+        .iter()
+        .map(|item| match item {
+            TraitItem::Function(f) => (f.name.as_str(), f.default_body.is_some()),
+            _ => panic!("expected method"),
+        })
+        .collect();
+    assert_eq!(methods, vec![("area", false), ("describe", true)]);
+}
+
+#[test]
+fn test_parse_trait_and_inherent_impls() {
+    let source = r#"
+        impl Shape for Circle {
+            fn area(&self) -> f64 { self.radius }
+        }
+        impl Circle {
+            fn grow(&mut self, by: f64) {}
+        }
+    "#;
+    let program = parse(lex(source).unwrap()).unwrap();
+
+    let (trait_impl, inherent) = match (&program.items[0], &program.items[1]) {
+        (Item::Impl(a), Item::Impl(b)) => (a, b),
+        _ => panic!("expected two impls"),
+    };
+    assert_eq!(trait_impl.trait_path.as_deref(), Some("Shape"));
+    assert_eq!(trait_impl.self_type, Type::Custom("Circle".to_string()));
+    assert_eq!(inherent.trait_path, None);
+
+    let ImplItem::Function(grow) = &inherent.items[0] else { panic!("expected method") };
+    assert_eq!(
+        grow.params,
+        vec![
+            Param {
+                name: "self".to_string(),
+                ty: Type::Reference { mutable: true, inner: Box::new(Type::Custom("Self".to_string())) },
+            },
+            Param { name: "by".to_string(), ty: Type::F64 },
+        ]
+    );
+}
+
+fn error_lines(errors: &[CompileError]) -> Vec<usize> {
+    errors
+        .iter()
+        .map(|error| match error {
+            CompileError::ParseError { line, .. } => *line,
+            other => panic!("expected a parse error, got {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_all_reports_every_error() {
+    let source = "fn a() { let x = ; let y = 1; }\n\
+                  struct S { x i32 }\n\
+                  fn b() { while true { let z = 2 + ; } return 1; }\n\
+                  fn c() -> i32 { 3 }";
+    let (program, errors) = parse_all(lex(source).unwrap());
+
+    assert_eq!(error_lines(&errors), vec![1, 2, 3]);
+
+    let names: Vec<&str> = program
+        .items
+        .iter()
+        .map(|item| match item {
+            Item::Function(f) => f.name.as_str(),
+            _ => panic!("expected only functions"),
+        })
+        .collect();
+    assert_eq!(names, vec!["a", "b", "c"]);
+    match &program.items[0] {
+        Item::Function(a) => assert!(matches!(&a.body[..], [Statement::Let { name, .. }] if name == "y")),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_parse_all_on_valid_input_matches_parse() {
+    let source = "fn main() { let x = 1; if x > 0 { return; } }";
+    let (program, errors) = parse_all(lex(source).unwrap());
+
+    assert!(errors.is_empty());
+    assert_eq!(program.items, parse(lex(source).unwrap()).unwrap().items);
+}
@@ -280,3 +280,220 @@ fn test_min_max() {
     assert_eq!(blaze_min_i64(3, 5), 3, "min(3, 5) should be 3");
     assert_eq!(blaze_max_i64(3, 5), 5, "max(3, 5) should be 5");
 }
+
+#[test]
+fn test_atomic_fetch_add_no_lost_updates() {
+    const THREADS: usize = 8;
+    const INCREMENTS: usize = 10_000;
+
+    let counter = Box::into_raw(Box::new(0i64)) as usize;
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            std::thread::spawn(move || {
+                for _ in 0..INCREMENTS {
+                    unsafe {
+                        blaze_atomic_fetch_add(counter as *mut i64, 1, 4);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    unsafe {
+        let total = blaze_atomic_load(counter as *const i64, 4);
+        assert_eq!(total, (THREADS * INCREMENTS) as i64, "fetch_add should not lose updates");
+        drop(Box::from_raw(counter as *mut i64));
+    }
+}
+
+#[test]
+fn test_atomic_store_and_compare_exchange() {
+    let mut value = 0i64;
+    let ptr = &mut value as *mut i64;
+
+    unsafe {
+        blaze_atomic_store(ptr, 10, 2);
+        assert_eq!(blaze_atomic_load(ptr, 1), 10);
+
+        assert_eq!(blaze_atomic_compare_exchange(ptr, 10, 20, 3, 1), 1, "exchange should succeed");
+        assert_eq!(blaze_atomic_load(ptr, 0), 20);
+
+        assert_eq!(blaze_atomic_compare_exchange(ptr, 10, 30, 3, 1), 0, "stale expected value should fail");
+        assert_eq!(blaze_atomic_load(ptr, 0), 20);
+    }
+}
+
+#[test]
+fn test_checked_arithmetic_i32() {
+    unsafe {
+        let mut out = 0i32;
+        assert_eq!(blaze_checked_add_i32(2, 3, &mut out), 0);
+        assert_eq!(out, 5);
+        assert_eq!(blaze_checked_sub_i32(2, 3, &mut out), 0);
+        assert_eq!(out, -1);
+        assert_eq!(blaze_checked_mul_i32(4, 3, &mut out), 0);
+        assert_eq!(out, 12);
+
+        out = 7;
+        assert_eq!(blaze_checked_add_i32(i32::MAX, 1, &mut out), 1, "add should overflow");
+        assert_eq!(blaze_checked_sub_i32(i32::MIN, 1, &mut out), 1, "sub should overflow");
+        assert_eq!(blaze_checked_mul_i32(i32::MAX, 2, &mut out), 1, "mul should overflow");
+        assert_eq!(out, 7, "overflow must not write the output");
+    }
+}
+
+#[test]
+fn test_checked_arithmetic_i64() {
+    unsafe {
+        let mut out = 0i64;
+        assert_eq!(blaze_checked_add_i64(40, 2, &mut out), 0);
+        assert_eq!(out, 42);
+        assert_eq!(blaze_checked_sub_i64(40, 2, &mut out), 0);
+        assert_eq!(out, 38);
+        assert_eq!(blaze_checked_mul_i64(40, 2, &mut out), 0);
+        assert_eq!(out, 80);
+
+        out = 7;
+        assert_eq!(blaze_checked_add_i64(i64::MAX, 1, &mut out), 1, "add should overflow");
+        assert_eq!(blaze_checked_sub_i64(i64::MIN, 1, &mut out), 1, "sub should overflow");
+        assert_eq!(blaze_checked_mul_i64(i64::MIN, -1, &mut out), 1, "mul should overflow");
+        assert_eq!(out, 7, "overflow must not write the output");
+    }
+}
+
+type Codec = unsafe extern "C" fn(*const u8, usize, *mut u8, usize) -> isize;
+
+fn run_codec(codec: Codec, input: &[u8], cap: usize) -> Option<Vec<u8>> {
+    let mut out = vec![0u8; cap];
+    let n = unsafe { codec(input.as_ptr(), input.len(), out.as_mut_ptr(), out.len()) };
+    if n < 0 {
+        None
+    } else {
+        out.truncate(n as usize);
+        Some(out)
+    }
+}
+
+#[test]
+fn test_base64_known_vectors() {
+    assert_eq!(run_codec(blaze_base64_encode, b"", 8).unwrap(), b"");
+    assert_eq!(run_codec(blaze_base64_encode, b"f", 8).unwrap(), b"Zg==");
+    assert_eq!(run_codec(blaze_base64_encode, b"fo", 8).unwrap(), b"Zm8=");
+    assert_eq!(run_codec(blaze_base64_encode, b"foobar", 8).unwrap(), b"Zm9vYmFy");
+}
+
+#[test]
+fn test_base64_and_hex_round_trip() {
+    let data: Vec<u8> = (0..=255u8).chain([0, 0xff, 0x10]).collect();
+
+    for len in 0..data.len() {
+        let input = &data[..len];
+
+        let encoded = run_codec(blaze_base64_encode, input, 512).unwrap();
+        assert_eq!(run_codec(blaze_base64_decode, &encoded, 512).unwrap(), input);
+
+        let encoded = run_codec(blaze_hex_encode, input, 1024).unwrap();
+        assert_eq!(run_codec(blaze_hex_decode, &encoded, 512).unwrap(), input);
+    }
+}
+
+#[test]
+fn test_decode_rejects_invalid_input() {
+    assert!(run_codec(blaze_base64_decode, b"Zm9", 16).is_none());
+    assert!(run_codec(blaze_base64_decode, b"Zm*v", 16).is_none());
+    assert!(run_codec(blaze_base64_decode, b"Zg==Zg==", 16).is_none());
+    assert!(run_codec(blaze_hex_decode, b"abc", 16).is_none());
+    assert!(run_codec(blaze_hex_decode, b"zz", 16).is_none());
+}
+
+#[test]
+fn test_encode_rejects_insufficient_capacity() {
+    assert!(run_codec(blaze_base64_encode, b"foobar", 7).is_none());
+    assert!(run_codec(blaze_hex_encode, b"ab", 3).is_none());
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn test_sha256_known_digest() {
+    let mut digest = [0u8; BLAZE_DIGEST_LEN];
+    let input = b"abc";
+
+    assert_eq!(unsafe { blaze_sha256(input.as_ptr(), input.len(), digest.as_mut_ptr()) }, 0);
+    assert_eq!(
+        hex_string(&digest),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn test_blake3_known_digest() {
+    let mut digest = [0u8; BLAZE_DIGEST_LEN];
+
+    assert_eq!(unsafe { blaze_blake3(std::ptr::null(), 0, digest.as_mut_ptr()) }, 0);
+    assert_eq!(
+        hex_string(&digest),
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+    );
+    assert_eq!(unsafe { blaze_blake3(b"x".as_ptr(), 1, std::ptr::null_mut()) }, -1);
+}
+
+#[test]
+fn test_random_bytes_differ_across_calls() {
+    let mut first = [0u8; 32];
+    let mut second = [0u8; 32];
+
+    assert_eq!(unsafe { blaze_random_bytes(first.as_mut_ptr(), first.len()) }, 0);
+    assert_eq!(unsafe { blaze_random_bytes(second.as_mut_ptr(), second.len()) }, 0);
+    assert_ne!(first, second);
+}
+
+#[derive(Clone, Default)]
+struct CapturedLog(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_log_level_filtering() {
+    let captured = CapturedLog::default();
+    let writer = captured.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let log = |level, msg: &str| unsafe { blaze_log(level, msg.as_ptr(), msg.len()) };
+
+        assert_eq!(blaze_log_set_level(BLAZE_LOG_WARN), 0);
+        assert_eq!(log(BLAZE_LOG_ERROR, "disk full"), 0);
+        assert_eq!(log(BLAZE_LOG_WARN, "retrying"), 0);
+        assert_eq!(log(BLAZE_LOG_INFO, "connected"), 0);
+        assert_eq!(log(BLAZE_LOG_DEBUG, "bytes=42"), 0);
+        assert_eq!(log(7, "bad level"), -1);
+        assert_eq!(blaze_log_set_level(BLAZE_LOG_TRACE), 0);
+    });
+
+    let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("ERROR") && output.contains("disk full"));
+    assert!(output.contains("WARN") && output.contains("retrying"));
+    assert!(!output.contains("connected"));
+    assert!(!output.contains("bytes=42"));
+}
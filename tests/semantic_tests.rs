@@ -0,0 +1,496 @@
+use blaze_compiler::const_eval::ConstFnChecker;
+use blaze_compiler::parser::{BinaryOp, ConstDeclaration, Expression, Function, Impl, ImplItem, Item, Program, Struct, Type, Visibility};
+use blaze_compiler::error::CompileError;
+use blaze_compiler::semantic::{ConstCycleChecker, EntryPointChecker, LoopLabelChecker, MatchChecker, MethodOwner, MethodResolver, MustUseChecker, SymbolTable};
+use blaze_compiler::trait_system::TraitRegistry;
+use blaze_compiler::{lex, parse};
+
+fn must_use_warnings(source: &str) -> usize {
+    let program = parse(lex(source).unwrap()).unwrap();
+    MustUseChecker::new().check(&program).unwrap().len()
+}
+
+#[test]
+fn test_discarded_result_warns() {
+    let source = r#"
+        fn may_fail() -> Result { return Ok(1); }
+        fn main() { may_fail(); }
+    "#;
+
+    assert_eq!(must_use_warnings(source), 1);
+}
+
+#[test]
+fn test_propagated_result_does_not_warn() {
+    let source = r#"
+        fn may_fail() -> Result { return Ok(1); }
+        fn run() -> Result { may_fail()?; return Ok(0); }
+    "#;
+
+    assert_eq!(must_use_warnings(source), 0);
+}
+
+#[test]
+fn test_explicitly_ignored_result_does_not_warn() {
+    let source = r#"
+        fn may_fail() -> Option { return Some(1); }
+        fn main() { let _ = may_fail(); }
+    "#;
+
+    assert_eq!(must_use_warnings(source), 0);
+}
+
+#[test]
+fn test_discarded_result_errors_in_strict_mode() {
+    let source = r#"
+        fn may_fail() -> Result { return Ok(1); }
+        fn main() { may_fail(); }
+    "#;
+    let program = parse(lex(source).unwrap()).unwrap();
+
+    assert!(MustUseChecker::new().with_strict(true).check(&program).is_err());
+}
+
+fn method(name: &str, return_type: Type) -> ImplItem {
+    ImplItem::Function(Function {
+        attributes: Vec::new(),
+        visibility: Visibility::Public,
+        name: name.to_string(),
+        generics: Vec::new(),
+        params: Vec::new(),
+        return_type: Some(return_type),
+        where_clause: None,
+        body: Vec::new(),
+        is_async: false,
+        is_unsafe: false,
+        is_const: false,
+    })
+}
+
+fn impl_block(trait_path: Option<&str>, self_type: &str, items: Vec<ImplItem>) -> Item {
+    Item::Impl(Impl {
+        attributes: Vec::new(),
+        generics: Vec::new(),
+        trait_path: trait_path.map(str::to_string),
+        self_type: Type::Custom(self_type.to_string()),
+        where_clause: None,
+        items,
+    })
+}
+
+#[test]
+fn test_inherent_method_shadows_trait_method() {
+    let program = Program {
+        items: vec![
+            impl_block(Some("Collection"), "Buffer", vec![method("len", Type::I64)]),
+            impl_block(None, "Buffer", vec![method("len", Type::I32)]),
+        ],
+    };
+    let resolver = MethodResolver::collect(&program);
+    let buffer = Type::Custom("Buffer".to_string());
+
+    let resolved = resolver.resolve(&buffer, "len").unwrap().unwrap();
+    assert_eq!(resolved.owner, MethodOwner::Inherent);
+    assert_eq!(resolved.return_type, Some(Type::I32));
+
+    let through_ref = Type::Reference { mutable: false, inner: Box::new(buffer) };
+    assert_eq!(resolver.resolve(&through_ref, "len").unwrap().unwrap().owner, MethodOwner::Inherent);
+}
+
+#[test]
+fn test_trait_method_resolves_on_its_receiver_type_only() {
+    let program = Program {
+        items: vec![impl_block(Some("Collection"), "Buffer", vec![method("len", Type::I64)])],
+    };
+    let resolver = MethodResolver::collect(&program);
+
+    let resolved = resolver.resolve(&Type::Custom("Buffer".to_string()), "len").unwrap().unwrap();
+    assert_eq!(resolved.owner, MethodOwner::Trait("Collection".to_string()));
+    assert!(resolver.resolve(&Type::Custom("Other".to_string()), "len").unwrap().is_none());
+}
+
+#[test]
+fn test_same_method_from_two_traits_is_ambiguous() {
+    let program = Program {
+        items: vec![
+            impl_block(Some("Pilot"), "Human", vec![method("foo", Type::I32)]),
+            impl_block(Some("Wizard"), "Human", vec![method("foo", Type::I32)]),
+        ],
+    };
+    let resolver = MethodResolver::collect(&program);
+
+    let err = resolver.resolve(&Type::Custom("Human".to_string()), "foo").unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("multiple applicable methods named `foo`"));
+}
+
+fn generic_call_check(source: &str) -> Result<(), CompileError> {
+    let program = parse(lex(source).unwrap()).unwrap();
+    MethodResolver::collect(&program)
+        .check_generic_calls(&program)
+        .map_err(|e| e.downcast::<CompileError>().unwrap())
+}
+
+#[test]
+fn test_method_from_two_bounds_of_generic_is_ambiguous() {
+    let source = r#"
+        fn show<T: Display + Debug>(t: T) {
+            t.fmt();
+        }
+    "#;
+
+    match generic_call_check(source).unwrap_err() {
+        CompileError::SemanticError { message, suggestion, .. } => {
+            assert!(message.contains("multiple applicable methods named `fmt`"));
+            assert_eq!(suggestion.as_deref(), Some("disambiguate the call, e.g. `Display::fmt(t)`"));
+        }
+        other => panic!("expected a semantic error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_method_from_one_bound_of_generic_is_accepted() {
+    let source = r#"
+        fn show<T: Display + Clone>(t: T) {
+            let copy = t.clone();
+            t.fmt();
+        }
+    "#;
+
+    assert!(generic_call_check(source).is_ok());
+}
+
+fn const_fn_errors(source: &str) -> Vec<String> {
+    let program = parse(lex(source).unwrap()).unwrap();
+    match ConstFnChecker::new(&program).check_program(&program) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors.iter().map(|e| e.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_pure_const_fn_is_accepted() {
+    let source = r#"
+        const fn square(x: i32) -> i32 { return x * x; }
+        const fn area(w: i32, h: i32) -> i32 { return square(w) + w * h - 1; }
+    "#;
+    let program = parse(lex(source).unwrap()).unwrap();
+
+    assert!(matches!(&program.items[0], Item::Function(f) if f.is_const));
+    assert!(const_fn_errors(source).is_empty());
+}
+
+#[test]
+fn test_const_fn_rejects_io() {
+    let source = r#"
+        const fn noisy(x: i32) -> i32 { print(x); return x; }
+    "#;
+
+    let errors = const_fn_errors(source);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("I/O call `print()`"));
+}
+
+#[test]
+fn test_const_fn_rejects_allocation_and_non_const_calls() {
+    let source = r#"
+        fn helper() -> i32 { return 1; }
+        const fn build() -> i32 { let p = alloc(16); return helper(); }
+    "#;
+
+    let errors = const_fn_errors(source);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("heap allocation via `alloc()`"));
+    assert!(errors[1].contains("non-const function `helper()`"));
+}
+
+fn symbol_table_error(source: &str) -> CompileError {
+    let program = parse(lex(source).unwrap()).unwrap();
+    let err = SymbolTable::new().analyze(&program).unwrap_err();
+    err.downcast::<CompileError>().unwrap()
+}
+
+#[test]
+fn test_redefined_function_reports_both_definitions() {
+    let source = r#"
+        fn helper() -> i32 { return 1; }
+        struct Point { x: i32 }
+        fn helper() -> i32 { return 2; }
+    "#;
+
+    match symbol_table_error(source) {
+        CompileError::SemanticError { message, related_info, .. } => {
+            assert!(message.contains("`helper` is defined multiple times"));
+            assert_eq!(related_info.len(), 2);
+            assert!(related_info[0].contains("item 1"));
+            assert!(related_info[1].contains("item 3"));
+        }
+        other => panic!("expected semantic error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_struct_with_duplicate_field_is_rejected() {
+    let source = "struct Point { x: i32, y: i32, x: i64 }";
+
+    match symbol_table_error(source) {
+        CompileError::SemanticError { message, related_info, .. } => {
+            assert!(message.contains("field `x` is already declared in struct `Point`"));
+            assert!(related_info[0].contains("field 1"));
+            assert!(related_info[1].contains("field 3"));
+        }
+        other => panic!("expected semantic error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_struct_named_after_a_primitive_is_rejected() {
+    // struct bool {} -- `bool` is a keyword to the parser, so build the item directly.
+    let program = Program {
+        items: vec![Item::Struct(Struct {
+            attributes: Vec::new(),
+            visibility: Visibility::Private,
+            name: "bool".to_string(),
+            generics: Vec::new(),
+            fields: Vec::new(),
+            where_clause: None,
+        })],
+    };
+
+    let err = SymbolTable::new().analyze(&program).unwrap_err();
+    match err.downcast::<CompileError>().unwrap() {
+        CompileError::SemanticError { message, .. } => {
+            assert_eq!(message, "cannot define struct `bool`: `bool` is a built-in type");
+        }
+        other => panic!("expected semantic error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_redefining_a_builtin_trait_is_rejected() {
+    match symbol_table_error("trait Clone { fn clone(&self) -> i32; }") {
+        CompileError::SemanticError { message, suggestion, .. } => {
+            assert_eq!(message, "cannot define trait `Clone`: `Clone` is a built-in trait");
+            assert!(suggestion.unwrap().contains("rename the trait"));
+        }
+        other => panic!("expected semantic error, got {:?}", other),
+    }
+}
+
+fn match_check(source: &str) -> Result<(), String> {
+    let program = parse(lex(source).unwrap()).unwrap();
+    MatchChecker::new().check(&program).map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_non_exhaustive_match_is_rejected() {
+    let source = r#"
+        fn describe(n: i32) -> i32 {
+            return match n {
+                0 => 10,
+                1 => 20,
+                _ if n > 100 => 30,
+            };
+        }
+    "#;
+
+    let err = match_check(source).unwrap_err();
+    assert!(err.contains("non-exhaustive patterns"), "{}", err);
+    assert!(err.contains("describe"), "{}", err);
+}
+
+#[test]
+fn test_wildcard_covered_match_is_accepted() {
+    let source = r#"
+        fn describe(n: i32) -> i32 {
+            let label = match n {
+                0 => 10,
+                _ => 20,
+            };
+            match (n, label) {
+                (0, _) => { print(0); }
+                other => { print(1); }
+            }
+            return label;
+        }
+    "#;
+
+    assert_eq!(match_check(source), Ok(()));
+}
+
+fn label_check(source: &str) -> Result<usize, String> {
+    let program = parse(lex(source).unwrap()).unwrap();
+    LoopLabelChecker::new().check(&program).map(|warnings| warnings.len()).map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_break_to_enclosing_label_is_accepted() {
+    let source = r#"
+        fn main() {
+            'outer: while true {
+                loop {
+                    if done() {
+                        break 'outer;
+                    }
+                    continue 'outer;
+                }
+            }
+        }
+    "#;
+
+    assert_eq!(label_check(source), Ok(0));
+}
+
+#[test]
+fn test_undefined_or_out_of_scope_label_is_rejected() {
+    let undefined = r#"
+        fn main() {
+            while true {
+                break 'missing;
+            }
+        }
+    "#;
+    let err = label_check(undefined).unwrap_err();
+    assert!(err.contains("`break 'missing` in `main` does not refer to an enclosing loop"), "{}", err);
+
+    // The label belongs to a loop that has already ended.
+    let out_of_scope = r#"
+        fn main() {
+            'first: loop {
+                break 'first;
+            }
+            loop {
+                continue 'first;
+            }
+        }
+    "#;
+    let err = label_check(out_of_scope).unwrap_err();
+    assert!(err.contains("`continue 'first`"), "{}", err);
+}
+
+#[test]
+fn test_shadowed_label_warns() {
+    let source = r#"
+        fn main() {
+            'scan: loop {
+                'scan: for x in 0..10 {
+                    break 'scan;
+                }
+                break 'scan;
+            }
+        }
+    "#;
+
+    assert_eq!(label_check(source), Ok(1));
+}
+
+fn entry_point_check(source: &str) -> Result<(), String> {
+    let program = parse(lex(source).unwrap()).unwrap();
+    EntryPointChecker::new().check(&program).map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_missing_main_is_rejected() {
+    let err = entry_point_check("fn helper() -> i32 { return 1; }").unwrap_err();
+    assert!(err.contains("executable has no `main` function"));
+}
+
+#[test]
+fn test_main_with_bad_signature_is_rejected() {
+    let err = entry_point_check("fn main(x: i32, y: i32) {}").unwrap_err();
+    assert!(err.contains("`main` has invalid parameters"));
+
+    let err = entry_point_check("fn main() -> bool { return true; }").unwrap_err();
+    assert!(err.contains("must return `()` or `i32`"));
+}
+
+#[test]
+fn test_valid_main_signatures_are_accepted() {
+    assert!(entry_point_check("fn main() {}").is_ok());
+    assert!(entry_point_check("fn main() -> i32 { return 0; }").is_ok());
+    assert!(entry_point_check("fn main(args: Vec<String>) {}").is_ok());
+}
+
+#[test]
+fn test_declared_impl_is_registered() {
+    let source = r#"
+        struct Circle { radius: f64 }
+        trait Shape {
+            fn area(&self) -> f64;
+            fn sides(&self) -> i32 { 0 }
+        }
+        impl Shape for Circle {
+            fn area(&self) -> f64 { self.radius }
+        }
+        impl Circle {
+            fn grow(&mut self) {}
+        }
+    "#;
+    let program = parse(lex(source).unwrap()).unwrap();
+    let mut registry = TraitRegistry::new();
+    registry.register_program(&program);
+
+    let circle = Type::Custom("Circle".to_string());
+    assert!(registry.has_impl("Shape", &circle));
+    assert!(!registry.has_impl("Shape", &Type::Custom("Square".to_string())));
+
+    let methods = &registry.get_impl("Shape", &circle).unwrap().methods;
+    assert_eq!(methods["area"], "Circle::area");
+    assert_eq!(methods["sides"], "Shape::sides");
+}
+
+/// `const NAME: i64 = <value>;`
+fn const_item(name: &str, value: Expression) -> Item {
+    Item::Const(ConstDeclaration {
+        attributes: Vec::new(),
+        visibility: Visibility::Private,
+        name: name.to_string(),
+        ty: Type::I64,
+        value,
+    })
+}
+
+/// `<name> + 1`
+fn plus_one(name: &str) -> Expression {
+    Expression::Binary {
+        op: BinaryOp::Add,
+        left: Box::new(Expression::Ident(name.to_string())),
+        right: Box::new(Expression::IntLit(1)),
+    }
+}
+
+fn const_cycle_error(items: Vec<Item>) -> Option<String> {
+    ConstCycleChecker::new().check(&Program { items }).err().map(|error| error.to_string())
+}
+
+#[test]
+fn test_direct_const_cycle_is_rejected() {
+    let error = const_cycle_error(vec![const_item("A", plus_one("B")), const_item("B", plus_one("A"))]).unwrap();
+
+    assert!(error.contains("A -> B -> A"), "{}", error);
+}
+
+#[test]
+fn test_indirect_const_cycle_is_rejected() {
+    let error = const_cycle_error(vec![
+        const_item("A", plus_one("C")),
+        const_item("B", plus_one("A")),
+        const_item("C", plus_one("B")),
+        const_item("D", plus_one("A")),
+    ])
+    .unwrap();
+
+    assert!(error.contains("A -> C -> B -> A"), "{}", error);
+    assert!(!error.contains('D'), "{}", error);
+}
+
+#[test]
+fn test_const_chain_without_cycle_is_accepted() {
+    let error = const_cycle_error(vec![
+        const_item("A", Expression::IntLit(1)),
+        const_item("B", plus_one("A")),
+        const_item("C", plus_one("B")),
+    ]);
+
+    assert_eq!(error, None);
+}
@@ -0,0 +1,314 @@
+use blaze_compiler::parser::{ClosureParam, ConstDeclaration, Expression, Function, Item, Program, Statement, Type, Visibility};
+use blaze_compiler::trait_system::{TraitImpl, TraitRegistry};
+use blaze_compiler::type_inference::TypeInferenceEngine;
+use blaze_compiler::{lex, parse};
+use std::collections::HashMap;
+
+fn first_function(source: &str) -> Function {
+    let program = parse(lex(source).unwrap()).unwrap();
+    match program.items.into_iter().next() {
+        Some(Item::Function(func)) => func,
+        _ => panic!("expected a function"),
+    }
+}
+
+#[test]
+fn test_implicit_return_value_is_function_type() {
+    let func = first_function("fn double(a: i32) -> i32 { a + a }");
+    let mut engine = TypeInferenceEngine::new();
+
+    assert_eq!(engine.infer_function(&func), Ok(Type::I32));
+}
+
+#[test]
+fn test_implicit_return_type_mismatch() {
+    let func = first_function("fn f(a: i32) -> bool { a + a }");
+    let mut engine = TypeInferenceEngine::new();
+
+    assert!(engine.infer_function(&func).is_err());
+}
+
+fn registry_with_point_add() -> TraitRegistry {
+    let mut registry = TraitRegistry::new();
+    registry.register_impl(TraitImpl {
+        trait_name: "Add".to_string(),
+        for_type: Type::Custom("Point".to_string()),
+        methods: HashMap::from([("add".to_string(), "Point::add".to_string())]),
+        associated_types: HashMap::from([("Output".to_string(), Type::Custom("Vector".to_string()))]),
+    });
+    registry
+}
+
+#[test]
+fn test_operator_on_struct_uses_trait_output_type() {
+    let func = first_function("fn sum(a: Point, b: Point) -> Vector { let v = a + b; return v; }");
+    let mut engine = TypeInferenceEngine::new().with_trait_registry(registry_with_point_add());
+
+    assert_eq!(engine.infer_function(&func), Ok(Type::Custom("Vector".to_string())));
+}
+
+#[test]
+fn test_operator_without_trait_impl_is_rejected() {
+    let func = first_function("fn diff(a: Point, b: Point) -> Point { return a - b; }");
+    let mut engine = TypeInferenceEngine::new().with_trait_registry(registry_with_point_add());
+
+    let err = engine.infer_function(&func).unwrap_err();
+    assert!(err.contains("No implementation of 'Sub'"), "{}", err);
+}
+
+#[test]
+fn test_tuple_literal_types() {
+    let mut engine = TypeInferenceEngine::new();
+
+    let unit = first_function("fn f() -> () { () }");
+    assert_eq!(engine.infer_function(&unit), Ok(Type::Tuple(vec![])));
+
+    let single = first_function("fn f(a: i32) -> (i32,) { (a,) }");
+    assert_eq!(engine.infer_function(&single), Ok(Type::Tuple(vec![Type::I32])));
+
+    let nested = first_function("fn f(a: i32) -> (i32, (bool, i32)) { (a, (true, a)) }");
+    assert_eq!(
+        engine.infer_function(&nested),
+        Ok(Type::Tuple(vec![Type::I32, Type::Tuple(vec![Type::Bool, Type::I32])]))
+    );
+}
+
+#[test]
+fn test_tuple_element_type_mismatch() {
+    let func = first_function("fn f(a: i32) -> (i32, i32) { (a, true) }");
+    let mut engine = TypeInferenceEngine::new();
+
+    assert!(engine.infer_function(&func).is_err());
+}
+
+#[test]
+fn test_every_return_is_checked_against_the_declared_type() {
+    let func = first_function("fn f(a: i32, c: bool) -> i32 { if c { return true; } return a; }");
+    let mut engine = TypeInferenceEngine::new();
+
+    let err = engine.infer_function(&func).unwrap_err();
+    assert!(err.contains("Return type mismatch"), "{}", err);
+}
+
+#[test]
+fn test_return_with_value_in_unit_function() {
+    let mut engine = TypeInferenceEngine::new();
+
+    let func = first_function("fn f(a: i32) { while true { return a; } }");
+    let err = engine.infer_function(&func).unwrap_err();
+    assert!(err.contains("Return type mismatch"), "{}", err);
+
+    let func = first_function("fn f(c: bool) { if c { return; } }");
+    assert!(engine.infer_function(&func).is_ok());
+}
+
+#[test]
+fn test_return_outside_function_is_rejected() {
+    let program = Program {
+        items: vec![Item::Const(ConstDeclaration {
+            attributes: Vec::new(),
+            visibility: Visibility::Private,
+            name: "LIMIT".to_string(),
+            ty: Type::I64,
+            value: Expression::Block(vec![Statement::Return(Some(Expression::IntLit(1)))]),
+        })],
+    };
+    let mut engine = TypeInferenceEngine::new();
+
+    assert_eq!(engine.infer_program(&program), Err("'return' outside of a function".to_string()));
+}
+
+#[test]
+fn test_unsigned_types_only_match_themselves() {
+    let mut engine = TypeInferenceEngine::new();
+    let func = first_function("fn widen(a: u8, b: u32) -> u32 { let x: u8 = a; let y: u32 = b ^ b; return y; }");
+    assert!(engine.infer_function(&func).is_ok());
+
+    for source in ["fn f(a: u8) { let x: u16 = a; }", "fn f(a: u32) { let x: i32 = a; }", "fn f(a: u64) -> i64 { return a; }"] {
+        let mut engine = TypeInferenceEngine::new();
+        assert!(engine.infer_function(&first_function(source)).is_err(), "{}", source);
+    }
+}
+
+fn closure(param: &str, body: Expression) -> Expression {
+    Expression::Closure {
+        params: vec![ClosureParam { name: param.to_string(), ty: None }],
+        body: Box::new(body),
+    }
+}
+
+fn call(func: &str, arg: Expression) -> Expression {
+    Expression::Call { func: Box::new(Expression::Ident(func.to_string())), args: vec![arg] }
+}
+
+fn let_stmt(name: &str, value: Expression) -> Statement {
+    Statement::Let { name: name.to_string(), mutable: false, ty: None, value: Some(value) }
+}
+
+fn function_with_body(body: Vec<Statement>) -> Function {
+    let mut func = first_function("fn f() {}");
+    func.body = body;
+    func
+}
+
+#[test]
+fn test_let_bound_identity_closure_is_polymorphic() {
+    // let id = |x| x; let a = id(1); let b = id(true);
+    let func = function_with_body(vec![
+        let_stmt("id", closure("x", Expression::Ident("x".to_string()))),
+        let_stmt("a", call("id", Expression::IntLit(1))),
+        Statement::Let { name: "b".to_string(), mutable: false, ty: Some(Type::Bool), value: Some(call("id", Expression::BoolLit(true))) },
+    ]);
+    let mut engine = TypeInferenceEngine::new();
+
+    assert_eq!(engine.infer_function(&func), Ok(Type::Bool));
+}
+
+#[test]
+fn test_result_of_call_is_not_generalized() {
+    // let id = |x| x; let same = id(id); same(1); same(true);
+    let func = function_with_body(vec![
+        let_stmt("id", closure("x", Expression::Ident("x".to_string()))),
+        let_stmt("same", call("id", Expression::Ident("id".to_string()))),
+        Statement::Expression(call("same", Expression::IntLit(1))),
+        Statement::Expression(call("same", Expression::BoolLit(true))),
+    ]);
+    let mut engine = TypeInferenceEngine::new();
+
+    let err = engine.infer_function(&func).unwrap_err();
+    assert!(err.contains("Cannot unify"), "{}", err);
+}
+
+#[test]
+fn test_integer_literal_takes_the_annotated_type() {
+    let mut engine = TypeInferenceEngine::new();
+
+    let func = first_function("fn f() -> u64 { let x: i32 = 5; let y: u64 = 5; y }");
+    assert_eq!(engine.infer_function(&func), Ok(Type::U64));
+
+    let func = first_function("fn f() { let z: bool = 5; }");
+    let err = engine.infer_function(&func).unwrap_err();
+    assert!(err.contains("Type mismatch in let binding 'z'"), "{}", err);
+}
+
+#[test]
+fn test_unconstrained_integer_literal_defaults_to_i32() {
+    let mut engine = TypeInferenceEngine::new();
+
+    assert_eq!(engine.infer_function(&first_function("fn f() { let x = 5; x }")), Ok(Type::I32));
+    assert_eq!(engine.infer_function(&first_function("fn f(a: u8) { a + 1 }")), Ok(Type::U8));
+}
+
+#[test]
+fn test_variable_holding_a_literal_is_fixed_by_its_first_use() {
+    let mut engine = TypeInferenceEngine::new();
+
+    let func = first_function("fn f() { let x = 5; let y: u64 = x; x }");
+    assert_eq!(engine.infer_function(&func), Ok(Type::U64));
+
+    let func = first_function("fn f() { let x = 5; let y: u64 = x; let z: i8 = x; }");
+    assert!(engine.infer_function(&func).is_err());
+}
+
+fn infer_source(source: &str) -> Result<(), String> {
+    let program = parse(lex(source).unwrap()).unwrap();
+    TypeInferenceEngine::new().infer_program(&program)
+}
+
+#[test]
+fn test_call_takes_the_callee_return_type() {
+    let source = "fn add(a: i32, b: i32) -> i32 { a + b } fn main() -> bool { let x: i32 = add(1, 2); add(x, 3) == x }";
+
+    assert_eq!(infer_source(source), Ok(()));
+    let err = infer_source("fn flag() -> bool { true } fn f() -> i32 { flag() }").unwrap_err();
+    assert!(err.contains("Return type mismatch"), "{}", err);
+}
+
+#[test]
+fn test_call_with_wrong_arity_is_rejected() {
+    let err = infer_source("fn add(a: i32, b: i32) -> i32 { a + b } fn main() { add(1); }").unwrap_err();
+
+    assert_eq!(err, "Function 'add' takes 2 argument(s) but 1 were supplied");
+}
+
+#[test]
+fn test_call_with_wrong_argument_type_reports_its_position() {
+    let err = infer_source("fn scale(a: i32, on: bool) -> i32 { a } fn main() { scale(2, 3); }").unwrap_err();
+
+    assert_eq!(err, "Argument 2 of call to 'scale': expected Bool, found Custom(\"{integer}\")");
+}
+
+#[test]
+fn test_annotation_fixes_the_element_type_of_an_empty_generic_container() {
+    let generics = "fn empty<T>() -> Vec<T> { empty() } fn first<T>(items: Vec<T>) -> T { first(items) }";
+
+    let source = format!("{} fn main() -> i32 {{ let v: Vec<i32> = empty(); first(v) }}", generics);
+    assert_eq!(infer_source(&source), Ok(()));
+
+    let source = format!("{} fn main() -> i32 {{ let v: Vec<bool> = empty(); first(v) }}", generics);
+    assert_eq!(
+        infer_source(&source),
+        Err("Argument 1 of call to 'first': expected Generic(\"Vec\", [I32]), found Generic(\"Vec\", [Bool])".to_string())
+    );
+
+    let source = format!("{} fn main() {{ let v = empty(); }}", generics);
+    let err = infer_source(&source).unwrap_err();
+    assert!(err.contains("Cannot infer type parameter 'T' of call to 'empty'"), "{}", err);
+}
+
+fn annotated_let(name: &str, ty: Type, value: Expression) -> Statement {
+    Statement::Let { name: name.to_string(), mutable: false, ty: Some(ty), value: Some(value) }
+}
+
+fn array_of(element: Type, size: usize) -> Type {
+    Type::Array { element: Box::new(element), size: Some(size) }
+}
+
+#[test]
+fn test_annotation_types_array_literal_elements() {
+    // let xs: [u8; 0] = []; let ys: [u64; 2] = [1, 2];
+    let func = function_with_body(vec![
+        annotated_let("xs", array_of(Type::U8, 0), Expression::ArrayLiteral(vec![])),
+        annotated_let("ys", array_of(Type::U64, 2), Expression::ArrayLiteral(vec![Expression::IntLit(1), Expression::IntLit(2)])),
+    ]);
+    assert!(TypeInferenceEngine::new().infer_function(&func).is_ok());
+
+    // let zs: [u8; 2] = [1, true];
+    let func = function_with_body(vec![annotated_let(
+        "zs",
+        array_of(Type::U8, 2),
+        Expression::ArrayLiteral(vec![Expression::IntLit(1), Expression::BoolLit(true)]),
+    )]);
+    assert_eq!(TypeInferenceEngine::new().infer_function(&func), Err("Array element 2: expected U8, found Bool".to_string()));
+}
+
+#[test]
+fn test_array_literal_elements_share_one_type() {
+    // let xs = [1, 2, 3];
+    let ints = vec![Expression::IntLit(1), Expression::IntLit(2), Expression::IntLit(3)];
+    let func = function_with_body(vec![let_stmt("xs", Expression::ArrayLiteral(ints))]);
+    assert!(TypeInferenceEngine::new().infer_function(&func).is_ok());
+
+    // let ys = [1, 2, true];
+    let mixed = vec![Expression::IntLit(1), Expression::IntLit(2), Expression::BoolLit(true)];
+    let func = function_with_body(vec![let_stmt("ys", Expression::ArrayLiteral(mixed))]);
+    assert_eq!(
+        TypeInferenceEngine::new().infer_function(&func),
+        Err("Array element 3: expected Custom(\"{integer}\"), found Bool".to_string())
+    );
+}
+
+#[test]
+fn test_array_literal_length_must_match_its_annotation() {
+    // let xs: [i32; 3] = [1, 2];
+    let func = function_with_body(vec![annotated_let(
+        "xs",
+        array_of(Type::I32, 3),
+        Expression::ArrayLiteral(vec![Expression::IntLit(1), Expression::IntLit(2)]),
+    )]);
+
+    assert_eq!(
+        TypeInferenceEngine::new().infer_function(&func),
+        Err("Array literal has 2 element(s) but Array { element: I32, size: Some(3) } expects 3".to_string())
+    );
+}